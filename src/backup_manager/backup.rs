@@ -1,5 +1,6 @@
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use chrono::Local;
 use super::ArgMatches;
 use crate::{
     loglib,
@@ -8,18 +9,40 @@ use crate::{
     dblib
 };
 
+/// Check the backup copy against the origin file, for `--verify`
+/// (e.g. a partial upload in a sync folder).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// 
+/// ### Example:
+/// ```
+/// verify_copy(Path::new("./dir/passwords.db.x"), Path::new("./to/passwords.db.x"));
+/// ```
+fn verify_copy(file: &Path, to_file: &Path) {
+    let logger = loglib::Logger::new("verify-backup");
+    if !filelib::same_contents(file, to_file) {
+        logger.error(
+            &format!("the backup '{}' does NOT match the origin file!", to_file.display()),
+            errorlib::ExitErrorCode::FileWrite
+        );
+    }
+    logger.info(&format!("the backup '{}' verified successfully.", to_file.display()));
+}
+
 /// Backup the password manager database.
 /// 
 /// ### Exit:
 /// - `errorlib::ExitErrorCode::FileNotEncrypted`
 /// - `errorlib::ExitErrorCode::PMDatabaseNotFound`
+/// - `errorlib::ExitErrorCode::FileWrite`
 /// 
 /// ### Example:
 /// ```
 /// // find it at: `./to/passwords.db.x`
-/// password_manager("./to".to_string());
+/// password_manager("./to".to_string(), None, false);
 /// ```
-fn password_manager(path: String) {
+fn password_manager(path: String, rate: Option<u64>, is_verify: bool) {
     let logger = loglib::Logger::new("password-manager-backup");
     let pm_db_state = filelib::pm::db_state();
     if pm_db_state == filelib::FileState::Decrypted {
@@ -37,33 +60,70 @@ fn password_manager(path: String) {
     let backup_name = encryption_db_path
         .file_name()
         .unwrap();
-    filelib::copy(
+    let backup_path = PathBuf::new()
+        .join(path)
+        .join(backup_name);
+    filelib::copy_limited(
         encryption_db_path
             .to_str()
             .unwrap()
             .to_owned(), 
-        PathBuf::new()
-            .join(path)
-            .join(&backup_name)
+        backup_path
             .to_str()
             .unwrap()
-            .to_owned()
-    );
-    dblib::log::register("created password manager backup.", filelib::log::get_log_db_path());
+            .to_owned(),
+        rate
+    ).unwrap_or_else(|err| err.exit());
+    if is_verify {
+        verify_copy(&encryption_db_path, &backup_path);
+    }
+    dblib::log::register("created password manager backup.", dblib::log::LogAction::Backup, filelib::log::get_log_db_path());
     logger.info("created the password manager backup successfully.");
 }
 
+/// Backup the password manager database next to it as
+/// `<name>.<timestamp>.bak`, as it is (encrypted or decrypted).
+/// Returns the backup path.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::DirNotFound`
+/// 
+/// ### Example:
+/// ```
+/// let backup_path = password_manager_snapshot(filelib::pm::get_encrypted_db_path());
+/// println!("backup saved at '{}'.", backup_path.display());
+/// ```
+pub fn password_manager_snapshot(db_path: PathBuf) -> PathBuf {
+    let backup_path = db_path.with_file_name(format!(
+        "{}.{}.bak",
+        db_path.file_name().unwrap().to_str().unwrap(),
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+    filelib::copy(
+        db_path.to_str().unwrap().to_owned(),
+        backup_path.to_str().unwrap().to_owned()
+    ).unwrap_or_else(|err| err.exit());
+    dblib::log::register(
+        &format!("created password manager backup at '{}'.", backup_path.display()),
+        dblib::log::LogAction::Backup,
+        filelib::log::get_log_db_path()
+    );
+    backup_path
+}
+
 /// Backup the log manager database.
 /// 
 /// ### Exit:
 /// - `errorlib::ExitErrorCode::LMDatabaseNotFound`
+/// - `errorlib::ExitErrorCode::FileWrite`
 /// 
 /// ### Example:
 /// ```
 /// // find it at: `./to/xpm-log.db`
-/// logs_manager("./to".to_string());
+/// logs_manager("./to".to_string(), None, false);
 /// ```
-fn logs_manager(path: String) {
+fn logs_manager(path: String, rate: Option<u64>, is_verify: bool) {
     let logger = loglib::Logger::new("logs-manager-backup");
     let log_db_path = filelib::log::get_log_db_path()
             .to_str()
@@ -78,20 +138,26 @@ fn logs_manager(path: String) {
             errorlib::ExitErrorCode::LMDatabaseNotFound
         );
     }
-    filelib::copy(
+    let backup_path = PathBuf::new()
+        .join(path)
+        .join(
+            PathBuf::new()
+                .join(log_db_path.clone())
+                .file_name()
+                .unwrap()
+        );
+    filelib::copy_limited(
         log_db_path.clone(), 
-        PathBuf::new()
-            .join(path)
-            .join(
-                PathBuf::new()
-                    .join(log_db_path)
-                    .file_name()
-                    .unwrap()
-            ).to_str()
+        backup_path
+            .to_str()
             .unwrap()
-            .to_owned()
-    );
-    dblib::log::register("created logs manager backup.", filelib::log::get_log_db_path());
+            .to_owned(),
+        rate
+    ).unwrap_or_else(|err| err.exit());
+    if is_verify {
+        verify_copy(Path::new(&log_db_path), &backup_path);
+    }
+    dblib::log::register("created logs manager backup.", dblib::log::LogAction::Backup, filelib::log::get_log_db_path());
     logger.info("created the logs manager backup successfully.");
 }
 
@@ -100,11 +166,18 @@ pub fn main(command: &ArgMatches) {
     let path = command.get_one::<String>("PATH").unwrap();
     let is_password = *command.get_one("password").unwrap_or(&false);
     let is_log = *command.get_one("log").unwrap_or(&false);
+    let rate = filelib::limit_rate(command.get_one::<String>("limit-rate"));
+    let is_verify = *command.get_one::<bool>("verify").unwrap_or(&false);
+    if let Some(name) = filelib::sync_in_progress(&PathBuf::from(path)) {
+        logger.warning(
+            &format!("'{}' looks like a sync folder in the middle of a sync ('{}')!", path, name)
+        );
+    }
     if  is_password {
-        password_manager(path.clone());
+        password_manager(path.clone(), rate, is_verify);
     }
     if is_log {
-        logs_manager(path.clone());
+        logs_manager(path.clone(), rate, is_verify);
     }
     if !is_password && !is_log {
         logger.error(
@@ -1,16 +1,21 @@
 
 use std::{
-    collections::HashMap, 
+    collections::{HashMap, HashSet},
     path::PathBuf
 };
-use super::ArgMatches;
+use super::{
+    ArgMatches,
+    backup
+};
 use crate::{
+    configlib,
     dblib, 
     encryption_manager::decrypt_file, 
     errorlib, 
     filelib, 
     loglib, 
-    password_manager::PMDatabaseEncrption, 
+    displaylib,
+    password_manager::{diff::{DiffEntry, DiffStatus}, PMDatabaseEncrption}, 
     utilities
 };
 
@@ -21,11 +26,13 @@ use crate::{
 /// let passwords_map = vec_to_hash_map(
 ///     vec![
 ///         dblib::pm::PasswordInfoForm {
+///             icon: "".to_string(),
 ///             id: 32,
 ///             name: "XPManager".to_string(),
 ///             password: "pass123".to_string(),
 ///             create_at: "2025-05-24 15:59:12".to_string(),
-///             update_at: "2025-05-24 15:59:12".to_string()
+///             update_at: "2025-05-24 15:59:12".to_string(),
+///             tags: "work".to_string()
 ///         }
 ///     ]
 /// );
@@ -42,6 +49,7 @@ fn hash_map_to_pm_db(data: HashMap<String, String>, pm_db_path: PathBuf) {
         );
         dblib::log::register(
             &format!("'{}' saved successfully.", name), 
+            dblib::log::LogAction::Restore, 
             filelib::log::get_log_db_path()
         );
         logger.info(
@@ -57,11 +65,13 @@ fn hash_map_to_pm_db(data: HashMap<String, String>, pm_db_path: PathBuf) {
 /// let map = vec_to_hash_map(
 ///     vec![
 ///         dblib::pm::PasswordInfoForm {
+///             icon: "".to_string(),
 ///             id: 32,
 ///             name: "XPManager".to_string(),
 ///             password: "pass123".to_string(),
 ///             create_at: "2025-05-24 15:59:12".to_string(),
-///             update_at: "2025-05-24 15:59:12".to_string()
+///             update_at: "2025-05-24 15:59:12".to_string(),
+///             tags: "work".to_string()
 ///         }
 ///     ]
 /// );
@@ -80,6 +90,226 @@ fn vec_to_hash_map(data: Vec<dblib::pm::PasswordInfoForm>) -> HashMap<String, St
     return passwords;
 }
 
+/// Resolve the name conflicts with the current passwords for `--keep-both`,
+/// so no password is lost: a restored password with the same name and
+/// password (or already renamed) is skipped, and a different one is renamed to
+/// `<name> (restored)` (or `<name> (restored N)` if it is taken too).
+/// 
+/// ### Example:
+/// ```
+/// // {"github (restored)": "new-pass"}
+/// let passwords = keep_both(
+///     HashMap::from([("github".to_string(), "new-pass".to_string())]),
+///     &dblib::pm::get_passwords(PathBuf::new().join("./dir/passwords.db"))
+/// );
+/// ```
+fn keep_both(
+    data: HashMap<String, String>,
+    current: &[dblib::pm::PasswordInfoForm]
+) -> HashMap<String, String> {
+    let mut names: HashSet<String> = current.iter()
+        .map(|pass| pass.name.clone())
+        .collect();
+    let mut restored: Vec<(String, String)> = data.into_iter().collect();
+    // Sort by the name, so the renamed names do NOT depend on the map order.
+    restored.sort();
+    let mut passwords: HashMap<String, String> = HashMap::new();
+    for (name, password) in restored {
+        // Skip it if it is saved, or renamed by an old restore.
+        if current.iter().any(|pass| pass.password == password && (
+            pass.name == name || pass.name.starts_with(&format!("{} (restored", name))
+        )) {
+            continue;
+        }
+        let mut new_name = name.clone();
+        let mut count = 1;
+        while names.contains(&new_name) {
+            new_name = if count == 1 {
+                format!("{} (restored)", name)
+            } else {
+                format!("{} (restored {})", name, count)
+            };
+            count += 1;
+        }
+        names.insert(new_name.clone());
+        passwords.insert(new_name, password);
+    }
+    passwords
+}
+
+/// What `--dedupe` does with a restored password if its name is already
+/// saved with another password, the config `restore.conflict`.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ConflictStrategy {
+    /// Keep the current password, skip the restored one.
+    Skip,
+    /// Keep both passwords, like `--keep-both` (default).
+    KeepBoth,
+    /// Update the current password with the restored one.
+    Overwrite
+}
+
+impl ConflictStrategy {
+    /// Get the strategy from its name (skip, keep-both, overwrite).
+    pub fn from_name(name: &str) -> Option<ConflictStrategy> {
+        match name {
+            "skip" => Some(ConflictStrategy::Skip),
+            "keep-both" => Some(ConflictStrategy::KeepBoth),
+            "overwrite" => Some(ConflictStrategy::Overwrite),
+            _ => None
+        }
+    }
+
+    /// Get the strategy from the config `restore.conflict`, default is `KeepBoth`.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// 
+    /// ### Example:
+    /// ```toml
+    /// [restore]
+    /// conflict = "overwrite"
+    /// ```
+    /// ```
+    /// let strategy = ConflictStrategy::resolve(&configlib::Config::load());
+    /// ```
+    pub fn resolve(config: &configlib::Config) -> ConflictStrategy {
+        let logger = loglib::Logger::new("restore-conflict");
+        match config.get_str("restore.conflict") {
+            Some(name) => ConflictStrategy::from_name(&name).unwrap_or_else(|| logger.error(
+                &format!("invalid config 'restore.conflict = \"{}\"', use skip, keep-both, or overwrite!", name),
+                errorlib::ExitErrorCode::InvalidConfig
+            )),
+            None => ConflictStrategy::KeepBoth
+        }
+    }
+}
+
+/// The passwords to save for `--dedupe`.
+#[derive(PartialEq, Debug)]
+struct Dedupe {
+    /// The passwords to add (new names, or renamed by `KeepBoth`).
+    new: HashMap<String, String>,
+    /// The current passwords ids to update by `Overwrite`, with the restored password.
+    updates: Vec<(i32, String)>,
+    /// The number of skipped passwords, the same ones and the `Skip` conflicts.
+    skipped: usize
+}
+
+/// Merge the restored passwords with the current passwords for `--dedupe`,
+/// without duplicates: a restored password with the same name and password
+/// is skipped, and a different one is resolved by the `strategy`.
+/// 
+/// ### Example:
+/// ```
+/// let result = dedupe(
+///     HashMap::from([("github".to_string(), "new-pass".to_string())]),
+///     &dblib::pm::get_passwords(PathBuf::new().join("./dir/passwords.db")),
+///     ConflictStrategy::Overwrite
+/// );
+/// save_dedupe(result, PathBuf::new().join("./dir/passwords.db"));
+/// ```
+fn dedupe(
+    data: HashMap<String, String>,
+    current: &[dblib::pm::PasswordInfoForm],
+    strategy: ConflictStrategy
+) -> Dedupe {
+    let count = data.len();
+    if strategy == ConflictStrategy::KeepBoth {
+        let new = keep_both(data, current);
+        return Dedupe {
+            skipped: count - new.len(),
+            new,
+            updates: vec![]
+        };
+    }
+    let mut result = Dedupe {
+        new: HashMap::new(),
+        updates: vec![],
+        skipped: 0
+    };
+    for (name, password) in data {
+        let same_name: Vec<&dblib::pm::PasswordInfoForm> = current.iter()
+            .filter(|pass| pass.name == name)
+            .collect();
+        if same_name.is_empty() {
+            result.new.insert(name, password);
+        } else if same_name.iter().any(|pass| pass.password == password)
+            || strategy == ConflictStrategy::Skip {
+            result.skipped += 1;
+        } else {
+            result.updates.push((same_name[0].id, password));
+        }
+    }
+    // Sort by the id, so the updates do NOT depend on the map order.
+    result.updates.sort();
+    result
+}
+
+/// Save the `--dedupe` passwords to the password manager database.
+/// 
+/// ### Example:
+/// ```
+/// save_dedupe(
+///     dedupe(passwords, &current, ConflictStrategy::Skip),
+///     PathBuf::new().join("./dir/passwords.db")
+/// );
+/// ```
+fn save_dedupe(result: Dedupe, pm_db_path: PathBuf) {
+    let logger = loglib::Logger::new("password-restore");
+    for (id, password) in result.updates {
+        dblib::pm::update_password(pm_db_path.clone(), id.to_string(), password);
+        dblib::log::register(
+            &format!("password {} overwritten by the restore.", id),
+            dblib::log::LogAction::Restore,
+            filelib::log::get_log_db_path()
+        );
+        logger.info(&format!("password {} overwritten successfully.", id));
+    }
+    hash_map_to_pm_db(result.new, pm_db_path);
+}
+
+/// Compare the restored passwords with the current passwords by the name,
+/// for `--preview`, sorted by the name. The new names are only in the
+/// restore (`DiffStatus::OnlyTheirs`), the same name and password are
+/// `DiffStatus::Same`, and the same name with another password conflicts
+/// (`DiffStatus::Changed`). Nothing is written.
+/// 
+/// ### Example:
+/// ```
+/// let entries = preview(
+///     &HashMap::from([("github".to_string(), "new-pass".to_string())]),
+///     &dblib::pm::get_passwords(PathBuf::new().join("./dir/passwords.db"))
+/// );
+/// displaylib::passwords::display_diff(&entries);
+/// ```
+fn preview(
+    data: &HashMap<String, String>,
+    current: &[dblib::pm::PasswordInfoForm]
+) -> Vec<DiffEntry> {
+    let mut entries: Vec<DiffEntry> = data.iter()
+        .map(|(name, password)| {
+            let same_name: Vec<&dblib::pm::PasswordInfoForm> = current.iter()
+                .filter(|pass| pass.name == *name)
+                .collect();
+            let (status, fields) = if same_name.is_empty() {
+                (DiffStatus::OnlyTheirs, vec![])
+            } else if same_name.iter().any(|pass| pass.password == *password) {
+                (DiffStatus::Same, vec![])
+            } else {
+                (DiffStatus::Changed, vec!["password"])
+            };
+            DiffEntry {
+                name: name.clone(),
+                status,
+                fields
+            }
+        })
+        .collect();
+    entries.sort_by(|entry, other| entry.name.cmp(&other.name));
+    entries
+}
+
 /// Restore log manager database.
 /// 
 /// ### Example:
@@ -94,9 +324,9 @@ fn logs_manager(path: String) {
         .to_owned();
     let logs_db_state = filelib::get_file_state(logs_manager_db_path.clone());
     if logs_db_state == filelib::FileState::Decrypted {
-        filelib::wipe_delete(logs_manager_db_path.clone());
+        filelib::wipe_delete(logs_manager_db_path.clone()).unwrap_or_else(|err| err.exit());
     }
-    filelib::copy(path, logs_manager_db_path);
+    filelib::copy(path, logs_manager_db_path).unwrap_or_else(|err| err.exit());
     logger.info("restore the logs manager database successfully.");
 }
 
@@ -110,6 +340,10 @@ pub fn main(command: &ArgMatches) {
     let is_xpmv1 = *command.get_one::<bool>("xpmv1").unwrap_or(&false);
     let is_password_json = *command.get_one::<bool>("password-json").unwrap_or(&false);
     let is_logs = *command.get_one("log").unwrap_or(&false);
+    let is_replace = *command.get_one::<bool>("replace").unwrap_or(&false);
+    let is_keep_both = *command.get_one::<bool>("keep-both").unwrap_or(&false);
+    let is_preview = *command.get_one::<bool>("preview").unwrap_or(&false);
+    let is_dedupe = *command.get_one::<bool>("dedupe").unwrap_or(&false);
 
     // can not restore --password-json or --log when the file is encrypted,
     // file must be decrypted befor the restore, user can use enctryption-manager
@@ -120,6 +354,11 @@ pub fn main(command: &ArgMatches) {
             "can not restore --password-json or --log when the file is encrypted!", 
             errorlib::ExitErrorCode::Input
         )
+    } else if (is_replace || is_keep_both || is_preview || is_dedupe) && !(is_password || is_xpmv1 || is_password_json) {
+        logger.error(
+            "--replace, --keep-both, --dedupe and --preview work with the passwords restore only!",
+            errorlib::ExitErrorCode::Input
+        )
     }  else if file_state == filelib::FileState::NotFound {
         logger.error(
             "file not found!", 
@@ -135,7 +374,7 @@ pub fn main(command: &ArgMatches) {
         let mut _is_pm_db_decrypted: bool = false;
 
         if is_password_json {
-            _hash_map_data = filelib::read_json(path.clone());
+            _hash_map_data = filelib::read_json(path.clone()).unwrap_or_else(|err| err.exit());
         } else if is_xpmv1 {
             if file_state == filelib::FileState::Decrypted {
                 logger.error(
@@ -148,8 +387,8 @@ pub fn main(command: &ArgMatches) {
                 utilities::input("Enter file decrpytion key: ")
             );
             let decrpted_path = filelib::make_decrypt_path(path.clone());
-            _hash_map_data = filelib::read_json(decrpted_path.clone());
-            filelib::wipe_delete(decrpted_path);
+            _hash_map_data = filelib::read_json(decrpted_path.clone()).unwrap_or_else(|err| err.exit());
+            filelib::wipe_delete(decrpted_path).unwrap_or_else(|err| err.exit());
         } else if is_password {
             let mut _decrpted_path: PathBuf = PathBuf::new().join(path.clone());
             if file_state == filelib::FileState::Encrypted {
@@ -163,7 +402,56 @@ pub fn main(command: &ArgMatches) {
                 dblib::pm::get_passwords(_decrpted_path.clone())
             );
             if file_state == filelib::FileState::Encrypted { 
-                filelib::wipe_delete(_decrpted_path.to_str().unwrap().to_owned());
+                filelib::wipe_delete(_decrpted_path.to_str().unwrap().to_owned()).unwrap_or_else(|err| err.exit());
+            }
+        }
+
+        // Show what the merge will do, without writing anything.
+        if is_preview {
+            let mut current: Vec<dblib::pm::PasswordInfoForm> = vec![];
+            if pm_db_state == filelib::FileState::Encrypted {
+                logger.warning("database is encrypted!");
+                pm_db_encryption.decrypt();
+                logger.start();
+                current = dblib::pm::get_passwords(pm_db_path.clone());
+                pm_db_encryption.encrypt();
+            } else if pm_db_state == filelib::FileState::Decrypted {
+                current = dblib::pm::get_passwords(pm_db_path.clone());
+            }
+            let entries = preview(&_hash_map_data, &current);
+            let count = |status: DiffStatus| entries.iter().filter(|entry| entry.status == status).count();
+            if !entries.is_empty() {
+                displaylib::passwords::display_diff(&entries);
+            }
+            logger.info(&format!(
+                "{} to add, {} same, {} conflicts, nothing restored.",
+                count(DiffStatus::OnlyTheirs),
+                count(DiffStatus::Same),
+                count(DiffStatus::Changed)
+            ));
+            if !(is_keep_both || is_dedupe) {
+                logger.info("the same and the conflicted passwords are saved again, use --dedupe or --keep-both to skip them.");
+            }
+            return;
+        }
+
+        // Keep a copy of the old passwords before replacing them,
+        // so the user can recover them.
+        if is_replace {
+            logger.warning("all the passwords will be replaced!");
+            utilities::confirm();
+            logger.start();
+            if pm_db_state != filelib::FileState::NotFound {
+                let backup_path = backup::password_manager_snapshot(
+                    if pm_db_state == filelib::FileState::Encrypted {
+                        filelib::pm::get_encrypted_db_path()
+                    } else {
+                        pm_db_path.clone()
+                    }
+                );
+                logger.info(
+                    &format!("the old database backup saved at '{}'.", backup_path.display())
+                );
             }
         }
 
@@ -172,9 +460,9 @@ pub fn main(command: &ArgMatches) {
         if pm_db_state == filelib::FileState::NotFound {
             filelib::create_file(
                 pm_db_path.clone()
-            );
+            ).unwrap_or_else(|err| err.exit());
             dblib::pm::create_passwords_table(pm_db_path.clone());
-            dblib::log::register("create passwords table", filelib::log::get_log_db_path());
+            dblib::log::register("create passwords table", dblib::log::LogAction::Create, filelib::log::get_log_db_path());
         } else if pm_db_state == filelib::FileState::Encrypted {
             logger.warning("database is encrypted!");
             pm_db_encryption.decrypt();
@@ -184,8 +472,46 @@ pub fn main(command: &ArgMatches) {
         }
 
         // Save the restore passwords to password manager database. We just
-        // merge the new passwords in the password manager database.
-        hash_map_to_pm_db(_hash_map_data, pm_db_path.clone());
+        // merge the new passwords in the password manager database,
+        // unless --replace is used.
+        if is_replace {
+            let rows = dblib::pm::replace_passwords(
+                pm_db_path.clone(),
+                _hash_map_data.into_iter().collect()
+            );
+            dblib::log::register(
+                &format!("passwords replaced by {} restored passwords.", rows),
+                dblib::log::LogAction::Restore,
+                filelib::log::get_log_db_path()
+            );
+            logger.info(&format!("{} passwords restored successfully.", rows));
+        } else if is_keep_both {
+            let count = _hash_map_data.len();
+            let passwords = keep_both(
+                _hash_map_data,
+                &dblib::pm::get_passwords(pm_db_path.clone())
+            );
+            if passwords.len() < count {
+                logger.info(
+                    &format!("{} passwords are already saved, skipped.", count - passwords.len())
+                );
+            }
+            hash_map_to_pm_db(passwords, pm_db_path.clone());
+        } else if is_dedupe {
+            let result = dedupe(
+                _hash_map_data,
+                &dblib::pm::get_passwords(pm_db_path.clone()),
+                ConflictStrategy::resolve(&configlib::Config::load())
+            );
+            if result.skipped > 0 {
+                logger.info(
+                    &format!("{} passwords are already saved or conflicted, skipped.", result.skipped)
+                );
+            }
+            save_dedupe(result, pm_db_path.clone());
+        } else {
+            hash_map_to_pm_db(_hash_map_data, pm_db_path.clone());
+        }
 
         // Encrypt the password manager database if it is decrypted.
         if _is_pm_db_decrypted {
@@ -200,4 +526,201 @@ pub fn main(command: &ArgMatches) {
             errorlib::ExitErrorCode::MissingArg
         )
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+    use crate::{
+        backup_manager::backup,
+        dblib,
+        filelib::create_file,
+        password_manager::diff::DiffStatus
+    };
+
+    #[test]
+    fn restore_replace() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/restore_replace");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        dblib::pm::save_password(db_path.clone(), "old-1".to_owned(), "pass-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "old-2".to_owned(), "pass-2".to_owned());
+
+        let backup_path = backup::password_manager_snapshot(db_path.clone());
+        let rows = dblib::pm::replace_passwords(
+            db_path.clone(),
+            vec![("new-1".to_owned(), "pass-3".to_owned())]
+        );
+        assert_eq!(rows, 1, "Number of replaced passwords NOT match!!");
+        let passwords = dblib::pm::get_passwords(db_path.clone());
+        assert!(
+            passwords.len() == 1 && passwords[0].name == "new-1" && passwords[0].password == "pass-3",
+            "Replaced passwords NOT match!!"
+        );
+
+        assert!(backup_path.exists(), "Pre-replace backup NOT found!!");
+        let old: Vec<String> = dblib::pm::get_passwords(backup_path)
+            .into_iter()
+            .map(|pass| pass.name)
+            .collect();
+        assert_eq!(old, vec!["old-1", "old-2"], "Backup passwords NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn restore_keep_both() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/restore_keep_both");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        dblib::pm::save_password(db_path.clone(), "github".to_owned(), "pass-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "github (restored)".to_owned(), "pass-2".to_owned());
+        dblib::pm::save_password(db_path.clone(), "gitlab".to_owned(), "pass-3".to_owned());
+
+        let passwords = super::keep_both(
+            HashMap::from([
+                ("github".to_owned(), "pass-4".to_owned()),
+                ("gitlab".to_owned(), "pass-3".to_owned()),
+                ("email".to_owned(), "pass-5".to_owned())
+            ]),
+            &dblib::pm::get_passwords(db_path.clone())
+        );
+        assert_eq!(
+            passwords,
+            HashMap::from([
+                ("github (restored 2)".to_owned(), "pass-4".to_owned()),
+                ("email".to_owned(), "pass-5".to_owned())
+            ]),
+            "Keep-both passwords NOT match!!"
+        );
+        super::hash_map_to_pm_db(passwords, db_path.clone());
+        let mut saved: Vec<(String, String)> = dblib::pm::get_passwords(db_path.clone())
+            .into_iter()
+            .map(|pass| (pass.name, pass.password))
+            .collect();
+        saved.sort();
+        assert_eq!(
+            saved,
+            vec![
+                ("email".to_owned(), "pass-5".to_owned()),
+                ("github".to_owned(), "pass-1".to_owned()),
+                ("github (restored 2)".to_owned(), "pass-4".to_owned()),
+                ("github (restored)".to_owned(), "pass-2".to_owned()),
+                ("gitlab".to_owned(), "pass-3".to_owned())
+            ],
+            "Saved passwords NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn restore_dedupe() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/restore_dedupe");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let restored = HashMap::from([
+            ("github".to_owned(), "pass-1".to_owned()),
+            ("gitlab".to_owned(), "pass-x".to_owned()),
+            ("email".to_owned(), "pass-3".to_owned())
+        ]);
+        let strategies = [
+            (super::ConflictStrategy::Skip, vec![
+                ("email", "pass-3"), ("github", "pass-1"), ("gitlab", "pass-2")
+            ]),
+            (super::ConflictStrategy::Overwrite, vec![
+                ("email", "pass-3"), ("github", "pass-1"), ("gitlab", "pass-x")
+            ]),
+            (super::ConflictStrategy::KeepBoth, vec![
+                ("email", "pass-3"), ("github", "pass-1"), ("gitlab", "pass-2"), ("gitlab (restored)", "pass-x")
+            ])
+        ];
+        for (index, (strategy, expected)) in strategies.into_iter().enumerate() {
+            let db_path = temp_dir.join(format!("test-{}.db", index));
+            create_file(db_path.clone()).unwrap();
+            dblib::pm::create_passwords_table(db_path.clone());
+            dblib::pm::save_password(db_path.clone(), "github".to_owned(), "pass-1".to_owned());
+            dblib::pm::save_password(db_path.clone(), "gitlab".to_owned(), "pass-2".to_owned());
+
+            // Importing twice must NOT double the passwords.
+            for _ in 0..2 {
+                let result = super::dedupe(
+                    restored.clone(),
+                    &dblib::pm::get_passwords(db_path.clone()),
+                    strategy
+                );
+                super::save_dedupe(result, db_path.clone());
+            }
+            let mut saved: Vec<(String, String)> = dblib::pm::get_passwords(db_path.clone())
+                .into_iter()
+                .map(|pass| (pass.name, pass.password))
+                .collect();
+            saved.sort();
+            let expected: Vec<(String, String)> = expected
+                .into_iter()
+                .map(|(name, password)| (name.to_owned(), password.to_owned()))
+                .collect();
+            assert_eq!(saved, expected, "Deduped passwords of {:?} NOT match!!", strategy);
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn restore_preview() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/restore_preview");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        dblib::pm::save_password(db_path.clone(), "github".to_owned(), "pass-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "gitlab".to_owned(), "pass-2".to_owned());
+        let before = std::fs::read(&db_path).unwrap();
+
+        let entries: Vec<(String, DiffStatus)> = super::preview(
+            &HashMap::from([
+                ("github".to_owned(), "pass-1".to_owned()),
+                ("gitlab".to_owned(), "pass-x".to_owned()),
+                ("email".to_owned(), "pass-3".to_owned())
+            ]),
+            &dblib::pm::get_passwords(db_path.clone())
+        )
+            .into_iter()
+            .map(|entry| (entry.name, entry.status))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("email".to_owned(), DiffStatus::OnlyTheirs),
+                ("github".to_owned(), DiffStatus::Same),
+                ("gitlab".to_owned(), DiffStatus::Changed)
+            ],
+            "Preview NOT match!!"
+        );
+        assert_eq!(std::fs::read(&db_path).unwrap(), before, "Preview changed the database!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
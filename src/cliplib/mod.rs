@@ -0,0 +1,292 @@
+use arboard::Clipboard;
+use std::{
+    thread,
+    time::Duration
+};
+use crate::{
+    errorlib,
+    loglib,
+    utilities
+};
+
+/// Seconds to wait before clearing the clipboard, the default of `--clear-after`.
+pub const CLEAR_AFTER_SECS: u64 = 15;
+
+/// The countdown before clearing the clipboard is logged every
+/// `COUNTDOWN_STEP_SECS` seconds, and every second at the end.
+const COUNTDOWN_STEP_SECS: u64 = 5;
+
+/// Get the seconds to wait before clearing the clipboard from
+/// `--clear-after`, default is `CLEAR_AFTER_SECS`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// 
+/// ### Example:
+/// ```
+/// let clear_after = cliplib::resolve_clear_after(command.get_one::<String>("clear-after"));
+/// ```
+pub fn resolve_clear_after(flag: Option<&String>) -> u64 {
+    let logger = loglib::Logger::new("clipboard");
+    match flag {
+        Some(secs) => secs.parse::<u64>()
+            .ok()
+            .filter(|secs| *secs > 0)
+            .unwrap_or_else(|| logger.error(
+                "--clear-after must be a positive number of seconds!",
+                errorlib::ExitErrorCode::Input
+            )),
+        None => CLEAR_AFTER_SECS
+    }
+}
+
+/// Check if the countdown logs the remaining seconds.
+fn is_countdown_step(remaining: u64) -> bool {
+    remaining.is_multiple_of(COUNTDOWN_STEP_SECS) || remaining <= 3
+}
+
+/// When the copied value can be cleared from the clipboard.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ClearPolicy {
+    /// Clear after the timeout.
+    Timeout,
+    /// Clear after the timeout, only if the user confirms that the
+    /// value is saved (the clipboard may have the only copy).
+    Confirm,
+    /// Never clear, keep the value until the next copy replaces it.
+    Keep
+}
+
+/// The clear decision for the current clipboard content.
+#[derive(PartialEq, Debug)]
+pub enum ClearDecision {
+    /// Clear the clipboard.
+    Clear,
+    /// Ask the user to confirm first.
+    WaitConfirmation,
+    /// The user did NOT confirm, keep the value until the next copy
+    /// replaces it (it may be the only copy).
+    Keep,
+    /// Leave the clipboard as it is.
+    Skip
+}
+
+/// Decide when the clipboard is cleared, it never clears a value
+/// that the user copied after us.
+pub struct ClearCoordinator {
+    copied: String,
+    policy: ClearPolicy,
+    is_confirmed: bool,
+    is_declined: bool
+}
+
+impl ClearCoordinator {
+    pub fn new(copied: String, policy: ClearPolicy) -> ClearCoordinator {
+        ClearCoordinator {
+            copied,
+            policy,
+            is_confirmed: false,
+            is_declined: false
+        }
+    }
+
+    /// The user confirmed the value is saved.
+    pub fn confirm(&mut self) {
+        self.is_confirmed = true;
+    }
+
+    /// The user did NOT confirm the value is saved.
+    pub fn decline(&mut self) {
+        self.is_declined = true;
+    }
+
+    /// Get the decision for the current clipboard content.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let coordinator = ClearCoordinator::new("pass123".to_owned(), ClearPolicy::Timeout);
+    /// assert_eq!(coordinator.decide(Some("pass123")), ClearDecision::Clear);
+    /// assert_eq!(coordinator.decide(Some("other")), ClearDecision::Skip);
+    /// ```
+    pub fn decide(&self, current: Option<&str>) -> ClearDecision {
+        if current != Some(self.copied.as_str()) || self.policy == ClearPolicy::Keep {
+            ClearDecision::Skip
+        } else if self.policy == ClearPolicy::Confirm && self.is_declined {
+            ClearDecision::Keep
+        } else if self.policy == ClearPolicy::Confirm && !self.is_confirmed {
+            ClearDecision::WaitConfirmation
+        } else {
+            ClearDecision::Clear
+        }
+    }
+}
+
+/// Open the system clipboard.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Clipboard`
+fn open() -> Clipboard {
+    let logger = loglib::Logger::new("clipboard");
+    if let Ok(clipboard) = Clipboard::new() {
+        return clipboard;
+    }
+    logger.error(
+        "can NOT open the system clipboard!",
+        errorlib::ExitErrorCode::Clipboard
+    );
+}
+
+/// Copy the text to the clipboard, keep it until the next copy
+/// replaces it. On Linux the clipboard content is owned by the
+/// process, so it waits until another application takes it.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Clipboard`
+fn copy_and_keep(text: String) {
+    let logger = loglib::Logger::new("clipboard");
+    let mut clipboard = open();
+    #[cfg(target_os = "linux")]
+    let result = {
+        use arboard::SetExtLinux;
+        logger.info("waiting for the next copy to replace the password...");
+        clipboard.set().wait().text(text)
+    };
+    #[cfg(not(target_os = "linux"))]
+    let result = clipboard.set_text(text);
+    if result.is_err() {
+        logger.error(
+            "can NOT copy to the clipboard!",
+            errorlib::ExitErrorCode::Clipboard
+        );
+    }
+}
+
+/// Copy the text to the clipboard, then clear it after `clear_after`
+/// seconds based on the policy. The wait has a countdown, and it stops
+/// early if the clipboard content changed (it is NOT cleared then).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Clipboard`
+/// 
+/// ### Example:
+/// ```
+/// cliplib::copy("pass123".to_owned(), cliplib::ClearPolicy::Confirm, cliplib::CLEAR_AFTER_SECS);
+/// ```
+pub fn copy(text: String, policy: ClearPolicy, clear_after: u64) {
+    let mut logger = loglib::Logger::new("clipboard");
+    if policy == ClearPolicy::Keep {
+        copy_and_keep(text);
+        return;
+    }
+    let mut clipboard = open();
+    if clipboard.set_text(text.clone()).is_err() {
+        logger.error(
+            "can NOT copy to the clipboard!",
+            errorlib::ExitErrorCode::Clipboard
+        );
+    }
+    logger.info(
+        &format!("copied to the clipboard, it will be cleared after {}s.", clear_after)
+    );
+    let mut coordinator = ClearCoordinator::new(text.clone(), policy);
+    for remaining in (1..=clear_after).rev() {
+        if remaining < clear_after && is_countdown_step(remaining) {
+            logger.info(&format!("clearing the clipboard in {}s...", remaining));
+        }
+        thread::sleep(Duration::from_secs(1));
+        if coordinator.decide(clipboard.get_text().ok().as_deref()) == ClearDecision::Skip {
+            break;
+        }
+    }
+    let mut decision = coordinator.decide(clipboard.get_text().ok().as_deref());
+    if decision == ClearDecision::WaitConfirmation {
+        let answer = utilities::input("Did you save the password? (y/N): ");
+        logger.start();
+        if answer.trim().to_lowercase() == "y" {
+            coordinator.confirm();
+        } else {
+            coordinator.decline();
+        }
+        decision = coordinator.decide(clipboard.get_text().ok().as_deref());
+    }
+    match decision {
+        ClearDecision::Clear => {
+            if clipboard.clear().is_err() {
+                logger.error(
+                    "can NOT clear the clipboard!",
+                    errorlib::ExitErrorCode::Clipboard
+                );
+            }
+            logger.info("clipboard cleared successfully.");
+        },
+        ClearDecision::WaitConfirmation | ClearDecision::Keep => {
+            logger.warning("the clipboard is NOT cleared, the password is still in it!");
+            // The process owns the clipboard content on Linux, keep it after the exit.
+            drop(clipboard);
+            copy_and_keep(text);
+        },
+        ClearDecision::Skip => {
+            logger.info("the clipboard changed, it is NOT cleared.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClearCoordinator, ClearDecision, ClearPolicy};
+
+    #[test]
+    fn clear_after_confirmation() {
+        let mut coordinator = ClearCoordinator::new("pass123".to_owned(), ClearPolicy::Confirm);
+        assert_eq!(
+            coordinator.decide(Some("pass123")),
+            ClearDecision::WaitConfirmation,
+            "Cleared befor the confirmation!!"
+        );
+        coordinator.confirm();
+        assert_eq!(
+            coordinator.decide(Some("pass123")),
+            ClearDecision::Clear,
+            "Not cleared after the confirmation!!"
+        );
+        assert_eq!(
+            coordinator.decide(Some("other")),
+            ClearDecision::Skip,
+            "Cleared a changed clipboard!!"
+        );
+    }
+
+    #[test]
+    fn keep_after_declined_confirmation() {
+        let mut coordinator = ClearCoordinator::new("pass123".to_owned(), ClearPolicy::Confirm);
+        coordinator.decline();
+        assert_eq!(
+            coordinator.decide(Some("pass123")),
+            ClearDecision::Keep,
+            "Declined value NOT kept!!"
+        );
+        assert_eq!(
+            coordinator.decide(Some("other")),
+            ClearDecision::Skip,
+            "Kept a changed clipboard!!"
+        );
+    }
+
+    #[test]
+    fn countdown_steps() {
+        let steps: Vec<u64> = (1..15).rev()
+            .filter(|remaining| super::is_countdown_step(*remaining))
+            .collect();
+        assert_eq!(steps, vec![10, 5, 3, 2, 1], "Countdown steps NOT match!!");
+    }
+
+    #[test]
+    fn keep_until_next_copy() {
+        let coordinator = ClearCoordinator::new("pass123".to_owned(), ClearPolicy::Keep);
+        assert_eq!(
+            coordinator.decide(Some("pass123")),
+            ClearDecision::Skip,
+            "Cleared a kept value!!"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{arg, Command};
+use clap::{arg, ArgAction, Command};
 
 pub fn commands() -> Vec<Command> {
     vec![
@@ -10,31 +10,105 @@ pub fn commands() -> Vec<Command> {
                     .alias("g")
                     .about("Generate new password as ASCII/HEX.")
                     .args([
-                        arg!([LENGTH]             "Password length (e.g. 128)."),
-                        arg!(--hex                "Password as hexadecimal."),
+                        arg!([LENGTH]             "Password length (e.g. 128), random if NOT set."),
+                        arg!(--"min-length" <N>   "The min random length (default: 32).")
+                            .conflicts_with_all(["LENGTH", "policy"]),
+                        arg!(--"max-length" <N>   "The max random length (default: 72).")
+                            .conflicts_with_all(["LENGTH", "policy"]),
+                        arg!(--ascii              "Password as letters, numbers, and symbols (default).")
+                            .conflicts_with_all(["hex", "no-symbols"]),
+                        arg!(--hex                "Password as hexadecimal.")
+                            .conflicts_with("no-symbols"),
                         arg!(--"no-symbols"       "Password as letters and numbers (a-z, A-Z, 1-0)."),
                         arg!(--"add-set" <STRING> "Add custom set to the samples."),
                         arg!(--custom    <STRING> "Generate from custom samples."),
-                        arg!(--save      <NAME>   "Save the password (e.g. \"mohaned2023 github\").")
+                        arg!(--"no-ambiguous"     "Remove the look-alike characters (e.g. 0/O, 1/l/I) from the sample."),
+                        arg!(--"min-symbols" <N>  "At least N symbols in the password."),
+                        arg!(--"no-repeats"       "No character twice in a row (e.g. \"aa\")."),
+                        arg!(--"max-occurrences" <N> "Any character at most N times in the password."),
+                        arg!(--"require-classes"  "At least one lowercase, uppercase, digit, and symbol in the ASCII password.")
+                            .conflicts_with_all(["hex", "no-symbols", "custom", "min-symbols", "no-repeats", "max-occurrences", "policy"]),
+                        arg!(--profile   <NAME>   "Use the config profile '[generate.<NAME>]' instead of '[generate.default]'."),
+                        arg!(--policy    <NAME>   "Follow a site policy (legacy-bank, alphanumeric, short-symbols, strict) or the config '[policy.<NAME>]'.")
+                            .conflicts_with_all(["ascii", "hex", "no-symbols", "add-set", "custom", "min-symbols", "no-repeats", "max-occurrences"]),
+                        arg!(--count     <N>      "Generate N passwords, a password per line (plain if the output is NOT a terminal).")
+                            .conflicts_with_all(["save", "clipboard", "confirm-saved", "keep-clipboard"]),
+                        arg!(--save      <NAME>   "Save the password (e.g. \"mohaned2023 github\")."),
+                        arg!(--format    <FORMAT> "Output format (pretty, plain, export).")
+                            .value_parser(["pretty", "plain", "export"]),
+                        arg!(--clipboard          "Copy the password to the clipboard instead of printing it."),
+                        arg!(--"confirm-saved"    "Clear the clipboard only after you confirm the password is saved."),
+                        arg!(--"keep-clipboard"   "Keep the password in the clipboard until the next copy.")
+                            .conflicts_with("confirm-saved"),
+                        arg!(--"clear-after" <SECS> "Clear the clipboard after SECS seconds (default: 15).")
+                            .requires("clipboard")
+                            .conflicts_with("keep-clipboard")
                     ]),
                 Command::new("save")
                     .about("Save custom password in the database.")
-                    .arg(arg!(<NAME> "Password name (e.g. \"mohaned2023 github\").")),
+                    .args([
+                        arg!([NAME]            "Password name (e.g. \"mohaned2023 github\").")
+                            .required_unless_present("batch"),
+                        arg!(--batch <PATH>    "Save many passwords from a file, each line is 'name=password'.")
+                            .conflicts_with("NAME"),
+                        arg!(--icon <ICON>     "A short visual hint shown in the table (e.g. an emoji).")
+                            .conflicts_with("batch"),
+                        arg!(--"on-error" <MODE> "Stop at the first malformed line, or continue and report them at the end (default).")
+                            .value_parser(["stop", "continue"])
+                            .requires("batch")
+                    ]),
                 Command::new("find")
                     .about("Search for password in the database.")
-                    .arg(arg!(<STRING> "String in the password name (e.g. \"github\").")),
+                    .args([
+                        arg!(<STRING>         "String in the password name (e.g. \"github\")."),
+                        arg!(--exact          "Match the whole name (case-insensitively), not a part of it."),
+                        arg!(--fold           "Ignore the case and the accents (e.g. \"cafe\" finds \"Café\")."),
+                        arg!(--"count-only"   "Print the number of matched passwords only."),
+                        arg!(--"group-by" <FIELD> "Group the passwords under headers (tag).")
+                            .value_parser(["tag"])
+                            .conflicts_with("count-only"),
+                        arg!(--print0 [FIELD]  "Print the matched names (or ids with '--print0=id') NUL delimited, for 'xargs -0'.")
+                            .value_parser(["name", "id"])
+                            .default_missing_value("name")
+                            .conflicts_with_all(["count-only", "group-by"]),
+                        arg!(--format <FORMAT> "Output format (table, list, json, plain), default is table.")
+                            .value_parser(["table", "list", "json", "plain"])
+                            .conflicts_with_all(["count-only", "group-by", "print0"]),
+                        arg!(--sort <FIELD>   "Passwords order (id, name, update), default from the config 'passwords.sort'.")
+                            .value_parser(["id", "name", "update"])
+                    ]),
                 Command::new("show")
                     .about("Display all passwords in the database.")
-                    .arg(arg!(-t --table "Show as table.")),
+                    .args([
+                        arg!(-t --table       "Show as table, same as '--format table'."),
+                        arg!(--format <FORMAT> "Output format (table, list, json, plain), default is table.")
+                            .value_parser(["table", "list", "json", "plain"])
+                            .conflicts_with("table"),
+                        arg!(--fields <FIELDS> "Table columns in order (icon, id, name, tags, created, updated), e.g. \"id,name,updated\".")
+                            .conflicts_with("group-by"),
+                        arg!(--"group-by" <FIELD> "Group the passwords under headers (tag).")
+                            .value_parser(["tag"])
+                            .conflicts_with_all(["table", "format"]),
+                        arg!(--sort <FIELD>   "Passwords order (id, name, update), default from the config 'passwords.sort'.")
+                            .value_parser(["id", "name", "update"]),
+                        arg!(--clipboard <ID> "Copy the password of the id to the clipboard instead of printing the passwords.")
+                            .conflicts_with_all(["table", "format", "fields", "group-by", "sort"]),
+                        arg!(--"clear-after" <SECS> "Clear the clipboard after SECS seconds (default: 15).")
+                            .requires("clipboard")
+                    ]),
                 Command::new("count")
-                    .about("Get the number of passwords you saved in the database."),
+                    .about("Get the number of passwords you saved in the database.")
+                    .args([
+                        arg!(--"by-tag" "Count the passwords of each tag, a password with many tags is counted in each.")
+                    ]),
                 Command::new("update")
                     .alias("up")
                     .about("Update the password information.")
                     .args([
                         arg!(<ID>          "Password id (e.g. 23)."),
                         arg!(-n --name     "Update the name."),
-                        arg!(-p --password "Update the password.")
+                        arg!(-p --password "Update the password."),
+                        arg!(-i --icon <ICON> "Update the icon, empty to remove it (e.g. \"🐙\").")
                     ]),
                 Command::new("delete")
                     .about("Delete specific password from the database.")
@@ -43,11 +117,67 @@ pub fn commands() -> Vec<Command> {
                     .alias("en")
                     .about("Encrypt the password manager database.")
                     .args([
-                        arg!(--key    "Use custom key.")
+                        arg!(--key    "Use custom key."),
+                        arg!(--passphrase "Use a passphrase, the key is derived from it (Argon2id).")
+                            .conflicts_with("key"),
+                        arg!(--"key-file" <PATH> "Read the key from the first line of a file, for the unattended backups.")
+                            .conflicts_with_all(["key", "passphrase"])
                     ]),
                 Command::new("decrypt")
                     .alias("de")
-                    .about("Decrypt the password manager database."),
+                    .about("Decrypt the password manager database.")
+                    .args([
+                        arg!(--"key-file" <PATH> "Read the key from the first line of a file, instead of asking it.")
+                    ]),
+                Command::new("strength")
+                    .about("Check the password strength.")
+                    .args([
+                        arg!([PASSWORD] "The password, asked if it is NOT set (NOT saved in the shell history)."),
+                        arg!(--explain  "List the weaknesses found with suggestions.")
+                    ]),
+                Command::new("rotate")
+                    .about("Give the breached or reused passwords new generated passwords.")
+                    .args([
+                        arg!(--breached <PATH> "Rotate the passwords in a breached passwords list, a password per line."),
+                        arg!(--reused          "Rotate the passwords used by many entries.")
+                    ]),
+                Command::new("verify-key")
+                    .about("Check the key decrypts the password manager database, without decrypting it."),
+                Command::new("migrate-timestamps")
+                    .about("Convert the old local timestamps to UTC, for the config 'passwords.utc'.")
+                    .args([
+                        arg!(--"from-offset" <OFFSET> "The UTC offset of the old timestamps (e.g. \"+03:00\").")
+                            .required(true)
+                    ]),
+                Command::new("export")
+                    .about("Export the passwords as a JSON file (NOT encrypted).")
+                    .args([
+                        arg!(--out <PATH> "Output file path (e.g. \"/home/user/passwords.json\")."),
+                        arg!(--force      "Allow writing the passwords to the terminal."),
+                        arg!(--"since-id" <ID> "Export only the passwords with id greater than ID (the previous export 'max_id')."),
+                        arg!(--fields <LIST> "Export only these fields, never the passwords (icon, id, name, tags, created, updated)."),
+                        arg!(--format <FORMAT> "The --fields output format (default: json).")
+                            .value_parser(["json", "csv"])
+                            .requires("fields")
+                    ]),
+                Command::new("tag")
+                    .about("Add/Remove a tag for many passwords.")
+                    .subcommands([
+                        Command::new("add")
+                            .about("Add the tag to all matched passwords.")
+                            .args([
+                                arg!(<TAG>            "The tag (e.g. \"work\")."),
+                                arg!(--name <STRING>  "String in the password name (e.g. \"github\").")
+                                    .required(true)
+                            ]),
+                        Command::new("remove")
+                            .about("Remove the tag from all matched passwords.")
+                            .args([
+                                arg!(<TAG>            "The tag (e.g. \"work\")."),
+                                arg!(--name <STRING>  "String in the password name (e.g. \"github\").")
+                                    .required(true)
+                            ])
+                    ]),
             ]),
         Command::new("encryption-manager")
             .alias("em")
@@ -57,9 +187,38 @@ pub fn commands() -> Vec<Command> {
                     .alias("enf")
                     .about("Encrypt file.")
                     .args([
-                        arg!(<PATH>   "File path (e.g. \"/home/user/important/image.png\")."),
+                        arg!([PATH]...  "File paths, encrypted with one key (e.g. \"/home/user/important/image.png\").")
+                            .required_unless_present("stdin"),
                         arg!(--key    "Use custom key."),
-                        arg!(--delete "Delete the origin file.")
+                        arg!(--keyring [ACCOUNT] "Use the key saved in the OS keyring with 'key store'.")
+                            .default_missing_value(crate::keyringlib::DEFAULT_ACCOUNT)
+                            .conflicts_with("key"),
+                        arg!(--"key-file" <PATH> "Read the key from the first line of a file, instead of asking it.")
+                            .alias("input-file")
+                            .conflicts_with("keyring"),
+                        arg!(--"key-stdin" "Read the key from the first line of the stdin (e.g. a pipe), instead of asking it.")
+                            .conflicts_with_all(["keyring", "key-file"]),
+                        arg!(--delete "Delete the origin file."),
+                        arg!(--fsync  "Sync the encrypted files to the disk before exiting, always on with --delete."),
+                        arg!(--"name-from-path" "Save the key in the password manager, named after the file path."),
+                        arg!(--out <DIR> "Write the encrypted files to this directory (e.g. a sync folder) and check they decrypt, the encrypted file path with --stdin."),
+                        arg!(--stdin "Encrypt the data from the stdin (e.g. a pipe) to the file at --out, instead of the files.")
+                            .requires("out")
+                            .conflicts_with_all(["PATH", "key", "key-stdin", "delete", "name-from-path", "age-recipient"]),
+                        arg!(--compress "Compress the file with zstd before the encryption, like '--compress-algo zstd'.")
+                            .conflicts_with("compress-algo"),
+                        arg!(--"compress-algo" <ALGO>  "Compress the file before the encryption (gzip, zstd).")
+                            .value_parser(["gzip", "zstd"]),
+                        arg!(--"compress-level" <LEVEL> "Compression level (gzip: 0-9, zstd: 1-22)."),
+                        arg!(--"age-recipient" <PUBKEY> "Encrypt to an age public key (age1...), decrypt it with --age-identity.")
+                            .conflicts_with_all(["key", "keyring", "key-file", "key-stdin", "name-from-path", "compress", "compress-algo", "compress-level", "out"]),
+                        arg!(--"dry-wipe"   "Show the wipe passes and bytes of --delete, without writing anything.")
+                            .requires("delete"),
+                        arg!(--"wipe-method" <METHOD> "Wipe method of --delete (simple, dod, gutmann), default from the config 'wipe.method'.")
+                            .value_parser(["simple", "dod", "gutmann"])
+                            .requires("delete"),
+                        arg!(--passes <N>   "Wipe passes of --delete, default from the config 'wipe.passes'.")
+                            .requires("delete")
                     ]),
                 Command::new("decrypt-file")
                     .alias("def")
@@ -67,7 +226,26 @@ pub fn commands() -> Vec<Command> {
                     .args([
                         arg!(<PATH>   "File path (e.g. \"/home/user/important/image.png.x\")."),
                         arg!(--delete "Delete the origin file."),
-                        arg!(--xpmv1  "Decrypt XPManager v1.0 file.")
+                        arg!(--force  "Overwrite the decrypted file if it exists, or allow --stdout to the terminal, without the confirmation."),
+                        arg!(--xpmv1  "Decrypt XPManager v1.0 file."),
+                        arg!(--stdout "Write the decrypted data to the stdout (e.g. a pipe), without creating a file.")
+                            .conflicts_with_all(["delete", "xpmv1", "age-identity"]),
+                        arg!(--keyring [ACCOUNT] "Use the key saved in the OS keyring with 'key store'.")
+                            .default_missing_value(crate::keyringlib::DEFAULT_ACCOUNT),
+                        arg!(--"key-file" <PATH> "Read the key from the first line of a file, instead of asking it.")
+                            .alias("input-file")
+                            .conflicts_with_all(["keyring", "xpmv1"]),
+                        arg!(--"key-stdin" "Read the key from the first line of the stdin (e.g. a pipe), instead of asking it.")
+                            .conflicts_with_all(["keyring", "key-file"]),
+                        arg!(--"age-identity" <KEYFILE> "Decrypt an age file using the private keys file.")
+                            .conflicts_with_all(["xpmv1", "keyring", "key-file", "key-stdin"]),
+                        arg!(--"dry-wipe"   "Show the wipe passes and bytes of --delete, without writing anything.")
+                            .requires("delete"),
+                        arg!(--"wipe-method" <METHOD> "Wipe method of --delete (simple, dod, gutmann), default from the config 'wipe.method'.")
+                            .value_parser(["simple", "dod", "gutmann"])
+                            .requires("delete"),
+                        arg!(--passes <N>   "Wipe passes of --delete, default from the config 'wipe.passes'.")
+                            .requires("delete")
                     ]),
                 Command::new("encrypt-dir")
                     .alias("end")
@@ -76,7 +254,35 @@ pub fn commands() -> Vec<Command> {
                         arg!(<PATH>         "Directory path (e.g. \"/home/user/important\")."),
                         arg!(--delete       "Delete the origin files in the directory."),
                         arg!(--"no-threads" "Encrypt directory using the main thread only."),
-                        arg!(--key          "Use custom key.")
+                        arg!(--key          "Use custom key."),
+                        arg!(--keyring [ACCOUNT] "Use the key saved in the OS keyring with 'key store'.")
+                            .default_missing_value(crate::keyringlib::DEFAULT_ACCOUNT)
+                            .conflicts_with("key"),
+                        arg!(--"key-file" <PATH> "Read the key from the first line of a file, instead of asking it.")
+                            .alias("input-file")
+                            .conflicts_with("keyring"),
+                        arg!(--"key-stdin" "Read the key from the first line of the stdin (e.g. a pipe), instead of asking it.")
+                            .conflicts_with_all(["keyring", "key-file"]),
+                        arg!(--archive      "Encrypt the directory as one tar archive '<PATH>.tar.x', decrypt it with 'decrypt-file'.")
+                            .conflicts_with_all(["delete", "no-threads"]),
+                        arg!(--"max-file-size" <BYTES> "Refuse archiving files bigger than BYTES, default from the config 'archive.max_file_size'.")
+                            .requires("archive"),
+                        arg!(--"on-error" <MODE> "Stop at the first failed file, or continue and report them at the end (default).")
+                            .value_parser(["stop", "continue"])
+                            .conflicts_with("archive"),
+                        arg!(--glob <PATTERN> "Encrypt only the files that match the pattern, can be repeated (e.g. \"**/*.pdf\").")
+                            .action(ArgAction::Append)
+                            .conflicts_with("archive"),
+                        arg!(--exclude <PATTERN> "Skip the files and the directories that match the pattern, can be repeated (e.g. \"**/node_modules\").")
+                            .action(ArgAction::Append)
+                            .conflicts_with("archive"),
+                        arg!(--"dry-wipe"   "Show the wipe passes and bytes of --delete, without writing anything.")
+                            .requires("delete"),
+                        arg!(--"wipe-method" <METHOD> "Wipe method of --delete (simple, dod, gutmann), default from the config 'wipe.method'.")
+                            .value_parser(["simple", "dod", "gutmann"])
+                            .requires("delete"),
+                        arg!(--passes <N>   "Wipe passes of --delete, default from the config 'wipe.passes'.")
+                            .requires("delete")
                     ]),
                 Command::new("decrypt-dir")
                     .alias("ded")
@@ -85,7 +291,40 @@ pub fn commands() -> Vec<Command> {
                         arg!(<PATH>         "Directory path (e.g. \"/home/user/important\")."),
                         arg!(--delete       "Delete the origin files in the directory."),
                         arg!(--"no-threads" "Decrypt directory using the main thread only."),
-                        arg!(--xpmv1        "Decrypt XPManager v1.0 directory.")
+                        arg!(--force        "Overwrite the decrypted files if they exist, without the confirmation."),
+                        arg!(--keyring [ACCOUNT] "Use the key saved in the OS keyring with 'key store'.")
+                            .default_missing_value(crate::keyringlib::DEFAULT_ACCOUNT),
+                        arg!(--"key-file" <PATH> "Read the key from the first line of a file, instead of asking it.")
+                            .alias("input-file")
+                            .conflicts_with("keyring"),
+                        arg!(--"key-stdin" "Read the key from the first line of the stdin (e.g. a pipe), instead of asking it.")
+                            .conflicts_with_all(["keyring", "key-file"]),
+                        arg!(--"on-error" <MODE> "Stop at the first failed file, or continue and report them at the end (default).")
+                            .value_parser(["stop", "continue"]),
+                        arg!(--xpmv1        "Decrypt XPManager v1.0 directory."),
+                        arg!(--out <DIR>    "Write the decrypted files to this directory (e.g. \"/home/user/decrypted\").")
+                            .conflicts_with("xpmv1"),
+                        arg!(--"preserve-structure" "Keep the subdirectories of the files under --out.")
+                            .requires("out"),
+                        arg!(--"dry-wipe"   "Show the wipe passes and bytes of --delete, without writing anything.")
+                            .requires("delete"),
+                        arg!(--"wipe-method" <METHOD> "Wipe method of --delete (simple, dod, gutmann), default from the config 'wipe.method'.")
+                            .value_parser(["simple", "dod", "gutmann"])
+                            .requires("delete"),
+                        arg!(--passes <N>   "Wipe passes of --delete, default from the config 'wipe.passes'.")
+                            .requires("delete")
+                    ]),
+                Command::new("wipe")
+                    .alias("wp")
+                    .about("Wipe and delete file or directory.")
+                    .args([
+                        arg!(<PATH>   "File or directory path (e.g. \"/home/user/important/image.png\")."),
+                        arg!(--resume "Resume a failed file wipe from the failed level, use the same method and passes."),
+                        arg!(--"wipe-method" <METHOD> "Wipe method (simple, dod: 3 passes, gutmann: 35 slow passes for the old magnetic disks), default from the config 'wipe.method'.")
+                            .value_parser(["simple", "dod", "gutmann"]),
+                        arg!(--passes <N>     "Wipe passes, default from the config 'wipe.passes'."),
+                        arg!(--"limit-rate" <RATE> "Max write rate on shared disks (e.g. \"50MB/s\", \"512KB/s\")."),
+                        arg!(--verify "Read back every pass and check it, default from the config 'wipe.verify'.")
                     ]),
                 Command::new("encode")
                     .alias("enc")
@@ -94,15 +333,21 @@ pub fn commands() -> Vec<Command> {
                         arg!(--xpmv1      "XPManager v1.0 key technique."),
                         arg!(--hex        "Hexadecimal."),
                         arg!(--"hex-hash" "Hash using hexadecimal."),
-                        arg!(--bin        "Binary.")
+                        arg!(--bin        "Binary."),
+                        arg!(--base64     "Base64."),
+                        arg!(--file <PATH> "Encode the file contents instead of a string, to hexadecimal or base64 (e.g. \"/home/user/key.bin\").")
+                            .conflicts_with_all(["xpmv1", "hex-hash", "bin"])
                     ]),
                 Command::new("decode")
                     .alias("dec")
                     .about("Decode strings using different techniques.")
                     .args([
                         arg!(--xpmv1 "XPManager v1.0 key technique."),
-                        arg!(--hex   "Hexadecimal."),
-                        arg!(--bin   "Binary.")
+                        arg!(--hex   "Hexadecimal code points separated by spaces (e.g. \"78 70 6D\")."),
+                        arg!(--bytes "With --hex, the UTF-8 bytes without spaces (e.g. \"78706D\", the 'encode --file' output).")
+                            .requires("hex"),
+                        arg!(--bin   "Binary."),
+                        arg!(--base64 "Base64.")
                     ])
             ]),
         Command::new("backup-manager")
@@ -114,7 +359,9 @@ pub fn commands() -> Vec<Command> {
                     .args([
                         arg!(<PATH>     "The directory you want to save to (e.g. \"/home/user/backup\")."),
                         arg!(--password "Password manager database."),
-                        arg!(--log      "Log manager database.")
+                        arg!(--log      "Log manager database."),
+                        arg!(--"limit-rate" <RATE> "Max write rate on shared disks (e.g. \"50MB/s\", \"512KB/s\")."),
+                        arg!(--verify   "Check the backup against the origin after the copy (e.g. to a sync folder).")
                     ]),
                 Command::new("restore")
                     .about("Restore passwords/logs database.")
@@ -123,7 +370,14 @@ pub fn commands() -> Vec<Command> {
                         arg!(--password        "Password manager database."),
                         arg!(--xpmv1           "XPManager v1.0 database."),
                         arg!(--"password-json" "Restore from a custom JSON file."),
-                        arg!(--log             "Log manager database.")
+                        arg!(--log             "Log manager database."),
+                        arg!(--replace         "Replace all the passwords instead of merging, after a backup of the database."),
+                        arg!(--"keep-both"     "Keep both passwords on a name conflict, the restored one is renamed '<name> (restored)'.")
+                            .conflicts_with("replace"),
+                        arg!(--dedupe          "Skip the already saved passwords, the conflicts follow the config 'restore.conflict' (skip, keep-both, overwrite).")
+                            .conflicts_with_all(["replace", "keep-both"]),
+                        arg!(--preview         "Show the passwords to add, the same, and the conflicts, without restoring anything.")
+                            .conflicts_with("replace")
                     ])
             ]),
         Command::new("log-manager")
@@ -135,7 +389,9 @@ pub fn commands() -> Vec<Command> {
                 Command::new("show")
                     .about("Display all/some logs.")
                     .args([
-                        arg!(-l --length <NUMBER> "Number of logs (e.g. 20).")
+                        arg!(-l --length <NUMBER> "Number of logs (e.g. 20)."),
+                        arg!(-a --action <TYPE>   "Show the logs of an action type only (e.g. encrypt).")
+                            .value_parser(crate::dblib::log::LogAction::ALL.map(|action| action.name()))
                     ]),
                 Command::new("find")
                     .about("Find logs based on date/string.")
@@ -155,7 +411,67 @@ pub fn commands() -> Vec<Command> {
                     .about("Delete single log by id.")
                     .args([
                         arg!(<ID>   "Log id.")
+                    ]),
+                Command::new("encrypt")
+                    .alias("en")
+                    .about("Encrypt the log manager database.")
+                    .args([
+                        arg!(--key    "Use custom key.")
+                    ]),
+                Command::new("decrypt")
+                    .alias("de")
+                    .about("Decrypt the log manager database.")
+            ]),
+        Command::new("browse")
+            .about("Browse, filter, reveal, and copy passwords in the terminal."),
+        Command::new("upgrade")
+            .about("Re-encrypt an old format encrypted file in the latest format.")
+            .args([
+                arg!(<PATH>        "Encrypted file path (e.g. \"/home/user/important/image.png.x\")."),
+                arg!(--key <KEY>   "The file key, it will be asked if NOT set."),
+                arg!(--"key-file" <PATH> "Read the key from the first line of a file, instead of asking it.")
+                    .conflicts_with("key"),
+                arg!(--"key-stdin" "Read the key from the first line of the stdin (e.g. a pipe), instead of asking it.")
+                    .conflicts_with_all(["key", "key-file"]),
+                arg!(--keyring [ACCOUNT] "Use the key saved in the OS keyring with 'key store'.")
+                    .default_missing_value(crate::keyringlib::DEFAULT_ACCOUNT)
+                    .conflicts_with_all(["key", "key-file", "key-stdin"])
+            ]),
+        Command::new("verify")
+            .about("Check an encrypted file decrypts with the key, without writing the plaintext.")
+            .args([
+                arg!(<PATH>        "Encrypted file path (e.g. \"/home/user/backups/image.png.x\")."),
+                arg!(--key <KEY>   "The file key, it will be asked if NOT set."),
+                arg!(--"key-file" <PATH> "Read the key from the first line of a file, instead of asking it.")
+                    .conflicts_with("key"),
+                arg!(--"key-stdin" "Read the key from the first line of the stdin (e.g. a pipe), instead of asking it.")
+                    .conflicts_with_all(["key", "key-file"]),
+                arg!(--keyring [ACCOUNT] "Use the key saved in the OS keyring with 'key store'.")
+                    .default_missing_value(crate::keyringlib::DEFAULT_ACCOUNT)
+                    .conflicts_with_all(["key", "key-file", "key-stdin"])
+            ]),
+        Command::new("forget")
+            .about("Delete the session cached key (config 'session.cache', the key is NOT encrypted until it expires)."),
+        Command::new("key")
+            .about("Save the encryption key in the OS keyring, use it with '--keyring'.")
+            .subcommands([
+                Command::new("store")
+                    .about("Save a key in the OS keyring, replaces the old key of the account.")
+                    .args([
+                        arg!(--account <NAME> "The keyring account name, default 'default' (e.g. \"work\").")
+                    ]),
+                Command::new("forget")
+                    .about("Delete a key from the OS keyring.")
+                    .args([
+                        arg!(--account <NAME> "The keyring account name, default 'default' (e.g. \"work\").")
                     ])
             ]),
+        Command::new("lock")
+            .about("Encrypt the password manager database with the cached key, then forget the key and clear the clipboard."),
+        Command::new("diff")
+            .about("Compare the passwords with another database, without changing anything.")
+            .args([
+                arg!(<PATH> "The other database path (e.g. \"/home/user/backup/passwords.db.x\").")
+            ]),
     ]
 }
\ No newline at end of file
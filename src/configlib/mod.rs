@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+use dirs::data_dir;
+use toml::{Table, Value};
+use crate::{
+    errorlib,
+    loglib
+};
+
+/// Get the config file path.
+/// It will return the config path in the user's data directory:
+/// - Linux: `/home/{user}/.local/share/XPManager/config.toml`
+/// - MacOS: `/Users/{user}/Library/Application Support/XPManager/config.toml`
+/// - Windows: `C:\Users\{user}\AppData\Roaming\XPManager\config.toml`
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::SystemDataDirNotFound`
+/// 
+/// ### Example:
+/// ```
+/// let config_path = configlib::get_config_path();
+/// println!("Config path: {}", config_path.display());
+/// ```
+pub fn get_config_path() -> PathBuf {
+    let logger = loglib::Logger::new("get-config-path");
+    if let Some(data_path) = data_dir() {
+        return data_path.join("XPManager/config.toml");
+    }
+    logger.error(
+        "can NOT get the system data directory path!",
+        errorlib::ExitErrorCode::SystemDataDirNotFound
+    );
+}
+
+/// The user config, a TOML file read once per command.
+/// 
+/// ### Example:
+/// ```toml
+/// [passwords]
+/// sort = "name"
+/// ```
+pub struct Config {
+    table: Table
+}
+
+impl Config {
+    /// Load the config from the user's config file,
+    /// the config is empty if the file NOT found.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// 
+    /// ### Example:
+    /// ```
+    /// let config = configlib::Config::load();
+    /// ```
+    pub fn load() -> Config {
+        Config::load_from(get_config_path())
+    }
+
+    /// Load the config from a file.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// 
+    /// ### Example:
+    /// ```
+    /// let config = configlib::Config::load_from(PathBuf::new().join("./config.toml"));
+    /// ```
+    pub fn load_from(path: PathBuf) -> Config {
+        let logger = loglib::Logger::new("load-config");
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        if let Ok(table) = contents.parse::<Table>() {
+            return Config { table };
+        }
+        logger.error(
+            &format!("invalid config file '{}'!", path.display()),
+            errorlib::ExitErrorCode::InvalidConfig
+        );
+    }
+
+    /// Get a value by its dotted key (e.g. `passwords.sort`).
+    fn get(&self, key: &str) -> Option<&Value> {
+        let mut keys = key.split('.');
+        let mut value = self.table.get(keys.next()?)?;
+        for key in keys {
+            value = value.as_table()?.get(key)?;
+        }
+        Some(value)
+    }
+
+    /// Get a string value by its dotted key.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// 
+    /// ### Example:
+    /// ```
+    /// let sort = config.get_str("passwords.sort").unwrap_or("id".to_owned());
+    /// ```
+    pub fn get_str(&self, key: &str) -> Option<String> {
+        let logger = loglib::Logger::new("config");
+        match self.get(key) {
+            Some(Value::String(value)) => Some(value.clone()),
+            Some(_) => logger.error(
+                &format!("config '{}' must be a string!", key),
+                errorlib::ExitErrorCode::InvalidConfig
+            ),
+            None => None
+        }
+    }
+
+    /// Get an integer value by its dotted key.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// 
+    /// ### Example:
+    /// ```
+    /// let length = config.get_int("generate.default.length").unwrap_or(32);
+    /// ```
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        let logger = loglib::Logger::new("config");
+        match self.get(key) {
+            Some(Value::Integer(value)) => Some(*value),
+            Some(_) => logger.error(
+                &format!("config '{}' must be an integer!", key),
+                errorlib::ExitErrorCode::InvalidConfig
+            ),
+            None => None
+        }
+    }
+
+    /// Get a boolean value by its dotted key.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// 
+    /// ### Example:
+    /// ```
+    /// let no_ambiguous = config.get_bool("generate.default.no_ambiguous").unwrap_or(false);
+    /// ```
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        let logger = loglib::Logger::new("config");
+        match self.get(key) {
+            Some(Value::Boolean(value)) => Some(*value),
+            Some(_) => logger.error(
+                &format!("config '{}' must be a boolean!", key),
+                errorlib::ExitErrorCode::InvalidConfig
+            ),
+            None => None
+        }
+    }
+
+    /// Check if a dotted key exists.
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::filelib::create_file;
+
+    #[test]
+    fn get_str() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/config_get_str");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let config_path = temp_dir.join("config.toml");
+        create_file(config_path.clone()).unwrap();
+        std::fs::write(&config_path, "[passwords]\nsort = \"name\"\n")
+            .expect("Can NOT write the test config!!");
+
+        let config = super::Config::load_from(config_path);
+        assert_eq!(config.get_str("passwords.sort"), Some("name".to_owned()), "Config value NOT match!!");
+        assert_eq!(config.get_str("passwords.missing"), None, "Missing config NOT match!!");
+        let empty = super::Config::load_from(temp_dir.join("not-found.toml"));
+        assert_eq!(empty.get_str("passwords.sort"), None, "Empty config NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
@@ -14,7 +14,89 @@ use crate::{
 pub struct LogInfoForamt {
     pub id: u32,
     pub log: String,
-    pub create_at: String
+    pub create_at: String,
+    /// The `LogAction` name, `unknown` for the old logs.
+    pub action_type: String
+}
+
+/// The logged action type, to filter and count the logs.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum LogAction {
+    Create,
+    Save,
+    Update,
+    Delete,
+    Export,
+    Encrypt,
+    Decrypt,
+    Wipe,
+    Backup,
+    Restore
+}
+
+impl LogAction {
+    /// All the action types.
+    pub const ALL: [LogAction; 10] = [
+        LogAction::Create,
+        LogAction::Save,
+        LogAction::Update,
+        LogAction::Delete,
+        LogAction::Export,
+        LogAction::Encrypt,
+        LogAction::Decrypt,
+        LogAction::Wipe,
+        LogAction::Backup,
+        LogAction::Restore
+    ];
+
+    /// The action type name, stored in the database.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogAction::Create => "create",
+            LogAction::Save => "save",
+            LogAction::Update => "update",
+            LogAction::Delete => "delete",
+            LogAction::Export => "export",
+            LogAction::Encrypt => "encrypt",
+            LogAction::Decrypt => "decrypt",
+            LogAction::Wipe => "wipe",
+            LogAction::Backup => "backup",
+            LogAction::Restore => "restore"
+        }
+    }
+
+    /// Get the action type from its name (e.g. encrypt).
+    pub fn from_name(name: &str) -> Option<LogAction> {
+        LogAction::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// The logs columns, the old logs have NO action type.
+const LOG_COLUMNS: &str = "id, log, create_at, IFNULL(action_type, 'unknown')";
+
+/// Add the `action_type` column to the logs table if it is an
+/// old table, the old logs action type is NULL.
+/// 
+/// ### Example:
+/// ```
+/// migrate_action_type(&conn);
+/// ```
+fn migrate_action_type(conn: &Connection) {
+    if conn.prepare("SELECT action_type FROM logs LIMIT 0").is_err() {
+        let _ = conn.execute("ALTER TABLE logs ADD COLUMN action_type TEXT", []);
+    }
+}
+
+/// Read a log row selected with `LOG_COLUMNS`.
+fn log_from_row(row: &rusqlite::Row) -> rusqlite::Result<LogInfoForamt> {
+    Ok(
+        LogInfoForamt {
+            id: row.get::<_, u32>(0)?,
+            log: row.get::<_, String>(1)?,
+            create_at: row.get::<_, String>(2)?,
+            action_type: row.get::<_, String>(3)?
+        }
+    )
 }
 
 /// Create the log table in the database.
@@ -35,11 +117,12 @@ fn create_log_table(log_db_path: PathBuf) {
             CREATE TABLE IF NOT EXISTS logs(
                 id INTEGER PRIMARY KEY,
                 log TEXT NOT NULL,
-                create_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                create_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                action_type TEXT
             )
         ", []) {
             conn.close().unwrap();
-            filelib::delete_file(log_db_path);
+            filelib::delete_file(log_db_path).unwrap_or_else(|err| err.exit());
             logger.error(
                 "can NOT create the logs table!", 
                 errorlib::ExitErrorCode::DBCreateTable
@@ -57,43 +140,45 @@ fn create_log_table(log_db_path: PathBuf) {
     }
 }
 
-/// Register log.
+/// Register log with its action type. The log is skipped with a
+/// warning if the log database is encrypted, the callers (e.g. in the
+/// middle of a password manager command) must NOT exit.
 /// 
 /// ### Exit:
-/// - `errorlib::ExitErrorCode::LMDatabaseEncrypted`
 /// - `errorlib::ExitErrorCode::DBInsert`
 /// - `errorlib::ExitErrorCode::DBConnection`
 /// 
 /// ### Example:
 /// ```
 /// let log_db_path = PathBuf::new().join("./dir/logs.db");
-/// dblib::log::register("'XPManager' seved.".to_string(), log_db_path);
+/// dblib::log::register("'XPManager' seved.", dblib::log::LogAction::Save, log_db_path);
 /// ```
-pub fn register(log: &str, log_db_path: PathBuf) {
+pub fn register(log: &str, action: LogAction, log_db_path: PathBuf) {
     let logger = loglib::Logger::new("register-log");
     let log_db_path_str = log_db_path.to_str().unwrap().to_string();
-    let mut log_db_state = filelib::get_file_state(log_db_path_str.clone());
+
+    // check db is not encrypted, befor creating a new one.
+    if filelib::log::is_encrypted(&log_db_path) {
+        logger.warning("the log manager database is encrypted, the log is NOT registered!");
+        return;
+    }
 
     // cerate the db file and the logs table
+    let log_db_state = filelib::get_file_state(log_db_path_str);
     if log_db_state == filelib::FileState::NotFound {
-        filelib::create_file(log_db_path.clone());
+        filelib::create_file(log_db_path.clone()).unwrap_or_else(|err| err.exit());
         create_log_table(log_db_path.clone());
     } 
 
-    // check db is not encrypted
-    log_db_state = filelib::get_file_state(log_db_path_str);
-    if log_db_state == filelib::FileState::Encrypted {
-        logger.error(
-            "Your log manager database is encrypted, Please decrypt it and try again!!",
-            errorlib::ExitErrorCode::LMDatabaseEncrypted
-        );
-    }
-
     // register the log
     if let Ok(conn) = Connection::open(&log_db_path) {
-        if let Err(_) = conn.execute("INSERT INTO logs (log) VALUES (?1)", [log]) {
+        migrate_action_type(&conn);
+        if conn.execute(
+            "INSERT INTO logs (log, action_type) VALUES (?1, ?2)",
+            [log, action.name()]
+        ).is_err() {
             conn.close().unwrap();
-            filelib::delete_file(log_db_path);
+            filelib::delete_file(log_db_path).unwrap_or_else(|err| err.exit());
             logger.error(
                 "can NOT insert into the logs table!", 
                 errorlib::ExitErrorCode::DBInsert
@@ -167,37 +252,32 @@ pub fn delete_all(log_db_path: PathBuf) -> usize {
 pub fn get_logs(log_db_path: PathBuf, length: u16, string: String) -> Vec<LogInfoForamt> {
     let logger = loglib::Logger::new("get-logs");
     if let Ok(conn) = Connection::open(&log_db_path) {
+        migrate_action_type(&conn);
         let sql = if length > 0 {
             &format!("
-                    SELECT id, log, create_at 
+                    SELECT {} 
                     FROM logs
                     ORDER BY create_at DESC, id DESC
                     LIMIT {}
                 ", 
+                LOG_COLUMNS,
                 length
             )
         } else if string.len() > 0 {
             &format!("
-                    SELECT id, log, create_at 
+                    SELECT {} 
                     FROM logs
                     WHERE log LIKE '%{}%'
                 ", 
+                LOG_COLUMNS,
                 string
             )
         } else {
-            "SELECT id, log, create_at FROM logs"
+            &format!("SELECT {} FROM logs", LOG_COLUMNS)
         };
         let e = conn.prepare(sql);
         if let Ok(mut stmt) = e {
-            let password: Result<Vec<LogInfoForamt>, _> = stmt.query_map([], |row| {
-                Ok(
-                    LogInfoForamt {
-                        id: row.get::<_, u32>(0).unwrap(),
-                        log: row.get::<_, String>(1).unwrap(),
-                        create_at: row.get::<_, String>(2).unwrap(),
-                    }
-                )
-            })
+            let password: Result<Vec<LogInfoForamt>, _> = stmt.query_map([], log_from_row)
                 .unwrap()
                 .collect();
             return password.unwrap();
@@ -212,6 +292,52 @@ pub fn get_logs(log_db_path: PathBuf, length: u16, string: String) -> Vec<LogInf
     );
 }
 
+/// Get logs by the action type, the last `length` logs
+/// of the type or all of them if `length` is 0.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let log_db_path = PathBuf::new().join("./dir/logs.db");
+/// let logs = dblib::log::get_logs_by_action(log_db_path, dblib::log::LogAction::Encrypt, 0);
+/// println!("{} encrypt actions.", logs.len());
+/// ```
+pub fn get_logs_by_action(log_db_path: PathBuf, action: LogAction, length: u16) -> Vec<LogInfoForamt> {
+    let logger = loglib::Logger::new("get-logs");
+    if let Ok(conn) = Connection::open(&log_db_path) {
+        migrate_action_type(&conn);
+        let sql = if length > 0 {
+            format!("
+                    SELECT {} 
+                    FROM logs
+                    WHERE action_type = ?1
+                    ORDER BY create_at DESC, id DESC
+                    LIMIT {}
+                ",
+                LOG_COLUMNS,
+                length
+            )
+        } else {
+            format!("SELECT {} FROM logs WHERE action_type = ?1", LOG_COLUMNS)
+        };
+        if let Ok(mut stmt) = conn.prepare(&sql) {
+            return stmt.query_map([action.name()], log_from_row)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        }
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'", 
+            log_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
 /// Get log by date. 
 /// 
 /// ### Exit:
@@ -236,11 +362,12 @@ pub fn get_logs(log_db_path: PathBuf, length: u16, string: String) -> Vec<LogInf
 pub fn get_logs_by_date(log_db_path: PathBuf, date: (u16, u8, u8)) -> Vec<LogInfoForamt> {
     let logger = loglib::Logger::new("get-logs");
     if let Ok(conn) = Connection::open(&log_db_path) {
-        let mut sql: String = "
-            SELECT id, log, create_at
+        migrate_action_type(&conn);
+        let mut sql: String = format!("
+            SELECT {}
             FROM logs
             WHERE log LIKE '%%'
-        ".to_owned();
+        ", LOG_COLUMNS);
         // SELECT * FROM logs
         // WHER log LIKE '%%' -- to use the AND date
         //      AND strftime('%Y', create_at) = year
@@ -257,17 +384,7 @@ pub fn get_logs_by_date(log_db_path: PathBuf, date: (u16, u8, u8)) -> Vec<LogInf
         }
         let e = conn.prepare(&sql);
         if let Ok(mut stmt) = e {
-            let password = stmt.query_map(
-                [],
-                |row| {
-                    Ok(
-                        LogInfoForamt {
-                            id: row.get::<_, u32>(0).unwrap(),
-                            log: row.get::<_, String>(1).unwrap(),
-                            create_at: row.get::<_, String>(2).unwrap(),
-                        }
-                    )
-            })
+            let password = stmt.query_map([], log_from_row)
                 .unwrap()
                 .collect::<Result<Vec<_>, _>>();
             return password.unwrap();
@@ -334,7 +451,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -355,7 +472,7 @@ mod tests {
         let db_path = temp_dir.join("test.db");
 
         // This will panic and exit the program if an error occurs.
-        super::register("test", db_path.clone());
+        super::register("test", super::LogAction::Save, db_path.clone());
         let logs = super::get_logs(
             db_path.clone(),
             0, // if 0 then all logs
@@ -379,8 +496,8 @@ mod tests {
         let db_path = temp_dir.join("test.db");
 
         // This will panic and exit the program if an error occurs.
-        super::register("test-1", db_path.clone());
-        super::register("test-2", db_path.clone());
+        super::register("test-1", super::LogAction::Save, db_path.clone());
+        super::register("test-2", super::LogAction::Save, db_path.clone());
         let mut logs = super::get_logs(
             db_path.clone(),
             0, // if 0 then all logs
@@ -410,9 +527,9 @@ mod tests {
         let db_path = temp_dir.join("test.db");
 
         // This will panic and exit the program if an error occurs.
-        super::register("test-1", db_path.clone());
-        super::register("test-2", db_path.clone());
-        super::register("test-3", db_path.clone());
+        super::register("test-1", super::LogAction::Save, db_path.clone());
+        super::register("test-2", super::LogAction::Save, db_path.clone());
+        super::register("test-3", super::LogAction::Save, db_path.clone());
         let mut logs = super::get_logs(
             db_path.clone(),
             2, // if 2 then return 2 logs
@@ -458,7 +575,7 @@ mod tests {
             .expect("Can NOT parse to u8!!");
 
         // This will panic and exit the program if an error occurs.
-        super::register("test", db_path.clone());
+        super::register("test", super::LogAction::Save, db_path.clone());
         let mut logs = super::get_logs_by_date(
             db_path.clone(), 
             (year, 0, 0) // (year, month, day)
@@ -490,7 +607,7 @@ mod tests {
         let db_path = temp_dir.join("test.db");
 
         // This will panic and exit the program if an error occurs.
-        super::register("test", db_path.clone());
+        super::register("test", super::LogAction::Save, db_path.clone());
         super::delete_one(db_path.clone(), "1".to_string());
         let logs = super::get_logs(db_path.clone(), 0, "".to_string());
         assert_eq!(logs.len(), 0, "Can NOT delete the log!!");
@@ -498,4 +615,86 @@ mod tests {
         std::fs::remove_dir_all(temp_dir.clone())
             .expect("Can NOT delete temp dir!!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn encrypted_log_database() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypted_log_database");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        let db_path_str = db_path.to_str().unwrap().to_owned();
+        let en_db_path_str = super::filelib::make_encrypt_path(db_path_str.clone());
+        super::register("test-1", super::LogAction::Save, db_path.clone());
+        super::register("test-2", super::LogAction::Save, db_path.clone());
+
+        let key = fernet::Fernet::generate_key();
+        crate::encryption_manager::encrypt_file::encrypt(db_path_str.clone(), key.clone());
+        super::filelib::wipe_delete(db_path_str.clone()).unwrap();
+        assert!(
+            super::filelib::log::is_encrypted(&db_path),
+            "Encrypted log database NOT detected!!"
+        );
+        // `register` skips the log, it does NOT create a new database over the encrypted one.
+        super::register("test-3", super::LogAction::Save, db_path.clone());
+        assert!(!db_path.exists(), "Log database created over the encrypted one!!");
+
+        crate::encryption_manager::decrypt_file::decrypt(en_db_path_str.clone(), key);
+        super::filelib::wipe_delete(en_db_path_str).unwrap();
+        assert!(
+            !super::filelib::log::is_encrypted(&db_path),
+            "Decrypted log database NOT detected!!"
+        );
+        let logs = super::get_logs(db_path.clone(), 0, "".to_string());
+        assert_eq!(logs.len(), 2, "Number of logs NOT match!!");
+        assert_eq!(logs[1].log, "test-2", "Log NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn get_logs_by_action() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/get_logs_by_action");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+
+        // An old logs table, without the action type.
+        create_file(db_path.clone()).unwrap();
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute(
+            "CREATE TABLE logs(id INTEGER PRIMARY KEY, log TEXT NOT NULL, create_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+            []
+        ).unwrap();
+        conn.execute("INSERT INTO logs (log) VALUES ('old log')", []).unwrap();
+        conn.close().unwrap();
+
+        super::register("encrypt f1", super::LogAction::Encrypt, db_path.clone());
+        super::register("wipe f1", super::LogAction::Wipe, db_path.clone());
+        super::register("encrypt f2", super::LogAction::Encrypt, db_path.clone());
+        let logs = super::get_logs_by_action(db_path.clone(), super::LogAction::Encrypt, 0);
+        assert_eq!(
+            logs.iter().map(|log| log.log.as_str()).collect::<Vec<&str>>(),
+            vec!["encrypt f1", "encrypt f2"],
+            "Encrypt logs NOT match!!"
+        );
+        assert!(logs.iter().all(|log| log.action_type == "encrypt"), "Action type NOT match!!");
+        let logs = super::get_logs_by_action(db_path.clone(), super::LogAction::Encrypt, 1);
+        assert_eq!(logs[0].log, "encrypt f2", "Last encrypt log NOT match!!");
+        assert_eq!(
+            super::get_logs(db_path.clone(), 0, "old".to_string())[0].action_type,
+            "unknown",
+            "Old log action type NOT match!!"
+        );
+        assert_eq!(super::LogAction::from_name("wipe"), Some(super::LogAction::Wipe), "Action name NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     loglib,
     errorlib,
-    filelib
+    filelib,
+    utilities
 };
 use super::{
     params,
@@ -9,15 +10,155 @@ use super::{
     PathBuf,
     Tabled
 };
+use std::cell::Cell;
+use chrono::{FixedOffset, NaiveDateTime, TimeZone, Utc};
+use unicode_normalization::{
+    char::is_combining_mark,
+    UnicodeNormalization
+};
+
+thread_local! {
+    /// Write the timestamps in UTC ISO-8601, from the config `passwords.utc`.
+    static UTC_TIMESTAMPS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The format of the UTC timestamps (e.g. `2025-05-24T15:59:12+00:00`).
+pub const UTC_FORMAT: &str = "%Y-%m-%dT%H:%M:%S+00:00";
+
+/// The format of the old timestamps (e.g. `2025-05-24 15:59:12`).
+const LOCAL_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Write the new `create_at`/`update_at` in UTC ISO-8601 with
+/// an offset instead of `YYYY-MM-DD HH:MM:SS`.
+/// 
+/// ### Example:
+/// ```
+/// dblib::pm::set_utc_timestamps(true);
+/// dblib::pm::save_password(pm_db_path, "XPManager".to_string(), "pass123".to_string());
+/// ```
+pub fn set_utc_timestamps(is_utc: bool) {
+    UTC_TIMESTAMPS.with(|utc| utc.set(is_utc));
+}
+
+/// The SQL expression of the current time for the writes.
+fn now_sql() -> String {
+    if UTC_TIMESTAMPS.with(|utc| utc.get()) {
+        // The same format works with SQLite `strftime` and chrono.
+        format!("strftime('{}', 'now')", UTC_FORMAT)
+    } else {
+        "CURRENT_TIMESTAMP".to_owned()
+    }
+}
 
 /// The password DTO.
 #[derive(Tabled)]
 pub struct PasswordInfoForm {
+    /// A short visual hint (e.g. an emoji), the first table column.
+    pub icon: String,
     pub id: i32,
     pub name: String,
     pub password: String,
     pub create_at: String,
     pub update_at: String,
+    pub tags: String,
+}
+
+impl PasswordInfoForm {
+    /// The names of the fields changed in `new`, only the names
+    /// so the secrets are NOT leaked (e.g. in the logs).
+    ///
+    /// ### Example:
+    /// ```
+    /// for field in old.changed_fields(&new) {
+    ///     println!("'{}' changed", field);
+    /// }
+    /// ```
+    pub fn changed_fields(&self, new: &PasswordInfoForm) -> Vec<&'static str> {
+        let mut fields: Vec<&'static str> = vec![];
+        if self.name != new.name {
+            fields.push("name");
+        }
+        if self.password != new.password {
+            fields.push("password");
+        }
+        if self.icon != new.icon {
+            fields.push("icon");
+        }
+        if self.tags != new.tags {
+            fields.push("tags");
+        }
+        fields
+    }
+}
+
+/// The passwords order.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SortBy {
+    /// The insertion order.
+    Id,
+    Name,
+    /// The last update first.
+    Update
+}
+
+impl SortBy {
+    /// Get the order from its name (id, name, update).
+    pub fn from_name(name: &str) -> Option<SortBy> {
+        match name {
+            "id" => Some(SortBy::Id),
+            "name" => Some(SortBy::Name),
+            "update" => Some(SortBy::Update),
+            _ => None
+        }
+    }
+
+    fn order_by(&self) -> &str {
+        match self {
+            SortBy::Id => "id",
+            SortBy::Name => "name COLLATE NOCASE, id",
+            SortBy::Update => "update_at DESC, id"
+        }
+    }
+}
+
+/// The password name matching strategy.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MatchBy {
+    /// The name contains the string.
+    Substring,
+    /// The name equals the string (case-insensitively).
+    Exact
+}
+
+impl MatchBy {
+    fn where_clause(&self) -> &str {
+        match self {
+            MatchBy::Substring => "name LIKE ?1",
+            MatchBy::Exact => "name = ?1 COLLATE NOCASE"
+        }
+    }
+
+    fn pattern(&self, string: &str) -> String {
+        match self {
+            MatchBy::Substring => format!("%{}%", string),
+            MatchBy::Exact => string.to_owned()
+        }
+    }
+}
+
+/// Fold the string for the search, lowercase and without
+/// the diacritics (e.g. "Café" -> "cafe", "naïve" -> "naive").
+/// 
+/// ### Example:
+/// ```
+/// assert_eq!(dblib::pm::fold("Café"), "cafe");
+/// ```
+pub fn fold(string: &str) -> String {
+    string
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect::<String>()
+        .to_lowercase()
 }
 
 /// Create the password table in the database.
@@ -40,7 +181,9 @@ pub fn create_passwords_table(password_manager_db_path: PathBuf) {
                 name TEXT NOT NULL,
                 password TEXT NOT NULL,
                 create_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                update_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                update_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                tags TEXT NOT NULL DEFAULT '',
+                icon TEXT NOT NULL DEFAULT ''
             )
         ", []) {
             // We run this function when the passwords.db first created,
@@ -48,7 +191,7 @@ pub fn create_passwords_table(password_manager_db_path: PathBuf) {
             // we must delete the db file to tell xpm 'there is no database'.
             // Disconnect the conn from the file befor you delete it.
             conn.close().unwrap();
-            filelib::delete_file(password_manager_db_path);
+            filelib::delete_file(password_manager_db_path).unwrap_or_else(|err| err.exit());
             logger.error(
                 "can NOT create the password table!", 
                 errorlib::ExitErrorCode::DBCreateTable
@@ -75,24 +218,26 @@ pub fn create_passwords_table(password_manager_db_path: PathBuf) {
 /// ### Example:
 /// ```
 /// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
-/// dblib::pm::save_password(pm_db_path, "XPManager".to_string(), "pass123".to_string());
+/// let id = dblib::pm::save_password(pm_db_path, "XPManager".to_string(), "pass123".to_string());
+/// println!("saved with id {}.", id);
 /// ```
-pub fn save_password(password_manager_db_path: PathBuf, name: String, password: String) {
+pub fn save_password(password_manager_db_path: PathBuf, name: String, password: String) -> i64 {
     let logger = loglib::Logger::new("save-password");
     if let Ok(conn) = Connection::open(&password_manager_db_path) {
-        if let Err(_) = conn.execute("
+        if conn.execute(&format!("
                 INSERT INTO passwords 
-                (name, password, update_at) VALUES 
-                (?1, ?2, CURRENT_TIMESTAMP)
-            ", 
+                (name, password, create_at, update_at) VALUES 
+                (?1, ?2, {now}, {now})
+            ", now = now_sql()), 
             params![name.clone(), password]
-        ) {
+        ).is_err() {
             logger.error(
                 "can NOT save the password!", 
                 errorlib::ExitErrorCode::DBInsert
             );
         }
         logger.info(&format!("'{}' saved successfully.", name) );
+        conn.last_insert_rowid()
     } else {
         logger.error(
             &format!(
@@ -104,6 +249,174 @@ pub fn save_password(password_manager_db_path: PathBuf, name: String, password:
     }
 }
 
+/// Save many passwords in one transaction, nothing will be
+/// saved if one of them fails.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBInsert`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let rows = dblib::pm::save_passwords(
+///     pm_db_path,
+///     vec![("github".to_string(), "pass123".to_string())]
+/// );
+/// println!("{} passwords saved.", rows);
+/// ```
+pub fn save_passwords(password_manager_db_path: PathBuf, passwords: Vec<(String, String)>) -> usize {
+    let logger = loglib::Logger::new("save-passwords");
+    if let Ok(mut conn) = Connection::open(&password_manager_db_path) {
+        let insert = format!("
+            INSERT INTO passwords 
+            (name, password, create_at, update_at) VALUES 
+            (?1, ?2, {now}, {now})
+        ", now = now_sql());
+        if let Ok(tx) = conn.transaction() {
+            for (name, password) in passwords.iter() {
+                if tx.execute(&insert,
+                    params![name, password]
+                ).is_err() {
+                    logger.error(
+                        &format!("can NOT save '{}', no password saved!", name),
+                        errorlib::ExitErrorCode::DBInsert
+                    );
+                }
+            }
+            if tx.commit().is_ok() {
+                return passwords.len();
+            }
+        }
+        logger.error(
+            "can NOT save the passwords!",
+            errorlib::ExitErrorCode::DBInsert
+        );
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'",
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
+/// Replace all the passwords with new ones in one transaction,
+/// the old passwords stay as they are if one of them fails.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBInsert`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let rows = dblib::pm::replace_passwords(
+///     pm_db_path,
+///     vec![("github".to_string(), "pass123".to_string())]
+/// );
+/// println!("the database has {} passwords now.", rows);
+/// ```
+pub fn replace_passwords(password_manager_db_path: PathBuf, passwords: Vec<(String, String)>) -> usize {
+    let logger = loglib::Logger::new("replace-passwords");
+    if let Ok(mut conn) = Connection::open(&password_manager_db_path) {
+        let insert = format!("
+            INSERT INTO passwords 
+            (name, password, create_at, update_at) VALUES 
+            (?1, ?2, {now}, {now})
+        ", now = now_sql());
+        if let Ok(tx) = conn.transaction() {
+            // On error the transaction will not be committed,
+            // and the deleted passwords will be back.
+            if tx.execute("DELETE FROM passwords", []).is_err() {
+                logger.error(
+                    "can NOT delete the old passwords, nothing replaced!",
+                    errorlib::ExitErrorCode::DBInsert
+                );
+            }
+            for (name, password) in passwords.iter() {
+                if tx.execute(&insert,
+                    params![name, password]
+                ).is_err() {
+                    logger.error(
+                        &format!("can NOT save '{}', nothing replaced!", name),
+                        errorlib::ExitErrorCode::DBInsert
+                    );
+                }
+            }
+            if tx.commit().is_ok() {
+                return passwords.len();
+            }
+        }
+        logger.error(
+            "can NOT replace the passwords!",
+            errorlib::ExitErrorCode::DBInsert
+        );
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'",
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
+/// Update many passwords by the id in one transaction,
+/// no password is updated if one of them fails.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBUpdate`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let rows = dblib::pm::update_passwords(
+///     pm_db_path,
+///     vec![(23, "new-pass123".to_string())]
+/// );
+/// println!("{} passwords updated.", rows);
+/// ```
+pub fn update_passwords(password_manager_db_path: PathBuf, passwords: Vec<(i32, String)>) -> usize {
+    let logger = loglib::Logger::new("update-passwords");
+    if let Ok(mut conn) = Connection::open(&password_manager_db_path) {
+        let update = format!("
+            UPDATE passwords 
+            SET password = ?1,
+            update_at = {} 
+            WHERE id=?2
+        ", now_sql());
+        if let Ok(tx) = conn.transaction() {
+            let mut rows: usize = 0;
+            for (id, password) in passwords.iter() {
+                match tx.execute(&update, params![password, id]) {
+                    Ok(count) => rows += count,
+                    Err(_) => logger.error(
+                        &format!("can NOT update the password {}, no password updated!", id),
+                        errorlib::ExitErrorCode::DBUpdate
+                    )
+                }
+            }
+            if tx.commit().is_ok() {
+                return rows;
+            }
+        }
+        logger.error(
+            "can NOT update the passwords!",
+            errorlib::ExitErrorCode::DBUpdate
+        );
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'",
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
 /// Find one password using string.
 /// 
 /// ### Exit:
@@ -118,10 +431,52 @@ pub fn save_password(password_manager_db_path: PathBuf, name: String, password:
 /// }
 /// ```
 pub fn find_password(password_manager_db_path: PathBuf, string: String) -> Vec<PasswordInfoForm> {
+    find_password_sorted(password_manager_db_path, string, SortBy::Id)
+}
+
+/// Find passwords using string, ordered by `sort`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example: 
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let passwords = dblib::pm::find_password_sorted(pm_db_path, "git".to_string(), dblib::pm::SortBy::Name);
+/// for pass in passwords {
+///     println!("{}: {}", pass.name, pass.password)
+/// }
+/// ```
+pub fn find_password_sorted(password_manager_db_path: PathBuf, string: String, sort: SortBy) -> Vec<PasswordInfoForm> {
+    find_password_matching(password_manager_db_path, string, MatchBy::Substring, sort)
+}
+
+/// Find passwords using string and the `match_by` strategy, ordered by `sort`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example: 
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let passwords = dblib::pm::find_password_matching(
+///     pm_db_path,
+///     "github".to_string(),
+///     dblib::pm::MatchBy::Exact,
+///     dblib::pm::SortBy::Id
+/// );
+/// ```
+pub fn find_password_matching(password_manager_db_path: PathBuf, string: String, match_by: MatchBy, sort: SortBy) -> Vec<PasswordInfoForm> {
     let logger = loglib::Logger::new("dblib");
     if let Ok(conn) = Connection::open(&password_manager_db_path) {
-        if let Ok(mut stmt) = conn.prepare("SELECT * FROM passwords WHERE name LIKE ?1") {
-            let pattern = format!("%{}%", string);
+        if let Ok(mut stmt) = conn.prepare(
+            &format!(
+                "SELECT * FROM passwords WHERE {} ORDER BY {}",
+                match_by.where_clause(),
+                sort.order_by()
+            )
+        ) {
+            let pattern = match_by.pattern(&string);
             let password: Result<Vec<PasswordInfoForm>, _> = stmt.query_map(params![pattern], |row| {
                 Ok(
                     PasswordInfoForm {
@@ -129,7 +484,10 @@ pub fn find_password(password_manager_db_path: PathBuf, string: String) -> Vec<P
                         name: row.get::<_, String>(1).unwrap(),
                         password: row.get::<_, String>(2).unwrap(), 
                         create_at: row.get::<_, String>(3).unwrap(),
-                        update_at: row.get::<_, String>(4).unwrap()
+                        update_at: row.get::<_, String>(4).unwrap(),
+                        // Databases created before the tags/icons have no tags/icon column.
+                        tags: row.get::<_, String>("tags").unwrap_or_default(),
+                        icon: row.get::<_, String>("icon").unwrap_or_default()
                     }
                 )
             })
@@ -147,6 +505,37 @@ pub fn find_password(password_manager_db_path: PathBuf, string: String) -> Vec<P
     );
 }
 
+/// Find passwords like `find_password_matching`, but compare the folded
+/// name and string (see `fold`), so "cafe" finds "Café".
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example: 
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let passwords = dblib::pm::find_password_folded(
+///     pm_db_path,
+///     "cafe".to_string(),
+///     dblib::pm::MatchBy::Substring,
+///     dblib::pm::SortBy::Id
+/// );
+/// ```
+pub fn find_password_folded(password_manager_db_path: PathBuf, string: String, match_by: MatchBy, sort: SortBy) -> Vec<PasswordInfoForm> {
+    // SQLite can NOT fold the names, so all passwords are compared here.
+    let string = fold(&string);
+    find_password_sorted(password_manager_db_path, String::new(), sort)
+        .into_iter()
+        .filter(|pass| {
+            let name = fold(&pass.name);
+            match match_by {
+                MatchBy::Substring => name.contains(&string),
+                MatchBy::Exact => name == string
+            }
+        })
+        .collect()
+}
+
 /// Get all passwords.
 /// 
 /// ### Exit:
@@ -164,6 +553,50 @@ pub fn get_passwords(password_manager_db_path: PathBuf) -> Vec<PasswordInfoForm>
     find_password(password_manager_db_path, "".to_owned())
 }
 
+/// Get the passwords with id greater than `id`, ordered by id.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let passwords: Vec<dblib::pm::PasswordInfoForm> = dblib::pm::get_passwords_since_id(pm_db_path, 32);
+/// for pass in passwords {
+///     println!("{}: {}", pass.id, pass.name)
+/// }
+/// ```
+pub fn get_passwords_since_id(password_manager_db_path: PathBuf, id: i32) -> Vec<PasswordInfoForm> {
+    let logger = loglib::Logger::new("dblib");
+    if let Ok(conn) = Connection::open(&password_manager_db_path) {
+        if let Ok(mut stmt) = conn.prepare("SELECT * FROM passwords WHERE id > ?1 ORDER BY id") {
+            let password: Result<Vec<PasswordInfoForm>, _> = stmt.query_map(params![id], |row| {
+                Ok(
+                    PasswordInfoForm {
+                        id: row.get::<_, i32>(0).unwrap(),
+                        name: row.get::<_, String>(1).unwrap(),
+                        password: row.get::<_, String>(2).unwrap(), 
+                        create_at: row.get::<_, String>(3).unwrap(),
+                        update_at: row.get::<_, String>(4).unwrap(),
+                        tags: row.get::<_, String>("tags").unwrap_or_default(),
+                        icon: row.get::<_, String>("icon").unwrap_or_default()
+                    }
+                )
+            })
+                .unwrap()
+                .collect();
+            return password.unwrap();
+        }
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'", 
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
 /// Update the password.
 /// 
 /// ### Exit:
@@ -182,12 +615,12 @@ pub fn get_passwords(password_manager_db_path: PathBuf) -> Vec<PasswordInfoForm>
 pub fn update_password(password_manager_db_path: PathBuf, id: String, password: String) -> usize {
     let logger = loglib::Logger::new("update-password");
     if let Ok(conn) = Connection::open(&password_manager_db_path) {
-        let rows = conn.execute("
+        let rows = conn.execute(&format!("
                     UPDATE passwords 
                     SET password = ?1,
-                    update_at = CURRENT_TIMESTAMP 
+                    update_at = {} 
                     WHERE id=?2
-                ",
+                ", now_sql()),
                 params![password, id]
             ).unwrap_or(0);
             return rows;
@@ -220,12 +653,12 @@ pub fn update_password(password_manager_db_path: PathBuf, id: String, password:
 pub fn update_password_name(password_manager_db_path: PathBuf, id: String, name: String) -> usize {
     let logger = loglib::Logger::new("update-password");
     if let Ok(conn) = Connection::open(&password_manager_db_path) {
-        let rows= conn.execute("
+        let rows = conn.execute(&format!("
                     UPDATE passwords 
                     SET name = ?1,
-                    update_at = CURRENT_TIMESTAMP 
+                    update_at = {} 
                     WHERE id=?2
-                ",
+                ", now_sql()),
                 params![name, id]
             ).unwrap_or(0);
             return rows;
@@ -269,6 +702,40 @@ pub fn get_passwords_number(password_manager_db_path: PathBuf) -> usize {
     );
 }
 
+/// Get the number of passwords that match the string,
+/// using the `match_by` strategy.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let number = dblib::pm::get_passwords_number_matching(
+///     pm_db_path,
+///     "git".to_string(),
+///     dblib::pm::MatchBy::Substring
+/// );
+/// println!("{} passwords match.", number);
+/// ```
+pub fn get_passwords_number_matching(password_manager_db_path: PathBuf, string: String, match_by: MatchBy) -> usize {
+    let logger = loglib::Logger::new("dblib");
+    if let Ok(conn) = Connection::open(&password_manager_db_path) {
+        return conn.query_row(
+            &format!("SELECT COUNT(*) FROM passwords WHERE {}", match_by.where_clause()),
+            params![match_by.pattern(&string)],
+            |row| row.get::<_, usize>(0),
+        ).unwrap();
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'", 
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
 /// Delete password by id.
 /// 
 /// ### Exit:
@@ -306,6 +773,287 @@ pub fn delete_password(password_manager_db_path: PathBuf, id: String) -> usize {
 }
 
 
+/// Add the tags column to the passwords table if it is not exist.
+/// Databases created before the tags have no tags column.
+/// 
+/// ### Example:
+/// ```
+/// let conn = Connection::open("./dir/passwords.db").unwrap();
+/// if !add_tags_column(&conn) {
+///     println!("can NOT add the tags column!");
+/// }
+/// ```
+fn add_tags_column(conn: &Connection) -> bool {
+    if conn.prepare("SELECT tags FROM passwords LIMIT 0").is_ok() {
+        return true;
+    }
+    conn.execute(
+        "ALTER TABLE passwords ADD COLUMN tags TEXT NOT NULL DEFAULT ''",
+        []
+    ).is_ok()
+}
+
+/// Add the icon column to the passwords table if it is not exist.
+/// Databases created before the icons have no icon column.
+/// 
+/// ### Example:
+/// ```
+/// let conn = Connection::open("./dir/passwords.db").unwrap();
+/// if !add_icon_column(&conn) {
+///     println!("can NOT add the icon column!");
+/// }
+/// ```
+fn add_icon_column(conn: &Connection) -> bool {
+    if conn.prepare("SELECT icon FROM passwords LIMIT 0").is_ok() {
+        return true;
+    }
+    conn.execute(
+        "ALTER TABLE passwords ADD COLUMN icon TEXT NOT NULL DEFAULT ''",
+        []
+    ).is_ok()
+}
+
+/// Set the password icon, an empty icon removes it.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBUpdate`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let rows_affected = dblib::pm::set_icon(pm_db_path, "1".to_string(), "🐙".to_string());
+/// if rows_affected > 0 {
+///     println!("{} passwords updated.", rows_affected);
+/// }
+/// ```
+pub fn set_icon(password_manager_db_path: PathBuf, id: String, icon: String) -> usize {
+    let logger = loglib::Logger::new("set-icon");
+    if let Ok(conn) = Connection::open(&password_manager_db_path) {
+        if !add_icon_column(&conn) {
+            logger.error(
+                "can NOT add the icon column!",
+                errorlib::ExitErrorCode::DBUpdate
+            );
+        }
+        return conn.execute(
+            "UPDATE passwords SET icon = ?1 WHERE id = ?2",
+            params![icon, id]
+        ).unwrap_or(0);
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'", 
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
+/// Add or remove a tag for all passwords that have the string
+/// in the name, in a single transaction. Returns the number of
+/// the changed passwords.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBUpdate`
+/// - `errorlib::ExitErrorCode::DBConnection`
+fn change_tag(password_manager_db_path: PathBuf, tag: String, string: String, is_add: bool) -> usize {
+    let logger = loglib::Logger::new("change-tag");
+    let tag = utilities::normalize_tag(&tag);
+    if let Ok(mut conn) = Connection::open(&password_manager_db_path) {
+        if !add_tags_column(&conn) {
+            logger.error(
+                "can NOT add the tags column!",
+                errorlib::ExitErrorCode::DBUpdate
+            );
+        }
+        if let Ok(tx) = conn.transaction() {
+            let pattern = format!("%{}%", string);
+            let passwords: Vec<(i32, String)> = tx
+                .prepare("SELECT id, tags FROM passwords WHERE name LIKE ?1")
+                .and_then(|mut stmt| {
+                    stmt.query_map(params![pattern], |row| {
+                        Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?))
+                    })?.collect()
+                })
+                .unwrap_or_else(|_| logger.error(
+                    "can NOT get the passwords tags!",
+                    errorlib::ExitErrorCode::DBUpdate
+                ));
+            let mut rows: usize = 0;
+            for (id, tags) in passwords {
+                let mut tags_list = utilities::split_tags(&tags);
+                if tags_list.contains(&tag) == is_add {
+                    // Nothing to change for this password.
+                    continue;
+                }
+                if is_add {
+                    tags_list.push(tag.clone());
+                } else {
+                    tags_list.retain(|t| *t != tag);
+                }
+                // On error the transaction will not be committed,
+                // so all the changes will be rolled back.
+                if tx.execute(
+                    "UPDATE passwords SET tags = ?1 WHERE id = ?2",
+                    params![utilities::join_tags(tags_list), id]
+                ).is_err() {
+                    logger.error(
+                        &format!("can NOT update the tags of the password with id {}!", id),
+                        errorlib::ExitErrorCode::DBUpdate
+                    );
+                }
+                rows += 1;
+            }
+            if tx.commit().is_err() {
+                logger.error(
+                    "can NOT commit the tags changes!",
+                    errorlib::ExitErrorCode::DBUpdate
+                );
+            }
+            return rows;
+        }
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'", 
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
+/// Add a tag to all passwords that have the string in the name.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBUpdate`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let rows_affected = dblib::pm::add_tag(pm_db_path, "work".to_string(), "github".to_string());
+/// println!("{} passwords tagged.", rows_affected);
+/// ```
+pub fn add_tag(password_manager_db_path: PathBuf, tag: String, string: String) -> usize {
+    change_tag(password_manager_db_path, tag, string, true)
+}
+
+/// Remove a tag from all passwords that have the string in the name.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBUpdate`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let rows_affected = dblib::pm::remove_tag(pm_db_path, "work".to_string(), "github".to_string());
+/// println!("{} passwords untagged.", rows_affected);
+/// ```
+pub fn remove_tag(password_manager_db_path: PathBuf, tag: String, string: String) -> usize {
+    change_tag(password_manager_db_path, tag, string, false)
+}
+
+/// Parse a UTC offset (e.g. `+03:00`, `-05:30`).
+/// 
+/// ### Example:
+/// ```
+/// let offset = dblib::pm::parse_offset("+03:00").unwrap();
+/// assert_eq!(offset.local_minus_utc(), 3 * 3600);
+/// ```
+pub fn parse_offset(offset: &str) -> Option<FixedOffset> {
+    offset.parse::<FixedOffset>().ok()
+}
+
+/// Convert an old `YYYY-MM-DD HH:MM:SS` timestamp in the `offset` zone
+/// to UTC ISO-8601, None if it is NOT in the old format (e.g. already UTC).
+/// 
+/// ### Example:
+/// ```
+/// let offset = dblib::pm::parse_offset("+03:00").unwrap();
+/// let utc = dblib::pm::to_utc("2025-05-24 15:59:12", &offset);
+/// assert_eq!(utc, Some("2025-05-24T12:59:12+00:00".to_owned()));
+/// ```
+pub fn to_utc(timestamp: &str, offset: &FixedOffset) -> Option<String> {
+    let local = NaiveDateTime::parse_from_str(timestamp, LOCAL_FORMAT).ok()?;
+    let time = offset.from_local_datetime(&local).single()?;
+    Some(time.with_timezone(&Utc).format(UTC_FORMAT).to_string())
+}
+
+/// Convert all the old `create_at`/`update_at` timestamps to UTC ISO-8601,
+/// reading them in the `offset` zone, in a single transaction. The UTC
+/// timestamps are skipped, so it is safe to run it again. Returns the
+/// number of the changed passwords.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBUpdate`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// let rows = dblib::pm::migrate_timestamps(pm_db_path, dblib::pm::parse_offset("+03:00").unwrap());
+/// println!("{} passwords migrated.", rows);
+/// ```
+pub fn migrate_timestamps(password_manager_db_path: PathBuf, offset: FixedOffset) -> usize {
+    let logger = loglib::Logger::new("migrate-timestamps");
+    if let Ok(mut conn) = Connection::open(&password_manager_db_path) {
+        if let Ok(tx) = conn.transaction() {
+            let passwords: Vec<(i32, String, String)> = tx
+                .prepare("SELECT id, create_at, update_at FROM passwords")
+                .and_then(|mut stmt| {
+                    stmt.query_map([], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                    })?.collect()
+                })
+                .unwrap_or_else(|_| logger.error(
+                    "can NOT get the passwords timestamps!",
+                    errorlib::ExitErrorCode::DBUpdate
+                ));
+            let mut rows: usize = 0;
+            for (id, create_at, update_at) in passwords {
+                let new_create_at = to_utc(&create_at, &offset);
+                let new_update_at = to_utc(&update_at, &offset);
+                if new_create_at.is_none() && new_update_at.is_none() {
+                    continue;
+                }
+                // On error the transaction will not be committed,
+                // so all the changes will be rolled back.
+                if tx.execute(
+                    "UPDATE passwords SET create_at = ?1, update_at = ?2 WHERE id = ?3",
+                    params![
+                        new_create_at.unwrap_or(create_at),
+                        new_update_at.unwrap_or(update_at),
+                        id
+                    ]
+                ).is_err() {
+                    logger.error(
+                        &format!("can NOT update the timestamps of the password with id {}!", id),
+                        errorlib::ExitErrorCode::DBUpdate
+                    );
+                }
+                rows += 1;
+            }
+            if tx.commit().is_err() {
+                logger.error(
+                    "can NOT commit the timestamps changes!",
+                    errorlib::ExitErrorCode::DBUpdate
+                );
+            }
+            return rows;
+        }
+    }
+    logger.error(
+        &format!(
+            "can NOT create connection with '{}'", 
+            password_manager_db_path.display()
+        ),
+        errorlib::ExitErrorCode::DBConnection
+    );
+}
+
+
 #[cfg(test)]
 mod tests {
     
@@ -321,7 +1069,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -340,7 +1088,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -364,7 +1112,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -397,7 +1145,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -438,7 +1186,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -482,7 +1230,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -508,7 +1256,7 @@ mod tests {
                 .expect("Can NOT delete temp dir!!");
         }
         let db_path = temp_dir.join("test.db");
-        create_file(db_path.clone());
+        create_file(db_path.clone()).unwrap();
         assert_eq!(db_path.exists(), true, "Can NOT create the test file!!");
 
         // This will panic and exit the program if an error occurs.
@@ -527,4 +1275,250 @@ mod tests {
         std::fs::remove_dir_all(temp_dir)
             .expect("Can NOT delete temp dir!!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn add_and_remove_tag() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/add_and_remove_tag");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        assert!(db_path.exists(), "Can NOT create the test file!!");
+
+        // This will panic and exit the program if an error occurs.
+        super::create_passwords_table(db_path.clone());
+        for name in ["github-1", "github-2", "gitlab"] {
+            super::save_password(
+                db_path.clone(),
+                name.to_string(), 
+                "test123".to_string()
+            );
+        }
+        let mut rows = super::add_tag(
+            db_path.clone(),
+            " Work ".to_string(),
+            "github".to_string()
+        );
+        assert_eq!(rows, 2, "Number of tagged passwords NOT match!!");
+        let mut passwords = super::get_passwords(db_path.clone());
+        assert_eq!(passwords[0].tags, "work", "The tag NOT added!!");
+        assert_eq!(passwords[1].tags, "work", "The tag NOT added!!");
+        assert_eq!(passwords[2].tags, "", "The tag added to NOT matched password!!");
+
+        rows = super::remove_tag(
+            db_path.clone(),
+            "work".to_string(),
+            "github".to_string()
+        );
+        assert_eq!(rows, 2, "Number of untagged passwords NOT match!!");
+        passwords = super::get_passwords(db_path.clone());
+        for pass in passwords {
+            assert_eq!(pass.tags, "", "The tag NOT removed!!");
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn find_password_exact() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/find_password_exact");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+
+        // This will panic and exit the program if an error occurs.
+        super::create_passwords_table(db_path.clone());
+        for name in ["github", "Git", "gitlab", "digit"] {
+            super::save_password(
+                db_path.clone(),
+                name.to_string(), 
+                "test123".to_string()
+            );
+        }
+        let names = |match_by: super::MatchBy| -> Vec<String> {
+            super::find_password_matching(
+                db_path.clone(),
+                "git".to_string(),
+                match_by,
+                super::SortBy::Id
+            )
+                .into_iter()
+                .map(|pass| pass.name)
+                .collect()
+        };
+        assert_eq!(names(super::MatchBy::Exact), vec!["Git"], "Exact match NOT match!!");
+        assert_eq!(names(super::MatchBy::Substring).len(), 4, "Substring match NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn find_password_folded() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/find_password_folded");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+
+        // This will panic and exit the program if an error occurs.
+        super::create_passwords_table(db_path.clone());
+        for name in ["Café", "naïve", "cafeteria"] {
+            super::save_password(
+                db_path.clone(),
+                name.to_string(), 
+                "test123".to_string()
+            );
+        }
+        let names = |string: &str, match_by: super::MatchBy| -> Vec<String> {
+            super::find_password_folded(
+                db_path.clone(),
+                string.to_string(),
+                match_by,
+                super::SortBy::Id
+            )
+                .into_iter()
+                .map(|pass| pass.name)
+                .collect()
+        };
+        assert_eq!(names("cafe", super::MatchBy::Exact), vec!["Café"], "Folded exact match NOT match!!");
+        assert_eq!(names("CAFE", super::MatchBy::Substring), vec!["Café", "cafeteria"], "Folded match NOT match!!");
+        assert_eq!(names("naive", super::MatchBy::Substring), vec!["naïve"], "Folded match NOT match!!");
+        assert_eq!(super::fold("Naïve Café"), "naive cafe", "Folded string NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn get_passwords_number_matching() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/get_passwords_number_matching");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+
+        // This will panic and exit the program if an error occurs.
+        super::create_passwords_table(db_path.clone());
+        for name in ["github", "gitlab", "email"] {
+            super::save_password(
+                db_path.clone(),
+                name.to_string(), 
+                "test123".to_string()
+            );
+        }
+        assert_eq!(
+            super::get_passwords_number_matching(db_path.clone(), "git".to_string(), super::MatchBy::Substring),
+            2,
+            "Number of matched passwords NOT match!!"
+        );
+        assert_eq!(
+            super::get_passwords_number_matching(db_path.clone(), "git".to_string(), super::MatchBy::Exact),
+            0,
+            "Number of exact matched passwords NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn set_icon() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/set_icon");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+
+        // A database created before the icons.
+        let conn = super::Connection::open(&db_path).unwrap();
+        conn.execute("
+            CREATE TABLE passwords(
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                password TEXT NOT NULL,
+                create_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                update_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+        ", []).unwrap();
+        conn.close().unwrap();
+        let id = super::save_password(
+            db_path.clone(),
+            "github".to_string(), 
+            "test123".to_string()
+        );
+        assert_eq!(super::get_passwords(db_path.clone())[0].icon, "", "Default icon NOT match!!");
+        let rows = super::set_icon(db_path.clone(), id.to_string(), "🐙".to_string());
+        assert_eq!(rows, 1, "Number of updated passwords NOT match!!");
+        let passwords = super::get_passwords(db_path.clone());
+        assert!(
+            passwords[0].icon == "🐙" && passwords[0].tags.is_empty(),
+            "The icon NOT saved!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn utc_timestamps() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/utc_timestamps");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        super::create_passwords_table(db_path.clone());
+        super::save_password(db_path.clone(), "old".to_owned(), "pass-1".to_owned());
+
+        super::set_utc_timestamps(true);
+        super::save_password(db_path.clone(), "new".to_owned(), "pass-2".to_owned());
+        super::set_utc_timestamps(false);
+        let passwords = super::get_passwords(db_path.clone());
+        for time in [&passwords[1].create_at, &passwords[1].update_at] {
+            assert!(
+                chrono::DateTime::parse_from_rfc3339(time).is_ok() && time.ends_with("+00:00"),
+                "UTC timestamp NOT match!!"
+            );
+        }
+
+        let offset = super::parse_offset("+03:00").unwrap();
+        assert_eq!(
+            super::to_utc("2025-05-24 01:30:00", &offset),
+            Some("2025-05-23T22:30:00+00:00".to_owned()),
+            "UTC conversion NOT match!!"
+        );
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute(
+            "UPDATE passwords SET create_at = '2025-05-24 01:30:00' WHERE name = 'old'",
+            []
+        ).unwrap();
+        assert_eq!(super::migrate_timestamps(db_path.clone(), offset), 1, "Migrated passwords NOT match!!");
+        assert_eq!(super::migrate_timestamps(db_path.clone(), offset), 0, "Migration NOT idempotent!!");
+        let passwords = super::get_passwords(db_path.clone());
+        assert_eq!(passwords[0].create_at, "2025-05-23T22:30:00+00:00", "Migrated timestamp NOT match!!");
+        assert!(super::parse_offset("3 hours").is_none(), "Invalid offset NOT rejected!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
@@ -18,7 +18,8 @@ use super::{
 ///     dblib::log::LogInfoForamt {
 ///         id: 32,
 ///         log: "create pass123".to_string(),
-///         create_at: "2025-05-24 15:59:12".to_string()
+///         create_at: "2025-05-24 15:59:12".to_string(),
+///         action_type: "save".to_string()
 ///     }
 /// ];
 /// displaylib::log::display(logs);
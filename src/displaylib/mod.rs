@@ -3,18 +3,18 @@ pub mod key;
 pub mod encode;
 pub mod decode;
 pub mod log;
+pub mod wipe;
+pub mod strength;
 
 use colored::Colorize;
 use crossterm::terminal;
 use tabled::{
     settings::{
-        location::ByColumnName,
         object::{Columns, Rows}, 
         Alignment, 
         Modify, 
         Style, 
         Width,
-        Remove,
     }, 
     Table
 };
\ No newline at end of file
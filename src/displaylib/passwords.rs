@@ -1,4 +1,11 @@
-use crate::dblib;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tabled::{builder::Builder, Tabled};
+use crate::{
+    dblib,
+    password_manager,
+    utilities
+};
 use super::{
     Colorize,
     terminal,
@@ -8,106 +15,936 @@ use super::{
     Rows,
     Alignment,
     Columns,
-    Width,
-    Remove,
-    ByColumnName
+    Width
 };
 
+/// Mask the names and the passwords, uses with `--redact`.
+static REDACT: AtomicBool = AtomicBool::new(false);
+
+/// The password shown in the redact mode.
+pub const REDACTED_PASSWORD: &str = "••••••";
+
+/// Mask the names and the passwords in all the displays (e.g. for
+/// screen sharing), the ids, the counts and the dates stay visible.
+/// 
+/// ### Example:
+/// ```
+/// displaylib::passwords::redact();
+/// // Password: ••••••
+/// displaylib::passwords::display_one("pass123".to_string(), DisplayFormat::Pretty);
+/// ```
+pub fn redact() {
+    REDACT.store(true, Ordering::Relaxed);
+}
+
+/// Mask the name, keep the first character only.
+/// 
+/// ### Example:
+/// ```
+/// assert_eq!(mask_name("github"), "g*****");
+/// ```
+pub fn mask_name(name: &str) -> String {
+    name.chars()
+        .enumerate()
+        .map(|(index, c)| if index == 0 { c } else { '*' })
+        .collect()
+}
+
+/// Mask the password name and value.
+/// 
+/// ### Example:
+/// ```
+/// let pass = redact_password(pass);
+/// assert_eq!(pass.password, REDACTED_PASSWORD);
+/// ```
+pub fn redact_password(pass: dblib::pm::PasswordInfoForm) -> dblib::pm::PasswordInfoForm {
+    dblib::pm::PasswordInfoForm {
+        name: mask_name(&pass.name),
+        password: REDACTED_PASSWORD.to_owned(),
+        ..pass
+    }
+}
+
+/// Apply the redact mode to the passwords, if it is on.
+fn redacted(passwords: Vec<dblib::pm::PasswordInfoForm>) -> Vec<dblib::pm::PasswordInfoForm> {
+    if !REDACT.load(Ordering::Relaxed) {
+        return passwords;
+    }
+    passwords.into_iter().map(redact_password).collect()
+}
+
+/// The output formats of `display_one`.
+#[derive(PartialEq, Clone, Copy)]
+pub enum DisplayFormat {
+    /// Colored with the "Password:" label.
+    Pretty,
+    /// Just the value, no label and no color.
+    Plain,
+    /// Shell export line: `XPM_PASSWORD='...'`.
+    Export
+}
+
+impl DisplayFormat {
+    /// Get the format from its name, `Pretty` for unknown names.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let format = DisplayFormat::from_name("plain");
+    /// assert!(format == DisplayFormat::Plain);
+    /// ```
+    pub fn from_name(name: &str) -> DisplayFormat {
+        match name {
+            "plain" => DisplayFormat::Plain,
+            "export" => DisplayFormat::Export,
+            _ => DisplayFormat::Pretty
+        }
+    }
+}
+
+/// Quote a string for the shell using single quotes,
+/// every `'` in the string will be `'\''`.
+/// 
+/// ### Example:
+/// ```
+/// let quoted = shell_quote("it's");
+/// assert_eq!(quoted, "'it'\\''s'");
+/// ```
+fn shell_quote(string: &str) -> String {
+    format!("'{}'", string.replace("'", "'\\''"))
+}
+
+/// Format one password based on the `DisplayFormat`.
+/// The `Plain` and `Export` formats have no colors.
+/// 
+/// ### Example:
+/// ```
+/// let line = displaylib::passwords::format_one(
+///     "pass123".to_string(),
+///     DisplayFormat::Export
+/// );
+/// assert_eq!(line, "XPM_PASSWORD='pass123'");
+/// ```
+pub fn format_one(password: String, format: DisplayFormat) -> String {
+    match format {
+        DisplayFormat::Pretty => format!(
+            "\n{} {}\n",
+            "Password:".blue(),
+            password.green()
+        ),
+        DisplayFormat::Plain => password,
+        DisplayFormat::Export => format!(
+            "XPM_PASSWORD={}",
+            shell_quote(&password)
+        )
+    }
+}
+
 /// Display one password.
 /// 
 /// ### Example:
 /// ```
 /// let password = "pass123".to_string();
-/// displaylib::passwords::display_one(password);
+/// displaylib::passwords::display_one(password, DisplayFormat::Pretty);
+/// ```
+pub fn display_one(password: String, format: DisplayFormat) {
+    let password = if REDACT.load(Ordering::Relaxed) {
+        REDACTED_PASSWORD.to_owned()
+    } else {
+        password
+    };
+    println!("{}", format_one(password, format))
+}
+
+/// Format many passwords, a password per line. The pretty format is
+/// colored on a terminal only, piped it is the plain format.
+///
+/// ### Example:
+/// ```
+/// let lines = displaylib::passwords::format_many(
+///     vec!["pass123".to_string(), "pass456".to_string()],
+///     DisplayFormat::Pretty,
+///     false
+/// );
+/// assert_eq!(lines, "pass123\npass456");
 /// ```
-pub fn display_one(password: String) {
-    println!(
-        "\n{} {}\n",
-        "Password:".blue(),
-        password.green()
+pub fn format_many(passwords: Vec<String>, format: DisplayFormat, is_terminal: bool) -> String {
+    passwords.into_iter()
+        .map(|password| match format {
+            DisplayFormat::Pretty if is_terminal => password.green().to_string(),
+            DisplayFormat::Export => format_one(password, format),
+            _ => password
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Display many passwords, a password per line.
+///
+/// ### Example:
+/// ```
+/// let passwords = vec!["pass123".to_string(), "pass456".to_string()];
+/// displaylib::passwords::display_many(passwords, DisplayFormat::Plain, true);
+/// ```
+pub fn display_many(passwords: Vec<String>, format: DisplayFormat, is_terminal: bool) {
+    if passwords.is_empty() {
+        return;
+    }
+    let passwords = if REDACT.load(Ordering::Relaxed) {
+        vec![REDACTED_PASSWORD.to_owned(); passwords.len()]
+    } else {
+        passwords
+    };
+    println!("{}", format_many(passwords, format, is_terminal))
+}
+
+/// The header of the untagged passwords group.
+pub const UNTAGGED_HEADER: &str = "(untagged)";
+
+/// Format one password line of `ListFormat::List`, with `string`
+/// highlighted in the name.
+fn format_line(pass: &dblib::pm::PasswordInfoForm, string: &str) -> String {
+    let mut name = pass.name.clone();
+    if let Some(start_string_pos) = pass
+        .name
+        .to_lowercase()
+        .find(&string.to_lowercase()) {
+            let end_string_pos = start_string_pos+string.len();
+            let slice_string = &name[start_string_pos..end_string_pos];
+            name.replace_range(
+                start_string_pos..end_string_pos,
+                &slice_string.red().to_string()
+            );
+    }
+    format!(
+        "{} - {} - {} - {}: {}",
+        pass.id.to_string().blue(),
+        pass.create_at.blue(),
+        pass.update_at.blue(),
+        name.blue(),
+        pass.password.green()
     )
 }
 
-/// Display passwords as a list.
-/// 
+/// Group passwords by tag, sorted by the tag name with the untagged
+/// passwords last. A password with many tags is in each of their groups.
+///
 /// ### Example:
 /// ```
-/// let passwords = vec![
-///     dblib::pm::PasswordInfoForm {
-///         id: 32,
-///         name: "XPManager".to_string(),
-///         password: "pass123".to_string(),
-///         create_at: "2025-05-24 15:59:12".to_string(),
-///         update_at: "2025-05-24 15:59:12".to_string()
-///     }
-/// ];
-/// displaylib::passwords::display_many(passwords, "XPM".to_string());
+/// let groups = group_by_tag(&passwords);
+/// for (tag, passwords) in groups {
+///     println!("{}: {}", tag, passwords.len());
+/// }
 /// ```
-pub fn display_many(passwords: Vec<dblib::pm::PasswordInfoForm>, string: String) {
-    println!();
+pub fn group_by_tag(
+    passwords: &[dblib::pm::PasswordInfoForm]
+) -> Vec<(String, Vec<&dblib::pm::PasswordInfoForm>)> {
+    let mut groups: std::collections::BTreeMap<String, Vec<&dblib::pm::PasswordInfoForm>> =
+        std::collections::BTreeMap::new();
+    let mut untagged: Vec<&dblib::pm::PasswordInfoForm> = vec![];
     for pass in passwords {
-        let mut name = pass.name.clone();
-        if let Some(start_string_pos) = pass
-            .name
-            .to_lowercase()
-            .find(&string.to_lowercase()) {
-                let end_string_pos = start_string_pos+string.len();
-                let slice_string = &name[start_string_pos..end_string_pos];
-                name.replace_range(
-                    start_string_pos..end_string_pos,
-                    &slice_string.red().to_string()
-                );
+        let tags = utilities::split_tags(&pass.tags);
+        if tags.is_empty() {
+            untagged.push(pass);
+        }
+        for tag in tags {
+            let group = groups.entry(tag).or_default();
+            // The same tag twice should NOT show the password twice.
+            if !group.iter().any(|grouped| grouped.id == pass.id) {
+                group.push(pass);
+            }
+        }
+    }
+    let mut groups: Vec<(String, Vec<&dblib::pm::PasswordInfoForm>)> = groups.into_iter().collect();
+    if !untagged.is_empty() {
+        groups.push((UNTAGGED_HEADER.to_owned(), untagged));
+    }
+    groups
+}
+
+/// Display passwords as a list grouped under tag headers.
+/// 
+/// ### Example:
+/// ```
+/// displaylib::passwords::display_grouped(passwords, "XPM".to_string());
+/// ```
+pub fn display_grouped(passwords: Vec<dblib::pm::PasswordInfoForm>, string: String) {
+    let passwords = redacted(passwords);
+    for (tag, group) in group_by_tag(&passwords) {
+        println!();
+        println!("{}", format!("# {}", tag).yellow().bold());
+        for pass in group {
+            println!("{}", format_line(pass, &string));
         }
-        println!(
-            "{} - {} - {} - {}: {}",
-            pass.id.to_string().blue(),
-            pass.create_at.blue(),
-            pass.update_at.blue(),
-            name.blue(),
-            pass.password.green()
-        )
     }
     println!();
 }
 
-/// Display passwords in a table format.
+/// A passwords table column, the password is never shown in the table.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TableField {
+    Icon,
+    Id,
+    Name,
+    Tags,
+    CreateAt,
+    UpdateAt
+}
+
+impl TableField {
+    /// The default columns, in order.
+    pub const DEFAULT: [TableField; 6] = [
+        TableField::Icon,
+        TableField::Id,
+        TableField::Name,
+        TableField::CreateAt,
+        TableField::UpdateAt,
+        TableField::Tags
+    ];
+
+    /// Get the column from its name (icon, id, name, tags,
+    /// create_at or created, update_at or updated).
+    pub fn from_name(name: &str) -> Option<TableField> {
+        match name {
+            "icon" => Some(TableField::Icon),
+            "id" => Some(TableField::Id),
+            "name" => Some(TableField::Name),
+            "tags" => Some(TableField::Tags),
+            "create_at" | "created" => Some(TableField::CreateAt),
+            "update_at" | "updated" => Some(TableField::UpdateAt),
+            _ => None
+        }
+    }
+
+    /// Parse a comma separated list of columns (e.g. "id,name,updated"),
+    /// returns the first unknown name as the error.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let fields = TableField::parse_list("id, name").unwrap();
+    /// assert_eq!(fields, vec![TableField::Id, TableField::Name]);
+    /// ```
+    pub fn parse_list(list: &str) -> Result<Vec<TableField>, String> {
+        list.split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| TableField::from_name(name).ok_or_else(|| name.to_owned()))
+            .collect()
+    }
+
+    /// The column name.
+    pub fn header(&self) -> &str {
+        match self {
+            TableField::Icon => "icon",
+            TableField::Id => "id",
+            TableField::Name => "name",
+            TableField::Tags => "tags",
+            TableField::CreateAt => "create_at",
+            TableField::UpdateAt => "update_at"
+        }
+    }
+
+    /// The column value of the password, never the password itself.
+    pub fn value(&self, pass: &dblib::pm::PasswordInfoForm) -> String {
+        match self {
+            TableField::Icon => pass.icon.clone(),
+            TableField::Id => pass.id.to_string(),
+            TableField::Name => pass.name.clone(),
+            TableField::Tags => pass.tags.clone(),
+            TableField::CreateAt => pass.create_at.clone(),
+            TableField::UpdateAt => pass.update_at.clone()
+        }
+    }
+}
+
+/// The default max length of the names in the table, whatever the
+/// terminal width, the longer names (e.g. a pasted file) are cut.
+pub const MAX_NAME_LENGTH: usize = 200;
+
+/// Cut the text to `max` characters, ending with `…` if it is cut.
+fn truncate(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text;
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Format passwords as a table with the fields as columns in
+/// order, without the passwords. The names are cut to `max_name`
+/// characters.
 /// 
 /// ### Example:
 /// ```
 /// let passwords = vec![
 ///     dblib::pm::PasswordInfoForm {
+///         icon: "🐙".to_string(),
 ///         id: 32,
 ///         name: "XPManager".to_string(),
 ///         password: "pass123".to_string(),
 ///         create_at: "2025-05-24 15:59:12".to_string(),
-///         update_at: "2025-05-24 15:59:12".to_string()
+///         update_at: "2025-05-24 15:59:12".to_string(),
+///         tags: "work".to_string()
 ///     }
 /// ];
-/// displaylib::passwords::display_as_table(passwords);
+/// println!("{}", displaylib::passwords::format_table(passwords, &TableField::DEFAULT, 80, MAX_NAME_LENGTH));
 /// ```
-pub fn display_as_table(passwords: Vec<dblib::pm::PasswordInfoForm>) {
-    let (width, _) = terminal::size().unwrap_or((80, 0));
+pub fn format_table(
+    passwords: Vec<dblib::pm::PasswordInfoForm>,
+    fields: &[TableField],
+    width: u16,
+    max_name: usize
+) -> String {
     let max_col_width = (width as f32 * 0.7 ) as usize;
-    println!(
-        "{}",
-        Table::new(passwords)
-            .with(Style::rounded())
-            .with( // First Row: Set the alignment to center.
-                Modify::new(
-                    Rows::single(0)
-                ).with(Alignment::center())
-            )
-            .with( // Name Column: Set the max based on terminal width.
-                Modify::new(
-                    Columns::single(1)
-                ).with(
-                    Width::wrap(max_col_width)
-                )
-            )
-            .with( // Remove The Password Column.
-                Remove::column(
-                    ByColumnName::new("password")
-                )
+    let mut builder = Builder::default();
+    builder.push_record(fields.iter().map(|field| field.header()));
+    for pass in passwords.iter() {
+        builder.push_record(fields.iter().map(|field| match field {
+            TableField::Name => truncate(field.value(pass), max_name),
+            _ => field.value(pass)
+        }));
+    }
+    let mut table = builder.build();
+    table
+        .with(Style::rounded())
+        .with( // First Row: Set the alignment to center.
+            Modify::new(
+                Rows::single(0)
+            ).with(Alignment::center())
+        );
+    if let Some(index) = fields.iter().position(|field| *field == TableField::Name) {
+        table.with( // Name Column: Set the max based on terminal width.
+            Modify::new(
+                Columns::single(index)
+            ).with(
+                Width::wrap(max_col_width)
             )
-    );
-}
\ No newline at end of file
+        );
+    }
+    table.to_string()
+}
+
+/// The output formats of the passwords lists (`find`, `show`).
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ListFormat {
+    /// A table of the fields, without the passwords (default).
+    Table,
+    /// A colored line for every password.
+    List,
+    /// A JSON list of the passwords.
+    Json,
+    /// A `<name>\t<password>` line for every password, no color.
+    Plain
+}
+
+impl ListFormat {
+    /// Get the format from its name (table, list, json, plain).
+    pub fn from_name(name: &str) -> Option<ListFormat> {
+        match name {
+            "table" => Some(ListFormat::Table),
+            "list" => Some(ListFormat::List),
+            "json" => Some(ListFormat::Json),
+            "plain" => Some(ListFormat::Plain),
+            _ => None
+        }
+    }
+}
+
+/// Format passwords based on the `ListFormat`, the `fields` are the
+/// table columns cutting the names to `max_name` characters, and
+/// `string` is highlighted in the list names.
+/// 
+/// ### Example:
+/// ```
+/// let output = format_passwords(passwords, ListFormat::Json, &TableField::DEFAULT, "", 80, MAX_NAME_LENGTH);
+/// ```
+pub fn format_passwords(
+    passwords: Vec<dblib::pm::PasswordInfoForm>,
+    format: ListFormat,
+    fields: &[TableField],
+    string: &str,
+    width: u16,
+    max_name: usize
+) -> String {
+    let passwords = redacted(passwords);
+    match format {
+        ListFormat::Table => format_table(passwords, fields, width, max_name),
+        ListFormat::List => format!(
+            "\n{}\n",
+            passwords.iter()
+                .map(|pass| format_line(pass, string))
+                .collect::<Vec<String>>()
+                .join("\n")
+        ),
+        ListFormat::Json => serde_json::to_string_pretty(
+            &passwords.iter()
+                .map(|pass| serde_json::json!({
+                    "id": pass.id,
+                    "icon": pass.icon,
+                    "name": pass.name,
+                    "password": pass.password,
+                    "tags": pass.tags,
+                    "create_at": pass.create_at,
+                    "update_at": pass.update_at
+                }))
+                .collect::<Vec<serde_json::Value>>()
+        ).unwrap(),
+        ListFormat::Plain => passwords.iter()
+            .map(|pass| format!("{}\t{}", pass.name, pass.password))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Display passwords based on the `ListFormat`.
+/// 
+/// ### Example:
+/// ```
+/// displaylib::passwords::display_passwords(passwords, ListFormat::Table, &TableField::DEFAULT, "", MAX_NAME_LENGTH);
+/// ```
+pub fn display_passwords(
+    passwords: Vec<dblib::pm::PasswordInfoForm>,
+    format: ListFormat,
+    fields: &[TableField],
+    string: &str,
+    max_name: usize
+) {
+    let (width, _) = terminal::size().unwrap_or((80, 0));
+    println!("{}", format_passwords(passwords, format, fields, string, width, max_name));
+}
+
+/// Format the field (e.g. the name) of every password followed by a
+/// NUL byte, without any decoration, so it can be piped to `xargs -0`.
+/// 
+/// ### Example:
+/// ```
+/// // "github\0gitlab\0"
+/// let output = format_print0(passwords, TableField::Name);
+/// ```
+pub fn format_print0(passwords: Vec<dblib::pm::PasswordInfoForm>, field: TableField) -> String {
+    redacted(passwords)
+        .iter()
+        .map(|pass| format!("{}\0", field.value(pass)))
+        .collect()
+}
+
+/// Display the field of every password NUL delimited.
+/// 
+/// ### Example:
+/// ```
+/// displaylib::passwords::display_print0(passwords, TableField::Id);
+/// ```
+pub fn display_print0(passwords: Vec<dblib::pm::PasswordInfoForm>, field: TableField) {
+    print!("{}", format_print0(passwords, field));
+    let _ = std::io::stdout().flush();
+}
+
+/// Count the passwords of each tag, sorted by the count (descending)
+/// then the tag name, with the untagged passwords last. A password
+/// with many tags is counted in each of them.
+///
+/// ### Example:
+/// ```
+/// for (tag, count) in count_by_tag(&passwords) {
+///     println!("{}: {}", tag, count);
+/// }
+/// ```
+pub fn count_by_tag(passwords: &[dblib::pm::PasswordInfoForm]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = group_by_tag(passwords)
+        .into_iter()
+        .map(|(tag, group)| (tag, group.len()))
+        .collect();
+    let untagged = match counts.last() {
+        Some((tag, _)) if tag == UNTAGGED_HEADER => counts.pop(),
+        _ => None
+    };
+    // The groups are sorted by the tag name, the stable sort keeps it for the same counts.
+    counts.sort_by(|(_, count), (_, other_count)| other_count.cmp(count));
+    counts.extend(untagged);
+    counts
+}
+
+/// The tags count table row.
+#[derive(Tabled)]
+struct TagCountRow {
+    tag: String,
+    count: usize
+}
+
+/// Format the tags count as a table.
+///
+/// ### Example:
+/// ```
+/// println!("{}", format_tag_counts(&count_by_tag(&passwords)));
+/// ```
+pub fn format_tag_counts(counts: &[(String, usize)]) -> String {
+    let rows: Vec<TagCountRow> = counts
+        .iter()
+        .map(|(tag, count)| TagCountRow {
+            tag: tag.clone(),
+            count: *count
+        })
+        .collect();
+    Table::new(rows)
+        .with(Style::rounded())
+        .with( // First Row: Set the alignment to center.
+            Modify::new(
+                Rows::single(0)
+            ).with(Alignment::center())
+        )
+        .to_string()
+}
+
+/// Display the tags count as a table.
+///
+/// ### Example:
+/// ```
+/// displaylib::passwords::display_tag_counts(&count_by_tag(&passwords));
+/// ```
+pub fn display_tag_counts(counts: &[(String, usize)]) {
+    println!("{}", format_tag_counts(counts));
+}
+
+/// The diff table row.
+#[derive(Tabled)]
+struct DiffRow {
+    name: String,
+    status: String,
+    fields: String
+}
+
+/// Format the passwords diff as a table, every row is colored by its
+/// status (green: only mine, red: only theirs, yellow: changed, no color: same).
+/// 
+/// ### Example:
+/// ```
+/// println!("{}", format_diff(&password_manager::diff::diff(&mine, &theirs)));
+/// ```
+pub fn format_diff(entries: &[password_manager::diff::DiffEntry]) -> String {
+    let rows: Vec<DiffRow> = entries
+        .iter()
+        .map(|entry| DiffRow {
+            name: entry.name.clone(),
+            status: entry.status.label().to_owned(),
+            fields: entry.fields.join(", ")
+        })
+        .collect();
+    let table = Table::new(rows)
+        .with(Style::rounded())
+        .with( // First Row: Set the alignment to center.
+            Modify::new(
+                Rows::single(0)
+            ).with(Alignment::center())
+        )
+        .to_string();
+    // Color the rows after the table is built, so the colors
+    // do NOT change the columns width. The rows start after the
+    // top border, the header, and the header line.
+    table.lines()
+        .enumerate()
+        .map(|(index, line)| {
+            match index.checked_sub(3).and_then(|row| entries.get(row)) {
+                Some(entry) => match entry.status {
+                    password_manager::diff::DiffStatus::OnlyMine => line.green().to_string(),
+                    password_manager::diff::DiffStatus::OnlyTheirs => line.red().to_string(),
+                    password_manager::diff::DiffStatus::Changed => line.yellow().to_string(),
+                    password_manager::diff::DiffStatus::Same => line.to_owned()
+                },
+                None => line.to_owned()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Display the passwords diff as a table.
+/// 
+/// ### Example:
+/// ```
+/// displaylib::passwords::display_diff(&password_manager::diff::diff(&mine, &theirs));
+/// ```
+pub fn display_diff(entries: &[password_manager::diff::DiffEntry]) {
+    println!("{}", format_diff(entries));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::DisplayFormat;
+
+    #[test]
+    fn format_one_plain() {
+        let line = super::format_one(
+            "pass123".to_string(),
+            DisplayFormat::Plain
+        );
+        assert_eq!(line, "pass123", "Plain format NOT match!!");
+    }
+
+    #[test]
+    fn format_one_export() {
+        let line = super::format_one(
+            "it's".to_string(),
+            DisplayFormat::Export
+        );
+        assert_eq!(line, "XPM_PASSWORD='it'\\''s'", "Export format NOT match!!");
+    }
+
+    #[test]
+    fn format_many() {
+        let passwords = vec!["pass123".to_string(), "pass456".to_string()];
+        assert_eq!(
+            super::format_many(passwords.clone(), DisplayFormat::Pretty, false), "pass123\npass456",
+            "Piped pretty format NOT match!!"
+        );
+        assert_eq!(
+            super::format_many(passwords, DisplayFormat::Plain, true), "pass123\npass456",
+            "Plain format NOT match!!"
+        );
+        assert_eq!(super::format_many(vec![], DisplayFormat::Plain, false), "", "Empty format NOT match!!");
+    }
+
+    #[test]
+    fn format_table_icon() {
+        let table = super::format_table(
+            vec![
+                super::dblib::pm::PasswordInfoForm {
+                    icon: "🐙".to_string(),
+                    id: 32,
+                    name: "github".to_string(),
+                    password: "pass123".to_string(),
+                    create_at: "2025-05-24 15:59:12".to_string(),
+                    update_at: "2025-05-24 15:59:12".to_string(),
+                    tags: "work".to_string()
+                }
+            ],
+            &super::TableField::DEFAULT,
+            80,
+            super::MAX_NAME_LENGTH
+        );
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(
+            lines[1].trim_start_matches(['│', ' ']).starts_with("icon"),
+            "Icon is NOT the first column!!"
+        );
+        assert!(
+            lines[3].trim_start_matches(['│', ' ']).starts_with("🐙"),
+            "Icon NOT displayed!!"
+        );
+        assert!(!table.contains("pass123"), "Password displayed in the table!!");
+    }
+
+    #[test]
+    fn group_by_tag() {
+        let password = |id: i32, tags: &str| super::dblib::pm::PasswordInfoForm {
+            icon: String::new(),
+            id,
+            name: format!("pass-{}", id),
+            password: "pass123".to_string(),
+            create_at: "2025-05-24 15:59:12".to_string(),
+            update_at: "2025-05-24 15:59:12".to_string(),
+            tags: tags.to_string()
+        };
+        let passwords = vec![
+            password(1, "work"),
+            password(2, ""),
+            password(3, "social,work"),
+            password(4, "social")
+        ];
+        let groups: Vec<(String, Vec<i32>)> = super::group_by_tag(&passwords)
+            .into_iter()
+            .map(|(tag, group)| (tag, group.iter().map(|pass| pass.id).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("social".to_string(), vec![3, 4]),
+                ("work".to_string(), vec![1, 3]),
+                (super::UNTAGGED_HEADER.to_string(), vec![2])
+            ],
+            "Groups NOT match!!"
+        );
+        assert!(super::group_by_tag(&[]).is_empty(), "Empty groups NOT match!!");
+    }
+
+    #[test]
+    fn count_by_tag() {
+        let password = |id: i32, tags: &str| super::dblib::pm::PasswordInfoForm {
+            icon: String::new(),
+            id,
+            name: format!("pass-{}", id),
+            password: "pass123".to_string(),
+            create_at: "2025-05-24 15:59:12".to_string(),
+            update_at: "2025-05-24 15:59:12".to_string(),
+            tags: tags.to_string()
+        };
+        let passwords = [
+            password(1, "work"),
+            password(2, ""),
+            password(3, "social,work,bank"),
+            password(4, "social,work"),
+            password(5, ""),
+            password(6, "email")
+        ];
+        let counts = super::count_by_tag(&passwords);
+        assert_eq!(
+            counts,
+            vec![
+                ("work".to_string(), 3),
+                ("social".to_string(), 2),
+                ("bank".to_string(), 1),
+                ("email".to_string(), 1),
+                (super::UNTAGGED_HEADER.to_string(), 2)
+            ],
+            "Tags count NOT match!!"
+        );
+        let table = super::format_tag_counts(&counts);
+        assert!(
+            table.lines().nth(3).unwrap().contains("work") && table.lines().nth(3).unwrap().contains('3'),
+            "Tags count table NOT match!!"
+        );
+    }
+
+    #[test]
+    fn redact_password() {
+        let pass = super::redact_password(super::dblib::pm::PasswordInfoForm {
+            icon: String::new(),
+            id: 32,
+            name: "github".to_string(),
+            password: "pass123".to_string(),
+            create_at: "2025-05-24 15:59:12".to_string(),
+            update_at: "2025-05-25 10:00:00".to_string(),
+            tags: "work".to_string()
+        });
+        assert_eq!(pass.name, "g*****", "Masked name NOT match!!");
+        assert_eq!(pass.password, super::REDACTED_PASSWORD, "Masked password NOT match!!");
+        assert!(
+            pass.id == 32 &&
+            pass.create_at == "2025-05-24 15:59:12" &&
+            pass.update_at == "2025-05-25 10:00:00",
+            "Id and dates NOT preserved!!"
+        );
+        assert_eq!(super::mask_name(""), "", "Empty name mask NOT match!!");
+    }
+
+    #[test]
+    fn format_table_fields() {
+        let fields = super::TableField::parse_list("id, name,updated").unwrap();
+        let table = super::format_table(
+            vec![
+                super::dblib::pm::PasswordInfoForm {
+                    icon: "🐙".to_string(),
+                    id: 32,
+                    name: "github".to_string(),
+                    password: "pass123".to_string(),
+                    create_at: "2025-05-24 15:59:12".to_string(),
+                    update_at: "2025-05-25 10:00:00".to_string(),
+                    tags: "work".to_string()
+                }
+            ],
+            &fields,
+            80,
+            super::MAX_NAME_LENGTH
+        );
+        let cells = |line: &str| -> Vec<String> {
+            line.split('│')
+                .map(|cell| cell.trim().to_string())
+                .filter(|cell| !cell.is_empty())
+                .collect()
+        };
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(cells(lines[1]), vec!["id", "name", "update_at"], "Columns NOT match!!");
+        assert_eq!(cells(lines[3]), vec!["32", "github", "2025-05-25 10:00:00"], "Row NOT match!!");
+        assert_eq!(
+            super::TableField::parse_list("id,username"),
+            Err("username".to_string()),
+            "Unknown field NOT match!!"
+        );
+    }
+
+    #[test]
+    fn format_print0() {
+        let password = |id: i32, name: &str| super::dblib::pm::PasswordInfoForm {
+            icon: String::new(),
+            id,
+            name: name.to_owned(),
+            password: format!("pass-{}", id),
+            create_at: "2025-05-24 15:59:12".to_owned(),
+            update_at: "2025-05-24 15:59:12".to_owned(),
+            tags: String::new()
+        };
+        let passwords = || vec![password(3, "github work"), password(7, "gitlab")];
+        let output = super::format_print0(passwords(), super::TableField::Name);
+        assert!(output.ends_with('\0'), "Last name NOT NUL terminated!!");
+        assert_eq!(
+            output.split_terminator('\0').collect::<Vec<&str>>(),
+            vec!["github work", "gitlab"],
+            "NUL delimited names NOT match!!"
+        );
+        assert!(!output.contains("pass-") && !output.contains('\x1b'), "Output has decoration or passwords!!");
+        assert_eq!(
+            super::format_print0(passwords(), super::TableField::Id),
+            "3\x007\x00",
+            "NUL delimited ids NOT match!!"
+        );
+    }
+
+    #[test]
+    fn format_passwords() {
+        let format = |name: &str| super::format_passwords(
+            vec![
+                super::dblib::pm::PasswordInfoForm {
+                    icon: String::new(),
+                    id: 32,
+                    name: "github".to_string(),
+                    password: "pass123".to_string(),
+                    create_at: "2025-05-24 15:59:12".to_string(),
+                    update_at: "2025-05-24 15:59:12".to_string(),
+                    tags: "work".to_string()
+                }
+            ],
+            super::ListFormat::from_name(name).unwrap(),
+            &super::TableField::DEFAULT,
+            "",
+            80,
+            super::MAX_NAME_LENGTH
+        );
+        let table = format("table");
+        assert!(table.contains('╭') && table.contains('│'), "Table format NOT match!!");
+        assert!(!table.contains("pass123"), "Table format shows the password!!");
+        assert!(format("list").contains(" - "), "List format NOT match!!");
+        let json: serde_json::Value = serde_json::from_str(&format("json"))
+            .expect("Json format NOT valid!!");
+        assert_eq!(json[0]["password"], "pass123", "Json format NOT match!!");
+        assert!(format("json").starts_with('[') && format("json").contains('{'), "Json format NOT match!!");
+        assert_eq!(format("plain"), "github\tpass123", "Plain format NOT match!!");
+        assert_eq!(super::ListFormat::from_name("xml"), None, "Unknown format NOT refused!!");
+    }
+
+    #[test]
+    fn format_table_long_name() {
+        let table = super::format_table(
+            vec![
+                super::dblib::pm::PasswordInfoForm {
+                    icon: String::new(),
+                    id: 32,
+                    name: "x".repeat(5000),
+                    password: "pass123".to_string(),
+                    create_at: "2025-05-24 15:59:12".to_string(),
+                    update_at: "2025-05-24 15:59:12".to_string(),
+                    tags: "work".to_string()
+                }
+            ],
+            &super::TableField::parse_list("id,name").unwrap(),
+            80,
+            super::MAX_NAME_LENGTH
+        );
+        assert_eq!(
+            table.chars().filter(|c| *c == 'x').count() + table.matches('…').count(),
+            super::MAX_NAME_LENGTH,
+            "Long name NOT cut to the max length!!"
+        );
+        assert!(table.contains('…'), "Cut name NOT marked!!");
+        assert_eq!(super::truncate("github".to_string(), 6), "github", "Short name cut!!");
+    }
+}
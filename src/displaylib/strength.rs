@@ -0,0 +1,41 @@
+use crate::password_manager::strength::{Strength, Weakness};
+use super::Colorize;
+
+/// Format the password strength, and the weaknesses with the
+/// suggestions if `explain` is true.
+/// 
+/// ### Example:
+/// ```
+/// let found = strength::weaknesses("password1234");
+/// println!("{}", format(strength::strength("password1234", &found), &found, true));
+/// ```
+pub fn format(strength: Strength, weaknesses: &[Weakness], explain: bool) -> String {
+    let label = match strength {
+        Strength::VeryWeak | Strength::Weak => strength.label().red(),
+        Strength::Fair => strength.label().yellow(),
+        Strength::Strong | Strength::VeryStrong => strength.label().green()
+    };
+    let mut lines = vec![format!("Strength: {}", label)];
+    if explain {
+        if weaknesses.is_empty() {
+            lines.push("No weaknesses found.".to_owned());
+        }
+        for weakness in weaknesses {
+            lines.push(format!("{} {}", "-".red(), weakness.message()));
+            lines.push(format!("  {} {}", "Suggestion:".blue(), weakness.suggestion()));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Display the password strength, and the weaknesses with the
+/// suggestions if `explain` is true.
+/// 
+/// ### Example:
+/// ```
+/// let found = strength::weaknesses("password1234");
+/// displaylib::strength::display(strength::strength("password1234", &found), &found, true);
+/// ```
+pub fn display(strength: Strength, weaknesses: &[Weakness], explain: bool) {
+    println!("\n{}\n", format(strength, weaknesses, explain));
+}
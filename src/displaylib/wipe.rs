@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+use crate::{filelib, utilities};
+use super::Colorize;
+
+/// Display the files that will be wiped with the total size.
+/// 
+/// ### Example:
+/// ```
+/// let files = vec![
+///     (PathBuf::new().join("./dir/f.txt"), 1024)
+/// ];
+/// displaylib::wipe::display(files);
+/// ```
+pub fn display(files: Vec<(PathBuf, u64)>) {
+    println!();
+    let mut total: u64 = 0;
+    for (path, size) in files.iter() {
+        total += size;
+        println!(
+            "{} - {}",
+            utilities::format_size(*size).blue(),
+            path.display().to_string().red()
+        );
+    }
+    println!(
+        "\n{} {} files, {}\n",
+        "Will be wiped:".red(),
+        files.len(),
+        utilities::format_size(total).red()
+    );
+}
+
+/// Display the wipe estimate, the passes and the bytes
+/// that will be written, without wiping the files.
+/// 
+/// ### Example:
+/// ```
+/// let files = vec![
+///     (PathBuf::new().join("./dir/f.txt"), 1024)
+/// ];
+/// displaylib::wipe::display_dry(files, &filelib::WipeSettings::default());
+/// ```
+pub fn display_dry(files: Vec<(PathBuf, u64)>, settings: &filelib::WipeSettings) {
+    let dry = filelib::dry_wipe(&files, settings);
+    println!();
+    for (path, size) in files.iter() {
+        println!(
+            "{} x {} - {}",
+            dry.passes,
+            utilities::format_size(*size).blue(),
+            path.display()
+        );
+    }
+    println!(
+        "\n{} {} files, {} passes, {} per pass, {} total (nothing written)\n",
+        "Dry wipe:".yellow(),
+        files.len(),
+        dry.passes,
+        utilities::format_size(dry.bytes_per_pass),
+        utilities::format_size(dry.total_bytes).yellow()
+    );
+}
@@ -0,0 +1,189 @@
+use std::io::{Read, Write};
+use age::{Identity, Recipient};
+use super::{
+    FILE_MAGIC,
+    AGE_FILE_VERSION
+};
+use crate::{
+    errorlib,
+    filelib,
+    loglib
+};
+
+/// Check if the file is encrypted with an age recipient.
+/// 
+/// ### Example:
+/// ```
+/// if age_file::is_age_file("./dir/f.txt.x") {
+///     println!("use --age-identity to decrypt it.");
+/// }
+/// ```
+pub fn is_age_file(path: &str) -> bool {
+    let mut header = [0u8; 5];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|_| &header[..4] == FILE_MAGIC && header[4] == AGE_FILE_VERSION)
+}
+
+/// Encrypt file to an age X25519 recipient (e.g. `age1...`), only the holder
+/// of the matching private key can decrypt it.
+/// File format: "<magic><age-version><age-file>". With `fsync` the
+/// encrypted file is synced to the disk (e.g. before wiping the origin).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// 
+/// ### Example:
+/// ```
+/// // find it at: `./dir/f.txt.x`
+/// age_file::encrypt("./dir/f.txt".to_string(), "age1...", false);
+/// ```
+pub fn encrypt(path: String, recipient: &str, fsync: bool) {
+    let logger = loglib::Logger::new("age-encrypt-file");
+    let recipient = recipient.trim().parse::<age::x25519::Recipient>()
+        .unwrap_or_else(|_| logger.error(
+            "invalid age recipient, it must be an X25519 public key (age1...)!",
+            errorlib::ExitErrorCode::Input
+        ));
+    let encryptor = age::Encryptor::with_recipients(
+        std::iter::once(&recipient as &dyn Recipient)
+    ).unwrap_or_else(|_| logger.error(
+        "can NOT use the age recipient!",
+        errorlib::ExitErrorCode::Input
+    ));
+    let mut de_file = std::fs::File::open(&path).unwrap_or_else(|_| logger.error(
+        "can NOT open the file!",
+        errorlib::ExitErrorCode::FileOpen
+    ));
+    let en_path = filelib::make_encrypt_path(path);
+    let result = std::fs::File::create(&en_path)
+        .and_then(|mut en_file| {
+            en_file.write_all(FILE_MAGIC)?;
+            en_file.write_all(&[AGE_FILE_VERSION])?;
+            let mut writer = encryptor.wrap_output(&en_file)?;
+            std::io::copy(&mut de_file, &mut writer)?;
+            writer.finish()?;
+            if fsync {
+                filelib::sync_file(&en_file, &en_path)?;
+            }
+            Ok(())
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&en_path);
+        logger.error(
+            "can NOT write the encrypted file!",
+            errorlib::ExitErrorCode::FileWrite
+        );
+    }
+}
+
+/// Decrypt an age file using the identities (private keys) file.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// 
+/// ### Example:
+/// ```
+/// // find it at: `./dir/f.txt`
+/// age_file::decrypt("./dir/f.txt.x".to_string(), "./keys.txt");
+/// ```
+pub fn decrypt(path: String, identity_path: &str) {
+    let logger = loglib::Logger::new("age-decrypt-file");
+    let identities = age::IdentityFile::from_file(identity_path.to_owned())
+        .unwrap_or_else(|_| logger.error(
+            &format!("can NOT read the age identity file '{}'!", identity_path),
+            errorlib::ExitErrorCode::FileRead
+        ))
+        .into_identities()
+        .unwrap_or_else(|_| logger.error(
+            "invalid age identity file!",
+            errorlib::ExitErrorCode::InvalidKey
+        ));
+    let mut en_file = std::fs::File::open(&path).unwrap_or_else(|_| logger.error(
+        "can NOT open the file!",
+        errorlib::ExitErrorCode::FileOpen
+    ));
+    let mut header = [0u8; 5];
+    if en_file.read_exact(&mut header).is_err()
+        || &header[..4] != FILE_MAGIC
+        || header[4] != AGE_FILE_VERSION {
+        logger.error(
+            "the file is NOT encrypted with an age recipient!",
+            errorlib::ExitErrorCode::InvalidEncryptionData
+        );
+    }
+    let decryptor = age::Decryptor::new(en_file).unwrap_or_else(|_| logger.error(
+        "the age file is broken!",
+        errorlib::ExitErrorCode::InvalidEncryptionData
+    ));
+    let mut reader = decryptor
+        .decrypt(identities.iter().map(|identity| identity.as_ref() as &dyn Identity))
+        .unwrap_or_else(|_| logger.error(
+            "no identity matches the file recipient!",
+            errorlib::ExitErrorCode::InvalidKey
+        ));
+    let de_path = filelib::make_decrypt_path(path);
+    let result = std::fs::File::create(&de_path)
+        .and_then(|mut de_file| std::io::copy(&mut reader, &mut de_file));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&de_path);
+        logger.error(
+            "can NOT decrypt the file, it may be broken!",
+            errorlib::ExitErrorCode::InvalidEncryptionData
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use age::secrecy::ExposeSecret;
+    use crate::filelib::create_file;
+
+    #[test]
+    fn age_round_trip() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/age_round_trip");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        let identity_file = temp_dir.join("keys.txt");
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager age file").unwrap();
+
+        let identity = age::x25519::Identity::generate();
+        std::fs::write(&identity_file, identity.to_string().expose_secret()).unwrap();
+        super::encrypt(
+            file.to_str().unwrap().to_owned(),
+            &identity.to_public().to_string(),
+            true
+        );
+        assert!(
+            super::is_age_file(en_file.to_str().unwrap()),
+            "Age header NOT found!!"
+        );
+        std::fs::remove_file(&file).unwrap();
+
+        super::decrypt(
+            en_file.to_str().unwrap().to_owned(),
+            identity_file.to_str().unwrap()
+        );
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "XPManager age file",
+            "Decrypted data NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
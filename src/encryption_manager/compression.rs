@@ -0,0 +1,177 @@
+use super::{Read, Write};
+use flate2::{
+    read::GzDecoder,
+    write::GzEncoder
+};
+
+/// The compression algorithms, uses to compress
+/// every block before the encryption.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Compression {
+    /// No compression.
+    Off,
+    /// Gzip using `flate2`, levels from 0 to 9.
+    Gzip,
+    /// Zstandard using `zstd`, levels from 1 to 22.
+    Zstd
+}
+
+impl Compression {
+    /// Get the compression from the algorithm name.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let compression = Compression::from_name("zstd");
+    /// assert_eq!(compression, Some(Compression::Zstd));
+    /// ```
+    pub fn from_name(name: &str) -> Option<Compression> {
+        match name {
+            "gzip" => Some(Compression::Gzip),
+            "zstd" => Some(Compression::Zstd),
+            _ => None
+        }
+    }
+
+    /// Get the compression from the id stored in the file header.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let compression = Compression::from_id(2);
+    /// assert_eq!(compression, Some(Compression::Zstd));
+    /// ```
+    pub fn from_id(id: u8) -> Option<Compression> {
+        match id {
+            0 => Some(Compression::Off),
+            1 => Some(Compression::Gzip),
+            2 => Some(Compression::Zstd),
+            _ => None
+        }
+    }
+
+    /// Get the compression id to store it in the file header.
+    /// NOTE: Any change in the ids will be `breaking change`.
+    pub fn id(&self) -> u8 {
+        match self {
+            Compression::Off => 0,
+            Compression::Gzip => 1,
+            Compression::Zstd => 2
+        }
+    }
+
+    /// Get the default compression level.
+    pub fn default_level(&self) -> u8 {
+        match self {
+            Compression::Off => 0,
+            Compression::Gzip => 6,
+            Compression::Zstd => 3
+        }
+    }
+
+    /// Check the compression level is supported by the algorithm.
+    /// 
+    /// ### Example:
+    /// ```
+    /// assert!(Compression::Gzip.is_valid_level(9));
+    /// assert!(!Compression::Gzip.is_valid_level(10));
+    /// ```
+    pub fn is_valid_level(&self, level: u8) -> bool {
+        match self {
+            Compression::Off => level == 0,
+            Compression::Gzip => level <= 9,
+            Compression::Zstd => (1..=22).contains(&level)
+        }
+    }
+}
+
+/// Compress the data using the compression algorithm and level.
+/// Returns `None` if the compression fails.
+/// 
+/// ### Example:
+/// ```
+/// let data = compression::compress(b"xpm xpm xpm", Compression::Gzip, 6);
+/// ```
+pub fn compress(data: &[u8], compression: Compression, level: u8) -> Option<Vec<u8>> {
+    match compression {
+        Compression::Off => Some(data.to_vec()),
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(
+                Vec::new(),
+                flate2::Compression::new(level as u32)
+            );
+            encoder.write_all(data).ok()?;
+            encoder.finish().ok()
+        },
+        Compression::Zstd => zstd::encode_all(data, level as i32).ok()
+    }
+}
+
+/// Decompress the data using the compression algorithm, the decompressed
+/// data is read up to `limit` bytes so a small block can NOT inflate into
+/// a huge buffer. Returns `None` if the data is not valid or it decompresses
+/// to more than `limit` bytes, the `Off` data is returned as it is.
+/// 
+/// ### Example:
+/// ```
+/// let data = compression::compress(b"xpm", Compression::Zstd, 3).unwrap();
+/// let origin = compression::decompress(&data, Compression::Zstd, 1024).unwrap();
+/// assert_eq!(origin, b"xpm");
+/// ```
+pub fn decompress(data: &[u8], compression: Compression, limit: usize) -> Option<Vec<u8>> {
+    match compression {
+        Compression::Off => Some(data.to_vec()),
+        Compression::Gzip => read_limited(GzDecoder::new(data), limit),
+        Compression::Zstd => read_limited(zstd::stream::read::Decoder::new(data).ok()?, limit)
+    }
+}
+
+/// Read the decoder to the end, `None` if it has more than `limit` bytes.
+fn read_limited<R: Read>(decoder: R, limit: usize) -> Option<Vec<u8>> {
+    let mut decoded: Vec<u8> = Vec::new();
+    decoder.take(limit as u64 + 1).read_to_end(&mut decoded).ok()?;
+    if decoded.len() > limit {
+        return None;
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compression;
+
+    #[test]
+    fn compress_and_decompress() {
+        let data = "XPManager is the best! ".repeat(1000);
+        for (compression, levels) in [
+            (Compression::Gzip, [1u8, 9u8]),
+            (Compression::Zstd, [1u8, 19u8])
+        ] {
+            for level in levels {
+                let compressed = super::compress(data.as_bytes(), compression, level)
+                    .expect("Can NOT compress the data!!");
+                assert!(compressed.len() < data.len(), "Data NOT compressed!!");
+                let decompressed = super::decompress(&compressed, compression, data.len())
+                    .expect("Can NOT decompress the data!!");
+                assert_eq!(decompressed, data.as_bytes(), "Decompressed data NOT match!!");
+            }
+        }
+    }
+    #[test]
+    fn decompress_limit() {
+        // 1 MiB of zeros compresses to a few bytes.
+        let data = vec![0u8; 1024 * 1024];
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let compressed = super::compress(&data, compression, compression.default_level())
+                .expect("Can NOT compress the data!!");
+            assert_eq!(
+                super::decompress(&compressed, compression, data.len()),
+                Some(data.clone()),
+                "Data at the limit NOT decompressed!!"
+            );
+            assert_eq!(
+                super::decompress(&compressed, compression, 1000),
+                None,
+                "Data over the limit NOT rejected!!"
+            );
+        }
+    }
+}
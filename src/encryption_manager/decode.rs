@@ -1,4 +1,5 @@
 use super::ArgMatches;
+use base64::{Engine, engine::general_purpose::STANDARD};
 use crate::{
     displaylib, 
     loglib, 
@@ -27,22 +28,41 @@ pub fn xpmv1(string: String, n: u16) -> String {
         .collect::<String>()
 }
 
-/// Decode hexadecimal to string.
+/// Decode hexadecimal code points separated by spaces to string,
+/// None if it is NOT a valid hexadecimal code point.
 /// 
 /// ### Example:
 /// ```
 /// let xpm = decode::hex("78 70 6D");
-/// assert_eq!(xpm, "xpm");
+/// assert_eq!(xpm, Some("xpm".to_string()));
 /// ```
-pub fn hex(string: String) -> String {
+pub fn hex(string: String) -> Option<String> {
     string
-        .split(" ")
-        .map(|i| {
-            char::from_u32(
-                u32::from_str_radix(i, 16).unwrap()
-            ).unwrap()
-        })
-        .collect::<String>()
+        .split_whitespace()
+        .map(|i| u32::from_str_radix(i, 16).ok().and_then(char::from_u32))
+        .collect::<Option<String>>()
+        .filter(|string| !string.is_empty())
+}
+
+/// Decode hexadecimal UTF-8 bytes without spaces to string, two digits
+/// for every byte (e.g. the `encode --file` output). None if it is NOT
+/// a valid hexadecimal, or the bytes are NOT an even length UTF-8 text.
+/// 
+/// ### Example:
+/// ```
+/// let xpm = decode::hex_bytes("58504D616E61676572");
+/// assert_eq!(xpm, Some("XPManager".to_string()));
+/// ```
+pub fn hex_bytes(string: String) -> Option<String> {
+    let string = string.trim();
+    if string.is_empty() || !string.len().is_multiple_of(2) || !string.is_ascii() {
+        return None;
+    }
+    (0..string.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&string[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()
+        .and_then(|data| String::from_utf8(data).ok())
 }
 
 /// Decode binary string.
@@ -63,6 +83,20 @@ pub fn bin(string: String) -> String {
         .collect::<String>()
 }
 
+/// Decode base64 (standard alphabet) to string, None if it is
+/// NOT a valid base64 or the data is NOT a UTF-8 text.
+/// 
+/// ### Example:
+/// ```
+/// let xpm = decode::base64("eHBt");
+/// assert_eq!(xpm, Some("xpm".to_string()));
+/// ```
+pub fn base64(string: String) -> Option<String> {
+    STANDARD.decode(string.trim())
+        .ok()
+        .and_then(|data| String::from_utf8(data).ok())
+}
+
 pub fn main(command: &ArgMatches) {
     let mut logger = loglib::Logger::new("decode");
     let string = utilities::input("Enter the string: ");
@@ -85,10 +119,23 @@ pub fn main(command: &ArgMatches) {
                 errorlib::ExitErrorCode::Input
             )
         }
+    } else if *command.get_one::<bool>("base64").unwrap_or(&false) {
+        _encoded_date = base64(string.clone()).unwrap_or_else(|| logger.error(
+            "invalid base64 string, or it is NOT a text!",
+            errorlib::ExitErrorCode::InvalidEncoding
+        ));
     } else if *command.get_one::<bool>("bin").unwrap_or(&false) {
         _encoded_date = bin(string.clone());
+    } else if *command.get_one::<bool>("bytes").unwrap_or(&false) {
+        _encoded_date = hex_bytes(string.clone()).unwrap_or_else(|| logger.error(
+            "invalid hexadecimal bytes, or they are NOT a text!",
+            errorlib::ExitErrorCode::InvalidEncoding
+        ));
     } else {
-        _encoded_date = hex(string.clone());
+        _encoded_date = hex(string.clone()).unwrap_or_else(|| logger.error(
+            "invalid hexadecimal string, or it is NOT a text!",
+            errorlib::ExitErrorCode::InvalidEncoding
+        ));
     };
     displaylib::decode::display(_encoded_date);
     logger.info("string decoded successfully.");
@@ -112,8 +159,35 @@ mod tests {
         let result = super::hex(
             "58 50 4D 61 6E 61 67 65 72".to_string()
         );
-        let expected = "XPManager";
+        let expected = Some("XPManager".to_string());
         assert_eq!(result, expected, "Decoded value NOT match!!");
+        assert_eq!(super::hex("1F600".to_string()), Some("😀".to_string()), "Decoded code point NOT match!!");
+        assert_eq!(super::hex("4142".to_string()), Some("\u{4142}".to_string()), "Decoded code point NOT match!!");
+        assert_eq!(super::hex("58 5G".to_string()), None, "Invalid hexadecimal NOT match!!");
+        assert_eq!(super::hex("".to_string()), None, "Empty string NOT match!!");
+    }
+
+    #[test]
+    fn hex_bytes() {
+        let result = super::hex_bytes("58504D616E61676572".to_string());
+        assert_eq!(result, Some("XPManager".to_string()), "Decoded bytes NOT match!!");
+        // The short inputs are bytes too, NOT code points.
+        for string in ["A", "AB", "xpm", "é", "😀", "XPManager"] {
+            let encoded = crate::encryption_manager::encode::hex_bytes(string.as_bytes());
+            assert_eq!(super::hex_bytes(encoded), Some(string.to_string()), "Round trip of '{}' NOT match!!", string);
+        }
+        assert_eq!(super::hex_bytes("58504D616E6167657".to_string()), None, "Odd length NOT match!!");
+        assert_eq!(super::hex_bytes("585G".to_string()), None, "Invalid hexadecimal NOT match!!");
+        assert_eq!(super::hex_bytes("FF".to_string()), None, "NOT UTF-8 data NOT match!!");
+        assert_eq!(super::hex_bytes("".to_string()), None, "Empty string NOT match!!");
+    }
+
+    #[test]
+    fn base64() {
+        let result = super::base64("WFBNYW5hZ2Vy".to_string());
+        assert_eq!(result, Some("XPManager".to_string()), "Decoded value NOT match!!");
+        assert_eq!(super::base64("WFBN*W5h".to_string()), None, "Invalid base64 NOT match!!");
+        assert_eq!(super::base64("/w==".to_string()), None, "NOT UTF-8 data NOT match!!");
     }
 
     #[test]
@@ -7,14 +7,72 @@ use super::{
 };
 use crate::{
     displaylib, 
+    errorlib,
     filelib, 
     loglib, 
     utilities,
     dblib
 };
 
+/// The output directory of `--out`, the decrypted files are
+/// written to it instead of next to the encrypted files.
+struct OutputDir {
+    /// The decrypted directory.
+    root: PathBuf,
+    /// The output directory.
+    out: PathBuf,
+    /// Keep the subdirectories of the files under the output directory.
+    preserve_structure: bool
+}
+
+impl OutputDir {
+    /// Get the decrypted file path, without creating anything.
+    /// 
+    /// ### Example:
+    /// ```
+    /// // "./out/sub/f.txt" with the structure, "./out/f.txt" without it.
+    /// let path = output.target(&PathBuf::new().join("./dir/sub/f.txt.x"));
+    /// ```
+    fn target(&self, file: &std::path::Path) -> PathBuf {
+        let name = if self.preserve_structure {
+            file.strip_prefix(&self.root).unwrap_or(file).to_path_buf()
+        } else {
+            PathBuf::new().join(file.file_name().unwrap())
+        };
+        PathBuf::new().join(filelib::make_decrypt_path(
+            self.out.join(name).to_str().unwrap().to_owned()
+        ))
+    }
 
-/// Decrypt list of files.
+    /// Get the decrypted file path, and create its directory.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::DirCreate`
+    /// - `errorlib::ExitErrorCode::FileCreate`
+    /// 
+    /// ### Example:
+    /// ```
+    /// // "./out/sub/f.txt" with the structure, "./out/f.txt" without it.
+    /// let path = output.path(&PathBuf::new().join("./dir/sub/f.txt.x"));
+    /// ```
+    fn path(&self, file: &std::path::Path) -> PathBuf {
+        let path = self.target(file);
+        // Creates the missing subdirectories too.
+        filelib::create_file(path.clone()).unwrap_or_else(|err| err.exit());
+        path
+    }
+}
+
+/// Get the decrypted file path, in the output directory if any.
+fn decrypted_path(file: &std::path::Path, output: Option<&OutputDir>) -> PathBuf {
+    match output {
+        Some(output) => output.target(file),
+        None => PathBuf::new().join(filelib::make_decrypt_path(file.to_str().unwrap().to_owned()))
+    }
+}
+
+/// Decrypt list of files, the failed files are handled by the batch.
+/// An existing decrypted file is NOT overwritten without `force`.
 /// 
 /// ### Example:
 /// ```
@@ -24,14 +82,23 @@ use crate::{
 ///     PathBuf::new().join('/folder/to/file-3.txt.x')
 /// ];
 /// let decryption_key = "<your-key>".to_string();
-/// let delete_files = false;
+/// let wipe_settings = Some(filelib::WipeSettings::default());
 /// let is_xpmv1_files = false;
+/// let batch = utilities::Batch::new(utilities::OnError::Continue);
 /// 
-/// decrypt(&files, decryption_key, delete_files, is_xpmv1_files);
+/// decrypt(&files, decryption_key, wipe_settings, is_xpmv1_files, None, false, &batch);
 /// ```
-fn decrypt(paths: &Vec<PathBuf>, key: String, is_delete: bool, is_xpmv1: bool) {
+fn decrypt(
+    paths: &[PathBuf],
+    key: String,
+    wipe: Option<filelib::WipeSettings>,
+    is_xpmv1: bool,
+    output: Option<&OutputDir>,
+    force: bool,
+    batch: &utilities::Batch
+) {
     let mut logger = loglib::Logger::new("decrypt-dir-thread");
-    for file in paths {
+    batch.run(paths, |file| {
         logger.start();
         let file_path_string = file.to_str().unwrap().to_owned();
         if filelib::get_file_state(
@@ -40,13 +107,43 @@ fn decrypt(paths: &Vec<PathBuf>, key: String, is_delete: bool, is_xpmv1: bool) {
             logger.warning(
                 &format!("file not encrypted '{}'?", file_path_string)
             );
-            continue;
+            return Ok(());
+        }
+        // The file may be removed or locked after the listing.
+        if let Err(error) = std::fs::File::open(file) {
+            return Err(utilities::BatchFailure::new(
+                &file_path_string,
+                errorlib::ExitErrorCode::FileOpen,
+                &format!("can NOT open the file, {}", error)
+            ));
+        }
+        let de_path = decrypted_path(file, output);
+        if !force && de_path.exists() {
+            return Err(utilities::BatchFailure::new(
+                &file_path_string,
+                errorlib::ExitErrorCode::FileWrite,
+                &format!("'{}' already exists, use --force", de_path.display())
+            ));
+        }
+        // XPManager v1.0 files have no blocks to verify the key with.
+        if !is_xpmv1 && !decrypt_file::verify_key(&file_path_string, &key) {
+            return Err(utilities::BatchFailure::new(
+                &file_path_string,
+                errorlib::ExitErrorCode::InvalidKey,
+                "the key does NOT decrypt the file"
+            ));
         }
         if is_xpmv1 {
             decrypt_file::xpmv1_decryption(
                 file_path_string.clone(),
                 key.clone()
             );
+        } else if let Some(output) = output {
+            decrypt_file::decrypt_to(
+                file_path_string.clone(),
+                output.path(file).to_str().unwrap().to_owned(),
+                key.clone()
+            );
         } else {
             decrypt_file::decrypt(
                 file_path_string.clone(),
@@ -58,17 +155,20 @@ fn decrypt(paths: &Vec<PathBuf>, key: String, is_delete: bool, is_xpmv1: bool) {
         );
         dblib::log::register(
             &format!("file '{}' decrypted", file.display()),
+            dblib::log::LogAction::Decrypt,
             filelib::log::get_log_db_path()
         );
-        if is_delete {
-            filelib::wipe_delete(file_path_string.clone());
+        if let Some(settings) = wipe {
+            let report = filelib::wipe_delete_settings(file_path_string.clone(), &settings, false).unwrap_or_else(|err| err.exit());
             logger.info("file was wiped successfully.");
             dblib::log::register(
-                &format!("file '{}' wiped", file.display()),
+                &report.message(),
+                dblib::log::LogAction::Wipe,
                 filelib::log::get_log_db_path()
             );
         }
-    }
+        Ok(())
+    });
 }
 
 pub fn main(command: &ArgMatches) {
@@ -77,13 +177,38 @@ pub fn main(command: &ArgMatches) {
     let mut files_paths: Vec<PathBuf> = vec![];
     let is_xpmv1 = *command.get_one::<bool>("xpmv1").unwrap_or(&false);
     let is_delete = *command.get_one::<bool>("delete").unwrap_or(&false);
+    let wipe = if is_delete { Some(super::wipe_settings(command)) } else { None };
     let no_threads = *command.get_one::<bool>("no-threads").unwrap_or(&false);
-    let key = utilities::input("Enter your key: ");
-    logger.start();
+    let batch = utilities::Batch::new(
+        command.get_one::<String>("on-error")
+            .and_then(|mode| utilities::OnError::from_name(mode))
+            .unwrap_or(utilities::OnError::Continue)
+    );
     filelib::dir_files_tree(
         PathBuf::new().join(path), 
         &mut files_paths
-    );
+    ).unwrap_or_else(|err| err.exit());
+    let output = command.get_one::<String>("out").map(|out| OutputDir {
+        root: PathBuf::new().join(path),
+        out: PathBuf::new().join(out),
+        preserve_structure: *command.get_one::<bool>("preserve-structure").unwrap_or(&false)
+    });
+    if let Some(output) = output.as_ref() {
+        if !output.preserve_structure {
+            // Flat output, two files with the same name will overwrite each other.
+            let mut names: Vec<&std::ffi::OsStr> = files_paths
+                .iter()
+                .filter_map(|file| file.file_name())
+                .collect();
+            names.sort();
+            if names.windows(2).any(|pair| pair[0] == pair[1]) {
+                logger.error(
+                    "many files have the same name in the subdirectories, use --preserve-structure!",
+                    errorlib::ExitErrorCode::Input
+                );
+            }
+        }
+    }
     if is_delete {
         logger.warning(
             &format!(
@@ -92,18 +217,58 @@ pub fn main(command: &ArgMatches) {
                 path.clone()
             )
         );
+        // Decrypted files will be skipped, so they will not be wiped.
+        let wiped_files: Vec<PathBuf> = files_paths
+            .iter()
+            .filter(|file| filelib::get_file_state(
+                file.to_str().unwrap().to_owned()
+            ) != filelib::FileState::Decrypted)
+            .cloned()
+            .collect();
+        if *command.get_one::<bool>("dry-wipe").unwrap_or(&false) {
+            displaylib::wipe::display_dry(
+                filelib::wipe_preview(&wiped_files),
+                &wipe.unwrap_or_default()
+            );
+            return;
+        }
+        displaylib::wipe::display(
+            filelib::wipe_preview(&wiped_files)
+        );
         utilities::confirm();
         logger.start();
     }
+    // The decrypted files must NOT replace files silently.
+    let mut is_force = *command.get_one::<bool>("force").unwrap_or(&false);
+    let existing = files_paths
+        .iter()
+        .filter(|file| filelib::get_file_state(
+            file.to_str().unwrap().to_owned()
+        ) != filelib::FileState::Decrypted)
+        .filter(|file| decrypted_path(file, output.as_ref()).exists())
+        .count();
+    if !is_force && existing > 0 {
+        logger.warning(
+            &format!("{} decrypted files already exist and will be overwritten!", existing)
+        );
+        utilities::confirm();
+        logger.start();
+        is_force = true;
+    }
     logger.info("directory listed successfully.");
+    let key = super::KeySource::resolve(command).read();
+    logger.start();
     
     if no_threads {
         logger.info("start the decryption using the main thread.");
         decrypt(
             &files_paths, 
             key.clone(), 
-            is_delete, 
-            is_xpmv1
+            wipe, 
+            is_xpmv1,
+            output.as_ref(),
+            is_force,
+            &batch
         );
     } else {
         logger.info("start the decryption with the max number of threads.");
@@ -115,12 +280,74 @@ pub fn main(command: &ArgMatches) {
             decrypt(
                 paths, 
                 key.clone(), 
-                is_delete, 
-                is_xpmv1
+                wipe, 
+                is_xpmv1,
+                output.as_ref(),
+                is_force,
+                &batch
             );
         });
     }
 
-    logger.info("directory decrypted successfully.");
     displaylib::key::display(key);
-}
\ No newline at end of file
+    batch.finish(&logger);
+    logger.info("directory decrypted successfully.");
+}
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::OutputDir;
+    use crate::{
+        encryption_manager::encrypt_file,
+        filelib::create_file
+    };
+
+    #[test]
+    fn decrypt_preserve_structure() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/decrypt_preserve_structure");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let dir = temp_dir.join("data");
+        let key = fernet::Fernet::generate_key();
+        let names = ["a.txt", "sub/b.txt", "sub/deep/c.txt"];
+        let mut files_paths: Vec<PathBuf> = vec![];
+        for name in names {
+            let file = dir.join(name);
+            create_file(file.clone()).unwrap();
+            std::fs::write(&file, name).unwrap();
+            encrypt_file::encrypt(file.to_str().unwrap().to_owned(), key.clone());
+            std::fs::remove_file(&file).unwrap();
+            files_paths.push(dir.join(format!("{}.x", name)));
+        }
+
+        let out = temp_dir.join("out");
+        let output = OutputDir {
+            root: dir.clone(),
+            out: out.clone(),
+            preserve_structure: true
+        };
+        super::decrypt(
+            &files_paths,
+            key,
+            None,
+            false,
+            Some(&output),
+            false,
+            &crate::utilities::Batch::new(crate::utilities::OnError::Stop)
+        );
+        for name in names {
+            assert_eq!(
+                std::fs::read_to_string(out.join(name)).unwrap_or_default(),
+                name,
+                "'{}' NOT decrypted in the output tree!!", name
+            );
+            assert!(!dir.join(name).exists(), "'{}' decrypted next to the source!!", name);
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
@@ -2,12 +2,20 @@ use super::{
     ArgMatches,
     Fernet,
     Write,
-    Read
+    Read,
+    FILE_MAGIC,
+    FILE_VERSION,
+    AGE_FILE_VERSION,
+    BUFFER_SIZE,
+    compression::{self, Compression}
 };
+use std::io::{IsTerminal, Seek, SeekFrom};
+use std::path::PathBuf;
 use crate::{
     errorlib,
     filelib, 
     loglib,
+    displaylib,
     utilities,
     dblib
 };
@@ -45,51 +53,469 @@ pub fn xpmv1_decryption(path: String, key: String) {
     logger.error("can NOT open the file!", errorlib::ExitErrorCode::FileOpen);
 }
 
-pub fn decrypt(path: String, key: String) {
-    let logger = loglib::Logger::new("decrypt-file");
-    if let Some(fernet) = Fernet::new(&key) {
-        if let Ok(mut en_file) = std::fs::File::open(&path) {
-            if let Ok(mut de_file) = std::fs::File::create(
-                filelib::make_decrypt_path(path)
-            ) {
-                // The encryption block size buffer
-                // Uses to read the size of the block
-                let mut size_buf = [0u8; 4];
-                loop {
-                    // Read the size of the block before reading the block
-                    if en_file.read_exact(&mut size_buf).is_err() {
-                        break;
-                    }
-                    // Encryption file format: "<length><en-data><length><en-data>"
-                    // Read the block size and make a buffer with this size and then
-                    // read the encryption block using the buffer.
-                    let size = u32::from_be_bytes(size_buf) as usize;
-                    let mut encryption_buffer = vec![0u8; size];
-                    en_file.read_exact(&mut encryption_buffer).unwrap();
-                    let data = fernet.decrypt(
-                        &String::from_utf8(
-                            encryption_buffer
-                        ).unwrap()
-                    ).unwrap();
-                    // Save the decrypted blocks one by one.
-                    de_file.write_all(&data).unwrap();
-                }
-                return;
-            }
+/// Read the encryption file header and return the plaintext buffer
+/// size, the compression used in the encryption, and the passphrase
+/// salt (empty for a key). Returns `None` for legacy files (no header),
+/// after reading the file will be at the start of the first block.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileSeek`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// - `errorlib::ExitErrorCode::UnsupportedFormat`
+/// 
+/// ### Example:
+/// ```
+/// let mut en_file = std::fs::File::open("./dir/f.txt.x").unwrap();
+/// if let Some((buffer_size, compression, _)) = read_header(&mut en_file) {
+///     println!("encrypted with {} bytes buffer.", buffer_size);
+/// }
+/// ```
+fn read_header(en_file: &mut std::fs::File) -> Option<(usize, Compression, Vec<u8>)> {
+    let logger = loglib::Logger::new("read-file-header");
+    let mut magic = [0u8; 4];
+    if en_file.read_exact(&mut magic).is_err() || &magic != FILE_MAGIC {
+        // Legacy file, the first 4 bytes are the length of the first block.
+        if en_file.seek(SeekFrom::Start(0)).is_err() {
+            logger.error(
+                "can NOT seek the file!",
+                errorlib::ExitErrorCode::FileSeek
+            );
         }
+        return None;
+    }
+    let mut version = [0u8; 1];
+    let mut buffer_size = [0u8; 4];
+    if en_file.read_exact(&mut version).is_err() 
+        || en_file.read_exact(&mut buffer_size).is_err() {
         logger.error(
-            "can NOT open the file!", 
-            errorlib::ExitErrorCode::FileOpen
+            "the file header is broken!",
+            errorlib::ExitErrorCode::InvalidEncryptionData
         );
-    } else {
+    }
+    if version[0] == AGE_FILE_VERSION {
         logger.error(
-            "key error!", 
-            errorlib::ExitErrorCode::InvalidKey
+            "the file is encrypted with an age recipient, use --age-identity!",
+            errorlib::ExitErrorCode::InvalidEncryptionData
+        );
+    } else if version[0] == 0 || version[0] > FILE_VERSION {
+        logger.error(
+            &format!("unsupported encryption file version {}, update XPManager!", version[0]),
+            errorlib::ExitErrorCode::UnsupportedFormat
+        );
+    }
+    // Version 1 files have no compression.
+    let mut compression = Compression::Off;
+    if version[0] >= 2 {
+        // "<compression-id><compression-level>", the level
+        // is not needed for the decompression.
+        let mut compression_info = [0u8; 2];
+        if en_file.read_exact(&mut compression_info).is_err() {
+            logger.error(
+                "the file header is broken!",
+                errorlib::ExitErrorCode::InvalidEncryptionData
+            );
+        }
+        compression = Compression::from_id(compression_info[0])
+            .unwrap_or_else(|| logger.error(
+                &format!("unsupported compression id {}!", compression_info[0]),
+                errorlib::ExitErrorCode::InvalidEncryptionData
+            ));
+    }
+    // Version 3 saves the passphrase salt, "<salt-size><salt>".
+    let mut salt: Vec<u8> = vec![];
+    if version[0] >= 3 {
+        let mut salt_size = [0u8; 1];
+        if en_file.read_exact(&mut salt_size).is_err() {
+            logger.error(
+                "the file header is broken!",
+                errorlib::ExitErrorCode::InvalidEncryptionData
+            );
+        }
+        salt = vec![0u8; salt_size[0] as usize];
+        if en_file.read_exact(&mut salt).is_err() {
+            logger.error(
+                "the file header is broken!",
+                errorlib::ExitErrorCode::InvalidEncryptionData
+            );
+        }
+    }
+    Some((u32::from_be_bytes(buffer_size) as usize, compression, salt))
+}
+
+/// Get the passphrase salt of the encrypted file, empty if the file is
+/// encrypted with a key (or a legacy file).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// - `errorlib::ExitErrorCode::UnsupportedFormat`
+/// 
+/// ### Example:
+/// ```
+/// let salt = decrypt_file::read_salt("./dir/f.txt.x");
+/// if !salt.is_empty() {
+///     let key = derive_key_from_passphrase(&passphrase, &salt);
+/// }
+/// ```
+pub fn read_salt(path: &str) -> Vec<u8> {
+    let logger = loglib::Logger::new("read-salt");
+    let mut en_file = std::fs::File::open(path).unwrap_or_else(|_| logger.error(
+        "can NOT open the file!",
+        errorlib::ExitErrorCode::FileOpen
+    ));
+    read_header(&mut en_file)
+        .map(|(_, _, salt)| salt)
+        .unwrap_or_default()
+}
+
+/// Check if the key decrypts the encrypted file, by decrypting
+/// its first block only, nothing is written to the disk.
+/// A file without blocks (empty plaintext) accepts any valid key.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileSeek`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// 
+/// ### Example:
+/// ```
+/// if !verify_key("./dir/f.txt.x", "<your-key>") {
+///     println!("wrong key!");
+/// }
+/// ```
+pub fn verify_key(path: &str, key: &str) -> bool {
+    let logger = loglib::Logger::new("verify-key");
+    let fernet = match Fernet::new(key) {
+        Some(fernet) => fernet,
+        None => return false
+    };
+    let mut en_file = std::fs::File::open(path).unwrap_or_else(|_| logger.error(
+        "can NOT open the file!",
+        errorlib::ExitErrorCode::FileOpen
+    ));
+    read_header(&mut en_file);
+    let mut size_buf = [0u8; 4];
+    if en_file.read_exact(&mut size_buf).is_err() {
+        return true;
+    }
+    let mut encryption_buffer = vec![0u8; u32::from_be_bytes(size_buf) as usize];
+    if en_file.read_exact(&mut encryption_buffer).is_err() {
+        logger.error(
+            "the first encryption block is broken!",
+            errorlib::ExitErrorCode::InvalidEncryptionData
+        );
+    }
+    String::from_utf8(encryption_buffer)
+        .map(|token| fernet.decrypt(&token).is_ok())
+        .unwrap_or(false)
+}
+
+/// Why an encrypted file failed the verification.
+#[derive(PartialEq, Debug)]
+pub enum VerifyError {
+    /// The key is NOT a valid encryption key.
+    InvalidKey,
+    /// The file can NOT be opened or read.
+    FileRead,
+    /// The block at the index (starts from 0) is broken, and why.
+    Block(usize, &'static str)
+}
+
+impl VerifyError {
+    /// The error message of the verification.
+    pub fn message(&self) -> String {
+        match self {
+            VerifyError::InvalidKey => "the key is NOT a valid encryption key!".to_owned(),
+            VerifyError::FileRead => "can NOT read the file!".to_owned(),
+            VerifyError::Block(index, reason) => format!("block {} {}!", index, reason)
+        }
+    }
+
+    /// The exit error code of the verification error.
+    pub fn code(&self) -> errorlib::ExitErrorCode {
+        match self {
+            VerifyError::InvalidKey => errorlib::ExitErrorCode::InvalidKey,
+            VerifyError::FileRead => errorlib::ExitErrorCode::FileRead,
+            VerifyError::Block(..) => errorlib::ExitErrorCode::InvalidEncryptionData
+        }
+    }
+}
+
+/// Check that the whole encrypted file decrypts with the key, the blocks
+/// are decrypted in memory and dropped, nothing is written to the disk.
+/// Returns the decrypted size, or the first broken block. The Fernet
+/// token of every block has its own HMAC, so a changed byte fails the
+/// decryption of its block.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileSeek`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// 
+/// ### Example:
+/// ```
+/// match verify_blocks("./dir/f.txt.x", "<your-key>") {
+///     Ok(size) => println!("the file decrypts to {} bytes.", size),
+///     Err(err) => println!("{}", err.message())
+/// }
+/// ```
+pub fn verify_blocks(path: &str, key: &str) -> Result<u64, VerifyError> {
+    let fernet = Fernet::new(key).ok_or(VerifyError::InvalidKey)?;
+    let mut en_file = std::fs::File::open(path).map_err(|_| VerifyError::FileRead)?;
+    let (buffer_size, compression, _) = read_header(&mut en_file)
+        .unwrap_or((BUFFER_SIZE, Compression::Off, vec![]));
+    let mut size: u64 = 0;
+    let mut size_buf = [0u8; 4];
+    let mut index: usize = 0;
+    loop {
+        let broken = |reason| VerifyError::Block(index, reason);
+        // The file can end at a block boundary only.
+        if en_file.read(&mut size_buf[..1]).map_err(|_| VerifyError::FileRead)? == 0 {
+            return Ok(size);
+        }
+        en_file.read_exact(&mut size_buf[1..]).map_err(|_| broken("is cut"))?;
+        // A changed length must NOT allocate a huge buffer.
+        let length = u32::from_be_bytes(size_buf) as u64;
+        let mut encryption_buffer: Vec<u8> = vec![];
+        (&mut en_file).take(length).read_to_end(&mut encryption_buffer)
+            .map_err(|_| VerifyError::FileRead)?;
+        if encryption_buffer.len() as u64 != length {
+            return Err(broken("is cut"));
+        }
+        let data = String::from_utf8(encryption_buffer)
+            .ok()
+            .and_then(|token| fernet.decrypt(&token).ok())
+            .ok_or(broken("does NOT decrypt, the key is wrong or the data is changed"))?;
+        let data = compression::decompress(&data, compression, buffer_size)
+            .ok_or(broken("can NOT be decompressed, or it is bigger than the buffer size"))?;
+        if data.len() > buffer_size {
+            return Err(broken("is bigger than the buffer size"));
+        }
+        size += data.len() as u64;
+        index += 1;
+    }
+}
+
+/// Check that the whole encrypted file decrypts with the key like
+/// `verify_blocks`. Returns the decrypted size, None if any block is
+/// broken or changed.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileSeek`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// 
+/// ### Example:
+/// ```
+/// match verify_file("./dir/f.txt.x", "<your-key>") {
+///     Some(size) => println!("the file decrypts to {} bytes.", size),
+///     None => println!("the file is broken!")
+/// }
+/// ```
+pub fn verify_file(path: &str, key: &str) -> Option<u64> {
+    verify_blocks(path, key).ok()
+}
+
+pub fn decrypt(path: String, key: String) {
+    decrypt_to(path.clone(), filelib::make_decrypt_path(path), key);
+}
+
+/// Decrypt file to another path like `decrypt_to`, but an existing file
+/// is NOT overwritten without `force`. Returns false if the file is skipped.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::WrongKey`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// - `errorlib::ExitErrorCode::Compression`
+/// 
+/// ### Example:
+/// ```
+/// if !decrypt_file::decrypt_to_safe("./dir/f.txt.x", "./out/f.txt", "<your-key>", false) {
+///     println!("'./out/f.txt' already exists, use --force!");
+/// }
+/// ```
+pub fn decrypt_to_safe(path: String, out_path: String, key: String, force: bool) -> bool {
+    if !force && std::path::Path::new(&out_path).exists() {
+        return false;
+    }
+    decrypt_to(path, out_path, key);
+    true
+}
+
+/// Exit with the error, the partial output file (if any) is deleted,
+/// so a failed decryption never leaves a truncated plaintext file.
+fn decryption_error(logger: &loglib::Logger, out_path: Option<&str>, message: &str, code: errorlib::ExitErrorCode) -> ! {
+    if let Some(out_path) = out_path {
+        let _ = filelib::delete_file(PathBuf::new().join(out_path));
+    }
+    logger.error(message, code);
+}
+
+/// Exit on a block that does NOT decrypt, the partial output file (if
+/// any) is deleted. The first block fails with the wrong key as well as
+/// with a corrupt file, a later block fails only if the file is corrupt
+/// (every Fernet token has its own HMAC), so the key is right and a
+/// backup is needed.
+fn decryption_failed(logger: &loglib::Logger, out_path: Option<&str>, index: usize) -> ! {
+    if index == 0 {
+        decryption_error(
+            logger,
+            out_path,
+            "decryption failed: wrong key or corrupt file!",
+            errorlib::ExitErrorCode::WrongKey
         );
     }
+    decryption_error(
+        logger,
+        out_path,
+        &format!("decryption failed: the file is corrupt at block {}, restore it from a backup!", index),
+        errorlib::ExitErrorCode::InvalidEncryptionData
+    );
+}
+
+/// Open the encrypted file and read its header, the file is
+/// left at the start of the first block.
+fn open_encrypted(
+    logger: &loglib::Logger,
+    path: &str,
+    key: &str
+) -> (Fernet, std::fs::File, usize, Compression) {
+    let fernet = Fernet::new(key).unwrap_or_else(|| logger.error(
+        "key error!", 
+        errorlib::ExitErrorCode::InvalidKey
+    ));
+    let mut en_file = std::fs::File::open(path).unwrap_or_else(|_| logger.error(
+        "can NOT open the file!", 
+        errorlib::ExitErrorCode::FileOpen
+    ));
+    // Legacy files were encrypted with the default buffer size.
+    let (buffer_size, compression, _) = read_header(&mut en_file)
+        .unwrap_or((BUFFER_SIZE, Compression::Off, vec![]));
+    (fernet, en_file, buffer_size, compression)
+}
+
+/// Decrypt the blocks of the opened file and write them one by one,
+/// only one block is in the memory.
+fn decrypt_blocks<W: Write>(
+    logger: &loglib::Logger,
+    (fernet, mut en_file, buffer_size, compression): (Fernet, std::fs::File, usize, Compression),
+    out: &mut W,
+    out_path: Option<&str>
+) {
+    // The encryption block size buffer
+    // Uses to read the size of the block
+    let mut size_buf = [0u8; 4];
+    for index in 0.. {
+        // Read the size of the block before reading the block
+        if en_file.read_exact(&mut size_buf).is_err() {
+            break;
+        }
+        // Encryption file format: "<header><length><en-data><length><en-data>"
+        // Read the block size and make a buffer with this size and then
+        // read the encryption block using the buffer.
+        let size = u32::from_be_bytes(size_buf) as usize;
+        let mut encryption_buffer = vec![0u8; size];
+        if en_file.read_exact(&mut encryption_buffer).is_err() {
+            decryption_error(
+                logger,
+                out_path,
+                &format!("decryption failed: the file is cut at block {}, restore it from a backup!", index),
+                errorlib::ExitErrorCode::InvalidEncryptionData
+            );
+        }
+        let data = String::from_utf8(encryption_buffer)
+            .ok()
+            .and_then(|token| fernet.decrypt(&token).ok())
+            .unwrap_or_else(|| decryption_failed(logger, out_path, index));
+        let data = compression::decompress(&data, compression, buffer_size)
+            .unwrap_or_else(|| decryption_error(
+                logger,
+                out_path,
+                &format!("can NOT decompress the file at block {}, or it is bigger than the buffer size!", index),
+                errorlib::ExitErrorCode::Compression
+            ));
+        if data.len() > buffer_size {
+            decryption_error(
+                logger,
+                out_path,
+                "the decrypted block is bigger than the buffer size!",
+                errorlib::ExitErrorCode::InvalidEncryptionData
+            );
+        }
+        // Save the decrypted blocks one by one.
+        if out.write_all(&data).is_err() {
+            decryption_error(
+                logger,
+                out_path,
+                "can NOT write the decrypted data!",
+                errorlib::ExitErrorCode::FileWrite
+            );
+        }
+    }
+    if out.flush().is_err() {
+        decryption_error(
+            logger,
+            out_path,
+            "can NOT write the decrypted data!",
+            errorlib::ExitErrorCode::FileWrite
+        );
+    }
+}
+
+/// Decrypt file using **Fernet** to another path.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::WrongKey`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// - `errorlib::ExitErrorCode::UnsupportedFormat`
+/// - `errorlib::ExitErrorCode::Compression`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// 
+/// ### Example:
+/// ```
+/// decrypt_file::decrypt_to("./dir/f.txt.x", "./out/f.txt", "<your-key>");
+/// ```
+pub fn decrypt_to(path: String, out_path: String, key: String) {
+    let logger = loglib::Logger::new("decrypt-file");
+    // Read the header first, an unsupported file creates nothing.
+    let encrypted = open_encrypted(&logger, &path, &key);
+    let mut de_file = std::fs::File::create(&out_path).unwrap_or_else(|_| logger.error(
+        "can NOT open the file!", 
+        errorlib::ExitErrorCode::FileOpen
+    ));
+    decrypt_blocks(&logger, encrypted, &mut de_file, Some(&out_path));
+}
+
+/// Decrypt file using **Fernet** to the standard output (e.g. a pipe),
+/// the plaintext is never written to the disk.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::WrongKey`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// - `errorlib::ExitErrorCode::UnsupportedFormat`
+/// - `errorlib::ExitErrorCode::Compression`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// 
+/// ### Example:
+/// ```
+/// decrypt_file::decrypt_to_stdout("./dir/f.txt.x", "<your-key>");
+/// ```
+pub fn decrypt_to_stdout(path: String, key: String) {
+    let logger = loglib::Logger::new("decrypt-file");
+    let encrypted = open_encrypted(&logger, &path, &key);
+    decrypt_blocks(&logger, encrypted, &mut std::io::stdout().lock(), None);
 }
 
 pub fn main(command: &ArgMatches) {
+    let is_stdout = *command.get_one::<bool>("stdout").unwrap_or(&false);
+    if is_stdout {
+        // The stdout carries the decrypted data only.
+        loglib::stderr_only();
+    }
     let mut logger = loglib::Logger::new("decrypt-file");
     let path= command.get_one::<String>("PATH").unwrap();
     let file_state = filelib::get_file_state(path.clone());
@@ -104,27 +530,76 @@ pub fn main(command: &ArgMatches) {
             errorlib::ExitErrorCode::FileNotEncrypted
         );
     }
-    let key =  utilities::input("Enter your key: ");
-    logger.start();
-    logger.info("decryption in progress....");
-    if *command.get_one::<bool>("xpmv1").unwrap_or(&false) {
-        logger.warning("do not use --xpmv1 with the XPManager v2.0 encryption it will break your file!!");
-        logger.warning("XPManager v1.0 can not handle large files!!");
-        xpmv1_decryption(path.clone(), key);
+    if *command.get_one::<bool>("dry-wipe").unwrap_or(&false) {
+        displaylib::wipe::display_dry(
+            filelib::wipe_preview(&[PathBuf::new().join(path)]),
+            &super::wipe_settings(command)
+        );
+        return;
+    }
+    let de_path = filelib::make_decrypt_path(path.clone());
+    let mut is_force = *command.get_one::<bool>("force").unwrap_or(&false);
+    if is_stdout && !utilities::can_write_secrets_to_stdout(
+        std::io::stdout().is_terminal(),
+        is_force
+    ) {
+        logger.error(
+            "refusing to write the decrypted data to the terminal, pipe it or use --force!",
+            errorlib::ExitErrorCode::StdoutIsTerminal
+        );
+    }
+    // The decrypted file must NOT replace a file silently.
+    if !is_stdout && !is_force && PathBuf::new().join(&de_path).exists() {
+        logger.warning(&format!("'{}' already exists and will be overwritten!", de_path));
+        utilities::confirm();
+        is_force = true;
+    }
+    let is_delete = *command.get_one::<bool>("delete").unwrap_or(&false);
+    if is_delete {
+        logger.warning("the encrypted file will be wiped after the decryption!");
+        displaylib::wipe::display(
+            filelib::wipe_preview(&[PathBuf::new().join(path)])
+        );
+        utilities::confirm();
+    }
+    let identity_path = command.get_one::<String>("age-identity");
+    if identity_path.is_none() && super::age_file::is_age_file(path) {
+        logger.error(
+            "the file is encrypted with an age recipient, use --age-identity!",
+            errorlib::ExitErrorCode::InvalidEncryptionData
+        );
+    }
+    if let Some(identity_path) = identity_path {
+        logger.start();
+        logger.info("decryption in progress....");
+        super::age_file::decrypt(path.clone(), identity_path);
     } else {
-        decrypt(path.clone(), key);
+        let key = super::KeySource::resolve(command).read();
+        logger.start();
+        logger.info("decryption in progress....");
+        if *command.get_one::<bool>("xpmv1").unwrap_or(&false) {
+            logger.warning("do not use --xpmv1 with the XPManager v2.0 encryption it will break your file!!");
+            logger.warning("XPManager v1.0 can not handle large files!!");
+            xpmv1_decryption(path.clone(), key);
+        } else if is_stdout {
+            decrypt_to_stdout(path.clone(), key);
+        } else {
+            decrypt_to_safe(path.clone(), de_path, key, is_force);
+        }
     }
     logger.info("file decrypted successfully.");
     dblib::log::register(
         &format!("file '{}' encrypted", path.clone()),
+        dblib::log::LogAction::Decrypt,
         filelib::log::get_log_db_path()
     );
-    if *command.get_one::<bool>("delete").unwrap_or(&false) {
+    if is_delete {
         logger.start();
-        filelib::wipe_delete(path.clone());
+        let report = filelib::wipe_delete_settings(path.clone(), &super::wipe_settings(command), false).unwrap_or_else(|err| err.exit());
         logger.info("file wiped and deleted successfully.");
         dblib::log::register(
-            &format!("file '{}' wiped", path),
+            &report.message(),
+            dblib::log::LogAction::Wipe,
             filelib::log::get_log_db_path()
         );
     }
@@ -135,6 +610,8 @@ pub fn main(command: &ArgMatches) {
 mod tests {
     use std::path::PathBuf;
     use super::filelib::{create_file, delete_file};
+    use super::super::encrypt_file::{encrypt_with_options, EncryptOptions};
+    use super::Compression;
 
     #[test]
     fn decrypt() {
@@ -142,14 +619,14 @@ mod tests {
             .join("./temp/decrypt");
         let file = temp_dir.join("test.txt.x");
         let de_file = temp_dir.join("test.txt");
-        create_file(file.clone());
+        create_file(file.clone()).unwrap();
         assert_eq!(file.exists(), true, "Can NOT create the test file!!");
         let file_path_str = file
             .to_str()
             .expect("Can NOT parse PathBuf to &str!!")
             .to_string();
 
-        delete_file(de_file.clone());
+        delete_file(de_file.clone()).unwrap();
         assert_eq!(de_file.exists(), false, "Can NOT delete the test file!!");
         let key = super::Fernet::generate_key();
         super::decrypt(file_path_str.clone(), key.clone());
@@ -158,4 +635,259 @@ mod tests {
         std::fs::remove_dir_all(temp_dir)
             .expect("Can NOT delete temp files!!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn decrypt_different_buffer_sizes() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/decrypt_different_buffer_sizes");
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        let file_path_str = file
+            .to_str()
+            .expect("Can NOT parse PathBuf to &str!!")
+            .to_string();
+        let data: Vec<u8> = (0..200_000u32)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        for buffer_size in [64*1024, 1000] {
+            create_file(file.clone()).unwrap();
+            std::fs::write(&file, &data)
+                .expect("Can NOT write the test file!!");
+            let mut options = EncryptOptions::new();
+            options.buffer_size = buffer_size;
+            let key = encrypt_with_options(
+                file_path_str.clone(),
+                "".to_string(),
+                &options
+            );
+            delete_file(file.clone()).unwrap();
+            assert!(!file.exists(), "Can NOT delete the test file!!");
+            super::decrypt(
+                en_file
+                    .to_str()
+                    .expect("Can NOT parse PathBuf to &str!!")
+                    .to_string(),
+                key
+            );
+            let decrypted = std::fs::read(&file)
+                .expect("Can NOT read the decrypted file!!");
+            assert_eq!(decrypted, data, "Decrypted data NOT match!!");
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp files!!");
+    }
+
+    #[test]
+    fn verify_blocks() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/verify_blocks");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp files!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        let en_path = en_file.to_str().unwrap();
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager ".repeat(500)).unwrap();
+        let mut options = EncryptOptions::new();
+        options.buffer_size = 1000;
+        let key = encrypt_with_options(
+            file.to_str().unwrap().to_string(),
+            "".to_string(),
+            &options
+        );
+        delete_file(file.clone()).unwrap();
+        assert_eq!(super::verify_blocks(en_path, &key), Ok(5000), "Good file NOT verified!!");
+        assert!(!file.exists(), "Verify wrote the plaintext!!");
+        assert_eq!(
+            super::verify_blocks(en_path, &super::Fernet::generate_key()),
+            Err(super::VerifyError::Block(0, "does NOT decrypt, the key is wrong or the data is changed")),
+            "Wrong key NOT reported!!"
+        );
+
+        // A changed byte in the third block, blocks are "<length><token>".
+        let en_data = std::fs::read(&en_file).unwrap();
+        let mut changed = en_data.clone();
+        // "<magic><version><buffer-size><compression-id><compression-level><salt-size>"
+        let mut pos = 12;
+        for _ in 0..2 {
+            let length = u32::from_be_bytes(en_data[pos..pos + 4].try_into().unwrap()) as usize;
+            pos += 4 + length;
+        }
+        pos += 4;
+        changed[pos + 10] = if changed[pos + 10] == b'A' { b'B' } else { b'A' };
+        std::fs::write(&en_file, &changed).unwrap();
+        let err = super::verify_blocks(en_path, &key).expect_err("Changed block NOT reported!!");
+        assert_eq!(
+            err.message(),
+            "block 2 does NOT decrypt, the key is wrong or the data is changed!",
+            "Changed block error NOT match!!"
+        );
+
+        std::fs::write(&en_file, &en_data[..en_data.len() - 3]).unwrap();
+        assert_eq!(
+            super::verify_blocks(en_path, &key),
+            Err(super::VerifyError::Block(4, "is cut")),
+            "Cut block NOT reported!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp files!!");
+    }
+
+    #[test]
+    fn verify_blocks_decompress_limit() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/verify_blocks_decompress_limit");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp files!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        let en_path = en_file.to_str().unwrap();
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager").unwrap();
+        let mut options = EncryptOptions::new();
+        options.buffer_size = 1000;
+        options.compression = Compression::Zstd;
+        options.compression_level = 3;
+        let key = encrypt_with_options(
+            file.to_str().unwrap().to_string(),
+            "".to_string(),
+            &options
+        );
+        delete_file(file.clone()).unwrap();
+
+        // Keep the header and replace the block with 1 MiB of zeros,
+        // it is a few bytes after the compression.
+        let mut en_data = std::fs::read(&en_file).unwrap()[..12].to_vec();
+        let data = super::compression::compress(&vec![0u8; 1024 * 1024], Compression::Zstd, 3).unwrap();
+        let token = super::Fernet::new(&key).unwrap().encrypt(&data);
+        en_data.extend_from_slice(&(token.len() as u32).to_be_bytes());
+        en_data.extend_from_slice(token.as_bytes());
+        std::fs::write(&en_file, &en_data).unwrap();
+        assert_eq!(
+            super::verify_blocks(en_path, &key),
+            Err(super::VerifyError::Block(0, "can NOT be decompressed, or it is bigger than the buffer size")),
+            "Inflated block NOT reported!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp files!!");
+    }
+
+    #[test]
+    fn decrypt_compressed() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/decrypt_compressed");
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        let file_path_str = file
+            .to_str()
+            .expect("Can NOT parse PathBuf to &str!!")
+            .to_string();
+        let data = "XPManager is the best! ".repeat(10_000);
+        for (compression, level) in [
+            (Compression::Gzip, 1),
+            (Compression::Gzip, 9),
+            (Compression::Zstd, 1),
+            (Compression::Zstd, 19)
+        ] {
+            create_file(file.clone()).unwrap();
+            std::fs::write(&file, &data)
+                .expect("Can NOT write the test file!!");
+            let mut options = EncryptOptions::new();
+            options.compression = compression;
+            options.compression_level = level;
+            let key = encrypt_with_options(
+                file_path_str.clone(),
+                "".to_string(),
+                &options
+            );
+            delete_file(file.clone()).unwrap();
+            assert!(!file.exists(), "Can NOT delete the test file!!");
+            super::decrypt(
+                en_file
+                    .to_str()
+                    .expect("Can NOT parse PathBuf to &str!!")
+                    .to_string(),
+                key
+            );
+            let decrypted = std::fs::read_to_string(&file)
+                .expect("Can NOT read the decrypted file!!");
+            assert_eq!(decrypted, data, "Decrypted data NOT match!!");
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp files!!");
+    }
+
+    #[test]
+    fn verify_key() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/verify_key");
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager verify key")
+            .expect("Can NOT write the test file!!");
+        let key = encrypt_with_options(
+            file.to_str().unwrap().to_owned(),
+            "".to_string(),
+            &EncryptOptions::new()
+        );
+        delete_file(file.clone()).unwrap();
+        let en_file_str = en_file.to_str().unwrap();
+        assert!(super::verify_key(en_file_str, &key), "Right key NOT verified!!");
+        assert!(
+            !super::verify_key(en_file_str, &fernet::Fernet::generate_key()),
+            "Wrong key verified!!"
+        );
+        assert!(!super::verify_key(en_file_str, "not-a-key"), "Invalid key verified!!");
+        assert!(!file.exists(), "Verify key wrote the decrypted file!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp files!!");
+    }
+
+    #[test]
+    fn decrypt_no_overwrite() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/decrypt_no_overwrite");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager decrypted")
+            .expect("Can NOT write the test file!!");
+        let key = encrypt_with_options(
+            file.to_str().unwrap().to_owned(),
+            "".to_string(),
+            &EncryptOptions::new()
+        );
+        std::fs::write(&file, "XPManager existing")
+            .expect("Can NOT write the test file!!");
+        let en_file_str = en_file.to_str().unwrap().to_owned();
+        let file_str = file.to_str().unwrap().to_owned();
+
+        assert!(
+            !super::decrypt_to_safe(en_file_str.clone(), file_str.clone(), key.clone(), false),
+            "Existing file NOT refused!!"
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "XPManager existing", "Existing file overwritten!!");
+        assert!(
+            super::decrypt_to_safe(en_file_str, file_str, key, true),
+            "Forced decryption refused!!"
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "XPManager decrypted", "Forced decryption NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
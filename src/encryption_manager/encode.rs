@@ -1,4 +1,5 @@
 use super::ArgMatches;
+use base64::{Engine, engine::general_purpose::STANDARD};
 use crate::{
     displaylib, 
     loglib, 
@@ -37,6 +38,20 @@ pub fn hex(string: String) -> String {
         .join(" ")
 }
 
+/// Encode data to hexadecimal bytes, two digits for every byte
+/// without spaces, e.g. the file contents.
+/// 
+/// ### Example:
+/// ```
+/// let xpm_hex = encode::hex_bytes("XPManager".as_bytes());
+/// assert_eq!(xpm_hex, "58504D616E61676572");
+/// ```
+pub fn hex_bytes(data: &[u8]) -> String {
+    data.iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect()
+}
+
 /// Hash string as hexadecimal using simple operation.
 /// 
 /// ### Example:
@@ -76,8 +91,33 @@ pub fn bin(string: String) -> String {
         .join(" ")
 }
 
+/// Encode data to base64 (standard alphabet, with padding).
+/// 
+/// ### Example:
+/// ```
+/// let xpm_base64 = encode::base64(b"xpm");
+/// assert_eq!(xpm_base64, "eHBt");
+/// ```
+pub fn base64(data: &[u8]) -> String {
+    STANDARD.encode(data)
+}
+
 pub fn main(command: &ArgMatches) {
     let mut logger = loglib::Logger::new("encode");
+    // Any file can be encoded, e.g. a binary key.
+    if let Some(path) = command.get_one::<String>("file") {
+        let data = std::fs::read(path).unwrap_or_else(|_| logger.error(
+            &format!("can NOT read the file '{}'!", path),
+            errorlib::ExitErrorCode::FileRead
+        ));
+        if *command.get_one::<bool>("base64").unwrap_or(&false) {
+            displaylib::encode::display(base64(&data));
+        } else {
+            displaylib::encode::display(hex_bytes(&data));
+        }
+        logger.info("file encoded successfully.");
+        return;
+    }
     let string = utilities::input("Enter the string: ");
     logger.start();
     let mut _encoded_date = String::new();
@@ -98,6 +138,8 @@ pub fn main(command: &ArgMatches) {
                 errorlib::ExitErrorCode::Input
             )
         }
+    } else if *command.get_one::<bool>("base64").unwrap_or(&false) {
+        _encoded_date = base64(string.as_bytes());
     } else if *command.get_one::<bool>("bin").unwrap_or(&false) {
         _encoded_date = bin(string.clone());
     } else if *command.get_one::<bool>("hex-hash").unwrap_or(&false) {
@@ -126,6 +168,13 @@ mod tests {
         assert_eq!(result, expected, "Encode value NOT match!!");
     }
 
+    #[test]
+    fn hex_bytes() {
+        let result = super::hex_bytes(&[0x58, 0x50, 0x4D, 0x00, 0xFF]);
+        let expected = "58504D00FF";
+        assert_eq!(result, expected, "Encode value NOT match!!");
+    }
+
     #[test]
     fn hex_hash() {
         let result = super::hex_hash("XPManager".to_string());
@@ -133,6 +182,13 @@ mod tests {
         assert_eq!(result, expected, "Encode value NOT match!!");
     }
 
+    #[test]
+    fn base64() {
+        let result = super::base64("XPManager".as_bytes());
+        let expected = "WFBNYW5hZ2Vy";
+        assert_eq!(result, expected, "Encode value NOT match!!");
+    }
+
     #[test]
     fn bin() {
         let result = super::bin("XPManager".to_string());
@@ -5,18 +5,161 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use super::{
     ArgMatches,
     PathBuf,
-    encrypt_file,
+    encrypt_file::{self, BlockWriter, EncryptOptions},
     Fernet
 };
 use crate::{
+    configlib,
     displaylib, 
+    errorlib,
     filelib, 
     loglib, 
     utilities,
     dblib
 };
 
-/// Encrypt list of files.
+/// Get the archive path of the directory (e.g. `./dir` -> `./dir.tar.x`).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DirNotFound`
+fn archive_path(path: &std::path::Path) -> PathBuf {
+    let logger = loglib::Logger::new("archive-path");
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| logger.error(
+        "can NOT find the directory!",
+        errorlib::ExitErrorCode::DirNotFound
+    ));
+    let name = path.file_name().unwrap_or_else(|| logger.error(
+        "can NOT archive the root directory!",
+        errorlib::ExitErrorCode::DirUnsupported
+    ));
+    path.with_file_name(format!("{}.tar.x", name.to_str().unwrap()))
+}
+
+/// Encrypt the directory as one tar archive `<dir>.tar.x`. The tar is streamed
+/// into the encryption blocks, so only one buffer is in the memory for any
+/// directory size. The files bigger than `max_file_size` are refused.
+/// Returns the key and the number of the encrypted blocks.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DirNotFound`
+/// - `errorlib::ExitErrorCode::FileCreate`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileTooLarge`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::Compression`
+/// 
+/// ### Example:
+/// ```
+/// // find it at: `./dir.tar.x`
+/// let (key, blocks) = encrypt_archive(
+///     PathBuf::new().join("./dir"),
+///     "".to_string(),
+///     &EncryptOptions::new(),
+///     Some(1024 * 1024 * 1024)
+/// );
+/// ```
+pub fn encrypt_archive(path: PathBuf, key: String, options: &EncryptOptions, max_file_size: Option<u64>) -> (String, usize) {
+    let logger = loglib::Logger::new("encrypt-archive");
+    let key = if key.is_empty() {
+        Fernet::generate_key()
+    } else {
+        key
+    };
+    let fernet = Fernet::new(&key).unwrap_or_else(|| logger.error(
+        "key error!",
+        errorlib::ExitErrorCode::InvalidKey
+    ));
+    let mut files_paths: Vec<PathBuf> = vec![];
+    filelib::dir_files_tree(path.clone(), &mut files_paths).unwrap_or_else(|err| err.exit());
+    // Check all the files first, so nothing is written for a refused directory.
+    for file in files_paths.iter() {
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if max_file_size.is_some_and(|max_file_size| size > max_file_size) {
+            logger.error(
+                &format!(
+                    "'{}' is bigger than the archive limit ({} > {} bytes)!",
+                    file.display(),
+                    size,
+                    max_file_size.unwrap()
+                ),
+                errorlib::ExitErrorCode::FileTooLarge
+            );
+        }
+    }
+    let archive_path = archive_path(&path);
+    let root_name = archive_path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .trim_end_matches(".tar.x")
+        .to_owned();
+    let en_file = std::fs::File::create(&archive_path).unwrap_or_else(|_| logger.error(
+        &format!("can NOT create '{}'!", archive_path.display()),
+        errorlib::ExitErrorCode::FileCreate
+    ));
+    let writer = BlockWriter::new(en_file, &fernet, options).unwrap_or_else(|_| logger.error(
+        "can NOT write the file header!",
+        errorlib::ExitErrorCode::FileWrite
+    ));
+    let mut archive = tar::Builder::new(writer);
+    for file in files_paths.iter() {
+        let name = PathBuf::new()
+            .join(&root_name)
+            .join(file.strip_prefix(&path).unwrap());
+        if let Err(error) = archive.append_path_with_name(file, name) {
+            let _ = std::fs::remove_file(&archive_path);
+            if error.kind() == std::io::ErrorKind::InvalidData {
+                logger.error(
+                    "can NOT compress the archive!",
+                    errorlib::ExitErrorCode::Compression
+                );
+            }
+            logger.error(
+                &format!("can NOT archive '{}'!", file.display()),
+                errorlib::ExitErrorCode::FileRead
+            );
+        }
+    }
+    let blocks = archive
+        .into_inner()
+        .and_then(|writer| writer.finish())
+        .unwrap_or_else(|_| {
+            let _ = std::fs::remove_file(&archive_path);
+            logger.error(
+                "can NOT write the archive!",
+                errorlib::ExitErrorCode::FileWrite
+            )
+        });
+    (key, blocks)
+}
+
+/// Get the archive files size limit from `--max-file-size`, or from
+/// the config `archive.max_file_size`, None for no limit.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+fn max_file_size(flag: Option<&String>, config: &configlib::Config) -> Option<u64> {
+    let logger = loglib::Logger::new("max-file-size");
+    if let Some(size) = flag {
+        return Some(size.parse::<u64>().unwrap_or_else(|_| logger.error(
+            &format!("--max-file-size must be unsigned integer from 0 to {}!", u64::MAX),
+            errorlib::ExitErrorCode::Input
+        )));
+    }
+    config.get_int("archive.max_file_size").map(|size| {
+        u64::try_from(size).unwrap_or_else(|_| logger.error(
+            "config 'archive.max_file_size' must NOT be negative!",
+            errorlib::ExitErrorCode::InvalidConfig
+        ))
+    })
+}
+
+/// Encrypt list of files, the failed files are handled by the batch.
+/// The origin files are NOT wiped here, see `wipe`. Returns the
+/// encrypted files.
 /// 
 /// ### Example:
 /// ```
@@ -26,14 +169,25 @@ use crate::{
 ///     PathBuf::new().join('/folder/to/file-3.txt')
 /// ];
 /// let encryption_key = "<your-key>".to_string();
-/// let delete_files = false;
 /// let log_db_path = PathBuf::new().join('/folder/to/log.db');
+/// let batch = utilities::Batch::new(utilities::OnError::Continue);
 /// 
-/// encrypt(&files, encryption_key, delete_files, log_db_path);
+/// // Sync the encrypted files, they will be wiped.
+/// let encrypted = encrypt(&files, encryption_key, true, log_db_path, &batch);
 /// ```
-fn encrypt(paths: &Vec<PathBuf>, key: String, is_delete: bool, log_db_path: PathBuf) {
+fn encrypt(
+    paths: &[PathBuf],
+    key: String,
+    sync: bool,
+    log_db_path: PathBuf,
+    batch: &utilities::Batch
+) -> Vec<PathBuf> {
     let mut logger = loglib::Logger::new("encrypt-dir-thread");
-    for file in paths {
+    // The encrypted files must be on the disk before wiping the origin files.
+    let mut options = EncryptOptions::new();
+    options.fsync = sync;
+    let mut encrypted: Vec<PathBuf> = vec![];
+    batch.run(paths, |file| {
         logger.start();
         let file_path_string = file.to_str().unwrap().to_owned();
         if filelib::get_file_state(
@@ -42,46 +196,140 @@ fn encrypt(paths: &Vec<PathBuf>, key: String, is_delete: bool, log_db_path: Path
             logger.warning(
                 &format!("file already encrypted '{}'?", file_path_string)
             );
-            continue;
+            return Ok(());
+        }
+        // The file may be removed or locked after the listing.
+        if let Err(error) = std::fs::File::open(file) {
+            return Err(utilities::BatchFailure::new(
+                &file_path_string,
+                errorlib::ExitErrorCode::FileOpen,
+                &format!("can NOT open the file, {}", error)
+            ));
+        }
+        if !filelib::is_regular_file(file) {
+            return Err(utilities::BatchFailure::new(
+                &file_path_string,
+                errorlib::ExitErrorCode::FileUnsupported,
+                "NOT a regular file"
+            ));
         }
-        encrypt_file::encrypt(
+        encrypt_file::encrypt_with_options(
             file_path_string.clone(),
-            key.clone()
+            key.clone(),
+            &options
         );
-        if is_delete {
-            filelib::wipe_delete(file_path_string.clone());
-            logger.info(
-                &format!("wiped '{}'.", file.display())
-            );
-        }
+        encrypted.push(file.clone());
         dblib::log::register(
             &format!("encrypted '{}'.", file.display()), 
+            dblib::log::LogAction::Encrypt, 
             log_db_path.clone()
         );
         logger.info(
             &format!("encrypted '{}'.", file.display())
         );
+        Ok(())
+    });
+    encrypted
+}
+
+/// Wipe list of origin files, only after all the files are encrypted.
+/// 
+/// ### Exit:
+/// - The `filelib::wipe_delete_settings` errors.
+/// 
+/// ### Example:
+/// ```
+/// wipe_origins(&encrypted, &filelib::WipeSettings::default(), log_db_path);
+/// ```
+fn wipe_origins(paths: &[PathBuf], settings: &filelib::WipeSettings, log_db_path: PathBuf) {
+    let logger = loglib::Logger::new("wipe-dir-thread");
+    for file in paths {
+        let report = filelib::wipe_delete_settings(
+            file.to_str().unwrap().to_owned(),
+            settings,
+            false
+        ).unwrap_or_else(|err| err.exit());
+        logger.info(
+            &format!("wiped '{}'.", file.display())
+        );
+        dblib::log::register(
+            &report.message(),
+            dblib::log::LogAction::Wipe,
+            log_db_path.clone()
+        );
     }
 }
 
+/// Encrypt the directory as one archive, for `--archive`.
+fn main_archive(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("encrypt-dir");
+    let path = command.get_one::<String>("PATH").unwrap();
+    let max_file_size = max_file_size(
+        command.get_one::<String>("max-file-size"),
+        &configlib::Config::load()
+    );
+    let is_custom_key = *command.get_one::<bool>("key").unwrap_or(&false)
+        || super::KeySource::is_set(command);
+    let key = if is_custom_key {
+        let key = super::KeySource::resolve(command).read();
+        logger.start();
+        key
+    } else {
+        "".to_owned()
+    };
+    logger.info("archive encryption in progress....");
+    let (key, blocks) = encrypt_archive(
+        PathBuf::new().join(path),
+        key,
+        &EncryptOptions::new(),
+        max_file_size
+    );
+    logger.info(&format!("directory archived and encrypted successfully in {} blocks.", blocks));
+    if !is_custom_key {
+        displaylib::key::display(key);
+    }
+    dblib::log::register(
+        &format!("directory '{}' encrypted as an archive", path),
+        dblib::log::LogAction::Encrypt,
+        filelib::log::get_log_db_path()
+    );
+}
+
 pub fn main(command: &ArgMatches) {
+    if *command.get_one::<bool>("archive").unwrap_or(&false) {
+        return main_archive(command);
+    }
     let mut logger = loglib::Logger::new("encrypt-dir");
     let path = command.get_one::<String>("PATH").unwrap();
     let is_delete = *command.get_one::<bool>("delete").unwrap_or(&false);
+    let wipe = if is_delete { Some(super::wipe_settings(command)) } else { None };
     let no_threads = *command.get_one::<bool>("no-threads").unwrap_or(&false);
-    let mut files_paths: Vec<PathBuf> = vec![];
-    let key = if *command.get_one::<bool>("key")
-        .unwrap_or(&false) {
-            let _key = utilities::input("Enter your key: ");
-            logger.start();
-            _key
-    } else {
-        Fernet::generate_key()
-    };
-    filelib::dir_files_tree(
-        PathBuf::new().join(path), 
-        &mut files_paths
+    let batch = utilities::Batch::new(
+        command.get_one::<String>("on-error")
+            .and_then(|mode| utilities::OnError::from_name(mode))
+            .unwrap_or(utilities::OnError::Continue)
     );
+    let mut files_paths: Vec<PathBuf> = vec![];
+    // The excluded directories are NOT walked.
+    let exclude: Vec<String> = command.get_many::<String>("exclude")
+        .map(|patterns| patterns.cloned().collect())
+        .unwrap_or_default();
+    let folder_path = PathBuf::new().join(path);
+    let is_excluded = filelib::glob_matcher(&folder_path, &exclude);
+    filelib::dir_files_tree_filter(
+        folder_path.clone(),
+        &mut files_paths,
+        &|path| !is_excluded(path)
+    ).unwrap_or_else(|err| err.exit());
+    if let Some(patterns) = command.get_many::<String>("glob") {
+        let patterns: Vec<String> = patterns.cloned().collect();
+        files_paths = filelib::filter_glob(
+            &PathBuf::new().join(path),
+            files_paths,
+            &patterns
+        );
+        logger.info(&format!("{} files match the glob patterns.", files_paths.len()));
+    }
     logger.info("directory listed successfully.");
     if is_delete {
         logger.warning(
@@ -91,6 +339,24 @@ pub fn main(command: &ArgMatches) {
                 path.clone()
             )
         );
+        // Encrypted files will be skipped, so they will not be wiped.
+        let wiped_files: Vec<PathBuf> = files_paths
+            .iter()
+            .filter(|file| filelib::get_file_state(
+                file.to_str().unwrap().to_owned()
+            ) != filelib::FileState::Encrypted)
+            .cloned()
+            .collect();
+        if *command.get_one::<bool>("dry-wipe").unwrap_or(&false) {
+            displaylib::wipe::display_dry(
+                filelib::wipe_preview(&wiped_files),
+                &wipe.unwrap_or_default()
+            );
+            return;
+        }
+        displaylib::wipe::display(
+            filelib::wipe_preview(&wiped_files)
+        );
     } else {
         logger.warning(
             &format!(
@@ -101,36 +367,234 @@ pub fn main(command: &ArgMatches) {
     }
     utilities::confirm();
     logger.start();
+    let is_custom_key = *command.get_one::<bool>("key").unwrap_or(&false)
+        || super::KeySource::is_set(command);
+    let key = if is_custom_key {
+        let key = super::KeySource::resolve(command).read();
+        logger.start();
+        key
+    } else {
+        Fernet::generate_key()
+    };
     let log_db_path = filelib::log::get_log_db_path();
 
-    if no_threads {
+    let encrypted: Vec<PathBuf> = if no_threads {
         logger.info("start the encryption using the main thread.");
         encrypt(
             &files_paths, 
             key.clone(), 
-            is_delete, 
-            log_db_path.clone()
-        );
+            wipe.is_some(), 
+            log_db_path.clone(),
+            &batch
+        )
     } else {
         logger.info("start the encryption with the max number of threads.");
         // Distribute files over the number of threads 
         let distributed_paths: Vec<Vec<PathBuf>> = utilities::distribute_paths(files_paths.clone());
 
         // Run the threads
-        distributed_paths.par_iter().for_each(|paths| {
+        distributed_paths.par_iter().flat_map_iter(|paths| {
             encrypt(
                 paths, 
                 key.clone(), 
-                is_delete, 
-                log_db_path.clone()
-            );
-        });
-    }
+                wipe.is_some(), 
+                log_db_path.clone(),
+                &batch
+            )
+        }).collect()
+    };
 
-    logger.info("directory encrypted successfully.");
+    // The key is still needed for the encrypted files.
     displaylib::key::display(key);
+    if let Some(settings) = wipe {
+        // A failed file keeps all the origin files, nothing is lost without its key.
+        if !batch.failures().is_empty() {
+            logger.warning("some files failed, the origin files are NOT wiped!");
+        } else if no_threads {
+            wipe_origins(&encrypted, &settings, log_db_path.clone());
+        } else {
+            utilities::distribute_paths(encrypted).par_iter().for_each(|paths| {
+                wipe_origins(paths, &settings, log_db_path.clone());
+            });
+        }
+    }
+    batch.finish(&logger);
+    logger.info("directory encrypted successfully.");
     dblib::log::register(
         &format!("directory '{}' encrypted and wiped", path),
+        dblib::log::LogAction::Encrypt,
         log_db_path
     );
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::EncryptOptions;
+    use crate::{
+        encryption_manager::decrypt_file,
+        errorlib::ExitErrorCode,
+        filelib::create_file,
+        utilities::{Batch, OnError}
+    };
+
+    #[test]
+    fn encrypt_archive() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypt_archive");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let dir = temp_dir.join("data");
+        let data: Vec<u8> = (0..20_000u32)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        for name in ["a.bin", "sub/b.bin"] {
+            create_file(dir.join(name)).unwrap();
+            std::fs::write(dir.join(name), &data)
+                .expect("Can NOT write the test file!!");
+        }
+
+        // The directory is much bigger than the buffer.
+        let mut options = EncryptOptions::new();
+        options.buffer_size = 1024;
+        let (key, blocks) = super::encrypt_archive(dir.clone(), "".to_string(), &options, Some(20_000));
+        let archive = temp_dir.join("data.tar.x");
+        assert!(archive.exists(), "Archive NOT created!!");
+
+        decrypt_file::decrypt(archive.to_str().unwrap().to_owned(), key);
+        let tar_path = temp_dir.join("data.tar");
+        let tar_size = std::fs::metadata(&tar_path).unwrap().len() as usize;
+        // Every block has one full buffer at most, so the memory stays bounded.
+        assert_eq!(blocks, tar_size.div_ceil(1024), "Number of blocks NOT match!!");
+        let mut names: Vec<String> = tar::Archive::new(std::fs::File::open(&tar_path).unwrap())
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().display().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["data/a.bin", "data/sub/b.bin"], "Archive files NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn encrypt_glob() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypt_glob");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let dir = temp_dir.join("data");
+        for name in ["a.pdf", "b.txt", "sub/c.pdf", "sub/d.md"] {
+            create_file(dir.join(name)).unwrap();
+        }
+        let mut files_paths: Vec<PathBuf> = vec![];
+        crate::filelib::dir_files_tree(dir.clone(), &mut files_paths).unwrap();
+        let files_paths = crate::filelib::filter_glob(
+            &dir,
+            files_paths,
+            &["**/*.pdf".to_string(), "*.md".to_string(), "sub/*.md".to_string()]
+        );
+        super::encrypt(
+            &files_paths,
+            fernet::Fernet::generate_key(),
+            false,
+            temp_dir.join("log.db"),
+            &Batch::new(OnError::Stop)
+        );
+        for name in ["a.pdf.x", "sub/c.pdf.x", "sub/d.md.x", "b.txt"] {
+            assert!(dir.join(name).exists(), "'{}' NOT found!!", name);
+        }
+        assert!(!dir.join("b.txt.x").exists(), "Not matching file encrypted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn encrypt_on_error() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypt_on_error");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let dir = temp_dir.join("data");
+        for name in ["a.txt", "c.txt"] {
+            create_file(dir.join(name)).unwrap();
+        }
+        // "b.txt" is removed after the listing.
+        let files_paths = vec![dir.join("a.txt"), dir.join("b.txt"), dir.join("c.txt")];
+
+        let batch = Batch::new(OnError::Stop);
+        super::encrypt(&files_paths, fernet::Fernet::generate_key(), false, temp_dir.join("log.db"), &batch);
+        let failures = batch.failures();
+        assert!(
+            failures.len() == 1 &&
+            failures[0].item.ends_with("b.txt") &&
+            failures[0].code == ExitErrorCode::FileOpen,
+            "Stop mode failures NOT match!!"
+        );
+        assert!(dir.join("a.txt.x").exists(), "File before the failure NOT encrypted!!");
+        assert!(!dir.join("c.txt.x").exists(), "File after the failure encrypted!!");
+
+        let batch = Batch::new(OnError::Continue);
+        let encrypted = super::encrypt(&files_paths, fernet::Fernet::generate_key(), false, temp_dir.join("log.db"), &batch);
+        assert_eq!(batch.failures().len(), 1, "Continue mode failures NOT match!!");
+        assert_eq!(encrypted, vec![dir.join("a.txt"), dir.join("c.txt")], "Encrypted files NOT match!!");
+        assert!(dir.join("a.txt").exists() && dir.join("c.txt").exists(), "Origin files wiped before the wipe!!");
+        assert!(!batch.is_stopped(), "Continue mode stopped!!");
+        assert!(dir.join("c.txt.x").exists(), "File after the failure NOT encrypted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn encrypt_sync_before_wipe() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypt_sync_before_wipe");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let dir = temp_dir.join("data");
+        let file = dir.join("a.txt");
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager synced file").unwrap();
+
+        // The delete flow syncs every encrypted file before wiping its origin.
+        crate::filelib::EVENTS.with_borrow_mut(|events| events.clear());
+        let key = fernet::Fernet::generate_key();
+        let encrypted = super::encrypt(
+            std::slice::from_ref(&file),
+            key.clone(),
+            true,
+            temp_dir.join("log.db"),
+            &Batch::new(OnError::Stop)
+        );
+        assert!(file.exists(), "Origin file wiped before the wipe!!");
+        super::wipe_origins(&encrypted, &crate::filelib::WipeSettings::default(), temp_dir.join("log.db"));
+        assert!(!file.exists(), "Origin file NOT wiped!!");
+        assert_eq!(
+            crate::filelib::EVENTS.with_borrow(|events| events.clone()),
+            vec![
+                format!("sync {}", dir.join("a.txt.x").display()),
+                format!("wipe {}", file.display())
+            ],
+            "Sync and wipe order NOT match!!"
+        );
+        decrypt_file::decrypt(dir.join("a.txt.x").to_str().unwrap().to_owned(), key);
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "XPManager synced file",
+            "Decrypted data NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
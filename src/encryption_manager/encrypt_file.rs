@@ -2,17 +2,156 @@ use super::{
     ArgMatches,
     Fernet,
     Write,
-    Read
+    Read,
+    FILE_MAGIC,
+    FILE_VERSION,
+    BUFFER_SIZE,
+    compression::{self, Compression}
 };
+use std::path::PathBuf;
 use crate::{
     errorlib,
     filelib, 
     loglib,
     displaylib,
     utilities,
-    dblib
+    dblib,
+    password_manager::PMDatabaseEncrption
 };
 
+/// The file encryption options.
+pub struct EncryptOptions {
+    /// The plaintext buffer size of every block.
+    pub buffer_size: usize,
+    /// The compression algorithm used before the encryption.
+    pub compression: Compression,
+    /// The compression level.
+    pub compression_level: u8,
+    /// Sync the encrypted file to the disk before returning,
+    /// needed before wiping the origin file.
+    pub fsync: bool,
+    /// The passphrase salt saved in the header, empty for a key.
+    pub salt: Vec<u8>
+}
+
+impl EncryptOptions {
+    /// Creates a new instance of a `EncryptOptions` with 
+    /// 64KB buffer size, without compression and without sync.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut options = EncryptOptions::new();
+    /// options.compression = Compression::Zstd;
+    /// options.compression_level = 3;
+    /// ```
+    pub fn new() -> EncryptOptions {
+        EncryptOptions {
+            buffer_size: BUFFER_SIZE,
+            compression: Compression::Off,
+            compression_level: 0,
+            fsync: false,
+            salt: vec![]
+        }
+    }
+}
+
+/// Write the data as encrypted blocks, only one block is buffered, so the
+/// memory stays the same for any data size (e.g. a streamed archive).
+/// 
+/// ### Example:
+/// ```
+/// let mut writer = BlockWriter::new(en_file, &fernet, &EncryptOptions::new())?;
+/// std::io::copy(&mut de_file, &mut writer)?;
+/// let blocks = writer.finish()?;
+/// ```
+pub struct BlockWriter<'a, W: Write> {
+    writer: W,
+    fernet: &'a Fernet,
+    options: &'a EncryptOptions,
+    buffer: Vec<u8>,
+    blocks: usize
+}
+
+impl<'a, W: Write> BlockWriter<'a, W> {
+    /// Creates a new instance of a `BlockWriter` and writes the file header.
+    pub fn new(mut writer: W, fernet: &'a Fernet, options: &'a EncryptOptions) -> std::io::Result<BlockWriter<'a, W>> {
+        // File header: "<magic><version><buffer-size><compression-id><compression-level><salt-size><salt>",
+        // the buffer size as u32 is the plaintext size of every block (the last one
+        // can be smaller), so the decryption does not need to guess it.
+        // The salt re-derives a passphrase key, the size is 0 for a key.
+        let mut header: Vec<u8> = FILE_MAGIC.to_vec();
+        header.push(FILE_VERSION);
+        header.extend_from_slice(&(options.buffer_size as u32).to_be_bytes());
+        header.push(options.compression.id());
+        header.push(options.compression_level);
+        header.push(options.salt.len() as u8);
+        header.extend_from_slice(&options.salt);
+        writer.write_all(&header)?;
+        Ok(BlockWriter {
+            writer,
+            fernet,
+            options,
+            buffer: Vec::with_capacity(options.buffer_size),
+            blocks: 0
+        })
+    }
+
+    /// Encrypt and write the buffered data as one block.
+    fn write_block(&mut self) -> std::io::Result<()> {
+        // Compress every block alone, so the blocks format stay the same.
+        let block = compression::compress(
+            &self.buffer,
+            self.options.compression,
+            self.options.compression_level
+        ).ok_or(std::io::ErrorKind::InvalidData)?;
+        let encryption_data = self.fernet.encrypt(&block);
+        // When we use the buffers to read and encrypted we do not know the
+        // length of the data after the encryption, so we get the encryption
+        // data length and store it in the ecryption file with the data. When
+        // we decrypt the file we need to get the length of the data from the
+        // start of the block. Store format: "<header><length><en-data><length><en-data>",
+        // every block of data has been encrypted will have the length of it
+        // in the stat of the block, We use the length as u32 so it will be a 
+        // list with fixed 4 numbers as u8 (e.g. [0u8, 0u8, 0u8, 0u8]).
+        // NOTE: Any change in the size type or the Fernet encryption function
+        // will be `breaking change`, the buffer size is in the header.
+        let size = encryption_data.len() as u32;
+        // save the block length before the encrypted block
+        self.writer.write_all(&size.to_be_bytes())?;
+        // save the encrypted block after saving the length of it
+        self.writer.write_all(encryption_data.as_bytes())?;
+        self.buffer.clear();
+        self.blocks += 1;
+        Ok(())
+    }
+
+    /// Write the last block, returns the number of the written blocks.
+    pub fn finish(mut self) -> std::io::Result<usize> {
+        if !self.buffer.is_empty() {
+            self.write_block()?;
+        }
+        self.writer.flush()?;
+        Ok(self.blocks)
+    }
+}
+
+impl<W: Write> Write for BlockWriter<'_, W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let size = data.len().min(self.options.buffer_size - self.buffer.len());
+        self.buffer.extend_from_slice(&data[..size]);
+        if self.buffer.len() == self.options.buffer_size {
+            self.write_block()?;
+        }
+        Ok(size)
+    }
+
+    /// Flush the written blocks only, the buffered data stays
+    /// until the block is full or `finish`.
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
 /// Encrypt file using **Fernet**.
 /// 
 /// ### Exit:
@@ -26,6 +165,27 @@ use crate::{
 /// let key = encrypt_file::encrypt("./dir/f.txt", "");
 /// ```
 pub fn encrypt(path: String, key: String) -> String {
+    encrypt_with_options(path, key, &EncryptOptions::new())
+}
+
+/// Encrypt file using **Fernet** with custom options, the buffer
+/// size and the compression are stored in the file header.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileFlush`
+/// - `errorlib::ExitErrorCode::FileUnsupported`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::Compression`
+/// 
+/// ### Example:
+/// ```
+/// let mut options = EncryptOptions::new();
+/// options.buffer_size = 1024;
+/// let key = encrypt_file::encrypt_with_options("./dir/f.txt", "<your-key>", &options);
+/// ```
+pub fn encrypt_with_options(path: String, key: String, options: &EncryptOptions) -> String {
     let logger = loglib::Logger::new("encrypt-file");
 
     // Check the key or generate one
@@ -35,35 +195,49 @@ pub fn encrypt(path: String, key: String) -> String {
         key
     };
 
+    // Opening a FIFO blocks, and a device may never end.
+    if std::path::Path::new(&path).exists() && !filelib::is_regular_file(std::path::Path::new(&path)) {
+        logger.error(
+            &format!("'{}' is NOT a regular file (e.g. FIFO or device), can NOT encrypt it!", path),
+            errorlib::ExitErrorCode::FileUnsupported
+        );
+    }
+
     if let Some(fernet) = Fernet::new(&key) {
         // Open the source file
         if let Ok(mut de_file) = std::fs::File::open(&path) {
             // Create and open the encrypted file
-            if let Ok(mut en_file) = std::fs::File::create(
-                filelib::make_encrypt_path(path)
-            ) {
-                let mut buffer = vec![0u8;  64*1024]; // 64KB buffer.
-                loop {
-                    let bytes_read = de_file.read(&mut buffer).unwrap();
-                    if bytes_read == 0 {
-                        break;
+            let en_path = filelib::make_encrypt_path(path);
+            if let Ok(en_file) = std::fs::File::create(&en_path) {
+                let mut writer = BlockWriter::new(&en_file, &fernet, options)
+                    .unwrap_or_else(|_| logger.error(
+                        "can NOT write the file header!",
+                        errorlib::ExitErrorCode::FileWrite
+                    ));
+                if let Err(error) = std::io::copy(&mut de_file, &mut writer) {
+                    if error.kind() == std::io::ErrorKind::InvalidData {
+                        logger.error(
+                            "can NOT compress the file!",
+                            errorlib::ExitErrorCode::Compression
+                        );
                     }
-                    let encryption_data = fernet.encrypt(&buffer[..bytes_read] );
-                    // When we use the buffers to read and encrypted we do not know the
-                    // length of the data after the encryption, so we get the encryption
-                    // data length and store it in the ecryption file with the data. When
-                    // we decrypt the file we need to get the length of the data from the
-                    // start of the file. Store format: "<length><en-data><length><en-data>",
-                    // every block of data has been encrypted will have the length of it
-                    // in the stat of the block, We use the length as u32 so it will be a 
-                    // list with fixed 4 numbers as u8 (e.g. [0u8, 0u8, 0u8, 0u8]).
-                    // NOTE: Any change in the size type or the Fernet encryption function
-                    // or making the buffer size bigger will be `breaking change`.
-                    let size = encryption_data.len() as u32;
-                    // save the block length before the encrypted block
-                    en_file.write_all(&size.to_be_bytes()).unwrap();
-                    // save the encrypted block after saving the length of it
-                    en_file.write_all(&encryption_data.as_bytes()).unwrap();
+                    logger.error(
+                        "can NOT write the encrypted file!",
+                        errorlib::ExitErrorCode::FileWrite
+                    );
+                }
+                if writer.finish().is_err() {
+                    logger.error(
+                        "can NOT write the encrypted file!",
+                        errorlib::ExitErrorCode::FileWrite
+                    );
+                }
+                // A crash after the origin is wiped must NOT lose the data.
+                if options.fsync && filelib::sync_file(&en_file, &en_path).is_err() {
+                    logger.error(
+                        "can NOT sync the encrypted file to the disk!",
+                        errorlib::ExitErrorCode::FileFlush
+                    );
                 }
                 return key;
             }
@@ -73,49 +247,474 @@ pub fn encrypt(path: String, key: String) -> String {
     logger.error("key error!", errorlib::ExitErrorCode::InvalidKey);
 }
 
-pub fn main(command: &ArgMatches) {
+/// Encrypt the files with one key, the key is generated once if it
+/// is empty, so all the files can be decrypted with the returned key.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileUnsupported`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::Compression`
+/// 
+/// ### Example:
+/// ```
+/// let paths = vec!["./dir/f1.txt".to_string(), "./dir/f2.txt".to_string()];
+/// let key = encrypt_file::encrypt_batch(&paths, "".to_string(), &EncryptOptions::new());
+/// ```
+pub fn encrypt_batch(paths: &[String], key: String, options: &EncryptOptions) -> String {
+    let mut key = key;
+    for path in paths {
+        key = encrypt_with_options(path.clone(), key, options);
+    }
+    key
+}
+
+/// Encrypt the files with one key to the output directory, for `--out`
+/// (e.g. a Dropbox/Drive folder). Each file is written atomically, then
+/// decrypted in memory to check it before the next file, so a sync client
+/// that changes a file in the middle is caught here.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+/// 
+/// ### Example:
+/// ```
+/// let paths = vec!["./dir/f1.txt".to_string(), "./dir/f2.txt".to_string()];
+/// encrypt_out(&paths, "/home/user/Dropbox", "<your-key>".to_string(), &EncryptOptions::new());
+/// ```
+fn encrypt_out(paths: &[String], out: &str, key: String, options: &EncryptOptions) -> String {
+    let logger = loglib::Logger::new("encrypt-file");
+    if Fernet::new(&key).is_none() {
+        logger.error("key error!", errorlib::ExitErrorCode::InvalidKey);
+    }
+    for path in paths {
+        let en_path = out_path(path, out);
+        if !encrypt_to(path, &en_path, &key, options) {
+            logger.error(
+                &format!("can NOT write '{}'!", en_path),
+                errorlib::ExitErrorCode::FileWrite
+            );
+        }
+        let size = std::fs::metadata(path).map(|metadata| metadata.len()).ok();
+        if super::decrypt_file::verify_file(&en_path, &key) != size {
+            logger.error(
+                &format!("'{}' does NOT decrypt to the origin file, it may be changed by a sync client!", en_path),
+                errorlib::ExitErrorCode::InvalidEncryptionData
+            );
+        }
+    }
+    key
+}
+
+/// The size of the origin files over the size of the encrypted files,
+/// bigger is better, e.g. 4.0 for a quarter of the size.
+/// 
+/// ### Example:
+/// ```
+/// assert_eq!(compression_ratio(1000, 250), 4.0);
+/// ```
+fn compression_ratio(size: u64, en_size: u64) -> f64 {
+    if en_size == 0 {
+        return 0.0;
+    }
+    size as f64 / en_size as f64
+}
+
+/// The encrypted file path of `--out`, `<out>/<file-name>.x`.
+fn out_path(path: &str, out: &str) -> String {
+    let name = PathBuf::from(path).file_name().unwrap().to_string_lossy().into_owned();
+    filelib::make_encrypt_path(PathBuf::from(out).join(name).to_str().unwrap().to_owned())
+}
+
+/// Wipe and delete the encrypted files origin, for `--delete`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileDelete`
+fn wipe_files(paths: &[String], settings: &filelib::WipeSettings) {
     let mut logger = loglib::Logger::new("encrypt-file");
-    let path = command.get_one::<String>("PATH").unwrap();
-    let is_key = *command.get_one::<bool>("key").unwrap_or(&false);
-    let file_state = filelib::get_file_state(path.clone());
-    if file_state == filelib::FileState::NotFound {
-        logger.error(
-            "file NOT found!",
-            errorlib::ExitErrorCode::FileNotFound
+    for path in paths {
+        logger.start();
+        let report = filelib::wipe_delete_settings(path.clone(), settings, false).unwrap_or_else(|err| err.exit());
+        logger.info(&format!("file '{}' wiped and deleted successfully.", path));
+        dblib::log::register(
+            &report.message(), 
+            dblib::log::LogAction::Wipe, 
+            filelib::log::get_log_db_path()
         );
     }
+}
 
-    // Check the key
-    let mut _key = "".to_owned();
-    if is_key {
-        _key = utilities::input("Enter your key: ");
-        logger.start();
+/// Encrypt a file to another path without exiting, the encrypted data is
+/// written to `<en-path>.xpm-autosave` and then renamed over `en_path`, so
+/// `en_path` is always a complete file on the disk (synced before the rename).
+/// The origin file is NOT deleted. The salt of a passphrase key is saved in
+/// the header (empty for a key). Returns false if the encryption fails.
+/// 
+/// ### Example:
+/// ```
+/// if !encrypt_snapshot("./dir/f.db", "./dir/f.db.x", "<your-key>", &[]) {
+///     println!("snapshot NOT saved!");
+/// }
+/// ```
+pub fn encrypt_snapshot(path: &str, en_path: &str, key: &str, salt: &[u8]) -> bool {
+    let mut options = EncryptOptions::new();
+    options.salt = salt.to_vec();
+    encrypt_atomic(path, en_path, &format!("{}.xpm-autosave", en_path), key, &options)
+}
+
+/// Encrypt a file to another path with custom options, like `encrypt_snapshot`
+/// but the temp file is `<en-path>.xpm-part`, e.g. for `--out` to a sync folder.
+/// Returns false if the encryption fails.
+/// 
+/// ### Example:
+/// ```
+/// if !encrypt_to("./dir/f.txt", "/home/user/Dropbox/f.txt.x", "<your-key>", &EncryptOptions::new()) {
+///     println!("file NOT encrypted!");
+/// }
+/// ```
+pub fn encrypt_to(path: &str, en_path: &str, key: &str, options: &EncryptOptions) -> bool {
+    encrypt_atomic(path, en_path, &format!("{}.xpm-part", en_path), key, options)
+}
+
+/// Encrypt the data from the stdin (e.g. a pipe) to a file, like `encrypt_to`
+/// the data is written to `<en-path>.xpm-part` and renamed when the stdin
+/// ends, so a broken stream leaves no encrypted file. Only one block of the
+/// data is in the memory. Returns false if the encryption fails.
+/// 
+/// ### Example:
+/// ```
+/// // pg_dump | xpm encryption-manager encrypt-file --stdin --out backup.x
+/// if !encrypt_stdin("./backup.x", "<your-key>", &EncryptOptions::new()) {
+///     println!("data NOT encrypted!");
+/// }
+/// ```
+pub fn encrypt_stdin(en_path: &str, key: &str, options: &EncryptOptions) -> bool {
+    let temp_path = format!("{}.xpm-part", en_path);
+    encrypt_reader_atomic(&mut std::io::stdin().lock(), en_path, &temp_path, key, options)
+}
+
+/// Encrypt a file to the temp path, sync it, and rename it over `en_path`.
+fn encrypt_atomic(path: &str, en_path: &str, temp_path: &str, key: &str, options: &EncryptOptions) -> bool {
+    match std::fs::File::open(path) {
+        Ok(mut de_file) => encrypt_reader_atomic(&mut de_file, en_path, temp_path, key, options),
+        Err(_) => false
     }
-    logger.info("encryption in progress....");
+}
 
-    // Encrypt the file
-    let key = encrypt(path.clone(), _key);
-    if !is_key {
-        displaylib::key::display(key);
-        logger.warning("store the key somewhere safe!");
-        logger.warning("if you lose the key, you will not be able to recover the data!");
+/// Encrypt the data of the reader to the temp path, sync it, and rename it over `en_path`.
+fn encrypt_reader_atomic<R: Read>(reader: &mut R, en_path: &str, temp_path: &str, key: &str, options: &EncryptOptions) -> bool {
+    let result = Fernet::new(key)
+        .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::InvalidInput))
+        .and_then(|fernet| {
+            let en_file = std::fs::File::create(temp_path)?;
+            let mut writer = BlockWriter::new(&en_file, &fernet, options)?;
+            std::io::copy(reader, &mut writer)?;
+            writer.finish()?;
+            filelib::sync_file(&en_file, temp_path)?;
+            std::fs::rename(temp_path, en_path)
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(temp_path);
+        return false;
+    }
+    true
+}
+
+/// The password manager entry name of the file key, the absolute file path.
+/// 
+/// ### Example:
+/// ```
+/// // "/home/user/dir/f.txt"
+/// let name = key_entry_name("./dir/f.txt");
+/// ```
+fn key_entry_name(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .map(|path| path.display().to_string())
+        .unwrap_or(path.to_owned())
+}
+
+/// Save the file key in the password manager database as a password named
+/// after the file path. Returns false, without saving, if a password with
+/// the same name exists.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBInsert`
+/// - `errorlib::ExitErrorCode::DBConnection`
+/// 
+/// ### Example:
+/// ```
+/// let pm_db_path = PathBuf::new().join("./dir/passwords.db");
+/// if !save_key_entry(pm_db_path, "/home/user/f.txt".to_string(), "<your-key>".to_string()) {
+///     println!("the file key is already saved!");
+/// }
+/// ```
+pub fn save_key_entry(pm_db_path: PathBuf, name: String, key: String) -> bool {
+    let saved = dblib::pm::find_password_matching(
+        pm_db_path.clone(),
+        name.clone(),
+        dblib::pm::MatchBy::Exact,
+        dblib::pm::SortBy::Id
+    );
+    if !saved.is_empty() {
+        return false;
+    }
+    dblib::pm::save_password(pm_db_path, name, key);
+    true
+}
+
+/// Save the file key in the password manager, for `--name-from-path`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// - `errorlib::ExitErrorCode::DBInsert`
+/// - `errorlib::ExitErrorCode::DBConnection`
+fn save_key_in_vault(path: &str, key: String) {
+    let mut logger = loglib::Logger::new("save-file-key");
+    let name = key_entry_name(path);
+    let pm_db_state = filelib::pm::db_state();
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
+    if pm_db_state == filelib::FileState::NotFound {
+        filelib::create_file(
+            filelib::pm::get_decrypted_db_path()
+        ).unwrap_or_else(|err| err.exit());
+        dblib::pm::create_passwords_table(filelib::pm::get_decrypted_db_path());
+        dblib::log::register("create passwords table", dblib::log::LogAction::Create, filelib::log::get_log_db_path());
+    } else if pm_db_state == filelib::FileState::Encrypted {
+        logger.warning("database is encrypted!");
+        pm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("password manager database decrypted successfully.");
+    }
+    let is_saved = save_key_entry(
+        filelib::pm::get_decrypted_db_path(),
+        name.clone(),
+        key
+    );
+    if _is_db_decrypted {
+        pm_db_encryption.encrypt();
+        logger.info("password manager database encrypted successfully.");
+    }
+    if !is_saved {
+        logger.error(
+            &format!("a password named '{}' already exists, the file is NOT encrypted!", name),
+            errorlib::ExitErrorCode::Input
+        );
     }
-    logger.info("file encrypted successfully.");
     dblib::log::register(
-        &format!("encrypt file at '{}'", path.clone()), 
+        &format!("'{}' key saved successfully.", name),
+        dblib::log::LogAction::Save,
         filelib::log::get_log_db_path()
     );
+    logger.info(&format!("the file key saved in the password manager as '{}'.", name));
+}
 
-    // Delete the origin file
-    if *command.get_one::<bool>("delete").unwrap_or(&false) {
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("encrypt-file");
+    // No paths with --stdin.
+    let paths: Vec<String> = command.get_many::<String>("PATH").map(|paths| paths.cloned().collect()).unwrap_or_default();
+    let is_stdin = *command.get_one::<bool>("stdin").unwrap_or(&false);
+    let is_key = *command.get_one::<bool>("key").unwrap_or(&false);
+    for path in paths.iter() {
+        let file_state = filelib::get_file_state(path.clone());
+        if PathBuf::new().join(path).exists() && !filelib::is_regular_file(&PathBuf::new().join(path)) {
+            logger.error(
+                &format!("'{}' is NOT a regular file (e.g. FIFO or device), can NOT encrypt it!", path),
+                errorlib::ExitErrorCode::FileUnsupported
+            );
+        } else if file_state == filelib::FileState::NotFound {
+            logger.error(
+                &format!("file '{}' NOT found!", path),
+                errorlib::ExitErrorCode::FileNotFound
+            );
+        }
+    }
+    let files_paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let out = command.get_one::<String>("out");
+    if let Some(out) = out.filter(|_| !is_stdin) {
+        if !PathBuf::from(out).is_dir() {
+            logger.error(
+                &format!("directory '{}' NOT found!", out),
+                errorlib::ExitErrorCode::DirNotFound
+            );
+        }
+        // Two files with the same name would overwrite each other.
+        let mut en_paths: Vec<String> = paths.iter().map(|path| out_path(path, out)).collect();
+        en_paths.sort();
+        if let Some(en_path) = en_paths.windows(2).find(|pair| pair[0] == pair[1]) {
+            logger.error(
+                &format!("many files are encrypted to '{}'!", en_path[0]),
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        if let Some(name) = filelib::sync_in_progress(&PathBuf::from(out)) {
+            logger.warning(
+                &format!("'{}' looks like a sync folder in the middle of a sync ('{}')!", out, name)
+            );
+        }
+    }
+
+    // Confirm the wipe before the encryption
+    let is_delete = *command.get_one::<bool>("delete").unwrap_or(&false);
+    // The encrypted files must be on the disk before wiping the origin files.
+    let is_fsync = is_delete || *command.get_one::<bool>("fsync").unwrap_or(&false);
+    if *command.get_one::<bool>("dry-wipe").unwrap_or(&false) {
+        displaylib::wipe::display_dry(
+            filelib::wipe_preview(&files_paths),
+            &super::wipe_settings(command)
+        );
+        return;
+    }
+    if is_delete {
+        logger.warning("the origin files will be wiped after the encryption!");
+        displaylib::wipe::display(
+            filelib::wipe_preview(&files_paths)
+        );
+        utilities::confirm();
         logger.start();
-        filelib::wipe_delete(path.clone());
-        logger.info("file wiped and deleted successfully.");
+    }
+
+    // Encrypt the files to an age recipient, no key to show
+    if let Some(recipient) = command.get_one::<String>("age-recipient") {
+        logger.info("encryption in progress....");
+        for path in paths.iter() {
+            super::age_file::encrypt(path.clone(), recipient, is_fsync);
+            dblib::log::register(
+                &format!("encrypt file at '{}' to an age recipient", path.clone()), 
+                dblib::log::LogAction::Encrypt, 
+                filelib::log::get_log_db_path()
+            );
+        }
+        logger.info("files encrypted to the age recipient successfully.");
+        if is_delete {
+            wipe_files(&paths, &super::wipe_settings(command));
+        }
+        return;
+    }
+
+    // Check the key, one key for all the files
+    let is_custom_key = is_key || super::KeySource::is_set(command);
+    let key = if is_custom_key {
+        let key = super::KeySource::resolve(command).read();
+        logger.start();
+        key
+    } else {
+        Fernet::generate_key()
+    };
+
+    // Save the keys before the encryption, so the files are NOT
+    // encrypted with a key that is NOT saved.
+    if *command.get_one::<bool>("name-from-path").unwrap_or(&false) {
+        for path in paths.iter() {
+            save_key_in_vault(path, key.clone());
+        }
+        logger.start();
+    }
+    logger.info("encryption in progress....");
+
+    // Compression options
+    let mut options = EncryptOptions::new();
+    options.fsync = is_fsync;
+    if *command.get_one::<bool>("compress").unwrap_or(&false) {
+        options.compression = Compression::Zstd;
+        options.compression_level = options.compression.default_level();
+    }
+    if let Some(algo) = command.get_one::<String>("compress-algo") {
+        options.compression = Compression::from_name(algo).unwrap();
+        options.compression_level = options.compression.default_level();
+    }
+    if let Some(level) = command.get_one::<String>("compress-level") {
+        if options.compression == Compression::Off {
+            logger.error(
+                "use --compress-level with --compress or --compress-algo!",
+                errorlib::ExitErrorCode::MissingArg
+            );
+        }
+        options.compression_level = level.parse::<u8>().unwrap_or(u8::MAX);
+        if !options.compression.is_valid_level(options.compression_level) {
+            logger.error(
+                &format!("invalid compression level '{}'!", level),
+                errorlib::ExitErrorCode::Input
+            );
+        }
+    }
+
+    // Encrypt the stdin data to the --out file, there is no origin file.
+    if let Some(en_path) = out.filter(|_| is_stdin) {
+        if !encrypt_stdin(en_path, &key, &options) {
+            logger.error(
+                &format!("can NOT encrypt the stdin to '{}'!", en_path),
+                errorlib::ExitErrorCode::FileWrite
+            );
+        }
+        dblib::log::register(
+            &format!("encrypt the stdin to '{}'", en_path),
+            dblib::log::LogAction::Encrypt,
+            filelib::log::get_log_db_path()
+        );
+        logger.info("stdin encrypted successfully.");
+        if !is_custom_key {
+            displaylib::key::display(key);
+            logger.warning("store the key somewhere safe!");
+            logger.warning("if you lose the key, you will not be able to recover the data!");
+        }
+        return;
+    }
+
+    // Encrypt the files
+    let key = match out {
+        Some(out) => encrypt_out(&paths, out, key, &options),
+        None => encrypt_batch(&paths, key, &options)
+    };
+    for path in paths.iter() {
+        let message = match out {
+            Some(out) => format!("encrypt file at '{}' to '{}' (verified)", path, out_path(path, out)),
+            None => format!("encrypt file at '{}'", path.clone())
+        };
         dblib::log::register(
-            &format!("file '{}' wiped", path.clone()), 
+            &message, 
+            dblib::log::LogAction::Encrypt, 
             filelib::log::get_log_db_path()
         );
     }
+    if paths.len() > 1 {
+        logger.info(&format!("{} files encrypted with one key successfully.", paths.len()));
+    } else {
+        logger.info("file encrypted successfully.");
+    }
+    if options.compression != Compression::Off {
+        let size_of = |path: &str| std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+        let size: u64 = paths.iter().map(|path| size_of(path)).sum();
+        let en_size: u64 = paths.iter()
+            .map(|path| size_of(&match out {
+                Some(out) => out_path(path, out),
+                None => filelib::make_encrypt_path(path.clone())
+            }))
+            .sum();
+        logger.info(&format!(
+            "compression ratio {:.2} ({} bytes -> {} bytes encrypted).",
+            compression_ratio(size, en_size),
+            size,
+            en_size
+        ));
+    }
+
+    // Delete the origin files
+    if is_delete {
+        wipe_files(&paths, &super::wipe_settings(command));
+    }
+    if !is_custom_key {
+        displaylib::key::display(key);
+        logger.warning("store the key somewhere safe!");
+        logger.warning("if you lose the key, you will not be able to recover the data!");
+    }
 }
 
 
@@ -123,6 +722,7 @@ pub fn main(command: &ArgMatches) {
 mod test {
     use std::path::PathBuf;
     use super::filelib::{create_file, delete_file};
+    use crate::{dblib, encryption_manager::decrypt_file};
 
     #[test]
     fn encrypt() {
@@ -130,7 +730,7 @@ mod test {
             .join("./temp/encrypt");
         let file = temp_dir.join("test.txt");
         let en_file = temp_dir.join("test.txt.x");
-        create_file(file.clone());
+        create_file(file.clone()).unwrap();
         assert_eq!(file.exists(), true, "Can NOT create the test file!!");
         let file_path_str = file
             .to_str()
@@ -143,7 +743,7 @@ mod test {
         assert_eq!(en_file.exists(), true, "Can NOT encrypt the test file!!");
 
         // with key
-        delete_file(en_file.clone());
+        delete_file(en_file.clone()).unwrap();
         assert_eq!(en_file.exists(), false, "Can NOT delete the test file!!");
         let key = super::Fernet::generate_key();
         let old_key = super::encrypt(file_path_str.clone(), key.clone());
@@ -154,4 +754,152 @@ mod test {
         std::fs::remove_dir_all(temp_dir)
             .expect("Can NOT delete temp files!!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn save_key_entry() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/save_key_entry");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        let file = temp_dir.join("test.txt");
+        create_file(db_path.clone()).unwrap();
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager").unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+
+        let name = super::key_entry_name(file.to_str().unwrap());
+        assert!(PathBuf::new().join(&name).is_absolute(), "Entry name is NOT the absolute path!!");
+        let key = super::encrypt(file.to_str().unwrap().to_owned(), "".to_owned());
+        assert!(
+            super::save_key_entry(db_path.clone(), name.clone(), key.clone()),
+            "Key entry NOT saved!!"
+        );
+        assert!(
+            !super::save_key_entry(db_path.clone(), name.clone(), "other-key".to_owned()),
+            "Duplicate key entry saved!!"
+        );
+        let passwords = dblib::pm::get_passwords(db_path.clone());
+        assert!(
+            passwords.len() == 1 && passwords[0].name == name && passwords[0].password == key,
+            "Key entry NOT match!!"
+        );
+
+        // The saved key decrypts the file.
+        std::fs::remove_file(&file).unwrap();
+        decrypt_file::decrypt(
+            temp_dir.join("test.txt.x").to_str().unwrap().to_owned(),
+            passwords[0].password.clone()
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "XPManager", "Decrypted data NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn compression_ratio() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/compression_ratio");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let file = temp_dir.join("export.csv");
+        create_file(file.clone()).unwrap();
+        std::fs::write(&file, "name,password,site\n".repeat(20_000)).unwrap();
+        let mut options = super::EncryptOptions::new();
+        options.compression = super::Compression::Zstd;
+        options.compression_level = options.compression.default_level();
+        super::encrypt_with_options(file.to_str().unwrap().to_owned(), "".to_owned(), &options);
+        let size = file.metadata().unwrap().len();
+        let en_size = temp_dir.join("export.csv.x").metadata().unwrap().len();
+        assert!(super::compression_ratio(size, en_size) > 10.0, "Compression ratio NOT match!!");
+        assert_eq!(super::compression_ratio(1000, 250), 4.0, "Ratio NOT match!!");
+        assert_eq!(super::compression_ratio(1000, 0), 0.0, "Empty ratio NOT match!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn encrypt_batch() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypt_batch");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let mut paths: Vec<String> = vec![];
+        for index in 1..=3 {
+            let file = temp_dir.join(format!("test{}.txt", index));
+            create_file(file.clone()).unwrap();
+            std::fs::write(&file, format!("XPManager {}", index)).unwrap();
+            paths.push(file.to_str().unwrap().to_owned());
+        }
+
+        let key = super::encrypt_batch(&paths, "".to_owned(), &super::EncryptOptions::new());
+        for (index, path) in paths.iter().enumerate() {
+            std::fs::remove_file(path).unwrap();
+            decrypt_file::decrypt(format!("{}.x", path), key.clone());
+            assert_eq!(
+                std::fs::read_to_string(path).unwrap(),
+                format!("XPManager {}", index + 1),
+                "Decrypted data NOT match!!"
+            );
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn encrypt_out_verify() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypt_out_verify");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let out = temp_dir.join("sync");
+        create_file(file.clone()).unwrap();
+        std::fs::create_dir_all(&out).unwrap();
+        let data = "XPManager ".repeat(2_000);
+        std::fs::write(&file, &data).unwrap();
+        let path = file.to_str().unwrap().to_owned();
+        let key = fernet::Fernet::generate_key();
+
+        super::encrypt_out(std::slice::from_ref(&path), out.to_str().unwrap(), key.clone(), &super::EncryptOptions::new());
+        let en_path = out.join("test.txt.x");
+        assert!(en_path.exists(), "Encrypted file NOT written to the output directory!!");
+        assert!(!out.join("test.txt.x.xpm-part").exists(), "Temp file NOT renamed!!");
+        assert_eq!(
+            decrypt_file::verify_file(en_path.to_str().unwrap(), &key),
+            Some(data.len() as u64),
+            "Verified size NOT match!!"
+        );
+
+        // A changed byte in the middle of a block.
+        let mut en_data = std::fs::read(&en_path).unwrap();
+        let middle = en_data.len() / 2;
+        en_data[middle] = if en_data[middle] == b'A' { b'B' } else { b'A' };
+        std::fs::write(&en_path, &en_data).unwrap();
+        assert_eq!(decrypt_file::verify_file(en_path.to_str().unwrap(), &key), None, "Changed file NOT flagged!!");
+        // A partial upload.
+        std::fs::write(&en_path, &en_data[..middle]).unwrap();
+        assert_eq!(decrypt_file::verify_file(en_path.to_str().unwrap(), &key), None, "Partial file NOT flagged!!");
+
+        assert_eq!(super::filelib::sync_in_progress(&out), None, "Sync folder NOT match!!");
+        create_file(out.join(".syncthing.test.txt.x.tmp")).unwrap();
+        assert_eq!(
+            super::filelib::sync_in_progress(&out),
+            Some(".syncthing.test.txt.x.tmp".to_owned()),
+            "Sync in progress NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
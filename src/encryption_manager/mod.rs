@@ -4,8 +4,197 @@ pub mod encrypt_dir;
 pub mod decrypt_dir;
 pub mod encode;
 pub mod decode;
+pub mod compression;
+pub mod upgrade;
+pub mod verify;
+pub mod wipe;
+pub mod age_file;
 
 use clap::ArgMatches;
 use fernet::Fernet;
 use std::io::{Read, Write};
-use std::path::PathBuf;
\ No newline at end of file
+use base64::{Engine, engine::general_purpose::URL_SAFE};
+use rand::Rng;
+use std::path::PathBuf;
+use crate::{configlib, errorlib, filelib, loglib};
+
+/// The XPManager encryption file magic, the start of the file header.
+/// Files without it are legacy files, they have no header.
+const FILE_MAGIC: &[u8; 4] = b"XPMF";
+
+/// The XPManager encryption file format version.
+/// - Version 1: "<magic><version><buffer-size>"
+/// - Version 2: "<magic><version><buffer-size><compression-id><compression-level>"
+/// - Version 3: "<magic><version><buffer-size><compression-id><compression-level><salt-size><salt>"
+const FILE_VERSION: u8 = 3;
+
+/// The version byte of the files encrypted with an age recipient,
+/// "<magic><age-version><age-file>", it is NOT a Fernet format version.
+const AGE_FILE_VERSION: u8 = 0x80;
+
+/// The plaintext buffer size used in the encryption.
+const BUFFER_SIZE: usize = 64*1024; // 64KB buffer.
+
+/// The passphrase salt size in bytes, the salt is saved in the file header.
+pub const SALT_SIZE: usize = 16;
+
+/// Generate a random salt for `derive_key_from_passphrase`.
+pub fn generate_salt() -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_SIZE];
+    rand::rng().fill(&mut salt[..]);
+    salt
+}
+
+/// Derive a Fernet key from a passphrase using Argon2id, the same
+/// passphrase and salt give the same key.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// 
+/// ### Example:
+/// ```
+/// let salt = generate_salt();
+/// let key = derive_key_from_passphrase("correct horse battery staple", &salt);
+/// assert!(Fernet::new(&key).is_some());
+/// ```
+pub fn derive_key_from_passphrase(passphrase: &str, salt: &[u8]) -> String {
+    let logger = loglib::Logger::new("derive-key");
+    if passphrase.is_empty() {
+        logger.error(
+            "the passphrase must NOT be empty!",
+            errorlib::ExitErrorCode::InvalidKey
+        );
+    }
+    // Fernet key: 32 bytes (signing key and encryption key) as URL-safe base64.
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .unwrap_or_else(|_| logger.error(
+            "can NOT derive the key from the passphrase!",
+            errorlib::ExitErrorCode::InvalidKey
+        ));
+    URL_SAFE.encode(key)
+}
+
+/// Get the wipe settings from the `--wipe-method` and `--passes`
+/// flags, or from the config `[wipe]`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+/// - `errorlib::ExitErrorCode::Input`
+fn wipe_settings(command: &ArgMatches) -> filelib::WipeSettings {
+    filelib::WipeSettings::resolve(
+        &configlib::Config::load(),
+        command.get_one::<String>("wipe-method"),
+        command.get_one::<String>("passes")
+    )
+}
+
+/// The environment variable of the key, for the scripts and the tests.
+pub const KEY_ENV: &str = "XPM_KEY";
+
+/// Where the key of a command is read from.
+#[derive(PartialEq, Debug)]
+pub enum KeySource {
+    /// The OS keyring account, `--keyring`.
+    Keyring(String),
+    /// The first line of a file, `--key-file`.
+    File(String),
+    /// The first line of the stdin, `--key-stdin`.
+    Stdin,
+    /// The `XPM_KEY` environment variable.
+    Env(String),
+    /// Ask the user, the last resort.
+    Prompt
+}
+
+impl KeySource {
+    /// Get the key source of the command, in order: `--keyring`,
+    /// `--key-file`, `--key-stdin`, the `XPM_KEY` environment variable,
+    /// then the prompt.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let key = KeySource::resolve(command).read();
+    /// ```
+    pub fn resolve(command: &ArgMatches) -> KeySource {
+        if let Some(account) = command.get_one::<String>("keyring") {
+            return KeySource::Keyring(account.clone());
+        }
+        if let Some(path) = command.get_one::<String>("key-file") {
+            return KeySource::File(path.clone());
+        }
+        if *command.get_one::<bool>("key-stdin").unwrap_or(&false) {
+            return KeySource::Stdin;
+        }
+        match std::env::var(KEY_ENV) {
+            Ok(key) if !key.is_empty() => KeySource::Env(key),
+            _ => KeySource::Prompt
+        }
+    }
+
+    /// Check if the command sets the key source with a flag, the
+    /// environment variable and the prompt are NOT counted.
+    pub fn is_set(command: &ArgMatches) -> bool {
+        command.get_one::<String>("keyring").is_some()
+            || command.get_one::<String>("key-file").is_some()
+            || *command.get_one::<bool>("key-stdin").unwrap_or(&false)
+    }
+
+    /// Read the key, the trailing whitespace of the file and the stdin is
+    /// removed. The key file must have a Fernet key (44 base64 characters).
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::Keyring`
+    /// - `errorlib::ExitErrorCode::FileNotFound`
+    /// - `errorlib::ExitErrorCode::FileRead`
+    /// - `errorlib::ExitErrorCode::InvalidKey`
+    /// - `errorlib::ExitErrorCode::Input`
+    pub fn read(&self) -> String {
+        let logger = loglib::Logger::new("read-key");
+        let first_line = |text: &str| text.lines()
+            .next()
+            .unwrap_or("")
+            .trim_end()
+            .to_owned();
+        let key = match self {
+            KeySource::Keyring(account) => crate::keyringlib::get_key(account),
+            KeySource::File(path) => {
+                if !PathBuf::new().join(path).is_file() {
+                    logger.error(
+                        &format!("key file '{}' NOT found!", path),
+                        errorlib::ExitErrorCode::FileNotFound
+                    );
+                }
+                let key = first_line(&std::fs::read_to_string(path).unwrap_or_else(|_| logger.error(
+                    &format!("can NOT read the key file '{}'!", path),
+                    errorlib::ExitErrorCode::FileRead
+                )));
+                if Fernet::new(&key).is_none() {
+                    logger.error(
+                        &format!("the key file '{}' has NO Fernet key (44 base64 characters)!", path),
+                        errorlib::ExitErrorCode::InvalidKey
+                    );
+                }
+                key
+            },
+            KeySource::Stdin => {
+                let mut line = String::new();
+                std::io::stdin().read_line(&mut line).unwrap_or_else(|_| logger.error(
+                    "can NOT read the key from the stdin!",
+                    errorlib::ExitErrorCode::Input
+                ));
+                first_line(&line)
+            },
+            KeySource::Env(key) => key.clone(),
+            KeySource::Prompt => crate::utilities::input("Enter your key: ")
+        };
+        if key.is_empty() {
+            logger.error(
+                "the key is empty!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        key
+    }
+}
@@ -0,0 +1,188 @@
+use super::{
+    ArgMatches,
+    KeySource,
+    Read,
+    FILE_MAGIC,
+    FILE_VERSION,
+    encrypt_file,
+    decrypt_file
+};
+use crate::{
+    errorlib,
+    filelib,
+    loglib,
+    dblib
+};
+
+/// Get the encryption file format version, `0` for
+/// legacy files (no header).
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileOpen`
+///
+/// ### Example:
+/// ```
+/// if file_version("./dir/f.txt.x") < FILE_VERSION {
+///     println!("old encryption format.");
+/// }
+/// ```
+fn file_version(path: &str) -> u8 {
+    let logger = loglib::Logger::new("file-version");
+    if let Ok(mut en_file) = std::fs::File::open(path) {
+        let mut header = [0u8; 5];
+        if en_file.read_exact(&mut header).is_ok() && &header[..4] == FILE_MAGIC {
+            return header[4];
+        }
+        return 0;
+    }
+    logger.error(
+        "can NOT open the file!",
+        errorlib::ExitErrorCode::FileOpen
+    );
+}
+
+/// Re-encrypt an old format file in the current format, in place.
+/// The whole file is checked with the key first, then the new file is
+/// written next to the old one and renamed over it, so the old file
+/// stays as it is if anything fails. Returns `false` if the file is
+/// already in the current format.
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::FileCreate`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::InvalidKey`
+/// - `errorlib::ExitErrorCode::InvalidEncryptionData`
+///
+/// ### Example:
+/// ```
+/// if upgrade("./dir/f.txt.x".to_string(), "<your-key>".to_string()) {
+///     println!("file upgraded.");
+/// }
+/// ```
+pub fn upgrade(path: String, key: String) -> bool {
+    let logger = loglib::Logger::new("upgrade-file");
+    if file_version(&path) >= FILE_VERSION {
+        return false;
+    }
+    // A wrong key or a broken block fails before the temp copy is made.
+    if let Err(err) = decrypt_file::verify_blocks(&path, &key) {
+        logger.error(&err.message(), err.code());
+    }
+    // "<name>.xpm-upgrade.x" -> "<name>.xpm-upgrade" -> "<name>.xpm-upgrade.x"
+    let temp_path = format!("{}.xpm-upgrade", filelib::make_decrypt_path(path.clone()));
+    let temp_en_path = filelib::make_encrypt_path(temp_path.clone());
+    if std::fs::copy(&path, &temp_en_path).is_err() {
+        logger.error(
+            &format!("can NOT create '{}'!", temp_en_path),
+            errorlib::ExitErrorCode::FileCreate
+        );
+    }
+    decrypt_file::decrypt(temp_en_path.clone(), key.clone());
+    encrypt_file::encrypt(temp_path.clone(), key);
+    filelib::wipe_delete(temp_path).unwrap_or_else(|err| err.exit());
+    if std::fs::rename(&temp_en_path, &path).is_err() {
+        filelib::delete_file(temp_en_path.into()).unwrap_or_else(|err| err.exit());
+        logger.error(
+            &format!("can NOT replace '{}'!", path),
+            errorlib::ExitErrorCode::FileWrite
+        );
+    }
+    true
+}
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("upgrade-file");
+    let path = command.get_one::<String>("PATH").unwrap();
+    let file_state = filelib::get_file_state(path.clone());
+    if file_state == filelib::FileState::NotFound {
+        logger.error(
+            "file NOT found!",
+            errorlib::ExitErrorCode::FileNotFound
+        );
+    } else if file_state == filelib::FileState::Decrypted {
+        logger.error(
+            "file NOT encrpted!",
+            errorlib::ExitErrorCode::FileNotEncrypted
+        );
+    }
+    if file_version(path) >= FILE_VERSION {
+        logger.info("file is already in the latest format, skipped.");
+        return;
+    }
+    let key = match command.get_one::<String>("key") {
+        Some(key) => key.clone(),
+        None => KeySource::resolve(command).read()
+    };
+    logger.start();
+    logger.info("upgrade in progress....");
+    upgrade(path.clone(), key);
+    logger.info(
+        &format!("file upgraded to the format version {} successfully.", FILE_VERSION)
+    );
+    dblib::log::register(
+        &format!("file '{}' upgraded", path),
+        dblib::log::LogAction::Encrypt,
+        filelib::log::get_log_db_path()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::{FILE_MAGIC, FILE_VERSION};
+    use crate::{
+        encryption_manager::decrypt_file,
+        filelib::create_file
+    };
+
+    #[test]
+    fn upgrade_legacy_file() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/upgrade_legacy_file");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let en_file = temp_dir.join("test.txt.x");
+        let en_file_str = en_file.to_str().unwrap().to_owned();
+        create_file(en_file.clone()).unwrap();
+
+        // Legacy format: "<length><en-data><length><en-data>", no header.
+        let key = fernet::Fernet::generate_key();
+        let fernet = fernet::Fernet::new(&key).unwrap();
+        let mut legacy: Vec<u8> = vec![];
+        for block in ["XPManager ", "legacy file"] {
+            let token = fernet.encrypt(block.as_bytes());
+            legacy.extend_from_slice(&(token.len() as u32).to_be_bytes());
+            legacy.extend_from_slice(token.as_bytes());
+        }
+        std::fs::write(&en_file, legacy)
+            .expect("Can NOT write the test file!!");
+        assert_eq!(super::file_version(&en_file_str), 0, "Legacy version NOT match!!");
+
+        assert!(super::upgrade(en_file_str.clone(), key.clone()), "File NOT upgraded!!");
+        let upgraded = std::fs::read(&en_file).unwrap();
+        assert_eq!(&upgraded[..4], FILE_MAGIC, "File magic NOT match!!");
+        assert_eq!(upgraded[4], FILE_VERSION, "File version NOT match!!");
+        assert!(
+            !super::upgrade(en_file_str.clone(), key.clone()),
+            "Latest format file NOT skipped!!"
+        );
+
+        decrypt_file::decrypt(en_file_str, key);
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "XPManager legacy file",
+            "Upgraded data NOT match!!"
+        );
+        assert_eq!(
+            std::fs::read_dir(&temp_dir).unwrap().count(), 2,
+            "Upgrade temp files NOT deleted!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
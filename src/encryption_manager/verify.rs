@@ -0,0 +1,38 @@
+use super::{
+    ArgMatches,
+    KeySource,
+    decrypt_file
+};
+use crate::{
+    errorlib,
+    filelib,
+    loglib
+};
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("verify-file");
+    let path = command.get_one::<String>("PATH").unwrap();
+    let file_state = filelib::get_file_state(path.clone());
+    if file_state == filelib::FileState::NotFound {
+        logger.error(
+            "file NOT found!",
+            errorlib::ExitErrorCode::FileNotFound
+        );
+    } else if file_state == filelib::FileState::Decrypted {
+        logger.error(
+            "file NOT encrpted!",
+            errorlib::ExitErrorCode::FileNotEncrypted
+        );
+    }
+    let key = match command.get_one::<String>("key") {
+        Some(key) => key.clone(),
+        None => KeySource::resolve(command).read()
+    };
+    logger.start();
+    logger.info("verification in progress....");
+    // Every block is decrypted and dropped, nothing is written.
+    match decrypt_file::verify_blocks(path, &key) {
+        Ok(size) => logger.info(&format!("OK, '{}' decrypts to {} bytes.", path, size)),
+        Err(err) => logger.error(&err.message(), err.code())
+    }
+}
@@ -0,0 +1,87 @@
+use super::{
+    ArgMatches,
+    PathBuf
+};
+use crate::{
+    errorlib,
+    filelib,
+    loglib,
+    utilities,
+    displaylib,
+    dblib
+};
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("wipe-file");
+    let path = command.get_one::<String>("PATH").unwrap();
+    let is_resume = *command.get_one::<bool>("resume").unwrap_or(&false);
+    let mut settings = super::wipe_settings(command);
+    settings.rate = filelib::limit_rate(command.get_one::<String>("limit-rate"));
+    if *command.get_one::<bool>("verify").unwrap_or(&false) {
+        settings.verify = true;
+    }
+    if PathBuf::new().join(path).is_dir() {
+        if is_resume {
+            logger.error(
+                "--resume can NOT be used with a directory, resume its failed file!!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        wipe_dir(PathBuf::new().join(path), &settings, &mut logger);
+        return;
+    }
+    if !filelib::is_regular_file(&PathBuf::new().join(path)) {
+        logger.error(
+            "file NOT found!",
+            errorlib::ExitErrorCode::FileNotFound
+        );
+    }
+    let completed = filelib::wipe_completed_levels(path);
+    if is_resume && completed == 0 {
+        logger.warning("no failed wipe found, the wipe will start from the first level!");
+    } else if is_resume {
+        logger.info(&format!("resume the wipe after {} completed levels.", completed));
+    }
+    if !is_resume || completed == 0 {
+        displaylib::wipe::display(
+            filelib::wipe_preview(&[PathBuf::new().join(path)])
+        );
+        utilities::confirm();
+        logger.start();
+    }
+    let report = filelib::wipe_delete_settings(path.clone(), &settings, is_resume).unwrap_or_else(|err| err.exit());
+    logger.info("file wiped and deleted successfully.");
+    dblib::log::register(
+        &report.message(),
+        dblib::log::LogAction::Wipe,
+        filelib::log::get_log_db_path()
+    );
+}
+
+/// Wipe and delete the directory with the wipe settings, and log the
+/// report of every wiped file.
+fn wipe_dir(folder: PathBuf, settings: &filelib::WipeSettings, logger: &mut loglib::Logger) {
+    let mut files_paths: Vec<PathBuf> = Vec::new();
+    let mut links: Vec<PathBuf> = Vec::new();
+    filelib::dir_links_tree(folder.clone(), &mut files_paths, &mut links).unwrap_or_else(|err| err.exit());
+    if !links.is_empty() {
+        logger.info(&format!("{} symlinks will be deleted without wiping their targets.", links.len()));
+    }
+    displaylib::wipe::display(filelib::wipe_preview(&files_paths));
+    utilities::confirm();
+    logger.start();
+    let reports = filelib::wipe_delete_dir(folder.clone(), settings).unwrap_or_else(|err| err.exit());
+    logger.info("directory wiped and deleted successfully.");
+    for report in reports {
+        dblib::log::register(
+            &report.message(),
+            dblib::log::LogAction::Wipe,
+            filelib::log::get_log_db_path()
+        );
+    }
+    dblib::log::register(
+        &format!("directory '{}' wiped and deleted", folder.display()),
+        dblib::log::LogAction::Wipe,
+        filelib::log::get_log_db_path()
+    );
+}
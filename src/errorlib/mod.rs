@@ -1,4 +1,5 @@
 
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum ExitErrorCode {
     // File
     FileNotFound           = 50,
@@ -11,6 +12,10 @@ pub enum ExitErrorCode {
     FileDelete             = 57,
     FileAlreadyEncrypted   = 58,
     FileNotEncrypted       = 59,
+    FileUnsupported        = 60,
+    FileTooLarge           = 61,
+    FileAlreadyDecrypted   = 62,
+    WipeVerifyFailed       = 63,
 
     // Dir
     DirNotFound            = 65,
@@ -19,28 +24,38 @@ pub enum ExitErrorCode {
     CanNotGetDirData       = 68,
     SystemDataDirNotFound  = 69,
     CanNotGetFileOrDirType = 70,
+    DirDelete              = 71,
 
     // JSON
     CanNotGetJsonObject    = 75,
     InvalidJson            = 76,
+    InvalidEncoding        = 77,
 
     // Encryption and Decryption
     InvalidKey             = 80,
     InvalidEncryptionData  = 81,
+    Compression            = 82,
+    UnsupportedFormat      = 83,
+    WrongKey               = 84,
 
     // Database
     DBConnection           = 85,
     DBInsert               = 86,
     DBCreateTable          = 87,
+    DBUpdate               = 88,
     PMDatabaseNotFound     = 89,
     PMDatabaseEmpty        = 90,
     LMDatabaseNotFound     = 91,
     LogNotFound            = 92,
-    LMDatabaseEncrypted    = 93,
 
     // Others
     Input                  = 95,
     MissingArg             = 96,
     ConfirmationNotMatch   = 97,
     SampleContainSpace     = 98,
+    StdoutIsTerminal       = 99,
+    Clipboard              = 100,
+    Terminal               = 101,
+    InvalidConfig          = 102,
+    Keyring                = 103,
 }
\ No newline at end of file
@@ -1,8 +1,12 @@
 use super::{
+    XPM_EXTENSION,
     loglib,
     data_dir,
     errorlib,
-    PathBuf
+    make_encrypt_path,
+    Path,
+    PathBuf,
+    FileState
 };
 
 /// Get the log manager database path.
@@ -29,6 +33,63 @@ pub fn get_log_db_path() -> PathBuf {
     }
 }
 
+/// Get the encrypted log manager database path.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::SystemDataDirNotFound`
+/// 
+/// ### Example: 
+/// ```
+/// let log_db_path = filelib::log::get_encrypted_log_db_path();
+/// prinln!("Encrypted log manager database path: {}", log_db_path.display());
+/// ```
+pub fn get_encrypted_log_db_path() -> PathBuf {
+    let logger = loglib::Logger::new("get-log-encrypted-db-pth");
+    if let Some(path) = data_dir() {
+        path.join(
+            format!("XPManager/data/xpm-log.db.{}", XPM_EXTENSION)
+        )
+    } else {
+        logger.error(
+            "can NOT get the system data directory path!", 
+            errorlib::ExitErrorCode::SystemDataDirNotFound
+        );
+    }
+}
+
+/// Check if the log database has an encrypted variant (`<path>.x`).
+/// 
+/// ### Example:
+/// ```
+/// let log_db_path = filelib::log::get_log_db_path();
+/// if filelib::log::is_encrypted(&log_db_path) {
+///     println!("log manager database is encrypted.");
+/// }
+/// ```
+pub fn is_encrypted(log_db_path: &Path) -> bool {
+    PathBuf::new()
+        .join(make_encrypt_path(log_db_path.to_str().unwrap().to_owned()))
+        .exists()
+}
+
+/// Get the log manager database state.
+/// 
+/// ### Example:
+/// ```
+/// if filelib::log::db_state() == filelib::FileState::Encrypted {
+///     println!("log manager database is encrypted.");
+/// }
+/// ```
+pub fn db_state() -> FileState {
+    if get_encrypted_log_db_path().exists() {
+        FileState::Encrypted
+    } else if get_log_db_path().exists() {
+        FileState::Decrypted
+    } else {
+        FileState::NotFound
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -9,9 +9,11 @@ use std::io::{
     BufReader, BufWriter, Read, Seek, SeekFrom, Write
 };
 use dirs::data_dir;
+use globset::{Glob, GlobSetBuilder};
 use rand::Rng;
 use serde_json::Value;
-use crate::{errorlib, loglib};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use crate::{configlib, errorlib, loglib, utilities};
 
 /// The XPManager encryption file extension.
 /// Like `file.txt.x` or `password.db.x`.
@@ -29,19 +31,382 @@ pub enum FileState {
 }
 
 /// The wipe types.
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 enum WipeType {
     /// Wipe using 0
     BZero,
     /// Wipe using 1
     BOne,
     /// Wipe using random data
-    Random
+    Random,
+    /// Wipe using a repeated 3 bytes pattern (e.g. `[0x92, 0x49, 0x24]`)
+    Pattern([u8; 3])
 }
 
-/// Create a file.
+/// The wipe levels used by `wipe_delete`, in order:
+/// - Level 1: `BOne` as 1s.
+/// - Level 2: `Random` as static random data.
+/// - Level 3: `Random` as static random data.
+/// - Level 4: `BZero` as 0s.
+const WIPE_LEVELS: [WipeType; 4] = [
+    WipeType::BOne,
+    WipeType::Random,
+    WipeType::Random,
+    WipeType::BZero
+];
+
+/// A repeated 1 byte pattern of the DoD and the Gutmann levels.
+const fn byte(value: u8) -> WipeType {
+    WipeType::Pattern([value; 3])
+}
+
+/// The DoD 5220.22-M (short) 3 levels, in order:
+/// - Level 1: `BZero` as 0x00 bytes.
+/// - Level 2: `byte(0xFF)` as 0xFF bytes (the complement of level 1).
+/// - Level 3: `Random` as static random data.
+const DOD_LEVELS: [WipeType; 3] = [
+    WipeType::BZero,
+    byte(0xFF),
+    WipeType::Random
+];
+
+/// The Gutmann 35 levels, in order:
+/// - Levels 1-4: `Random`.
+/// - Levels 5-31: the fixed patterns of the old magnetic disk encodings.
+/// - Levels 32-35: `Random`.
+const GUTMANN_LEVELS: [WipeType; 35] = [
+    WipeType::Random, WipeType::Random, WipeType::Random, WipeType::Random,
+    byte(0x55), byte(0xAA),
+    WipeType::Pattern([0x92, 0x49, 0x24]),
+    WipeType::Pattern([0x49, 0x24, 0x92]),
+    WipeType::Pattern([0x24, 0x92, 0x49]),
+    byte(0x00), byte(0x11), byte(0x22), byte(0x33),
+    byte(0x44), byte(0x55), byte(0x66), byte(0x77),
+    byte(0x88), byte(0x99), byte(0xAA), byte(0xBB),
+    byte(0xCC), byte(0xDD), byte(0xEE), byte(0xFF),
+    WipeType::Pattern([0x92, 0x49, 0x24]),
+    WipeType::Pattern([0x49, 0x24, 0x92]),
+    WipeType::Pattern([0x24, 0x92, 0x49]),
+    WipeType::Pattern([0x6D, 0xB6, 0xDB]),
+    WipeType::Pattern([0xB6, 0xDB, 0x6D]),
+    WipeType::Pattern([0xDB, 0x6D, 0xB6]),
+    WipeType::Random, WipeType::Random, WipeType::Random, WipeType::Random
+];
+
+/// The wipe standards.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum WipeStandard {
+    /// The `WIPE_LEVELS`, or a number of passes cycling through them and ending with 0s.
+    Simple,
+    /// The `DOD_LEVELS` (the passes are ignored), for the compliance
+    /// policies that ask for the DoD 5220.22-M short wipe.
+    Dod522022M,
+    /// The `GUTMANN_LEVELS` (the passes are ignored). It is very slow, and
+    /// it is for the old magnetic disks only, on the SSDs and the modern
+    /// disks it does NOT wipe better than one pass.
+    Gutmann
+}
+
+impl WipeStandard {
+    /// Get the wipe standard from its name (simple, dod, gutmann).
+    pub fn from_name(name: &str) -> Option<WipeStandard> {
+        match name {
+            "simple" => Some(WipeStandard::Simple),
+            "dod" => Some(WipeStandard::Dod522022M),
+            "gutmann" => Some(WipeStandard::Gutmann),
+            _ => None
+        }
+    }
+
+    /// Get the wipe standard name.
+    pub fn name(&self) -> &str {
+        match self {
+            WipeStandard::Simple => "simple",
+            WipeStandard::Dod522022M => "dod",
+            WipeStandard::Gutmann => "gutmann"
+        }
+    }
+}
+
+/// The wipe settings, from the config `[wipe]` and overridden by the flags.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct WipeSettings {
+    pub standard: WipeStandard,
+    /// None for the standard passes.
+    pub passes: Option<u8>,
+    /// The max write rate in bytes per second, None for no limit.
+    pub rate: Option<u64>,
+    /// Read back every pass and check it matches the written data.
+    pub verify: bool
+}
+
+impl Default for WipeSettings {
+    fn default() -> WipeSettings {
+        WipeSettings {
+            standard: WipeStandard::Simple,
+            passes: None,
+            rate: None,
+            verify: false
+        }
+    }
+}
+
+impl WipeSettings {
+    /// Get the wipe settings from the `--wipe-method` and `--passes`
+    /// flags, or from the config `wipe.method` and `wipe.passes`. The
+    /// config `wipe.verify` turns on the verification.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// - `errorlib::ExitErrorCode::Input`
+    /// 
+    /// ### Example:
+    /// ```toml
+    /// [wipe]
+    /// method = "simple"
+    /// passes = 8
+    /// verify = true
+    /// ```
+    /// ```
+    /// let settings = filelib::WipeSettings::resolve(&configlib::Config::load(), None, None);
+    /// assert_eq!(settings.passes, Some(8));
+    /// ```
+    pub fn resolve(config: &configlib::Config, method: Option<&String>, passes: Option<&String>) -> WipeSettings {
+        let logger = loglib::Logger::new("wipe-settings");
+        let mut settings = WipeSettings::default();
+        if let Some(name) = config.get_str("wipe.method") {
+            settings.standard = WipeStandard::from_name(&name).unwrap_or_else(|| logger.error(
+                &format!("invalid config 'wipe.method = \"{}\"', use simple, dod, or gutmann!", name),
+                errorlib::ExitErrorCode::InvalidConfig
+            ));
+        }
+        if let Some(number) = config.get_int("wipe.passes") {
+            settings.passes = Some(u8::try_from(number).unwrap_or_else(|_| logger.error(
+                &format!("config 'wipe.passes' must be from 0 to {}!", u8::MAX),
+                errorlib::ExitErrorCode::InvalidConfig
+            )));
+        }
+        if let Some(verify) = config.get_bool("wipe.verify") {
+            settings.verify = verify;
+        }
+        if let Some(name) = method {
+            settings.standard = WipeStandard::from_name(name).unwrap_or_else(|| logger.error(
+                &format!("invalid wipe method '{}', use simple, dod, or gutmann!", name),
+                errorlib::ExitErrorCode::Input
+            ));
+        }
+        if let Some(number) = passes {
+            settings.passes = Some(number.parse::<u8>().unwrap_or_else(|_| logger.error(
+                &format!("--passes must be unsigned integer from 0 to {}!", u8::MAX),
+                errorlib::ExitErrorCode::Input
+            )));
+        }
+        settings
+    }
+
+    /// Get the wipe levels, in order.
+    fn levels(&self) -> Vec<WipeType> {
+        match (self.standard, self.passes) {
+            (WipeStandard::Simple, None) => WIPE_LEVELS.to_vec(),
+            (WipeStandard::Simple, Some(0)) => vec![],
+            (WipeStandard::Simple, Some(passes)) => {
+                // Cycle through the levels, the last pass is always 0s.
+                let last = WIPE_LEVELS.len() - 1;
+                let mut levels: Vec<WipeType> = WIPE_LEVELS[..last]
+                    .iter()
+                    .cycle()
+                    .take(passes as usize - 1)
+                    .copied()
+                    .collect();
+                levels.push(WipeType::BZero);
+                levels
+            },
+            (WipeStandard::Dod522022M, _) => DOD_LEVELS.to_vec(),
+            (WipeStandard::Gutmann, _) => GUTMANN_LEVELS.to_vec()
+        }
+    }
+
+    /// Get the number of the wipe passes.
+    pub fn passes(&self) -> usize {
+        self.levels().len()
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The sync and wipe events of the thread in order, the tests check
+    /// that an encrypted file is synced before its origin is wiped.
+    pub static EVENTS: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Record a sync or wipe event in `EVENTS`.
+#[cfg(test)]
+fn record_event(event: String) {
+    EVENTS.with_borrow_mut(|events| events.push(event));
+}
+
+/// Record a sync or wipe event, the events are recorded in the tests only.
+#[cfg(not(test))]
+fn record_event(_event: String) {}
+
+/// Sync the file content and metadata to the disk, `path` is the file path
+/// used in the sync event.
+/// 
+/// ### Example:
+/// ```
+/// let file = std::fs::File::create("./dir/f.txt.x")?;
+/// filelib::sync_file(&file, "./dir/f.txt.x")?;
+/// ```
+pub fn sync_file(file: &std::fs::File, path: &str) -> std::io::Result<()> {
+    file.sync_all()?;
+    record_event(format!("sync {}", path));
+    Ok(())
+}
+
+/// The wipe state file extension, next to the wiped file.
+/// It has the number of the completed levels if the wipe failed.
+const WIPE_STATE_EXTENSION: &str = "xpm-wipe";
+
+/// The failed wipe level and why it failed.
+#[derive(PartialEq, Debug)]
+pub struct WipeFailure {
+    /// The failed level in `WIPE_LEVELS`, starts from 1.
+    pub level: usize,
+    pub reason: String,
+    pub code: errorlib::ExitErrorCode
+}
+
+/// The wipe estimate, what the wipe will write without writing it.
+#[derive(PartialEq, Debug)]
+pub struct DryWipe {
+    /// Number of passes over every file.
+    pub passes: usize,
+    /// Bytes written by one pass over all files.
+    pub bytes_per_pass: u64,
+    /// Bytes written by all passes.
+    pub total_bytes: u64
+}
+
+/// Parse a rate in bytes per second, with an optional `K`, `M`, or `G`
+/// (1024 based) unit and an optional `B/s` (e.g. `50MB/s`, `512K`, `1024`).
+/// Returns None for an invalid or a zero rate.
+/// 
+/// ### Example:
+/// ```
+/// assert_eq!(filelib::parse_rate("50MB/s"), Some(50 * 1024 * 1024));
+/// ```
+pub fn parse_rate(rate: &str) -> Option<u64> {
+    let rate = rate.trim().to_uppercase();
+    let rate = rate.strip_suffix("/S").unwrap_or(&rate);
+    let rate = rate.strip_suffix('B').unwrap_or(rate);
+    let (number, unit) = match rate.chars().last()? {
+        'K' => (&rate[..rate.len() - 1], 1024),
+        'M' => (&rate[..rate.len() - 1], 1024 * 1024),
+        'G' => (&rate[..rate.len() - 1], 1024 * 1024 * 1024),
+        _ => (rate, 1)
+    };
+    number.trim().parse::<u64>().ok()?
+        .checked_mul(unit)
+        .filter(|rate| *rate > 0)
+}
+
+/// Get the rate of `--limit-rate` (see `parse_rate`).
 /// 
 /// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// 
+/// ### Example:
+/// ```
+/// let rate = filelib::limit_rate(command.get_one::<String>("limit-rate"));
+/// ```
+pub fn limit_rate(flag: Option<&String>) -> Option<u64> {
+    let logger = loglib::Logger::new("limit-rate");
+    flag.map(|rate| parse_rate(rate).unwrap_or_else(|| logger.error(
+        &format!("invalid rate '{}', use a number of bytes per second (e.g. 50MB/s)!", rate),
+        errorlib::ExitErrorCode::Input
+    )))
+}
+
+/// Hold the average write rate near a limit, by sleeping
+/// after the writes that are ahead of the rate.
+pub struct Throttle {
+    /// Bytes per second, None for no limit.
+    rate: Option<u64>,
+    start: std::time::Instant,
+    bytes: u64
+}
+
+impl Throttle {
+    /// Creates a new `Throttle`, it starts counting now.
+    pub fn new(rate: Option<u64>) -> Throttle {
+        Throttle {
+            rate,
+            start: std::time::Instant::now(),
+            bytes: 0
+        }
+    }
+
+    /// Count the written bytes and sleep until the average rate
+    /// is NOT above the limit.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut throttle = filelib::Throttle::new(Some(1024 * 1024));
+    /// file.write_all(&buffer)?;
+    /// throttle.wrote(buffer.len() as u64);
+    /// ```
+    pub fn wrote(&mut self, bytes: u64) {
+        self.bytes += bytes;
+        if let Some(rate) = self.rate {
+            let expected = std::time::Duration::from_secs_f64(self.bytes as f64 / rate as f64);
+            if let Some(ahead) = expected.checked_sub(self.start.elapsed()) {
+                std::thread::sleep(ahead);
+            }
+        }
+    }
+}
+
+/// A failed file operation, with the exit code and the message the
+/// CLI commands exit with (see `FileLibError::exit`).
+#[derive(PartialEq, Debug)]
+pub struct FileLibError {
+    /// The logger name of the operation (e.g. "create-file").
+    pub scope: &'static str,
+    pub code: errorlib::ExitErrorCode,
+    pub message: String
+}
+
+impl FileLibError {
+    fn new(scope: &'static str, code: errorlib::ExitErrorCode, message: &str) -> FileLibError {
+        FileLibError {
+            scope,
+            code,
+            message: message.to_owned()
+        }
+    }
+
+    /// Log the error and exit with its code, for the CLI commands.
+    /// 
+    /// ### Example:
+    /// ```
+    /// filelib::create_file(path).unwrap_or_else(|err| err.exit());
+    /// ```
+    pub fn exit(&self) -> ! {
+        loglib::Logger::new(self.scope).error(&self.message, self.code)
+    }
+}
+
+impl std::fmt::Display for FileLibError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for FileLibError {}
+
+/// Create a file.
+/// 
+/// ### Errors:
 /// - `errorlib::ExitErrorCode::DirCreate`
 /// - `errorlib::ExitErrorCode::FileCreate`
 /// 
@@ -49,145 +414,236 @@ enum WipeType {
 /// ```
 /// filelib::create_file(
 ///     PathBuf::new().join("./dir/f.txt")
-/// );
+/// )?;
 /// ```
-pub fn create_file(path: PathBuf) {
+pub fn create_file(path: PathBuf) -> Result<(), FileLibError> {
     let logger = loglib::Logger::new("create-file");
     if path.exists() {
         logger.info(
             &format!("file found at '{}'", path.display())
         );
-        return;
+        return Ok(());
     }
     if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            if let Err(_) = std::fs::create_dir_all(parent) {
-                logger.error(
-                    &format!("can NOT create the directory at '{}'!", parent.display()),
-                    errorlib::ExitErrorCode::DirCreate
-                );
-            }
+        if !parent.exists() && std::fs::create_dir_all(parent).is_err() {
+            return Err(FileLibError::new(
+                "create-file",
+                errorlib::ExitErrorCode::DirCreate,
+                &format!("can NOT create the directory at '{}'!", parent.display())
+            ));
         }
     }
-    if let Err(_) = std::fs::File::create(&path) {
-        logger.error(
-            &format!("can NOT create the file at '{}'!", path.display()),
-                errorlib::ExitErrorCode::FileCreate
-            );
+    if std::fs::File::create(&path).is_err() {
+        return Err(FileLibError::new(
+            "create-file",
+            errorlib::ExitErrorCode::FileCreate,
+            &format!("can NOT create the file at '{}'!", path.display())
+        ));
     }
     logger.info(
         &format!("create file at '{}'", path.display())
     );
+    Ok(())
 }
 
-/// Delete a file.
+/// Delete a file, a missing file is NOT an error.
 /// 
-/// ### Exit:
+/// ### Errors:
 /// - `errorlib::ExitErrorCode::FileDelete`
 /// 
 /// ### Example:
 /// ```
 /// filelib::delete_file(
 ///     PathBuf::new().join("./dir/f.txt")
-/// );
+/// )?;
 /// ```
-pub fn delete_file(path: PathBuf) {
-    let logger = loglib::Logger::new("delete-file");
-    if path.exists() {
-        if let Err(_) = std::fs::remove_file(&path) {
-            logger.error(
-                &format!("can NOT delete the file at '{}'!", path.display()),
-                errorlib::ExitErrorCode::FileDelete
-            );
-        }
+pub fn delete_file(path: PathBuf) -> Result<(), FileLibError> {
+    if path.exists() && std::fs::remove_file(&path).is_err() {
+        return Err(FileLibError::new(
+            "delete-file",
+            errorlib::ExitErrorCode::FileDelete,
+            &format!("can NOT delete the file at '{}'!", path.display())
+        ));
     }
+    Ok(())
 }
 
-/// Wipe file using `WipeType` enum:
+/// Write one wipe pass over the first `len` bytes using `WipeType` enum:
 /// - BZero
 /// - BOne
 /// - Random
+/// - Pattern
 /// 
-/// ### Exit:
-/// - `errorlib::ExitErrorCode::FileSeek`
-/// - `errorlib::ExitErrorCode::FileWrite`
-/// - `errorlib::ExitErrorCode::FileFlush`
+/// Returns the written buffer (for `verify_pass`), or the error
+/// code and the reason if the pass failed.
 /// 
 /// ### Example:
 /// ```
-/// wipe_file("./dir/f.txt", WipeType::BOne);
+/// let mut file = OpenOptions::new().write(true).open("./dir/f.txt").unwrap();
+/// let len = file.metadata().unwrap().len();
+/// wipe_pass(&mut file, len, WipeType::BOne, &mut Throttle::new(None)).unwrap();
 /// ```
-fn wipe_file(path: String, wipe_type: WipeType) {
-    let logger = loglib::Logger::new("wipe-file");
-    let path = Path::new(&path);
-    if !path.exists() || !path.is_file() {
-        logger.error(
-            "file NOT found!", 
-            errorlib::ExitErrorCode::FileNotFound
-        );
+fn wipe_pass<W: Write + Seek>(
+    file: &mut W,
+    len: u64,
+    wipe_type: WipeType,
+    throttle: &mut Throttle
+) -> Result<Vec<u8>, (errorlib::ExitErrorCode, String)> {
+    if len == 0 {
+        // File len is 0, file is empty,
+        // we can not wipe an empty file.
+        return Ok(vec![]);
     }
-    if let Ok(mut file) = OpenOptions::new()
-        .write(true) 
-        .open(path) {
-        if let Ok(metadata) = file.metadata() {
-            let len = metadata.len();
-            if len == 0 {
-                // File len is 0, file is empty,
-                // we can not wipe an empty file.
-                return;
-            }
-            let mut size: usize = 64*1024; // 64KB.
-            size = if len < size as u64 {
-                // if the size of the file is less than 64KB.
-                len as usize
-            } else { size };
-            let mut pos= 0u64;
-            let mut rng = rand::rng();
-            // Make the data vec based on the wipe type.
-            let data = if wipe_type == WipeType::Random {
-                // Make a static rng for all buffers.
-                // When it is a static rng the speed is up!
-                let mut data = vec![0u8; size];
-                rng.fill(&mut data[..]);
-                data
-            } else if wipe_type == WipeType::BOne {
-                vec![1u8; size]
-            } else {
-                vec![0u8; size]
-            };
-            loop {
-                if pos + size as u64 > len && pos < len {
-                    // if len = 65KB and pos = 64KB we have 1KB to be
-                    // written. to write this 1KB: len - pos = 1KB 
-                    // We will use this as the size of the buffer.
-                    size = (len - pos) as usize;
-                } 
-                if pos > len { break; }
-                if let Err(_) = file.seek(SeekFrom::Start(pos)) {
-                    logger.error(
-                        "can NOT seek the file!", 
-                        errorlib::ExitErrorCode::FileSeek
-                    );
-                }
-                if let Err(_) = file.write_all(&data) {
-                    logger.error(
-                        "can NOT write to the file!", 
-                        errorlib::ExitErrorCode::FileWrite
-                    );
-                }
-                pos += size as u64;
-            }
-            if let Err(_) = file.flush() {
-                logger.error(
-                    "can NOT flush the file to the disk!", 
-                    errorlib::ExitErrorCode::FileFlush
-                );
-            }
+    let mut size: usize = 64*1024; // 64KB.
+    if let WipeType::Pattern(_) = wipe_type {
+        // Every buffer starts with the pattern first byte.
+        size -= size % 3;
+    }
+    size = if len < size as u64 {
+        // if the size of the file is less than 64KB.
+        len as usize
+    } else { size };
+    let mut pos = 0u64;
+    let mut rng = rand::rng();
+    // Make the data vec based on the wipe type.
+    let data = if wipe_type == WipeType::Random {
+        // Make a static rng for all buffers.
+        // When it is a static rng the speed is up!
+        let mut data = vec![0u8; size];
+        rng.fill(&mut data[..]);
+        data
+    } else if wipe_type == WipeType::BOne {
+        vec![1u8; size]
+    } else if let WipeType::Pattern(pattern) = wipe_type {
+        pattern.iter().copied().cycle().take(size).collect()
+    } else {
+        vec![0u8; size]
+    };
+    while pos < len {
+        // if len = 65KB and pos = 64KB we have 1KB to be
+        // written, the last buffer is cut to len - pos = 1KB.
+        let size = size.min((len - pos) as usize);
+        if let Err(err) = file.seek(SeekFrom::Start(pos)) {
+            return Err((
+                errorlib::ExitErrorCode::FileSeek,
+                format!("can NOT seek the file: {}", err)
+            ));
+        }
+        if let Err(err) = file.write_all(&data[..size]) {
+            return Err((
+                errorlib::ExitErrorCode::FileWrite,
+                format!("can NOT write to the file: {}", err)
+            ));
+        }
+        throttle.wrote(size as u64);
+        pos += size as u64;
+    }
+    if let Err(err) = file.flush() {
+        return Err((
+            errorlib::ExitErrorCode::FileFlush,
+            format!("can NOT flush the file to the disk: {}", err)
+        ));
+    }
+    Ok(data)
+}
+
+/// Read back the first `len` bytes after a wipe pass in `data` sized
+/// chunks, every chunk must match the buffer written by `wipe_pass`.
+/// Returns the error code and the reason if the read fails or a byte
+/// does NOT match.
+/// 
+/// ### Example:
+/// ```
+/// let data = wipe_pass(&mut file, len, WipeType::Random, &mut Throttle::new(None)).unwrap();
+/// verify_pass(&mut file, len, &data).unwrap();
+/// ```
+fn verify_pass<R: Read + Seek>(
+    file: &mut R,
+    len: u64,
+    data: &[u8]
+) -> Result<(), (errorlib::ExitErrorCode, String)> {
+    let mut chunk = vec![0u8; data.len()];
+    let mut pos = 0u64;
+    while pos < len {
+        let size = data.len().min((len - pos) as usize);
+        if let Err(err) = file.seek(SeekFrom::Start(pos)) {
+            return Err((
+                errorlib::ExitErrorCode::FileSeek,
+                format!("can NOT seek the file: {}", err)
+            ));
+        }
+        if let Err(err) = file.read_exact(&mut chunk[..size]) {
+            return Err((
+                errorlib::ExitErrorCode::FileRead,
+                format!("can NOT read back the file: {}", err)
+            ));
+        }
+        if let Some(index) = (0..size).find(|index| chunk[*index] != data[*index]) {
+            return Err((
+                errorlib::ExitErrorCode::WipeVerifyFailed,
+                format!("the data read back at byte {} does NOT match the written data", pos + index as u64)
+            ));
+        }
+        pos += size as u64;
+    }
+    Ok(())
+}
+
+/// Write the levels passes, starting after the `completed` levels, and
+/// read back every pass with `verify`. Returns the failed level if a
+/// pass failed.
+/// 
+/// ### Example:
+/// ```
+/// // Resume after the first two levels.
+/// if let Err(failure) = wipe_levels(&mut file, len, &WIPE_LEVELS, 2, None, false) {
+///     println!("level {} failed: {}", failure.level, failure.reason);
+/// }
+/// ```
+fn wipe_levels<W: Read + Write + Seek>(
+    file: &mut W,
+    len: u64,
+    levels: &[WipeType],
+    completed: usize,
+    rate: Option<u64>,
+    verify: bool
+) -> Result<(), WipeFailure> {
+    let mut throttle = Throttle::new(rate);
+    for (index, wipe_type) in levels.iter().enumerate().skip(completed) {
+        let result = wipe_pass(file, len, *wipe_type, &mut throttle)
+            .and_then(|data| if verify { verify_pass(file, len, &data) } else { Ok(()) });
+        if let Err((code, reason)) = result {
+            return Err(WipeFailure {
+                level: index + 1,
+                reason,
+                code
+            });
         }
     }
+    Ok(())
+}
+
+/// Get the wipe state path of a file, `<path>.xpm-wipe`.
+fn wipe_state_path(path: &str) -> PathBuf {
+    PathBuf::new().join(format!("{}.{}", path, WIPE_STATE_EXTENSION))
+}
+
+/// Get the number of the completed wipe levels of a failed
+/// wipe, `0` if the wipe never failed.
+/// 
+/// ### Example:
+/// ```
+/// let completed = filelib::wipe_completed_levels("./dir/f.txt");
+/// println!("{} levels completed", completed);
+/// ```
+pub fn wipe_completed_levels(path: &str) -> usize {
+    std::fs::read_to_string(wipe_state_path(path))
+        .ok()
+        .and_then(|state| state.trim().parse::<usize>().ok())
+        .unwrap_or(0)
 }
 
-/// Wipe and delete the file using levels:
+/// Wipe and delete the file using levels (`WIPE_LEVELS`):
 /// - Level 1: `BOne` as 1s.
 /// - Level 2: `Random` as random data.
 /// - Level 3: `Random` as random data.
@@ -195,25 +651,286 @@ fn wipe_file(path: String, wipe_type: WipeType) {
 /// 
 /// The `Random` is a static data for the wiped file, 
 /// The data generated by the `rand::rng()`.
+/// The config `[wipe]` can change the method, the passes and the verification.
 /// 
 /// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+/// 
+/// ### Errors:
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileSeek`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileFlush`
+/// - `errorlib::ExitErrorCode::FileDelete`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::WipeVerifyFailed`
+/// 
+/// ### Example:
+/// ```
+/// filelib::wipe_delete("./dir/f.txt")?;
+/// ```
+pub fn wipe_delete(path: String) -> Result<(), FileLibError> {
+    let settings = WipeSettings::resolve(&configlib::Config::load(), None, None);
+    wipe_delete_settings(path, &settings, false)?;
+    Ok(())
+}
+
+/// Wipe and delete the file with a number of passes, cycling through
+/// `WIPE_LEVELS` and ending with 0s. The config `wipe.verify` is kept,
+/// but the config method and passes are NOT used. `0` passes deletes
+/// the file without overwriting it, and `4` passes are the `WIPE_LEVELS`.
+/// 
+/// ### Errors:
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::FileSeek`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileFlush`
+/// - `errorlib::ExitErrorCode::FileDelete`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::WipeVerifyFailed`
+/// 
+/// ### Example:
+/// ```
+/// // A quick wipe, one pass of 0s.
+/// filelib::wipe_delete_with("./dir/f.txt".to_string(), 1)?;
+/// ```
+// For the callers that pick the passes, the commands use the config `[wipe]`.
+#[allow(dead_code)]
+pub fn wipe_delete_with(path: String, passes: u8) -> Result<WipeReport, FileLibError> {
+    wipe_delete_settings(
+        path,
+        &WipeSettings {
+            passes: Some(passes),
+            verify: configlib::Config::load().get_bool("wipe.verify").unwrap_or(false),
+            ..WipeSettings::default()
+        },
+        false
+    )
+}
+
+/// The secure-delete record of a wiped file, logged as an evidence of
+/// the erasure (the log has the timestamp), without the file contents.
+#[derive(PartialEq, Debug)]
+pub struct WipeReport {
+    pub path: String,
+    /// The file size in bytes.
+    pub size: u64,
+    pub standard: WipeStandard,
+    pub passes: usize,
+    /// None if the wiped data was NOT read back.
+    pub verified: Option<bool>
+}
+
+impl WipeReport {
+    /// The log message of the report.
+    /// 
+    /// ### Example:
+    /// ```
+    /// // "wipe report: '/dir/f.txt', 1024 bytes, method simple, 4 passes, verification skipped"
+    /// dblib::log::register(&report.message(), dblib::log::LogAction::Wipe, log_db_path);
+    /// ```
+    pub fn message(&self) -> String {
+        let verification = match self.verified {
+            Some(true) => "passed",
+            Some(false) => "failed",
+            None => "skipped"
+        };
+        format!(
+            "wipe report: '{}', {} bytes, method {}, {} passes, verification {}",
+            self.path,
+            self.size,
+            self.standard.name(),
+            self.passes,
+            verification
+        )
+    }
+}
+
+/// Wipe and delete the file using the wipe settings. If a level failed,
+/// the completed levels are saved in `<path>.xpm-wipe`, and the wipe
+/// can be resumed from the failed level with `resume`. Returns the
+/// wipe report of the file.
+/// 
+/// ### Errors:
 /// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileOpen`
 /// - `errorlib::ExitErrorCode::FileSeek`
 /// - `errorlib::ExitErrorCode::FileWrite`
 /// - `errorlib::ExitErrorCode::FileFlush`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::WipeVerifyFailed`
+/// - `errorlib::ExitErrorCode::FileDelete`
+/// 
+/// ### Example:
+/// ```
+/// // The last wipe of the file failed.
+/// let report = filelib::wipe_delete_settings("./dir/f.txt", &filelib::WipeSettings::default(), true)?;
+/// println!("{}", report.message());
+/// ```
+pub fn wipe_delete_settings(path: String, settings: &WipeSettings, resume: bool) -> Result<WipeReport, FileLibError> {
+    let file_path = Path::new(&path);
+    if !file_path.exists() || !file_path.is_file() {
+        return Err(FileLibError::new(
+            "wipe-file",
+            errorlib::ExitErrorCode::FileNotFound,
+            "file NOT found!"
+        ));
+    }
+    record_event(format!("wipe {}", path));
+    let levels = settings.levels();
+    let completed = if resume { wipe_completed_levels(&path).min(levels.len()) } else { 0 };
+    let mut file = OpenOptions::new()
+        .read(settings.verify)
+        .write(true)
+        .open(file_path)
+        .map_err(|_| FileLibError::new(
+            "wipe-file",
+            errorlib::ExitErrorCode::FileOpen,
+            &format!("can NOT open '{}'!", path)
+        ))?;
+    let len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    if let Err(failure) = wipe_levels(&mut file, len, &levels, completed, settings.rate, settings.verify) {
+        // Save the completed levels, the next wipe can resume from the failed one.
+        let _ = std::fs::write(wipe_state_path(&path), (failure.level - 1).to_string());
+        return Err(FileLibError::new(
+            "wipe-file",
+            failure.code,
+            &format!(
+                "wipe level {}/{} ({:?}) of '{}' failed, {}! use --resume to continue from level {}.",
+                failure.level,
+                levels.len(),
+                levels[failure.level - 1],
+                path,
+                failure.reason,
+                failure.level
+            )
+        ));
+    }
+    drop(file);
+    let report = WipeReport {
+        path: std::fs::canonicalize(&path)
+            .map(|path| path.display().to_string())
+            .unwrap_or(path.clone()),
+        size: len,
+        standard: settings.standard,
+        passes: levels.len(),
+        verified: if settings.verify { Some(true) } else { None }
+    };
+    delete_file(wipe_state_path(&path))?;
+    delete_file(PathBuf::new().join(path))?;
+    Ok(report)
+}
+
+/// Wipe and delete every file in the directory with the wipe settings
+/// using the threads, then remove the directory. With a write rate
+/// limit the files are wiped one by one, so the limit holds for the
+/// whole directory. The symlinks are removed without following them,
+/// and the directories are removed only after every file is deleted.
+/// Returns the wipe reports of the files.
+/// 
+/// ### Errors:
+/// - The `dir_links_tree` and the `wipe_delete_settings` errors.
 /// - `errorlib::ExitErrorCode::FileDelete`
+/// - `errorlib::ExitErrorCode::DirDelete`
+/// 
+/// ### Example:
+/// ```
+/// let settings = filelib::WipeSettings::resolve(&configlib::Config::load(), None, None);
+/// let reports = filelib::wipe_delete_dir(PathBuf::new().join("./exports"), &settings)?;
+/// ```
+pub fn wipe_delete_dir(folder: PathBuf, settings: &WipeSettings) -> Result<Vec<WipeReport>, FileLibError> {
+    let mut files_paths: Vec<PathBuf> = Vec::new();
+    let mut links: Vec<PathBuf> = Vec::new();
+    dir_links_tree(folder.clone(), &mut files_paths, &mut links)?;
+    for link in links {
+        std::fs::remove_file(&link).map_err(|_| FileLibError::new(
+            "wipe-dir",
+            errorlib::ExitErrorCode::FileDelete,
+            &format!("can NOT delete the link at '{}'!", link.display())
+        ))?;
+    }
+    let wipe = |paths: &Vec<PathBuf>| paths
+        .iter()
+        .map(|path| wipe_delete_settings(path.display().to_string(), settings, false))
+        .collect::<Result<Vec<WipeReport>, FileLibError>>();
+    let reports: Vec<Vec<WipeReport>> = if settings.rate.is_some() {
+        vec![wipe(&files_paths)?]
+    } else {
+        utilities::distribute_paths(files_paths.clone())
+            .par_iter()
+            .map(wipe)
+            .collect::<Result<Vec<Vec<WipeReport>>, FileLibError>>()?
+    };
+    if let Some(file) = files_paths.iter().find(|file| file.exists()) {
+        return Err(FileLibError::new(
+            "wipe-dir",
+            errorlib::ExitErrorCode::FileDelete,
+            &format!("can NOT delete the file at '{}'!", file.display())
+        ));
+    }
+    remove_empty_dirs(&folder)?;
+    Ok(reports.into_iter().flatten().collect())
+}
+
+/// Remove the empty directories tree, from the deepest one.
+fn remove_empty_dirs(folder_path: &Path) -> Result<(), FileLibError> {
+    if let Ok(paths) = folder_path.read_dir() {
+        for entry in paths.flatten() {
+            if entry.file_type().map(|file_type| file_type.is_dir()).unwrap_or(false) {
+                remove_empty_dirs(&entry.path())?;
+            }
+        }
+    }
+    std::fs::remove_dir(folder_path).map_err(|_| FileLibError::new(
+        "wipe-dir",
+        errorlib::ExitErrorCode::DirDelete,
+        &format!("can NOT delete the directory at '{}'!", folder_path.display())
+    ))
+}
+
+/// Estimate the passes and the bytes that `wipe_delete` will write
+/// for the files, from the `wipe_preview` sizes, without writing.
+/// 
+/// ### Example:
+/// ```
+/// let files = vec![PathBuf::new().join("./dir/f.txt")];
+/// let dry = filelib::dry_wipe(&filelib::wipe_preview(&files), &filelib::WipeSettings::default());
+/// println!("{} passes, {} bytes", dry.passes, dry.total_bytes);
+/// ```
+pub fn dry_wipe(files: &[(PathBuf, u64)], settings: &WipeSettings) -> DryWipe {
+    let passes = settings.passes();
+    let bytes_per_pass: u64 = files.iter().map(|(_, size)| size).sum();
+    DryWipe {
+        passes,
+        bytes_per_pass,
+        total_bytes: bytes_per_pass * passes as u64
+    }
+}
+
+/// Get the files that will be wiped with their sizes in bytes,
+/// uses to show the user what will be wiped before the confirmation.
+/// Files that can NOT be read will have size 0.
 /// 
 /// ### Example:
 /// ```
-/// filelib::wipe_delete("./dir/f.txt");
+/// let mut files_tree: Vec<PathBuf> = Vec::new();
+/// filelib::dir_files_tree(PathBuf::new().join("./dir"), &mut files_tree);
+/// for (path, size) in filelib::wipe_preview(&files_tree) {
+///     println!("{}: {} bytes", path.display(), size);
+/// }
 /// ```
-pub fn wipe_delete(path: String) {
-    // We will use 4 levels wiping:
-    wipe_file(path.clone(), WipeType::BOne);   // L1: with 1s.
-    wipe_file(path.clone(), WipeType::Random); // L2: with static random data.
-    wipe_file(path.clone(), WipeType::Random); // L3: with static random data.
-    wipe_file(path.clone(), WipeType::BZero);  // L4: with 0s.
-    delete_file(PathBuf::new().join(path));
+pub fn wipe_preview(files_paths: &[PathBuf]) -> Vec<(PathBuf, u64)> {
+    files_paths
+        .iter()
+        .map(|path| (
+            path.clone(),
+            std::fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0)
+        ))
+        .collect()
 }
 
 /// Get file state. It will return `FileState` enum:
@@ -233,12 +950,23 @@ pub fn wipe_delete(path: String) {
 /// }
 /// ```
 pub fn get_file_state(path: String) -> FileState {
-    let path = std::path::Path::new(&path);
+    get_path_state(Path::new(&path))
+}
+
+/// Get the file state like `get_file_state`, for a path that can be
+/// NOT UTF-8 (e.g. a Linux file name), such an extension is NOT `.x`.
+/// 
+/// ### Example:
+/// ```
+/// let file_state = filelib::get_path_state(Path::new("./dir/t.txt.x"));
+/// if file_state == filelib::FileState::Encrypted {
+///     println!("file is encrypted.");
+/// }
+/// ```
+pub fn get_path_state(path: &Path) -> FileState {
     let mut _state: FileState;
-    if path.extension()
-        .unwrap_or(&OsStr::new(""))
-        .to_str()
-        .unwrap() == XPM_EXTENSION {
+    // Only the extension is checked, legacy encrypted files have NO magic header.
+    if path.extension() == Some(OsStr::new(XPM_EXTENSION)) {
             _state = FileState::Encrypted;
     } else {
         _state = FileState::Decrypted;
@@ -249,6 +977,21 @@ pub fn get_file_state(path: String) -> FileState {
     _state
 }
 
+/// Check if the path is a regular file, not a FIFO, socket,
+/// or device that can block the reading or never end.
+/// 
+/// ### Example:
+/// ```
+/// if !filelib::is_regular_file(Path::new("/dev/zero")) {
+///     println!("can NOT encrypt it!");
+/// }
+/// ```
+pub fn is_regular_file(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .map(|metadata| metadata.file_type().is_file())
+        .unwrap_or(false)
+}
+
 /// Make encrypted path from decrypted path.
 /// 
 /// ### Example:
@@ -260,25 +1003,30 @@ pub fn make_encrypt_path(path: String) -> String {
     format!("{}.{}", path, XPM_EXTENSION)
 }
 
-/// Make decrypted path from encrypted path.
+/// Make decrypted path from encrypted path, only the `.x` extension
+/// is removed. A path without it is returned as it is.
 /// 
 /// ### Example:
 /// ```
 /// let d_path = filelib::make_decrypt_path("./dir/file.txt.x");
 /// assert_eq!(d_path, "./dir/file.txt");
+/// let d_path = filelib::make_decrypt_path("./dir.x/file");
+/// assert_eq!(d_path, "./dir.x/file");
 /// ```
-pub fn make_decrypt_path(path: String) -> String{
-    let path_split = path
-        .split(".")
-        .collect::<Vec<&str>>();
-
-    path_split[..path_split.len()-1]
-        .join(".")
+pub fn make_decrypt_path(path: String) -> String {
+    // The file name must have the extension, e.g. NOT "./dir/.x".
+    let is_encrypted = Path::new(&path)
+        .extension()
+        .is_some_and(|extension| extension == XPM_EXTENSION);
+    match path.strip_suffix(&format!(".{}", XPM_EXTENSION)) {
+        Some(de_path) if is_encrypted => de_path.to_owned(),
+        _ => path
+    }
 }
 
 /// Get the files tree in a directory.
 /// 
-/// ### Exit:
+/// ### Errors:
 /// - `errorlib::ExitErrorCode::DirNotFound`
 /// - `errorlib::ExitErrorCode::DirUnsupported`
 /// - `errorlib::ExitErrorCode::CanNotGetFileOrDirType`
@@ -291,7 +1039,7 @@ pub fn make_decrypt_path(path: String) -> String{
 /// filelib::dir_files_tree(
 ///     PathBuf::new().join("./dir"),
 ///     files_tree // it will add the paths to it.
-/// );
+/// )?;
 /// assert_eq!(
 ///     files_tree,
 ///     vec![
@@ -301,131 +1049,520 @@ pub fn make_decrypt_path(path: String) -> String{
 ///     ]
 /// );
 /// ```
-pub fn dir_files_tree(folder_path: PathBuf, files_paths: &mut Vec<PathBuf> ){
-    let logger = loglib::Logger::new("dir-files-tree");
-    if !folder_path.exists() {
-        logger.error(
-            "can NOT find the directory!", 
-            errorlib::ExitErrorCode::DirNotFound
-        );
-    }
-    if let Ok(paths) = folder_path.read_dir() {
-        for p in paths {
-            if let Ok(entry) = p {
-                if let Ok(file_type) = entry.file_type() {
-                    let entry_path = entry.path();
-                    if file_type.is_file() {
-                        files_paths.push(entry_path);
-                    } else if file_type.is_dir() {
-                        dir_files_tree(entry_path, files_paths);
-                    } else {
-                        logger.error(
-                            &format!("unsupported directory at '{}'!", entry_path.display()),
-                            errorlib::ExitErrorCode::DirUnsupported
-                        )
-                    }
-                } else {
-                    logger.error(
-                        "can NOT get the file/folder type!", 
-                        errorlib::ExitErrorCode::CanNotGetFileOrDirType
-                    )
-                }
-            } else {
-                logger.error(
-                    "can NOT get the folder entry!", 
-                    errorlib::ExitErrorCode::CanNotGetDirData
-                )
-            }
-        }
-    } else {
-        logger.error(
-            "can NOT get the folder data!", 
-            errorlib::ExitErrorCode::CanNotGetDirData
-        )
-    }
+pub fn dir_files_tree(folder_path: PathBuf, files_paths: &mut Vec<PathBuf>) -> Result<(), FileLibError> {
+    dir_files_tree_depth(folder_path, files_paths, usize::MAX)
 }
 
-/// Copy file using buffers.
+/// Get the files tree in a directory like `dir_files_tree`, but refuse the
+/// directories deeper than `max_depth` levels (the directory is level 0).
 /// 
-/// ### Exit: 
-/// - `errorlib::ExitErrorCode::FileNotFound`
+/// ### Errors:
 /// - `errorlib::ExitErrorCode::DirNotFound`
+/// - `errorlib::ExitErrorCode::DirUnsupported`
+/// - `errorlib::ExitErrorCode::CanNotGetFileOrDirType`
+/// - `errorlib::ExitErrorCode::CanNotGetDirData`
 /// 
 /// ### Example:
 /// ```
-/// filelib::copy("from.txt", "to.txt");
+/// let mut files_tree: Vec<PathBuf> = Vec::new();
+/// filelib::dir_files_tree_depth(PathBuf::new().join("./dir"), &mut files_tree, 64)?;
 /// ```
-pub fn copy(file: String, to_file: String) {
-    let logger = loglib::Logger::new("copy-file");
-    let file_path = PathBuf::new().join(file);
-    if !file_path.exists() || !file_path.is_file() {
-        logger.error(
-            "file NOT found!",
-            errorlib::ExitErrorCode::FileNotFound
-        )
-    }
-    let file_stream = std::fs::File::open(file_path).unwrap();
-    if let Ok(to_file)= std::fs::File::create(to_file) {
-        let mut reader = BufReader::new(file_stream);
-        let mut writer = BufWriter::new(to_file);
-        let mut buffer = vec![0; 64 * 1024]; // 64KB
-        loop {
-            let bytes_read = reader.read(&mut buffer).unwrap();
-            if bytes_read == 0 {
-                break;
-            }
-            writer.write_all(&buffer[..bytes_read]).unwrap();
-        }
-        writer.flush().unwrap();
-    } else {
-        logger.error(
-            "directory NOT found!",
-            errorlib::ExitErrorCode::DirNotFound
-        )
-    }
+pub fn dir_files_tree_depth(folder_path: PathBuf, files_paths: &mut Vec<PathBuf>, max_depth: usize) -> Result<(), FileLibError> {
+    dir_tree(folder_path, files_paths, None, &|_| true, max_depth)
 }
 
-/// From a json file to `HashMap<String, String>`, reading single key-value
-/// json object.
+/// Get the files tree and the symlinks in a directory, the symlinks
+/// are NOT followed (see `dir_files_tree`).
 /// 
-/// ### Exit:
-/// - `errorlib::ExitErrorCode::InvalidJson`
-/// - `errorlib::ExitErrorCode::CanNotGetJsonObject`
+/// ### Errors:
+/// - `errorlib::ExitErrorCode::DirNotFound`
+/// - `errorlib::ExitErrorCode::DirUnsupported`
+/// - `errorlib::ExitErrorCode::CanNotGetFileOrDirType`
+/// - `errorlib::ExitErrorCode::CanNotGetDirData`
+/// 
+/// ### Example:
+/// ```
+/// let mut files_tree: Vec<PathBuf> = Vec::new();
+/// let mut links: Vec<PathBuf> = Vec::new();
+/// filelib::dir_links_tree(PathBuf::new().join("./dir"), &mut files_tree, &mut links)?;
+/// ```
+pub fn dir_links_tree(folder_path: PathBuf, files_paths: &mut Vec<PathBuf>, links: &mut Vec<PathBuf>) -> Result<(), FileLibError> {
+    dir_tree(folder_path, files_paths, Some(links), &|_| true, usize::MAX)
+}
+
+/// Get the files tree in a directory like `dir_files_tree`, only the
+/// files and the directories that the filter accepts are walked, so a
+/// skipped directory (e.g. `node_modules`) is never read.
+/// 
+/// ### Errors:
+/// - `errorlib::ExitErrorCode::DirNotFound`
+/// - `errorlib::ExitErrorCode::DirUnsupported`
+/// - `errorlib::ExitErrorCode::CanNotGetFileOrDirType`
+/// - `errorlib::ExitErrorCode::CanNotGetDirData`
+/// 
+/// ### Example:
+/// ```
+/// let mut files_tree: Vec<PathBuf> = Vec::new();
+/// filelib::dir_files_tree_filter(
+///     PathBuf::new().join("./dir"),
+///     &mut files_tree,
+///     &|path| !path.ends_with("node_modules")
+/// )?;
+/// ```
+pub fn dir_files_tree_filter(folder_path: PathBuf, files_paths: &mut Vec<PathBuf>, filter: &dyn Fn(&Path) -> bool) -> Result<(), FileLibError> {
+    dir_tree(folder_path, files_paths, None, filter, usize::MAX)
+}
+
+/// Read the entries of a folder with their types, the types do NOT follow
+/// the symlinks.
+fn dir_entries(folder_path: &Path) -> Result<Vec<(PathBuf, std::fs::FileType)>, FileLibError> {
+    let paths = folder_path.read_dir().map_err(|_| FileLibError::new(
+        "dir-files-tree",
+        errorlib::ExitErrorCode::CanNotGetDirData,
+        "can NOT get the folder data!"
+    ))?;
+    let mut entries = Vec::new();
+    for p in paths {
+        let entry = p.map_err(|_| FileLibError::new(
+            "dir-files-tree",
+            errorlib::ExitErrorCode::CanNotGetDirData,
+            "can NOT get the folder entry!"
+        ))?;
+        let file_type = entry.file_type().map_err(|_| FileLibError::new(
+            "dir-files-tree",
+            errorlib::ExitErrorCode::CanNotGetFileOrDirType,
+            "can NOT get the file/folder type!"
+        ))?;
+        entries.push((entry.path(), file_type));
+    }
+    Ok(entries)
+}
+
+/// The directory walk, the symlinks are unsupported without `links`, the
+/// entries that the filter refuses are skipped, and a directory deeper than
+/// `max_depth` levels is refused (the directory is level 0). It walks with a
+/// stack of the open folders instead of the recursion, so a deep tree can NOT
+/// overflow the stack, and the files keep the depth-first order.
+fn dir_tree(
+    folder_path: PathBuf,
+    files_paths: &mut Vec<PathBuf>,
+    mut links: Option<&mut Vec<PathBuf>>,
+    filter: &dyn Fn(&Path) -> bool,
+    max_depth: usize
+) -> Result<(), FileLibError> {
+    if !folder_path.exists() {
+        return Err(FileLibError::new(
+            "dir-files-tree",
+            errorlib::ExitErrorCode::DirNotFound,
+            "can NOT find the directory!"
+        ));
+    }
+    let mut folders = vec![(dir_entries(&folder_path)?.into_iter(), 0)];
+    while let Some((entries, depth)) = folders.last_mut() {
+        let depth = *depth;
+        let Some((entry_path, file_type)) = entries.next() else {
+            folders.pop();
+            continue;
+        };
+        if !filter(&entry_path) {
+            continue;
+        }
+        if file_type.is_file() {
+            files_paths.push(entry_path);
+        } else if file_type.is_dir() {
+            if depth >= max_depth {
+                return Err(FileLibError::new(
+                    "dir-files-tree",
+                    errorlib::ExitErrorCode::DirUnsupported,
+                    &format!("the directory is deeper than {} levels at '{}'!", max_depth, entry_path.display())
+                ));
+            }
+            folders.push((dir_entries(&entry_path)?.into_iter(), depth + 1));
+        } else if let (true, Some(links)) = (file_type.is_symlink(), links.as_deref_mut()) {
+            links.push(entry_path);
+        } else {
+            return Err(FileLibError::new(
+                "dir-files-tree",
+                errorlib::ExitErrorCode::DirUnsupported,
+                &format!("unsupported directory at '{}'!", entry_path.display())
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Keep the files that match any of the glob patterns, the patterns
+/// match the path relative to the directory (e.g. `**/*.pdf`).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// 
+/// ### Example:
+/// ```
+/// let files = filelib::filter_glob(
+///     Path::new("./dir"),
+///     vec![PathBuf::new().join("./dir/a.pdf"), PathBuf::new().join("./dir/b.txt")],
+///     &vec!["**/*.pdf".to_string()]
+/// );
+/// assert_eq!(files, vec![PathBuf::new().join("./dir/a.pdf")]);
+/// ```
+pub fn filter_glob(folder_path: &Path, files_paths: Vec<PathBuf>, patterns: &[String]) -> Vec<PathBuf> {
+    let is_match = glob_matcher(folder_path, patterns);
+    files_paths
+        .into_iter()
+        .filter(|file| is_match(file))
+        .collect()
+}
+
+/// Make a matcher of the glob patterns, it checks if a path in the
+/// directory matches any of them, relative to the directory (e.g.
+/// `**/node_modules`), for `dir_files_tree_filter`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// 
+/// ### Example:
+/// ```
+/// let is_excluded = filelib::glob_matcher(Path::new("./dir"), &vec!["**/node_modules".to_string()]);
+/// assert!(is_excluded(Path::new("./dir/web/node_modules")));
+/// ```
+pub fn glob_matcher<'a>(folder_path: &'a Path, patterns: &[String]) -> impl Fn(&Path) -> bool + 'a {
+    let logger = loglib::Logger::new("filter-glob");
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).unwrap_or_else(|_| logger.error(
+            &format!("invalid glob pattern '{}'!", pattern),
+            errorlib::ExitErrorCode::Input
+        )));
+    }
+    let glob_set = builder.build().unwrap_or_else(|_| logger.error(
+        "invalid glob patterns!",
+        errorlib::ExitErrorCode::Input
+    ));
+    move |path: &Path| glob_set.is_match(path.strip_prefix(folder_path).unwrap_or(path))
+}
+
+/// Write the contents to a temp file next to the path (`<path>.xpm-tmp`),
+/// sync it, and rename it over the path. The rename is atomic on the same
+/// file system, so the path has the old or the new contents, never a part
+/// of them. The temp file is deleted if any step fails.
+/// 
+/// ### Errors:
+/// - `errorlib::ExitErrorCode::FileCreate`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileFlush`
+/// 
+/// ### Example:
+/// ```
+/// filelib::write_atomic(PathBuf::new().join("./dir/f.txt"), b"data")?;
+/// ```
+// For the small database files, the encrypted database uses `encrypt_snapshot`.
+#[allow(dead_code)]
+pub fn write_atomic(path: PathBuf, contents: &[u8]) -> Result<(), FileLibError> {
+    let mut temp_path = path.clone().into_os_string();
+    temp_path.push(".xpm-tmp");
+    let temp_path = PathBuf::from(temp_path);
+    let error = |code, message: &str| {
+        let _ = std::fs::remove_file(&temp_path);
+        FileLibError::new("write-atomic", code, message)
+    };
+    let mut file = std::fs::File::create(&temp_path).map_err(|_| error(
+        errorlib::ExitErrorCode::FileCreate,
+        &format!("can NOT create '{}'!", temp_path.display())
+    ))?;
+    file.write_all(contents).map_err(|_| error(
+        errorlib::ExitErrorCode::FileWrite,
+        &format!("can NOT write to '{}'!", temp_path.display())
+    ))?;
+    file.sync_all().map_err(|_| error(
+        errorlib::ExitErrorCode::FileFlush,
+        &format!("can NOT sync '{}' to the disk!", temp_path.display())
+    ))?;
+    std::fs::rename(&temp_path, &path).map_err(|_| error(
+        errorlib::ExitErrorCode::FileWrite,
+        &format!("can NOT replace '{}'!", path.display())
+    ))
+}
+
+/// Copy file using buffers, the copy keeps the file permissions
+/// (the Unix mode, or the Windows read-only attribute) and the
+/// modification time, see `copy_with` to NOT keep them.
+/// 
+/// ### Errors: 
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::DirNotFound`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileFlush`
+/// 
+/// ### Example:
+/// ```
+/// filelib::copy("from.txt", "to.txt")?;
+/// ```
+pub fn copy(file: String, to_file: String) -> Result<(), FileLibError> {
+    copy_limited(file, to_file, None)
+}
+
+/// Copy a file with a max write rate in bytes per second (see `copy`).
+/// 
+/// ### Errors: 
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::DirNotFound`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileFlush`
+/// 
+/// ### Example:
+/// ```
+/// // 50MB/s
+/// filelib::copy_limited("from.txt", "to.txt", Some(50 * 1024 * 1024))?;
+/// ```
+pub fn copy_limited(file: String, to_file: String, rate: Option<u64>) -> Result<(), FileLibError> {
+    copy_with(file, to_file, rate, true)
+}
+
+/// Copy a file with a max write rate in bytes per second like `copy_limited`,
+/// the file permissions and modification time are kept only if
+/// `keep_metadata` is true.
+/// 
+/// ### Errors: 
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileOpen`
+/// - `errorlib::ExitErrorCode::DirNotFound`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::FileWrite`
+/// - `errorlib::ExitErrorCode::FileFlush`
+/// 
+/// ### Example:
+/// ```
+/// // A fresh file, with the default permissions.
+/// filelib::copy_with("from.txt", "to.txt", None, false)?;
+/// ```
+pub fn copy_with(file: String, to_file: String, rate: Option<u64>, keep_metadata: bool) -> Result<(), FileLibError> {
+    let file_path = PathBuf::new().join(&file);
+    if !file_path.exists() || !file_path.is_file() {
+        return Err(FileLibError::new(
+            "copy-file",
+            errorlib::ExitErrorCode::FileNotFound,
+            "file NOT found!"
+        ));
+    }
+    let file_stream = std::fs::File::open(file_path).map_err(|_| FileLibError::new(
+        "copy-file",
+        errorlib::ExitErrorCode::FileOpen,
+        &format!("can NOT open '{}'!", file)
+    ))?;
+    let to_file = std::fs::File::create(to_file).map_err(|_| FileLibError::new(
+        "copy-file",
+        errorlib::ExitErrorCode::DirNotFound,
+        "directory NOT found!"
+    ))?;
+    let mut reader = BufReader::new(file_stream);
+    let mut writer = BufWriter::new(to_file);
+    let mut buffer = vec![0; 64 * 1024]; // 64KB
+    let mut throttle = Throttle::new(rate);
+    loop {
+        let bytes_read = reader.read(&mut buffer).map_err(|_| FileLibError::new(
+            "copy-file",
+            errorlib::ExitErrorCode::FileRead,
+            &format!("can NOT read '{}'!", file)
+        ))?;
+        if bytes_read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..bytes_read]).map_err(|_| FileLibError::new(
+            "copy-file",
+            errorlib::ExitErrorCode::FileWrite,
+            "can NOT write the copy!"
+        ))?;
+        throttle.wrote(bytes_read as u64);
+    }
+    let to_file = writer.into_inner().map_err(|_| FileLibError::new(
+        "copy-file",
+        errorlib::ExitErrorCode::FileFlush,
+        "can NOT flush the copy!"
+    ))?;
+    if keep_metadata {
+        // The time first, the permissions can make the copy read-only.
+        let metadata = std::fs::metadata(&file).and_then(|metadata| {
+            to_file.set_modified(metadata.modified()?)?;
+            Ok(metadata)
+        });
+        metadata
+            .and_then(|metadata| to_file.set_permissions(metadata.permissions()))
+            .map_err(|_| FileLibError::new(
+                "copy-file",
+                errorlib::ExitErrorCode::FileWrite,
+                "can NOT set the copy permissions and time!"
+            ))?;
+    }
+    Ok(())
+}
+
+/// Check if the files have the same contents, e.g. a backup copy in a
+/// sync folder. Returns false if any file can NOT be read.
+/// 
+/// ### Example:
+/// ```
+/// if !filelib::same_contents(Path::new("from.txt"), Path::new("to.txt")) {
+///     println!("the copy is broken!");
+/// }
+/// ```
+pub fn same_contents(file: &Path, other_file: &Path) -> bool {
+    let (file, other_file) = match (std::fs::File::open(file), std::fs::File::open(other_file)) {
+        (Ok(file), Ok(other_file)) => (file, other_file),
+        _ => return false
+    };
+    let mut reader = BufReader::new(file);
+    let mut other_reader = BufReader::new(other_file);
+    let mut buffer = vec![0; 64 * 1024]; // 64KB
+    let mut other_buffer = vec![0; 64 * 1024];
+    loop {
+        let bytes_read = match reader.read(&mut buffer) {
+            Ok(bytes_read) => bytes_read,
+            Err(_) => return false
+        };
+        // The other reader may return less bytes per read.
+        if other_reader.read_exact(&mut other_buffer[..bytes_read]).is_err() 
+            || buffer[..bytes_read] != other_buffer[..bytes_read] {
+            return false;
+        }
+        if bytes_read == 0 {
+            return other_reader.read(&mut other_buffer).map(|bytes| bytes == 0).unwrap_or(false);
+        }
+    }
+}
+
+/// The file names that sync clients use for the files in transfer:
+/// Dropbox, Google Drive, OneDrive, Nextcloud/ownCloud, Syncthing,
+/// and the office lock files.
+const SYNC_TEMP_PATTERNS: [&str; 8] = [
+    "*.tmp",
+    "*.part",
+    "*.partial",
+    "*.crdownload",
+    ".tmp.drive*",
+    ".syncthing.*",
+    ".dropbox.cache",
+    ".~*"
+];
+
+/// Guess if the directory is a sync folder in the middle of a sync, by
+/// the temp and lock files of the sync clients. Returns the first temp
+/// file name, None if there is no sign of a sync.
+/// 
+/// ### Example:
+/// ```
+/// if let Some(name) = filelib::sync_in_progress(Path::new("/home/user/Dropbox")) {
+///     println!("'{}' is still syncing.", name);
+/// }
+/// ```
+pub fn sync_in_progress(dir: &Path) -> Option<String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in SYNC_TEMP_PATTERNS {
+        builder.add(Glob::new(pattern).unwrap());
+    }
+    let patterns = builder.build().unwrap();
+    let mut names: Vec<String> = std::fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| patterns.is_match(name))
+        .collect();
+    names.sort();
+    names.into_iter().next()
+}
+
+/// Decode the json file bytes as UTF-8 text, and strip the UTF-8 BOM
+/// (e.g. files saved by Windows editors). Returns None if the bytes
+/// are NOT valid UTF-8.
 /// 
 /// ### Example:
 /// ```
-/// let object = filelib::read_json("file.json");
+/// let text = json_text(b"\xEF\xBB\xBF{}".to_vec());
+/// assert_eq!(text, Some("{}".to_owned()));
+/// ```
+fn json_text(bytes: Vec<u8>) -> Option<String> {
+    let text = String::from_utf8(bytes).ok()?;
+    Some(
+        text.strip_prefix('\u{FEFF}')
+            .map(|text| text.to_owned())
+            .unwrap_or(text)
+    )
+}
+
+/// From a json file to `HashMap<String, String>`, reading single key-value
+/// json object. The number and boolean values are read as strings.
+/// 
+/// ### Errors:
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileRead`
+/// - `errorlib::ExitErrorCode::InvalidEncoding`
+/// - `errorlib::ExitErrorCode::InvalidJson`
+/// - `errorlib::ExitErrorCode::CanNotGetJsonObject`
+/// 
+/// ### Example:
+/// ```
+/// let object = filelib::read_json("file.json")?;
 /// for (key, value) in object {
 ///     println!("{}: {}", key, value);
 /// }
 /// ```
-pub fn read_json(file: String) -> HashMap<String, String> {
-    let logger = loglib::Logger::new("read-json");
+pub fn read_json(file: String) -> Result<HashMap<String, String>, FileLibError> {
+    let error = |code, message: &str| FileLibError::new("read-json", code, message);
     let json_path = PathBuf::new().join(file);
-    let mut contents = String::new();
-    if let Ok(mut json_file) = std::fs::File::open(json_path) {
-        json_file.read_to_string(&mut contents).unwrap();
-    }
-    if let Ok(json) = serde_json::from_str(&contents) {
-        if let Value::Object(map) = json {
-            let data: HashMap<String, String> = map.into_iter()
-                .filter_map(|(key, value)| {
-                    if let Value::String(val) = value {
-                        Some((key, val))
-                    } else {
-                        logger.error(
-                            "invalid json file!",
-                            errorlib::ExitErrorCode::InvalidJson
-                        )
-                    }
-                }).collect();
-            return data;
-        }
-    }
-    logger.error(
-        "can not get the json data!", 
-        errorlib::ExitErrorCode::CanNotGetJsonObject
-    )
+    let bytes = std::fs::read(&json_path).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => error(
+            errorlib::ExitErrorCode::FileNotFound,
+            &format!("'{}' NOT found!", json_path.display())
+        ),
+        _ => error(
+            errorlib::ExitErrorCode::FileRead,
+            &format!("can NOT read '{}'!", json_path.display())
+        )
+    })?;
+    let contents = json_text(bytes).ok_or_else(|| error(
+        errorlib::ExitErrorCode::InvalidEncoding,
+        &format!("'{}' is NOT a valid UTF-8 file!", json_path.display())
+    ))?;
+    let json: Value = serde_json::from_str(&contents).map_err(|err| error(
+        errorlib::ExitErrorCode::InvalidJson,
+        &format!("invalid json file, {}!", err)
+    ))?;
+    let Value::Object(mut map) = json else {
+        return Err(error(
+            errorlib::ExitErrorCode::CanNotGetJsonObject,
+            "can not get the json data, the file must be a json object!"
+        ));
+    };
+    // Accept the `password-manager export` envelope too, the old one has an object
+    // of the passwords, the new one has a list of `{"id", "name", "password"}`.
+    match map.get("passwords") {
+        Some(Value::Object(passwords)) => map = passwords.clone(),
+        Some(Value::Array(passwords)) => {
+            map = passwords.iter()
+                .map(|pass| match (pass.get("name"), pass.get("password")) {
+                    (Some(Value::String(name)), Some(password)) => Ok((name.clone(), password.clone())),
+                    _ => Err(error(
+                        errorlib::ExitErrorCode::InvalidJson,
+                        "invalid json file, every exported password must have a name and a password!"
+                    ))
+                })
+                .collect::<Result<_, FileLibError>>()?;
+        },
+        _ => ()
+    }
+    map.into_iter()
+        .map(|(key, value)| match value {
+            Value::String(val) => Ok((key, val)),
+            Value::Number(_) | Value::Bool(_) => Ok((key, value.to_string())),
+            _ => Err(error(
+                errorlib::ExitErrorCode::InvalidJson,
+                &format!("invalid json file, the value of '{}' is NOT a string!", key)
+            ))
+        })
+        .collect()
 }
 
 
@@ -442,7 +1579,7 @@ mod tests {
                 .expect("Can NOT remove test temp dir!!");
         }
         let file_path = temp_dir.join("test.txt");
-        super::create_file(file_path.clone());
+        super::create_file(file_path.clone()).unwrap();
         assert_eq!(file_path.exists(), true, "Can NOT create the test file!!");
         std::fs::remove_dir_all(temp_dir)
             .expect("Can NOT remove test temp dir!!");
@@ -452,9 +1589,9 @@ mod tests {
     fn delete_file() {
         let file_path = super::PathBuf::new()
             .join("./temp/delete_file/test.txt");
-        super::create_file(file_path.clone());
+        super::create_file(file_path.clone()).unwrap();
         assert_eq!(file_path.exists(), true, "Can NOT create the test file!!");
-        super::delete_file(file_path.clone());
+        super::delete_file(file_path.clone()).unwrap();
         assert_eq!(file_path.exists(), false, "File NOT deleted!!");
     }
 
@@ -463,7 +1600,7 @@ mod tests {
         let file_path = super::PathBuf::new()
             .join("./temp/wipe_delete/test.txt");
         let message = "this is test message!";
-        super::create_file(file_path.clone());
+        super::create_file(file_path.clone()).unwrap();
         assert_eq!(file_path.exists(), true, "Can NOT create the test file!!");
         let mut file = std::fs::OpenOptions::new()
             .write(true)
@@ -477,10 +1614,270 @@ mod tests {
                 .to_str()
                 .expect("Can NOT parse PathBuf to &str")
                 .to_string()
-        );
+        ).unwrap();
         assert_eq!(file_path.exists(), false, "File NOT wiped and deleted!!");
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn wipe_delete_dir() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_delete_dir");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let dir = temp_dir.join("exports");
+        let outside = temp_dir.join("outside");
+        for name in ["a.txt", "sub/b.txt", "sub/deep/c.txt"] {
+            super::create_file(dir.join(name)).unwrap();
+            std::fs::write(dir.join(name), "secret").expect("Can NOT write the test file!!");
+        }
+        super::create_file(outside.join("keep.txt")).unwrap();
+        std::os::unix::fs::symlink(
+            std::fs::canonicalize(&outside).unwrap(),
+            dir.join("sub/link")
+        ).expect("Can NOT create the test symlink!!");
+        let settings = super::WipeSettings {
+            standard: super::WipeStandard::Dod522022M,
+            verify: true,
+            ..super::WipeSettings::default()
+        };
+        let reports = super::wipe_delete_dir(dir.clone(), &settings).unwrap();
+        assert_eq!(reports.len(), 3, "Wipe reports NOT match!!");
+        assert!(
+            reports.iter().all(|report| report.passes == 3 && report.verified == Some(true)),
+            "Wipe reports settings NOT match!!"
+        );
+        assert!(!dir.exists(), "The wiped directory NOT deleted!!");
+        assert!(outside.join("keep.txt").exists(), "The symlink target NOT match!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn wipe_delete_with() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_delete_with");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete the temp tests dir!!");
+        }
+        for passes in [0u8, 1, 4, 8] {
+            let file = temp_dir.join(format!("test-{}.txt", passes));
+            super::create_file(file.clone()).unwrap();
+            std::fs::write(&file, "this is test message!").unwrap();
+            let report = super::wipe_delete_with(file.to_str().unwrap().to_owned(), passes).unwrap();
+            assert_eq!(report.passes, passes as usize, "Wipe passes NOT match!!");
+            assert!(!file.exists(), "File NOT deleted after {} passes!!", passes);
+        }
+
+        // The last pass is always 0s.
+        for passes in [1u8, 3, 8] {
+            let levels = super::WipeSettings {
+                passes: Some(passes),
+                ..super::WipeSettings::default()
+            }.levels();
+            let mut data = std::io::Cursor::new(vec![7u8; 1024]);
+            super::wipe_levels(&mut data, 1024, &levels, 0, None, false).unwrap();
+            assert!(data.get_ref().iter().all(|byte| *byte == 0), "Last pass of {} passes is NOT 0s!!", passes);
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn wipe_gutmann() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_gutmann");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete the temp tests dir!!");
+        }
+        let settings = super::WipeSettings::resolve(
+            &super::configlib::Config::load_from(temp_dir.join("missing.toml")),
+            Some(&"gutmann".to_owned()),
+            Some(&"2".to_owned())
+        );
+        assert_eq!(settings.passes(), 35, "Gutmann passes NOT match!!");
+
+        // The pattern continues across the 64KB buffers.
+        let len: usize = 200_000;
+        let pattern = [0x92, 0x49, 0x24];
+        let mut data = std::io::Cursor::new(vec![0u8; len]);
+        super::wipe_pass(&mut data, len as u64, super::WipeType::Pattern(pattern), &mut super::Throttle::new(None))
+            .unwrap();
+        assert!(
+            data.get_ref()[..len].iter().enumerate().all(|(index, byte)| *byte == pattern[index % 3]),
+            "Gutmann pattern pass NOT match!!"
+        );
+
+        let file = temp_dir.join("test.txt");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, "this is test message!").unwrap();
+        let report = super::wipe_delete_settings(file.to_str().unwrap().to_owned(), &settings, false).unwrap();
+        assert_eq!(report.passes, 35, "Gutmann report passes NOT match!!");
+        assert!(!file.exists(), "File NOT wiped and deleted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn wipe_dod() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_dod");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete the temp tests dir!!");
+        }
+        let settings = super::WipeSettings::resolve(
+            &super::configlib::Config::load_from(temp_dir.join("missing.toml")),
+            Some(&"dod".to_owned()),
+            Some(&"7".to_owned())
+        );
+        assert_eq!(settings.standard, super::WipeStandard::Dod522022M, "DoD standard NOT match!!");
+        assert_eq!(
+            settings.levels(),
+            vec![super::WipeType::BZero, super::WipeType::Pattern([0xFF; 3]), super::WipeType::Random],
+            "DoD levels NOT match!!"
+        );
+
+        // Read back the bytes of each pass.
+        let len: usize = 200_000;
+        let mut data = std::io::Cursor::new(vec![7u8; len]);
+        let levels = settings.levels();
+        super::wipe_pass(&mut data, len as u64, levels[0], &mut super::Throttle::new(None)).unwrap();
+        assert!(data.get_ref()[..len].iter().all(|byte| *byte == 0x00), "DoD pass 1 NOT 0x00!!");
+        super::wipe_pass(&mut data, len as u64, levels[1], &mut super::Throttle::new(None)).unwrap();
+        assert!(data.get_ref()[..len].iter().all(|byte| *byte == 0xFF), "DoD pass 2 NOT 0xFF!!");
+        let written = super::wipe_pass(&mut data, len as u64, levels[2], &mut super::Throttle::new(None)).unwrap();
+        super::verify_pass(&mut data, len as u64, &written)
+            .expect("DoD pass 3 NOT the random data!!");
+        assert!(data.get_ref()[..len].iter().any(|byte| *byte != 0xFF), "DoD pass 3 NOT random!!");
+
+        let file = temp_dir.join("test.txt");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, "this is test message!").unwrap();
+        let report = super::wipe_delete_settings(file.to_str().unwrap().to_owned(), &settings, false).unwrap();
+        assert_eq!(report.passes, 3, "DoD report passes NOT match!!");
+        assert!(!file.exists(), "File NOT wiped and deleted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn wipe_exact_length() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_exact_length");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete the temp tests dir!!");
+        }
+        let file_path = temp_dir.join("test.txt");
+        super::create_file(file_path.clone()).unwrap();
+        // NOT a multiple of the 64KB buffer, and exactly one buffer.
+        for len in [100_000u64, 64 * 1024] {
+            std::fs::write(&file_path, vec![7u8; len as usize]).unwrap();
+            let mut file = super::OpenOptions::new()
+                .write(true)
+                .open(&file_path)
+                .unwrap();
+            super::wipe_levels(&mut file, len, &super::WIPE_LEVELS, 0, None, false)
+                .expect("Wipe failed!!");
+            drop(file);
+            assert_eq!(
+                std::fs::metadata(&file_path).unwrap().len(),
+                len,
+                "Wiped file length of {} bytes changed!!", len
+            );
+            assert!(
+                std::fs::read(&file_path).unwrap().iter().all(|byte| *byte == 0),
+                "File of {} bytes NOT wiped by the last level!!", len
+            );
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn wipe_verify() {
+        // The random pass is checked against its static buffer.
+        let len: usize = 200_000;
+        let mut data = std::io::Cursor::new(vec![7u8; len]);
+        let written = super::wipe_pass(&mut data, len as u64, super::WipeType::Random, &mut super::Throttle::new(None))
+            .unwrap();
+        super::verify_pass(&mut data, len as u64, &written)
+            .expect("Random pass NOT verified!!");
+
+        // A byte that did NOT reach the file.
+        let written = super::wipe_pass(&mut data, len as u64, super::WipeType::BZero, &mut super::Throttle::new(None))
+            .unwrap();
+        data.get_mut()[70_000] = 7;
+        let (code, reason) = super::verify_pass(&mut data, len as u64, &written)
+            .expect_err("Wrong byte verified!!");
+        assert_eq!(code, super::errorlib::ExitErrorCode::WipeVerifyFailed, "Verify error code NOT match!!");
+        assert!(reason.contains("byte 70000"), "Verify error reason NOT match!!");
+
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_verify");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete the temp tests dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, "this is test message!").unwrap();
+        let report = super::wipe_delete_settings(
+            file.to_str().unwrap().to_owned(),
+            &super::WipeSettings {
+                verify: true,
+                ..super::WipeSettings::default()
+            },
+            false
+        ).unwrap();
+        assert_eq!(report.verified, Some(true), "Wipe verification NOT match!!");
+        assert!(report.message().ends_with("verification passed"), "Wipe report message NOT match!!");
+        assert!(!file.exists(), "File NOT wiped and deleted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn wipe_report() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_report");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete the temp tests dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let log_db_path = temp_dir.join("logs.db");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, "XPManager secret contents").unwrap();
+
+        let settings = super::WipeSettings {
+            passes: Some(6),
+            ..super::WipeSettings::default()
+        };
+        let report = super::wipe_delete_settings(file.to_str().unwrap().to_owned(), &settings, false).unwrap();
+        crate::dblib::log::register(&report.message(), crate::dblib::log::LogAction::Wipe, log_db_path.clone());
+        let logs = crate::dblib::log::get_logs_by_action(log_db_path, crate::dblib::log::LogAction::Wipe, 0);
+        assert_eq!(logs.len(), 1, "Number of wipe logs NOT match!!");
+        assert!(
+            logs[0].log.contains("test.txt") &&
+            logs[0].log.contains("25 bytes") &&
+            logs[0].log.contains("method simple") &&
+            logs[0].log.contains("6 passes"),
+            "Wipe report NOT match '{}'!!", logs[0].log
+        );
+        assert!(!logs[0].log.contains("secret"), "Wipe report has the file contents!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
     #[test]
     fn get_file_state() {
         let temp_dir = super::PathBuf::new()
@@ -503,7 +1900,7 @@ mod tests {
         assert_eq!(state, true, "File state NOT match!!");
 
         // File is decrypted
-        super::create_file(de_file_path.clone());
+        super::create_file(de_file_path.clone()).unwrap();
         assert_eq!(de_file_path.exists(), true, "Can NOT create test file!!");
         state = super::get_file_state(
             de_file_path
@@ -514,7 +1911,7 @@ mod tests {
         assert_eq!(state, true, "File state NOT match!!");
 
         // File is encrypted
-        super::create_file(en_file_path.clone());
+        super::create_file(en_file_path.clone()).unwrap();
         assert_eq!(de_file_path.exists(), true, "Can NOT create test file!!");
         state = super::get_file_state(
             en_file_path
@@ -529,6 +1926,35 @@ mod tests {
             .expect("Can NOT delete the temp tests dir!!");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn get_path_state_not_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/get_path_state_not_utf8");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete the temp tests dir!!");
+        }
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let de_file_path = temp_dir.join(super::OsStr::from_bytes(b"test.\xff"));
+        let en_file_path = temp_dir.join(super::OsStr::from_bytes(b"test-\xff.x"));
+        std::fs::write(&de_file_path, "").unwrap();
+        std::fs::write(&en_file_path, "").unwrap();
+
+        assert!(
+            super::get_path_state(&de_file_path) == super::FileState::Decrypted,
+            "NOT UTF-8 extension state NOT match!!"
+        );
+        assert!(
+            super::get_path_state(&en_file_path) == super::FileState::Encrypted,
+            "NOT UTF-8 name state NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
     #[test]
     fn make_encrypt_path() {
         let file = super::make_encrypt_path(
@@ -551,6 +1977,24 @@ mod tests {
             "./temp/make_decrypt_path/test.txt",
             "Can NOT create decryption path!!"
         );
+        let paths = [
+            ("/home/a.b/file.x", "/home/a.b/file"),
+            ("./my.tar.gz.x", "./my.tar.gz"),
+            ("archive.x", "archive"),
+            // Not encrypted paths stay the same.
+            ("/home/a.b/file", "/home/a.b/file"),
+            ("noext", "noext"),
+            ("./dir.x/file.txt", "./dir.x/file.txt"),
+            ("./dir/file.xpm", "./dir/file.xpm"),
+            ("./dir/.x", "./dir/.x")
+        ];
+        for (path, de_path) in paths {
+            assert_eq!(
+                super::make_decrypt_path(path.to_string()),
+                de_path,
+                "Decryption path of '{}' NOT match!!", path
+            );
+        }
     }
 
     #[test]
@@ -565,10 +2009,10 @@ mod tests {
             temp_dir.join("dir/files/test.txt")
         ];
         for file in files.clone() {
-            super::create_file(file.clone());
+            super::create_file(file.clone()).unwrap();
             assert_eq!(file.exists(), true, "Can NOT create the test file!!");
         }
-        super::dir_files_tree(temp_dir.clone(), &mut files_paths);
+        super::dir_files_tree(temp_dir.clone(), &mut files_paths).unwrap();
         let mut found: bool = false;
         for file in files {
             for tree in files_paths.as_slice() {
@@ -600,7 +2044,7 @@ mod tests {
         let temp_dir = super::PathBuf::new()
             .join("./temp/copy");
         let file = temp_dir.join("src.txt");
-        super::create_file(file.clone());
+        super::create_file(file.clone()).unwrap();
         assert_eq!(file.exists(), true, "Can NOT create the test file!!");
         let to = temp_dir.join("to.txt");
         super::copy(
@@ -612,9 +2056,497 @@ mod tests {
                 .to_str()
                 .expect("Can NOT parse PathBuf to &str!!")
                 .to_string()
-        );
+        ).unwrap();
         assert_eq!(to.exists(), true, "Can NOT copy the test file!!");
         std::fs::remove_dir_all(temp_dir)
             .expect("Can NOT delete the temp tests dir!!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn file_lib_error() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/file_lib_error");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let missing = temp_dir.join("missing.txt");
+        let missing_path = missing.to_str().unwrap().to_owned();
+        let error = super::copy(missing_path.clone(), temp_dir.join("to.txt").to_str().unwrap().to_owned())
+            .expect_err("Copy a missing file!!");
+        assert_eq!(error.code, super::errorlib::ExitErrorCode::FileNotFound, "The copy error code NOT match!!");
+        assert_eq!(error.to_string(), "file NOT found!", "The copy error message NOT match!!");
+        let error = super::wipe_delete_with(missing_path, 1)
+            .expect_err("Wipe a missing file!!");
+        assert_eq!(error.code, super::errorlib::ExitErrorCode::FileNotFound, "The wipe error code NOT match!!");
+        let error = super::dir_files_tree(temp_dir.clone(), &mut Vec::new())
+            .expect_err("Get the tree of a missing directory!!");
+        assert_eq!(error.code, super::errorlib::ExitErrorCode::DirNotFound, "The tree error code NOT match!!");
+        assert_eq!(super::delete_file(missing), Ok(()), "Delete a missing file NOT match!!");
+    }
+
+    #[test]
+    fn wipe_preview() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_preview");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let files: [(super::PathBuf, usize); 3] = [
+            (temp_dir.join("test.txt"), 10),
+            (temp_dir.join("dir/test-0.txt"), 0),
+            (temp_dir.join("dir/files/test.txt"), 100)
+        ];
+        for (file, size) in files.clone() {
+            super::create_file(file.clone()).unwrap();
+            std::fs::write(&file, vec![1u8; size])
+                .expect("Can NOT write to the test file!!");
+        }
+        let mut files_paths: Vec<super::PathBuf> = vec![];
+        super::dir_files_tree(temp_dir.clone(), &mut files_paths).unwrap();
+        let mut preview = super::wipe_preview(&files_paths);
+        preview.sort();
+        let mut expected: Vec<(super::PathBuf, u64)> = files
+            .iter()
+            .map(|(file, size)| (file.clone(), *size as u64))
+            .collect();
+        expected.sort();
+        assert_eq!(preview, expected, "Wipe preview NOT match!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn json_text() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/json_text");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let bom_file = temp_dir.join("bom.json");
+        let invalid_file = temp_dir.join("invalid.json");
+        super::create_file(bom_file.clone()).unwrap();
+        super::create_file(invalid_file.clone()).unwrap();
+        std::fs::write(&bom_file, b"\xEF\xBB\xBF{\"github\": \"pass123\"}\r\n")
+            .expect("Can NOT write to the test file!!");
+        std::fs::write(&invalid_file, b"{\"github\": \"\xFF\xFE\"}")
+            .expect("Can NOT write to the test file!!");
+
+        let text = super::json_text(std::fs::read(&bom_file).unwrap())
+            .expect("Can NOT decode the BOM file!!");
+        let json: super::Value = serde_json::from_str(&text)
+            .expect("Can NOT parse the BOM file!!");
+        assert_eq!(json["github"], "pass123", "BOM json NOT match!!");
+        assert_eq!(
+            super::json_text(std::fs::read(&invalid_file).unwrap()),
+            None,
+            "Invalid UTF-8 NOT detected!!"
+        );
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_metadata() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/copy_metadata");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let file = temp_dir.join("config.toml");
+        let to = temp_dir.join("config.toml.bak");
+        let fresh_to = temp_dir.join("config.toml.fresh");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, "[passwords]\n").unwrap();
+        let mtime = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        std::fs::File::options().write(true).open(&file).unwrap().set_modified(mtime).unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o400)).unwrap();
+
+        super::copy(file.to_str().unwrap().to_owned(), to.to_str().unwrap().to_owned()).unwrap();
+        let metadata = std::fs::metadata(&to).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o400, "Copy mode NOT match!!");
+        assert_eq!(metadata.modified().unwrap(), mtime, "Copy time NOT match!!");
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "[passwords]\n", "Copy data NOT match!!");
+
+        super::copy_with(file.to_str().unwrap().to_owned(), fresh_to.to_str().unwrap().to_owned(), None, false).unwrap();
+        let metadata = std::fs::metadata(&fresh_to).unwrap();
+        assert_ne!(metadata.permissions().mode() & 0o777, 0o400, "Fresh copy mode NOT match!!");
+        assert_ne!(metadata.modified().unwrap(), mtime, "Fresh copy time NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn dir_files_tree_order() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/dir_files_tree_order");
+        for file in ["a/1.txt", "a/b/2.txt", "a/b/c/3.txt", "a/4.txt", "d/5.txt", "d/e/6.txt", "7.txt"] {
+            super::create_file(temp_dir.join(file)).unwrap();
+        }
+        // The recursive walk, the files of a directory come right where it is read.
+        fn walk(folder: &super::Path, files_paths: &mut Vec<super::PathBuf>) {
+            for entry in folder.read_dir().unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    walk(&path, files_paths);
+                } else {
+                    files_paths.push(path);
+                }
+            }
+        }
+        let mut expected = vec![];
+        walk(&temp_dir, &mut expected);
+        let mut files_paths = vec![];
+        super::dir_files_tree(temp_dir.clone(), &mut files_paths).unwrap();
+        assert_eq!(files_paths, expected, "Depth-first files tree order NOT match!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn dir_files_tree_deep() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/dir_files_tree_deep");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        // As deep as the path length limit allows (4096 bytes on Linux).
+        let deep_dir = (0..1500).fold(temp_dir.clone(), |dir, _| dir.join("d"));
+        super::create_file(deep_dir.join("f")).unwrap();
+        // A small stack is enough for any depth.
+        let files_paths = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || {
+                let mut files_paths = vec![];
+                super::dir_files_tree(super::PathBuf::new().join("./temp/dir_files_tree_deep"), &mut files_paths)
+                    .map(|_| files_paths)
+            })
+            .unwrap()
+            .join()
+            .expect("Deep tree overflowed the stack!!")
+            .unwrap();
+        assert_eq!(files_paths, vec![deep_dir.join("f")], "Deep files tree NOT match!!");
+
+        let error = super::dir_files_tree_depth(temp_dir.clone(), &mut vec![], 100)
+            .expect_err("Deeper than the max depth!!");
+        assert_eq!(error.code, super::errorlib::ExitErrorCode::DirUnsupported, "Max depth error NOT match!!");
+        let mut files_paths = vec![];
+        super::dir_files_tree_depth(temp_dir.clone(), &mut files_paths, 1500).unwrap();
+        assert_eq!(files_paths.len(), 1, "Max depth files tree NOT match!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn dir_files_tree_filter() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/dir_files_tree_filter");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        for name in [".env", "app.key", "src/main.rs", "node_modules/.env", "web/node_modules/lib/x.key"] {
+            super::create_file(temp_dir.join(name)).unwrap();
+        }
+        let is_excluded = super::glob_matcher(&temp_dir, &["**/node_modules".to_string()]);
+        let mut files_paths = vec![];
+        super::dir_files_tree_filter(temp_dir.clone(), &mut files_paths, &|path| !is_excluded(path)).unwrap();
+        files_paths.sort();
+        assert_eq!(
+            files_paths,
+            vec![temp_dir.join(".env"), temp_dir.join("app.key"), temp_dir.join("src/main.rs")],
+            "Filtered files tree NOT match!!"
+        );
+        std::fs::remove_dir_all(&temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn write_atomic() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/write_atomic");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let file = temp_dir.join("passwords.db");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, "old data").unwrap();
+        super::write_atomic(file.clone(), b"new data").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new data", "File data NOT match!!");
+        assert!(!temp_dir.join("passwords.db.xpm-tmp").exists(), "Temp file NOT renamed!!");
+
+        // A directory can NOT be replaced by a file, the temp file is deleted.
+        let dir = temp_dir.join("dir");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let error = super::write_atomic(dir.clone(), b"new data")
+            .expect_err("Replace a directory!!");
+        assert_eq!(error.code, super::errorlib::ExitErrorCode::FileWrite, "Rename error NOT match!!");
+        assert!(!temp_dir.join("dir.xpm-tmp").exists(), "Temp file NOT deleted!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn read_json() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/read_json");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let json_file = temp_dir.join("passwords.json");
+        let invalid_file = temp_dir.join("invalid.json");
+        super::create_file(json_file.clone()).unwrap();
+        super::create_file(invalid_file.clone()).unwrap();
+        std::fs::write(&json_file, r#"{"github": "pass123", "pin": 1234, "admin": true}"#)
+            .expect("Can NOT write to the test file!!");
+        std::fs::write(&invalid_file, r#"{"github": "pass123",}"#)
+            .expect("Can NOT write to the test file!!");
+
+        let json = super::read_json(json_file.to_str().unwrap().to_owned()).unwrap();
+        assert_eq!(json["github"], "pass123", "String value NOT match!!");
+        assert_eq!(json["pin"], "1234", "Number value NOT match!!");
+        assert_eq!(json["admin"], "true", "Boolean value NOT match!!");
+        std::fs::write(&json_file, r#"{"max_id": 2, "passwords": [{"id": 2, "name": "github", "password": "pass123"}]}"#)
+            .expect("Can NOT write to the test file!!");
+        let json = super::read_json(json_file.to_str().unwrap().to_owned()).unwrap();
+        assert_eq!(json["github"], "pass123", "Export envelope value NOT match!!");
+        assert_eq!(json.len(), 1, "Export envelope passwords NOT match!!");
+        let error = super::read_json(temp_dir.join("missing.json").to_str().unwrap().to_owned())
+            .expect_err("Read a missing json file!!");
+        assert_eq!(error.code, super::errorlib::ExitErrorCode::FileNotFound, "Missing file error NOT match!!");
+        let error = super::read_json(invalid_file.to_str().unwrap().to_owned())
+            .expect_err("Read an invalid json file!!");
+        assert_eq!(error.code, super::errorlib::ExitErrorCode::InvalidJson, "Invalid json error NOT match!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn dry_wipe() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/dry_wipe");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, vec![7u8; 1500])
+            .expect("Can NOT write to the test file!!");
+        let dry = super::dry_wipe(
+            &super::wipe_preview(&[file.clone(), file.clone()]),
+            &super::WipeSettings::default()
+        );
+        assert_eq!(
+            dry,
+            super::DryWipe {
+                passes: super::WIPE_LEVELS.len(),
+                bytes_per_pass: 3000,
+                total_bytes: 3000 * super::WIPE_LEVELS.len() as u64
+            },
+            "Dry wipe NOT match!!"
+        );
+        assert_eq!(
+            std::fs::read(&file).expect("Can NOT read the test file!!"),
+            vec![7u8; 1500],
+            "Dry wipe changed the file!!"
+        );
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn is_regular_file() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/is_regular_file");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let file = temp_dir.join("test.txt");
+        let fifo = temp_dir.join("test.fifo");
+        super::create_file(file.clone()).unwrap();
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .expect("Can NOT run mkfifo!!");
+        assert!(status.success(), "Can NOT create the test FIFO!!");
+        assert!(super::is_regular_file(&file), "Regular file NOT detected!!");
+        assert!(!super::is_regular_file(&fifo), "FIFO detected as a regular file!!");
+        assert!(!super::is_regular_file(&temp_dir), "Directory detected as a regular file!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    /// A writer that fails on the first write after `passes` flushes.
+    struct FailingWriter {
+        inner: std::io::Cursor<Vec<u8>>,
+        passes: usize,
+        flushes: usize
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.flushes >= self.passes {
+                return Err(std::io::Error::other("no space left on device"));
+            }
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    impl super::Read for FailingWriter {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl super::Seek for FailingWriter {
+        fn seek(&mut self, pos: super::SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn wipe_resume() {
+        let mut writer = FailingWriter {
+            inner: std::io::Cursor::new(vec![7u8; 100]),
+            passes: 2,
+            flushes: 0
+        };
+        let failure = super::wipe_levels(&mut writer, 100, &super::WIPE_LEVELS, 0, None, false)
+            .expect_err("Wipe NOT failed!!");
+        assert_eq!(failure.level, 3, "Failed wipe level NOT match!!");
+        assert!(
+            failure.code == super::errorlib::ExitErrorCode::FileWrite &&
+            failure.reason.contains("no space left on device"),
+            "Wipe failure reason NOT match!!"
+        );
+
+        // Resume from the failed level, the completed levels are NOT redone.
+        let mut writer = FailingWriter {
+            inner: std::io::Cursor::new(vec![7u8; 100]),
+            passes: 2,
+            flushes: 0
+        };
+        super::wipe_levels(&mut writer, 100, &super::WIPE_LEVELS, 2, None, false)
+            .expect("Resumed wipe failed!!");
+        assert!(
+            writer.inner.get_ref().iter().all(|byte| *byte == 0),
+            "File NOT wiped by the last level!!"
+        );
+
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_resume");
+        let file_path = temp_dir.join("test.txt");
+        let path = file_path.to_str().unwrap().to_owned();
+        super::create_file(file_path.clone()).unwrap();
+        std::fs::write(super::wipe_state_path(&path), "2")
+            .expect("Can NOT write the wipe state!!");
+        assert_eq!(super::wipe_completed_levels(&path), 2, "Completed levels NOT match!!");
+        super::wipe_delete_settings(path.clone(), &super::WipeSettings::default(), true).unwrap();
+        assert!(!file_path.exists(), "File NOT wiped and deleted!!");
+        assert!(!super::wipe_state_path(&path).exists(), "Wipe state NOT deleted!!");
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn wipe_settings() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/wipe_settings");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        let config_path = temp_dir.join("config.toml");
+        super::create_file(config_path.clone()).unwrap();
+        std::fs::write(&config_path, "[wipe]\nmethod = \"simple\"\npasses = 2\n")
+            .expect("Can NOT write the test config!!");
+        let config = super::configlib::Config::load_from(config_path);
+
+        let settings = super::WipeSettings::resolve(&config, None, None);
+        assert_eq!(settings.passes, Some(2), "Config passes NOT match!!");
+        assert_eq!(
+            settings.levels(),
+            vec![super::WipeType::BOne, super::WipeType::BZero],
+            "Config wipe levels NOT match!!"
+        );
+        let settings = super::WipeSettings::resolve(
+            &config,
+            Some(&"simple".to_owned()),
+            Some(&"6".to_owned())
+        );
+        assert_eq!(settings.passes(), 6, "Flag passes NOT override the config!!");
+        assert_eq!(settings.levels()[5], super::WipeType::BZero, "Last wipe level is NOT 0s!!");
+        assert_eq!(
+            super::WipeSettings::resolve(&config, None, Some(&"0".to_owned())).passes(),
+            0,
+            "Zero passes NOT match!!"
+        );
+        assert_eq!(
+            super::WipeSettings::default().levels(),
+            super::WIPE_LEVELS.to_vec(),
+            "Default wipe levels NOT match!!"
+        );
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+
+    #[test]
+    fn limit_rate() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/limit_rate");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)
+                .expect("Can NOT remove test temp dir!!");
+        }
+        assert_eq!(super::parse_rate("50MB/s"), Some(50 * 1024 * 1024), "Rate NOT match!!");
+        assert_eq!(super::parse_rate("512k"), Some(512 * 1024), "Rate NOT match!!");
+        assert_eq!(super::parse_rate("100"), Some(100), "Rate NOT match!!");
+        assert!(
+            super::parse_rate("0MB/s").is_none() && super::parse_rate("fast").is_none(),
+            "Invalid rate NOT match!!"
+        );
+
+        // 64KB at 256KB/s takes at least 250ms.
+        let rate = Some(256 * 1024);
+        let file = temp_dir.join("src.txt");
+        let to = temp_dir.join("to.txt");
+        super::create_file(file.clone()).unwrap();
+        std::fs::write(&file, vec![7u8; 64 * 1024]).unwrap();
+        let start = std::time::Instant::now();
+        super::copy_limited(
+            file.to_str().unwrap().to_owned(),
+            to.to_str().unwrap().to_owned(),
+            rate
+        ).unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(250), "Copy rate NOT limited!!");
+        assert_eq!(std::fs::read(&to).unwrap().len(), 64 * 1024, "Copied data NOT match!!");
+
+        // 4 passes of 16KB at 256KB/s.
+        std::fs::write(&file, vec![7u8; 16 * 1024]).unwrap();
+        let settings = super::WipeSettings {
+            rate,
+            ..super::WipeSettings::default()
+        };
+        let start = std::time::Instant::now();
+        super::wipe_delete_settings(file.to_str().unwrap().to_owned(), &settings, false).unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(250), "Wipe rate NOT limited!!");
+        assert!(!file.exists(), "File NOT wiped and deleted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete the temp tests dir!!");
+    }
+}
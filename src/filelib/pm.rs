@@ -1,3 +1,6 @@
+use std::cell::Cell;
+use std::path::Path;
+use dirs::runtime_dir;
 use super::{
     XPM_EXTENSION,
     data_dir,
@@ -36,6 +39,86 @@ pub fn get_encrypted_db_path() -> PathBuf {
     }
 }
 
+thread_local! {
+    /// Decrypt the encrypted database to a tmpfs, uses with the config `passwords.tmpfs`.
+    static TMPFS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Decrypt the encrypted database to a tmpfs (RAM) directory, so the
+/// plaintext never touches the disk, the encrypted database stays in
+/// the data directory.
+/// 
+/// ### Example:
+/// ```
+/// filelib::pm::set_tmpfs(true);
+/// ```
+pub fn set_tmpfs(is_tmpfs: bool) {
+    TMPFS.with(|tmpfs| tmpfs.set(is_tmpfs));
+}
+
+/// Get the tmpfs directory of the decrypted database, under
+/// `$XDG_RUNTIME_DIR` or `/dev/shm`, None if there is no tmpfs.
+/// 
+/// ### Example:
+/// ```
+/// if let Some(tmpfs_dir) = filelib::pm::get_tmpfs_dir() {
+///     println!("Path is: {}", tmpfs_dir.display());
+/// }
+/// ```
+pub fn get_tmpfs_dir() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    runtime_dir()
+        .or_else(|| Some(PathBuf::from("/dev/shm")))
+        .filter(|path| path.is_dir())
+        .map(|path| path.join("XPManager"))
+}
+
+/// Create the tmpfs directory of the decrypted database,
+/// readable by the owner only.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DirCreate`
+/// 
+/// ### Example:
+/// ```
+/// filelib::pm::create_tmpfs_dir("/run/user/1000/XPManager/passwords.db");
+/// ```
+pub fn create_tmpfs_dir(de_path: &str) {
+    let logger = loglib::Logger::new("create-tmpfs-dir");
+    let parent = Path::new(de_path).parent().unwrap_or(Path::new("."));
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    if builder.create(parent).is_err() {
+        logger.error(
+            &format!("can NOT create the directory at '{}'!", parent.display()),
+            errorlib::ExitErrorCode::DirCreate
+        );
+    }
+}
+
+/// Select the decrypted database path in the data directory, or in
+/// the tmpfs directory if it is set and the database is encrypted (or
+/// decrypted there). A decrypted database in the data directory is
+/// always used until it is encrypted.
+fn select_decrypted_db_path(data_path: &Path, tmpfs_dir: Option<&Path>) -> PathBuf {
+    let de_path = data_path.join("XPManager/data/passwords.db");
+    let en_path = data_path.join(format!("XPManager/data/passwords.db.{}", XPM_EXTENSION));
+    if let Some(tmpfs_dir) = tmpfs_dir {
+        let tmpfs_path = tmpfs_dir.join("passwords.db");
+        if !de_path.exists() && (en_path.exists() || tmpfs_path.exists()) {
+            return tmpfs_path;
+        }
+    }
+    de_path
+}
+
 /// Get the password manager database full path.
 /// It will return the database path in the user's 
 /// data directory: 
@@ -43,8 +126,12 @@ pub fn get_encrypted_db_path() -> PathBuf {
 /// - MacOS: `/Users/{user}/Library/Application Support/XPManager/data/passwords.db`
 /// - Windows: `C:\Users\{user}\AppData\Roaming\XPManager\data\passwords.db`
 /// 
+/// With the tmpfs mode, the encrypted database is decrypted to
+/// `$XDG_RUNTIME_DIR/XPManager/passwords.db` (or `/dev/shm`).
+/// 
 /// ### Exit:
 /// - `errorlib::ExitErrorCode::SystemDataDirNotFound`
+/// - `errorlib::ExitErrorCode::InvalidConfig`
 /// 
 /// ### Example:
 /// ```
@@ -54,7 +141,13 @@ pub fn get_encrypted_db_path() -> PathBuf {
 pub fn get_decrypted_db_path() -> PathBuf {
     let logger = loglib::Logger::new("get-pm-decrypted-db-path");
     if let Some(data_path) = data_dir() {
-        return data_path.join("XPManager/data/passwords.db");
+        let tmpfs_dir = TMPFS.with(|tmpfs| tmpfs.get()).then(|| {
+            get_tmpfs_dir().unwrap_or_else(|| logger.error(
+                "no tmpfs directory for the config 'passwords.tmpfs', it is supported on Linux only!",
+                errorlib::ExitErrorCode::InvalidConfig
+            ))
+        });
+        select_decrypted_db_path(&data_path, tmpfs_dir.as_deref())
     } else {
         logger.error(
             "can NOT get the system data directory path!", 
@@ -145,4 +238,50 @@ mod tests {
         let result = state == super::db_state();
         assert_eq!( result, true, "Password manager database state NOT match!!" );
     }
-}
\ No newline at end of file
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tmpfs_decrypted_db_path() {
+        let temp_dir = super::PathBuf::new()
+            .join("./temp/tmpfs_decrypted_db_path");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let tmpfs_dir = match super::get_tmpfs_dir() {
+            Some(tmpfs_dir) => tmpfs_dir,
+            None => return // No tmpfs in this system.
+        };
+        let runtime = tmpfs_dir.parent().unwrap().to_path_buf();
+        assert!(
+            Some(runtime.clone()) == super::runtime_dir() || runtime == super::Path::new("/dev/shm"),
+            "Tmpfs directory NOT match!!"
+        );
+        let de_path = temp_dir.join("XPManager/data/passwords.db");
+        assert_eq!(
+            super::select_decrypted_db_path(&temp_dir, Some(&tmpfs_dir)), de_path,
+            "New database path NOT match!!"
+        );
+
+        super::super::create_file(temp_dir.join("XPManager/data/passwords.db.x")).unwrap();
+        assert_eq!(
+            super::select_decrypted_db_path(&temp_dir, Some(&tmpfs_dir)),
+            tmpfs_dir.join("passwords.db"),
+            "Tmpfs database path NOT match!!"
+        );
+        assert_eq!(
+            super::select_decrypted_db_path(&temp_dir, None), de_path,
+            "Data directory database path NOT match!!"
+        );
+
+        // A decrypted database in the data directory is used until it is encrypted.
+        super::super::create_file(de_path.clone()).unwrap();
+        assert_eq!(
+            super::select_decrypted_db_path(&temp_dir, Some(&tmpfs_dir)), de_path,
+            "Decrypted database path NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
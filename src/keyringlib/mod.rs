@@ -0,0 +1,167 @@
+use clap::ArgMatches;
+use keyring::Entry;
+use crate::{
+    dblib,
+    errorlib,
+    filelib,
+    loglib,
+    utilities
+};
+
+/// The OS keyring service name of the keys.
+pub const SERVICE: &str = "XPManager";
+
+/// The default OS keyring account name, if `--account`/`--keyring` has no name.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// Get the OS keyring entry of an account (Keychain on MacOS,
+/// Secret Service on Linux, Credential Manager on Windows).
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Keyring`
+///
+/// ### Example:
+/// ```
+/// let entry = keyringlib::entry("work");
+/// ```
+pub fn entry(account: &str) -> Entry {
+    let logger = loglib::Logger::new("keyring-entry");
+    Entry::new(SERVICE, account).unwrap_or_else(|_| logger.error(
+        "can NOT open the OS keyring!",
+        errorlib::ExitErrorCode::Keyring
+    ))
+}
+
+/// Save the key in the keyring entry, replaces the old key.
+/// Returns false if the keyring can NOT save it.
+///
+/// ### Example:
+/// ```
+/// if !keyringlib::store(&keyringlib::entry("work"), "<your-key>") {
+///     println!("key NOT saved!");
+/// }
+/// ```
+pub fn store(entry: &Entry, key: &str) -> bool {
+    entry.set_password(key).is_ok()
+}
+
+/// Get the key from the keyring entry, None if there is no key.
+///
+/// ### Example:
+/// ```
+/// if let Some(key) = keyringlib::fetch(&keyringlib::entry("work")) {
+///     println!("the key is in the keyring.");
+/// }
+/// ```
+pub fn fetch(entry: &Entry) -> Option<String> {
+    entry.get_password().ok()
+}
+
+/// Delete the key from the keyring entry. Returns false if there is no key.
+///
+/// ### Example:
+/// ```
+/// if keyringlib::remove(&keyringlib::entry("work")) {
+///     println!("the key is deleted.");
+/// }
+/// ```
+pub fn remove(entry: &Entry) -> bool {
+    entry.delete_credential().is_ok()
+}
+
+/// Get the key of an account from the keyring, for `--keyring`.
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Keyring`
+///
+/// ### Example:
+/// ```
+/// let key = keyringlib::get_key("work");
+/// ```
+pub fn get_key(account: &str) -> String {
+    let logger = loglib::Logger::new("keyring-key");
+    fetch(&entry(account)).unwrap_or_else(|| logger.error(
+        &format!("there is no key of '{}' in the OS keyring, use 'key store'!", account),
+        errorlib::ExitErrorCode::Keyring
+    ))
+}
+
+fn store_main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("key-store");
+    let account = command.get_one::<String>("account")
+        .map(|account| account.as_str())
+        .unwrap_or(DEFAULT_ACCOUNT);
+    let key = utilities::input("Enter your key: ");
+    logger.start();
+    if fernet::Fernet::new(&key).is_none() {
+        logger.error(
+            "the key is NOT a valid encryption key!",
+            errorlib::ExitErrorCode::InvalidKey
+        );
+    }
+    if !store(&entry(account), &key) {
+        logger.error(
+            "can NOT save the key in the OS keyring!",
+            errorlib::ExitErrorCode::Keyring
+        );
+    }
+    logger.info(&format!("the key of '{}' saved in the OS keyring successfully.", account));
+    dblib::log::register(
+        &format!("key of '{}' saved in the OS keyring", account),
+        dblib::log::LogAction::Save,
+        filelib::log::get_log_db_path()
+    );
+}
+
+fn forget_main(command: &ArgMatches) {
+    let logger = loglib::Logger::new("key-forget");
+    let account = command.get_one::<String>("account")
+        .map(|account| account.as_str())
+        .unwrap_or(DEFAULT_ACCOUNT);
+    if !remove(&entry(account)) {
+        logger.info(&format!("there is no key of '{}' in the OS keyring.", account));
+        return;
+    }
+    logger.info(&format!("the key of '{}' deleted from the OS keyring successfully.", account));
+    dblib::log::register(
+        &format!("key of '{}' deleted from the OS keyring", account),
+        dblib::log::LogAction::Delete,
+        filelib::log::get_log_db_path()
+    );
+}
+
+pub fn main(command: &ArgMatches) {
+    let logger = loglib::Logger::new("key");
+    match command.subcommand() {
+        Some(("store", command)) => store_main(command),
+        Some(("forget", command)) => forget_main(command),
+        _ => logger.error(
+            "Run with 'key --help'",
+            errorlib::ExitErrorCode::MissingArg
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keyring::{mock::MockCredential, Entry};
+
+    #[test]
+    fn keyring_store_fetch_remove() {
+        // The mock keyring keeps the key in the entry only.
+        let entry = Entry::new_with_credential(Box::new(MockCredential::default()));
+        assert_eq!(super::fetch(&entry), None, "Missing key NOT match!!");
+        assert!(!super::remove(&entry), "Missing key removed!!");
+
+        let key = fernet::Fernet::generate_key();
+        assert!(super::store(&entry, &key), "Key NOT stored!!");
+        assert_eq!(super::fetch(&entry), Some(key), "Stored key NOT match!!");
+
+        let new_key = fernet::Fernet::generate_key();
+        assert!(super::store(&entry, &new_key), "Key NOT replaced!!");
+        assert_eq!(super::fetch(&entry), Some(new_key), "Replaced key NOT match!!");
+
+        assert!(super::remove(&entry), "Key NOT removed!!");
+        assert_eq!(super::fetch(&entry), None, "Removed key NOT match!!");
+    }
+}
@@ -0,0 +1,29 @@
+use super::{
+    ArgMatches, 
+    LMDatabaseEncrption
+};
+use crate::{
+    loglib,
+    filelib,
+    errorlib
+};
+
+pub fn main(_: &ArgMatches) {
+    let mut logger = loglib::Logger::new("decrypt-lm-database");
+    let lm_db_state = filelib::log::db_state();
+    if lm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "no logs database!", 
+            errorlib::ExitErrorCode::LMDatabaseNotFound
+        );
+    } else if lm_db_state == filelib::FileState::Decrypted {
+        logger.error(
+            "logs database not encrypted!", 
+            errorlib::ExitErrorCode::FileNotEncrypted
+        );
+    }
+    let mut lm_db_encryption = LMDatabaseEncrption::new();
+    lm_db_encryption.decrypt();
+    logger.start();
+    logger.info("logs database decrypted successfully.");
+}
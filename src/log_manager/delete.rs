@@ -1,4 +1,7 @@
-use super::ArgMatches;
+use super::{
+    ArgMatches,
+    LMDatabaseEncrption
+};
 use crate::{
     dblib, 
     filelib, 
@@ -7,22 +10,29 @@ use crate::{
 };
 
 pub fn main( command: &ArgMatches ) {
-    let logger = loglib::Logger::new("delete-log");
+    let mut logger = loglib::Logger::new("delete-log");
     if let Some(id) = command.get_one::<String>("ID") {
         let log_db_path = filelib::log::get_log_db_path();
-        let log_db_state = filelib::get_file_state(
-            log_db_path
-                .to_str()
-                .unwrap()
-                .to_owned()
-        );
+        let log_db_state = filelib::log::db_state();
+        let mut lm_db_encryption = LMDatabaseEncrption::new();
+        let mut _is_db_decrypted: bool = false;
         if log_db_state == filelib::FileState::NotFound {
             logger.error(
                 "no logs database!", 
                 errorlib::ExitErrorCode::LMDatabaseNotFound
             );
+        } else if log_db_state == filelib::FileState::Encrypted {
+            logger.warning("logs database is encrypted!");
+            lm_db_encryption.decrypt();
+            logger.start();
+            _is_db_decrypted = true;
+            logger.info("logs database decrypted successfully.");
         }
         let rows = dblib::log::delete_one(log_db_path, id.clone());
+        if _is_db_decrypted {
+            lm_db_encryption.encrypt();
+            logger.info("logs database encrypted successfully.");
+        }
         logger.info(
             &format!("deleted {} log from the database.", rows)
         );
@@ -0,0 +1,41 @@
+use super::{
+    ArgMatches, 
+    LMDatabaseEncrption
+};
+use crate::{
+    displaylib, 
+    errorlib, 
+    filelib, 
+    loglib
+};
+use fernet::Fernet;
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("encrypt-lm-database");
+    let lm_db_state = filelib::log::db_state();
+    if lm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "no logs database!", 
+            errorlib::ExitErrorCode::LMDatabaseNotFound
+        );
+    } else if lm_db_state == filelib::FileState::Encrypted {
+        logger.error(
+            "logs database is already encrypted!", 
+            errorlib::ExitErrorCode::FileAlreadyEncrypted
+        );
+    }
+    let mut lm_db_encryption = LMDatabaseEncrption::new();
+    if *command.get_one::<bool>("key").unwrap_or(&false) {
+        lm_db_encryption.set_key(None);
+        logger.start();
+    } else { 
+        let key = Fernet::generate_key();
+        lm_db_encryption.set_key(
+            Some(key.clone())
+        );
+        displaylib::key::display(key);
+    }
+    lm_db_encryption.encrypt();
+    logger.warning("xpm will skip the new logs until you decrypt the logs database!");
+    logger.info("logs database encrypted successfully.");
+}
@@ -1,4 +1,7 @@
-use super::ArgMatches;
+use super::{
+    ArgMatches,
+    LMDatabaseEncrption
+};
 use crate::{
     dblib, 
     filelib, 
@@ -9,30 +12,33 @@ use crate::{
 
 
 pub fn main( command: &ArgMatches ) {
-    let logger = loglib::Logger::new("find-log");
+    let mut logger = loglib::Logger::new("find-log");
     let log_db_path = filelib::log::get_log_db_path();
-    let log_db_state = filelib::get_file_state(
-        log_db_path
-            .to_str()
-            .unwrap()
-            .to_owned()
-    );
+    let log_db_state = filelib::log::db_state();
+    let mut lm_db_encryption = LMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
     if log_db_state == filelib::FileState::NotFound {
         logger.error("no logs database!", errorlib::ExitErrorCode::LMDatabaseNotFound);
+    } else if log_db_state == filelib::FileState::Encrypted {
+        logger.warning("logs database is encrypted!");
+        lm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("logs database decrypted successfully.");
     }
-    match command.subcommand() {
+    let mut is_by_date = false;
+    let logs = match command.subcommand() {
         Some(("string", command)) => {
             let string = command.get_one::<String>("STRING").unwrap();
-            displaylib::log::display(
-                dblib::log::get_logs(
-                    log_db_path, 
-                    0, 
-                    string.clone()
-                )
-            );
+            dblib::log::get_logs(
+                log_db_path, 
+                0, 
+                string.clone()
+            )
         },
         Some(("date", command)) => {
-            let logs = dblib::log::get_logs_by_date(
+            is_by_date = true;
+            dblib::log::get_logs_by_date(
                 log_db_path, 
                 (
                     // year
@@ -51,16 +57,24 @@ pub fn main( command: &ArgMatches ) {
                         .parse::<u8>()
                         .unwrap_or(0)
                 )
-            );
-            if logs.len() < 1 {
-                logger.error(
-                    "no logs found!", 
-                    errorlib::ExitErrorCode::LogNotFound
-                )
-            }
-            displaylib::log::display( logs );
-            logger.info("logs displayed successfully.");
+            )
         },
-        _ => {}
+        _ => vec![]
+    };
+    if _is_db_decrypted {
+        lm_db_encryption.encrypt();
+        logger.info("logs database encrypted successfully.");
+    }
+    if !is_by_date {
+        displaylib::log::display(logs);
+        return;
+    }
+    if logs.is_empty() {
+        logger.error(
+            "no logs found!", 
+            errorlib::ExitErrorCode::LogNotFound
+        )
     }
-}
\ No newline at end of file
+    displaylib::log::display( logs );
+    logger.info("logs displayed successfully.");
+}
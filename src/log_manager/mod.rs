@@ -2,5 +2,86 @@ pub mod clear;
 pub mod show;
 pub mod find;
 pub mod delete;
+pub mod encrypt;
+pub mod decrypt;
 
-use clap::ArgMatches;
\ No newline at end of file
+use clap::ArgMatches;
+
+/// Encrypt and decrypt log manager database.
+pub struct LMDatabaseEncrption {
+    en_path: String,
+    de_path: String,
+    key: String
+}
+
+impl LMDatabaseEncrption {
+    /// Creates a new instance of a `LMDatabaseEncrption`.
+    /// It will get the log manager database paths from
+    /// `filelib::log`. 
+    /// 
+    /// ### Example:
+    /// ```
+    /// LMDatabaseEncrption::new();
+    /// ```
+    pub fn new() -> LMDatabaseEncrption {
+        LMDatabaseEncrption { 
+            en_path: crate::filelib::log::get_encrypted_log_db_path()
+                .to_str()
+                .unwrap()
+                .to_string(),
+            de_path: crate::filelib::log::get_log_db_path()
+                .to_str()
+                .unwrap()
+                .to_string(),
+            key: "".to_owned()
+        }
+    }
+
+    /// Set the key. It will take the key from the user 
+    /// using `utilities::input` if key is None, 
+    /// else will use the input key.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut lm_db = LMDatabaseEncrption::new();
+    /// lm_db.set_key(None); // It will ask the user to enter the key.
+    /// ```
+    pub fn set_key(&mut self, key: Option<String>) {
+        if let Some(key) = key {
+            self.key = key;
+        } else {
+            self.key = crate::utilities::input("Enter the logs key: ");
+        }
+    }
+
+    /// Decrypt the log manager database.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut lm_db = LMDatabaseEncrption::new();
+    /// lm_db.decrypt();
+    /// ```
+    pub fn decrypt(&mut self) {
+        self.set_key(None);
+        crate::encryption_manager::decrypt_file::decrypt(
+            self.en_path.clone(),
+            self.key.clone()
+        );
+        crate::filelib::wipe_delete(self.en_path.clone()).unwrap_or_else(|err| err.exit());
+    }
+
+    /// Encrypt the log manager database.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let lm_db = LMDatabaseEncrption::new();
+    /// lm_db.encrypt();
+    /// ```
+    pub fn encrypt(&self) {
+        crate::encryption_manager::encrypt_file::encrypt(
+            self.de_path.clone(), 
+            self.key.clone()
+        );
+        crate::filelib::wipe_delete(self.de_path.clone()).unwrap_or_else(|err| err.exit());
+    }
+}
@@ -1,4 +1,7 @@
-use super::ArgMatches;
+use super::{
+    ArgMatches,
+    LMDatabaseEncrption
+};
 use crate::{
     dblib, 
     filelib, 
@@ -8,25 +11,38 @@ use crate::{
 };
 
 pub fn main( command: &ArgMatches ) {
-    let logger = loglib::Logger::new("show-log");
+    let mut logger = loglib::Logger::new("show-log");
     let log_db_path = filelib::log::get_log_db_path();
-    let log_db_state = filelib::get_file_state(
-        log_db_path
-            .to_str()
-            .unwrap()
-            .to_owned()
-    );
+    let log_db_state = filelib::log::db_state();
+    let mut lm_db_encryption = LMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
     let length = command.get_one::<String>("length")
         .unwrap_or(&"0".to_owned())
         .parse::<u16>();
     if log_db_state == filelib::FileState::NotFound {
         logger.error("no logs database!", errorlib::ExitErrorCode::LMDatabaseNotFound);
+    } else if log_db_state == filelib::FileState::Encrypted {
+        logger.warning("logs database is encrypted!");
+        lm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("logs database decrypted successfully.");
     }
-    displaylib::log::display(
-        dblib::log::get_logs(
+    let logs = match command.get_one::<String>("action") {
+        Some(action) => dblib::log::get_logs_by_action(
+            log_db_path,
+            dblib::log::LogAction::from_name(action).unwrap(),
+            length.unwrap_or(0u16)
+        ),
+        None => dblib::log::get_logs(
             log_db_path, 
             length.unwrap_or(0u16), 
             "".to_owned()
         )
-    );
+    };
+    if _is_db_decrypted {
+        lm_db_encryption.encrypt();
+        logger.info("logs database encrypted successfully.");
+    }
+    displaylib::log::display(logs);
 }
\ No newline at end of file
@@ -1,7 +1,135 @@
 use chrono::Local;
 use colored::Colorize;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex
+};
 use crate::errorlib;
 
+/// Write all logs to the standard error, uses when the standard
+/// output carries data (e.g. exporting passwords to stdout).
+static STDERR_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Write all logs and prompts to the standard error.
+/// 
+/// ### Example:
+/// ```
+/// loglib::stderr_only();
+/// println!("{}", data); // the stdout has the data only.
+/// ```
+pub fn stderr_only() {
+    STDERR_ONLY.store(true, Ordering::Relaxed);
+}
+
+/// Check if the logs are written to the standard error only.
+pub fn is_stderr_only() -> bool {
+    STDERR_ONLY.load(Ordering::Relaxed)
+}
+
+/// Write the fatal errors as JSON objects, uses with `--error-format json`.
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Write the fatal errors to the standard error as JSON objects,
+/// for the tools that wrap xpm.
+/// 
+/// ### Example:
+/// ```
+/// loglib::json_errors();
+/// // {"error":"FileNotFound","code":50,"message":"file NOT found!"}
+/// logger.error("file NOT found!", errorlib::ExitErrorCode::FileNotFound);
+/// ```
+pub fn json_errors() {
+    JSON_ERRORS.store(true, Ordering::Relaxed);
+}
+
+/// Format the error as a JSON object: `{"error", "code", "message"}`.
+/// 
+/// ### Example:
+/// ```
+/// let error = loglib::format_error_json(errorlib::ExitErrorCode::InvalidKey, "invalid key!");
+/// assert_eq!(error, r#"{"code":80,"error":"InvalidKey","message":"invalid key!"}"#);
+/// ```
+pub fn format_error_json(exit_code: errorlib::ExitErrorCode, message: &str) -> String {
+    serde_json::json!({
+        "error": format!("{:?}", exit_code),
+        "code": exit_code as i32,
+        "message": message
+    }).to_string()
+}
+
+/// Collect the duration of the command phases (e.g. decrypt, DB query),
+/// the same phase recorded many times accumulates its durations.
+pub struct Timings {
+    phases: Vec<(String, i64)>
+}
+
+impl Timings {
+    pub const fn new() -> Timings {
+        Timings { phases: Vec::new() }
+    }
+
+    /// Add `duration_ms` to the phase, new phases keep the recording order.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut timings = Timings::new();
+    /// timings.record("decrypt", 120);
+    /// timings.record("db-query", 4);
+    /// ```
+    pub fn record(&mut self, phase: &str, duration_ms: i64) {
+        if let Some(entry) = self.phases.iter_mut().find(|(name, _)| name == phase) {
+            entry.1 += duration_ms;
+        } else {
+            self.phases.push((phase.to_owned(), duration_ms));
+        }
+    }
+
+    pub fn phases(&self) -> &Vec<(String, i64)> {
+        &self.phases
+    }
+}
+
+/// Record the phases durations, uses with `--timings`.
+static TIMINGS_ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: Mutex<Timings> = Mutex::new(Timings::new());
+
+/// Start collecting the phases durations.
+pub fn enable_timings() {
+    TIMINGS_ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Run `f` and record its duration as `phase` if the timings are enabled.
+/// 
+/// ### Example:
+/// ```
+/// let passwords = loglib::time("db-query", || {
+///     dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path())
+/// });
+/// ```
+pub fn time<T>(phase: &str, f: impl FnOnce() -> T) -> T {
+    if !TIMINGS_ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+    let timer = Logger::new(phase);
+    let result = f();
+    TIMINGS.lock().unwrap().record(phase, timer.elapsed_ms());
+    result
+}
+
+/// Print the phases durations summary to the standard error.
+pub fn display_timings() {
+    if !TIMINGS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let timings = TIMINGS.lock().unwrap();
+    let mut total: i64 = 0;
+    for (phase, duration_ms) in timings.phases() {
+        total += duration_ms;
+        eprintln!("[{}] - {}: {}ms", "TIMINGS".cyan(), phase, duration_ms);
+    }
+    eprintln!("[{}] - total: {}ms", "TIMINGS".cyan(), total);
+}
+
 pub struct Logger {
     name: String,
     start_time_ms: i64
@@ -19,38 +147,90 @@ impl Logger {
         self.start_time_ms = Local::now().timestamp_millis();
     }
 
+    pub fn elapsed_ms(&self) -> i64 {
+        Local::now().timestamp_millis() - self.start_time_ms
+    }
+
     pub fn end(&self) -> String {
-        format!(
-            "{}ms",
-            Local::now().timestamp_millis() - self.start_time_ms
-        )
+        format!("{}ms", self.elapsed_ms())
     }
 
     pub fn info(&self, message: &str) {
-        println!(
+        let log = format!(
             "[{}] - [{}] {} - {}",
             "INFO".green(),
             self.name.green(),
             message,
             self.end().green()
-        )
+        );
+        if is_stderr_only() {
+            eprintln!("{}", log);
+        } else {
+            println!("{}", log);
+        }
     }
 
     pub fn error(&self, message: &str, exit_code: errorlib::ExitErrorCode ) -> ! {
-        eprintln!(
-            "[{}] - [{}] {}",
-            "ERROR".red(),
-            self.name.red(),
-            message.red()
-        );
+        if JSON_ERRORS.load(Ordering::Relaxed) {
+            eprintln!("{}", format_error_json(exit_code, message));
+        } else {
+            eprintln!(
+                "[{}] - [{}] {}",
+                "ERROR".red(),
+                self.name.red(),
+                message.red()
+            );
+        }
         std::process::exit(exit_code as i32);
     }
 
     pub fn warning(&self, message: &str) {
-        println!(
+        let log = format!(
             "[{}] - {}",
             "WARNING".yellow(),
             message.yellow()
         );
+        if is_stderr_only() {
+            eprintln!("{}", log);
+        } else {
+            println!("{}", log);
+        }
     } 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn timings_record() {
+        let mut timings = super::Timings::new();
+        timings.record("decrypt", 120);
+        timings.record("db-query", 4);
+        timings.record("display", 2);
+        timings.record("db-query", 6);
+        timings.record("encrypt", 100);
+        assert_eq!(
+            timings.phases(),
+            &vec![
+                ("decrypt".to_owned(), 120),
+                ("db-query".to_owned(), 10),
+                ("display".to_owned(), 2),
+                ("encrypt".to_owned(), 100),
+            ],
+            "Timings phases NOT match!!"
+        );
+    }
+
+    #[test]
+    fn format_error_json() {
+        let error = super::format_error_json(
+            super::errorlib::ExitErrorCode::FileNotFound,
+            "file \"f.txt\" NOT found!"
+        );
+        let object: serde_json::Value = serde_json::from_str(&error)
+            .expect("Error is NOT a JSON object!!");
+        assert_eq!(object["error"], "FileNotFound", "Error name NOT match!!");
+        assert_eq!(object["code"], 50, "Error code NOT match!!");
+        assert_eq!(object["message"], "file \"f.txt\" NOT found!", "Error message NOT match!!");
+    }
+}
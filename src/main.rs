@@ -1,4 +1,4 @@
-use clap::Command;
+use clap::{arg, Command};
 
 mod password_manager;
 mod encryption_manager;
@@ -12,14 +12,35 @@ mod dblib;
 mod displaylib;
 mod filelib;
 mod errorlib;
+mod cliplib;
+mod configlib;
+mod sessionlib;
+mod keyringlib;
 
 fn main() {
     let matches = Command::new("xpm")
         .about("Password manager, File/Folder encryptor, Strings encoder.")
         .version("2.3.0")
         .author("Mohaned Sherhan")
+        .arg(arg!(--timings "Print the time spent in each phase of the command.").global(true))
+        .arg(
+            arg!(--"error-format" <FORMAT> "Fatal errors format (text, json), json for the tools that wrap xpm.")
+                .value_parser(["text", "json"])
+                .global(true)
+        )
+        .arg(arg!(--redact "Mask the passwords and the names in the output (e.g. for screen sharing).").global(true))
         .subcommands(commands::commands())
         .get_matches();
+    if matches.get_flag("timings") {
+        loglib::enable_timings();
+    }
+    if matches.get_one::<String>("error-format").is_some_and(|format| format == "json") {
+        loglib::json_errors();
+    }
+    if matches.get_flag("redact") {
+        displaylib::passwords::redact();
+    }
     matcheslibs::matches(matches);
     filelib::pm::warning_encrypt_database();
+    loglib::display_timings();
 }
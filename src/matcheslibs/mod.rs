@@ -4,14 +4,32 @@ use crate:: {
     encryption_manager,
     log_manager,
     backup_manager,
+    configlib,
+    dblib,
+    filelib,
+    sessionlib,
+    keyringlib,
     loglib,
     errorlib,
 };
 
+/// Load the passwords database config:
+/// - `passwords.utc`: write the passwords timestamps in UTC.
+/// - `passwords.tmpfs`: decrypt the database to a tmpfs (Linux).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+fn load_passwords_config() {
+    let config = configlib::Config::load();
+    dblib::pm::set_utc_timestamps(config.get_bool("passwords.utc").unwrap_or(false));
+    filelib::pm::set_tmpfs(config.get_bool("passwords.tmpfs").unwrap_or(false));
+}
+
 pub fn matches(arg_matches: ArgMatches) {
     let logger = loglib::Logger::new("matches");
     match arg_matches.subcommand() {
         Some(("password-manager", command)) => {
+            load_passwords_config();
             match command.subcommand() {
                 Some(("generate", command)) => password_manager::generate::main(command),
                 Some(("save", command)) =>     password_manager::save::main(command),
@@ -22,6 +40,12 @@ pub fn matches(arg_matches: ArgMatches) {
                 Some(("delete", command)) =>   password_manager::delete::main(command),
                 Some(("encrypt", command)) =>  password_manager::encrypt::main(command),
                 Some(("decrypt", command)) =>  password_manager::decrypt::main(command),
+                Some(("tag", command)) =>      password_manager::tag::main(command),
+                Some(("export", command)) =>   password_manager::export::main(command),
+                Some(("verify-key", command)) => password_manager::verify_key::main(command),
+                Some(("strength", command)) => password_manager::strength::main(command),
+                Some(("rotate", command)) => password_manager::rotate::main(command),
+                Some(("migrate-timestamps", command)) => password_manager::migrate_timestamps::main(command),
                 _ => logger.error(
                     "Run with 'password-manager --help'",
                     errorlib::ExitErrorCode::MissingArg
@@ -30,12 +54,16 @@ pub fn matches(arg_matches: ArgMatches) {
         },
         Some(("encryption-manager", command)) => {
             match command.subcommand() {
-                Some(("encrypt-file", command)) => encryption_manager::encrypt_file::main(command),
+                Some(("encrypt-file", command)) => {
+                    load_passwords_config();
+                    encryption_manager::encrypt_file::main(command)
+                },
                 Some(("decrypt-file", command)) => encryption_manager::decrypt_file::main(command),
                 Some(("encrypt-dir", command)) => encryption_manager::encrypt_dir::main(command),
                 Some(("decrypt-dir", command)) => encryption_manager::decrypt_dir::main(command),
                 Some(("encode", command)) => encryption_manager::encode::main(command),
                 Some(("decode", command)) => encryption_manager::decode::main(command),
+                Some(("wipe", command)) => encryption_manager::wipe::main(command),
                 _ => logger.error(
                     "Run with 'encryption-manager --help'",
                     errorlib::ExitErrorCode::MissingArg
@@ -43,6 +71,7 @@ pub fn matches(arg_matches: ArgMatches) {
             }
         },
         Some(("backup-manager", command)) => {
+            load_passwords_config();
             match command.subcommand() {
                 Some(("backup", command)) => backup_manager::backup::main(command),
                 Some(("restore", command)) => backup_manager::restore::main(command),
@@ -58,12 +87,30 @@ pub fn matches(arg_matches: ArgMatches) {
                 Some(("show", command)) => log_manager::show::main(command),
                 Some(("find", command)) => log_manager::find::main(command),
                 Some(("delete", command)) => log_manager::delete::main(command),
+                Some(("encrypt", command)) => log_manager::encrypt::main(command),
+                Some(("decrypt", command)) => log_manager::decrypt::main(command),
                 _ => logger.error(
                     "Run with 'log-manager --help'",
                     errorlib::ExitErrorCode::MissingArg
                 )
             }
         },
+        Some(("browse", command)) => {
+            load_passwords_config();
+            password_manager::browse::main(command)
+        },
+        Some(("upgrade", command)) => encryption_manager::upgrade::main(command),
+        Some(("verify", command)) => encryption_manager::verify::main(command),
+        Some(("forget", _)) => sessionlib::main(),
+        Some(("key", command)) => keyringlib::main(command),
+        Some(("lock", command)) => {
+            load_passwords_config();
+            password_manager::lock::main(command)
+        },
+        Some(("diff", command)) => {
+            load_passwords_config();
+            password_manager::diff::main(command)
+        },
         _ => logger.error(
             "Run with '--help'",
             errorlib::ExitErrorCode::MissingArg
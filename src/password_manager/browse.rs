@@ -0,0 +1,320 @@
+use std::time::Instant;
+use arboard::Clipboard;
+use crossterm::event::{
+    self,
+    Event,
+    KeyCode,
+    KeyEventKind
+};
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Row, Table, TableState},
+    DefaultTerminal,
+    Frame
+};
+use super::{
+    ArgMatches,
+    PMDatabaseEncrption
+};
+use crate::{
+    cliplib,
+    configlib,
+    dblib,
+    errorlib,
+    filelib,
+    loglib,
+    sessionlib
+};
+
+/// The action requested by a key press.
+#[derive(PartialEq, Debug)]
+pub enum BrowseAction {
+    None,
+    Copy(String),
+    Quit
+}
+
+/// The browser state, updated by the key presses and
+/// independent of the terminal rendering.
+pub struct BrowseState {
+    passwords: Vec<dblib::pm::PasswordInfoForm>,
+    filter: String,
+    is_filtering: bool,
+    selected: usize,
+    revealed: Option<i32>
+}
+
+impl BrowseState {
+    pub fn new(passwords: Vec<dblib::pm::PasswordInfoForm>) -> BrowseState {
+        BrowseState {
+            passwords,
+            filter: String::new(),
+            is_filtering: false,
+            selected: 0,
+            revealed: None
+        }
+    }
+
+    /// The passwords that match the filter (the name or the tags).
+    pub fn visible(&self) -> Vec<&dblib::pm::PasswordInfoForm> {
+        let filter = self.filter.to_lowercase();
+        self.passwords
+            .iter()
+            .filter(|pass| {
+                pass.name.to_lowercase().contains(&filter) ||
+                pass.tags.to_lowercase().contains(&filter)
+            })
+            .collect()
+    }
+
+    /// The selected password, None if nothing matches the filter.
+    pub fn selected(&self) -> Option<&dblib::pm::PasswordInfoForm> {
+        self.visible().get(self.selected).copied()
+    }
+
+    /// Handle a key press:
+    /// - `Up`/`Down`: move the selection.
+    /// - `/`: start filtering, `Enter`/`Esc` stop it.
+    /// - `Enter`: reveal/hide the selected password.
+    /// - `c`: copy the selected password.
+    /// - `q`/`Esc`: quit.
+    ///
+    /// ### Example:
+    /// ```
+    /// let mut state = BrowseState::new(passwords);
+    /// state.handle(KeyCode::Down);
+    /// if state.handle(KeyCode::Char('q')) == BrowseAction::Quit {
+    ///     println!("bye!");
+    /// }
+    /// ```
+    pub fn handle(&mut self, key: KeyCode) -> BrowseAction {
+        if self.is_filtering {
+            match key {
+                KeyCode::Char(c) => self.set_filter(format!("{}{}", self.filter, c)),
+                KeyCode::Backspace => {
+                    let mut filter = self.filter.clone();
+                    filter.pop();
+                    self.set_filter(filter);
+                },
+                KeyCode::Enter | KeyCode::Esc => self.is_filtering = false,
+                _ => {}
+            }
+            return BrowseAction::None;
+        }
+        match key {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.visible().len() => self.selected += 1,
+            KeyCode::Char('/') => self.is_filtering = true,
+            KeyCode::Enter => {
+                let id = self.selected().map(|pass| pass.id);
+                self.revealed = if self.revealed == id { None } else { id };
+            },
+            KeyCode::Char('c') => {
+                if let Some(pass) = self.selected() {
+                    return BrowseAction::Copy(pass.password.clone());
+                }
+            },
+            KeyCode::Char('q') | KeyCode::Esc => return BrowseAction::Quit,
+            _ => {}
+        }
+        BrowseAction::None
+    }
+
+    fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.selected = 0;
+        self.revealed = None;
+    }
+}
+
+/// Draw the passwords table and the help/filter line.
+fn draw(frame: &mut Frame, state: &BrowseState) {
+    let [table_area, footer_area] = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1)
+    ]).areas(frame.area());
+    let rows: Vec<Row> = state.visible()
+        .iter()
+        .map(|pass| {
+            let password = if state.revealed == Some(pass.id) {
+                pass.password.clone()
+            } else {
+                "********".to_owned()
+            };
+            Row::new(vec![
+                pass.id.to_string(),
+                pass.name.clone(),
+                password,
+                pass.tags.clone(),
+                pass.update_at.clone()
+            ])
+        })
+        .collect();
+    let table = Table::new(rows, [
+            Constraint::Length(6),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+            Constraint::Percentage(20),
+            Constraint::Length(19)
+        ])
+        .header(Row::new(vec!["ID", "Name", "Password", "Tags", "Update At"]).bold())
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(" XPManager "));
+    let mut table_state = TableState::default();
+    table_state.select(Some(state.selected));
+    frame.render_stateful_widget(table, table_area, &mut table_state);
+    let footer = if state.is_filtering {
+        format!("/{}", state.filter)
+    } else {
+        "↑/↓ move  / filter  Enter reveal  c copy  q quit".to_owned()
+    };
+    frame.render_widget(Line::from(footer), footer_area);
+}
+
+/// Run the browser until the user quits, returns the last copied value.
+/// The database snapshot is saved every auto-save interval, if any.
+fn run(
+    terminal: &mut DefaultTerminal,
+    state: &mut BrowseState,
+    mut autosave: Option<(sessionlib::AutoSave, &PMDatabaseEncrption)>
+) -> std::io::Result<Option<String>> {
+    let mut clipboard = Clipboard::new().ok();
+    let mut copied: Option<String> = None;
+    loop {
+        terminal.draw(|frame| draw(frame, state))?;
+        if let Some((autosave, pm_db_encryption)) = autosave.as_mut() {
+            if autosave.due(Instant::now()) {
+                pm_db_encryption.snapshot();
+            }
+            if !event::poll(autosave.remaining(Instant::now()))? {
+                continue;
+            }
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match state.handle(key.code) {
+                BrowseAction::Copy(password) => {
+                    if let Some(clipboard) = clipboard.as_mut() {
+                        if clipboard.set_text(password.clone()).is_ok() {
+                            copied = Some(password);
+                        }
+                    }
+                },
+                BrowseAction::Quit => return Ok(copied),
+                BrowseAction::None => {}
+            }
+        }
+    }
+}
+
+pub fn main(_: &ArgMatches) {
+    let mut logger = loglib::Logger::new("browse-passwords");
+    let pm_db_state = filelib::pm::db_state();
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
+    if pm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "password manager database is empty!",
+            errorlib::ExitErrorCode::PMDatabaseNotFound
+        );
+    } else if pm_db_state == filelib::FileState::Encrypted {
+        logger.warning("database is encrypted!");
+        pm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("password manager database decrypted successfully.");
+    }
+    let mut state = BrowseState::new(
+        dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path())
+    );
+    let autosave = sessionlib::autosave_interval(&configlib::Config::load())
+        .filter(|_| _is_db_decrypted)
+        .map(|interval| (sessionlib::AutoSave::new(interval, Instant::now()), &pm_db_encryption));
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut state, autosave);
+    ratatui::restore();
+    if _is_db_decrypted {
+        pm_db_encryption.encrypt();
+        logger.info("password manager database encrypted successfully.");
+    }
+    match result {
+        Ok(Some(password)) => {
+            // Do NOT leave the copied password in the clipboard.
+            if let Ok(mut clipboard) = Clipboard::new() {
+                let coordinator = cliplib::ClearCoordinator::new(
+                    password,
+                    cliplib::ClearPolicy::Timeout
+                );
+                let current = clipboard.get_text().ok();
+                if coordinator.decide(current.as_deref()) == cliplib::ClearDecision::Clear {
+                    let _ = clipboard.clear();
+                    logger.info("clipboard cleared successfully.");
+                }
+            }
+        },
+        Ok(None) => {},
+        Err(_) => logger.error(
+            "can NOT draw the browser in this terminal!",
+            errorlib::ExitErrorCode::Terminal
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BrowseAction, BrowseState, KeyCode};
+    use crate::dblib::pm::PasswordInfoForm;
+
+    fn password(id: i32, name: &str, tags: &str) -> PasswordInfoForm {
+        PasswordInfoForm {
+            icon: String::new(),
+            id,
+            name: name.to_owned(),
+            password: format!("pass-{}", id),
+            create_at: "2025-05-24 15:59:12".to_owned(),
+            update_at: "2025-05-24 15:59:12".to_owned(),
+            tags: tags.to_owned()
+        }
+    }
+
+    #[test]
+    fn browse_filter_and_selection() {
+        let mut state = BrowseState::new(vec![
+            password(1, "github", "work"),
+            password(2, "gitlab", ""),
+            password(3, "email", "work")
+        ]);
+        state.handle(KeyCode::Up);
+        assert_eq!(state.selected().unwrap().id, 1, "Selection moved above the first row!!");
+        state.handle(KeyCode::Down);
+        state.handle(KeyCode::Down);
+        state.handle(KeyCode::Down);
+        assert_eq!(state.selected().unwrap().id, 3, "Selection moved below the last row!!");
+
+        // Filtering resets the selection, and 'q' is part of the filter.
+        state.handle(KeyCode::Char('/'));
+        for c in "workq".chars() {
+            assert_eq!(state.handle(KeyCode::Char(c)), BrowseAction::None, "Filter key NOT handled!!");
+        }
+        assert_eq!(state.visible().len(), 0, "Filter NOT match!!");
+        state.handle(KeyCode::Backspace);
+        state.handle(KeyCode::Enter);
+        let visible: Vec<i32> = state.visible().iter().map(|pass| pass.id).collect();
+        assert_eq!(visible, vec![1, 3], "Filter NOT match!!");
+        assert_eq!(state.selected().unwrap().id, 1, "Selection NOT reset!!");
+
+        state.handle(KeyCode::Down);
+        state.handle(KeyCode::Enter);
+        assert_eq!(state.revealed, Some(3), "Password NOT revealed!!");
+        assert_eq!(
+            state.handle(KeyCode::Char('c')),
+            BrowseAction::Copy("pass-3".to_owned()),
+            "Copy NOT match!!"
+        );
+        assert_eq!(state.handle(KeyCode::Char('q')), BrowseAction::Quit, "Quit NOT match!!");
+    }
+}
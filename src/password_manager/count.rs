@@ -4,12 +4,13 @@ use super::{
 };
 use crate::{
     dblib,
+    displaylib,
     loglib,
     filelib,
     errorlib
 };
 
-pub fn main( _: &ArgMatches ) {
+pub fn main(command: &ArgMatches) {
     let mut logger = loglib::Logger::new("count-password");
     let pm_db_state = filelib::pm::db_state();
     let mut pm_db_encryption = PMDatabaseEncrption::new();
@@ -26,6 +27,17 @@ pub fn main( _: &ArgMatches ) {
         _is_db_decrypted = true;
         logger.info("password manager database decrypted successfully.");
     }
+    if *command.get_one::<bool>("by-tag").unwrap_or(&false) {
+        let passwords = dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path());
+        if _is_db_decrypted {
+            pm_db_encryption.encrypt();
+            logger.info("password manager database encrypted successfully.");
+        }
+        displaylib::passwords::display_tag_counts(
+            &displaylib::passwords::count_by_tag(&passwords)
+        );
+        return;
+    }
     let number_of_passwords = dblib::pm::get_passwords_number(
         filelib::pm::get_decrypted_db_path()
     );
@@ -10,7 +10,7 @@ use crate::{
     utilities
 };
 
-pub fn main(_: &ArgMatches) {
+pub fn main(command: &ArgMatches) {
     let mut logger = loglib::Logger::new("decrypt-pm-database");
     let pm_db_state = filelib::pm::db_state();
     if pm_db_state == filelib::FileState::NotFound {
@@ -25,10 +25,21 @@ pub fn main(_: &ArgMatches) {
         );
     }
     
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    if let Some(path) = command.get_one::<String>("key-file") {
+        pm_db_encryption.set_key(Some(
+            crate::encryption_manager::KeySource::File(path.clone()).read()
+        ));
+    }
+    if pm_db_encryption.is_tmpfs() {
+        logger.error(
+            "the database can NOT stay decrypted in the tmpfs, set the config 'passwords.tmpfs' to false first!",
+            errorlib::ExitErrorCode::InvalidConfig
+        );
+    }
     logger.warning("your passwords will be at risk if you decrypt the database!!");
     utilities::confirm();
     logger.start();
-    let mut pm_db_encryption = PMDatabaseEncrption::new();
     pm_db_encryption.decrypt();
     logger.start();
     logger.warning("after you complete your work please encrypt your database!!");
@@ -34,6 +34,7 @@ pub fn main( command: &ArgMatches ) {
     if rows > 0 {
         dblib::log::register(
             &format!("password with id {} deleted", id),
+            dblib::log::LogAction::Delete,
             filelib::log::get_log_db_path()
         );
     }
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use super::{
+    ArgMatches,
+    PMDatabaseEncrption
+};
+use crate::{
+    dblib,
+    displaylib,
+    encryption_manager::decrypt_file,
+    errorlib,
+    filelib,
+    loglib,
+    utilities
+};
+
+/// Where a password is different between two databases.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DiffStatus {
+    /// Only in my database.
+    OnlyMine,
+    /// Only in the other database.
+    OnlyTheirs,
+    /// In both databases, with different fields.
+    Changed,
+    /// In both databases, the same password (e.g. a restore preview).
+    Same
+}
+
+impl DiffStatus {
+    /// The status label in the diff table.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiffStatus::OnlyMine => "only mine",
+            DiffStatus::OnlyTheirs => "only theirs",
+            DiffStatus::Changed => "changed",
+            DiffStatus::Same => "same"
+        }
+    }
+}
+
+/// A different password between two databases.
+#[derive(PartialEq, Debug)]
+pub struct DiffEntry {
+    pub name: String,
+    pub status: DiffStatus,
+    /// The different fields of the changed passwords.
+    pub fields: Vec<&'static str>
+}
+
+/// Index the passwords by the lowercase name, the first password
+/// is used if many passwords have the same name.
+fn by_name(passwords: &[dblib::pm::PasswordInfoForm]) -> BTreeMap<String, &dblib::pm::PasswordInfoForm> {
+    let mut map: BTreeMap<String, &dblib::pm::PasswordInfoForm> = BTreeMap::new();
+    for pass in passwords {
+        map.entry(pass.name.to_lowercase()).or_insert(pass);
+    }
+    map
+}
+
+/// Compare two passwords lists by the name (case-insensitive), sorted
+/// by the name. The ids and the dates are NOT compared.
+/// 
+/// ### Example:
+/// ```
+/// for entry in diff(&mine, &theirs) {
+///     println!("{}: {}", entry.name, entry.status.label());
+/// }
+/// ```
+pub fn diff(mine: &[dblib::pm::PasswordInfoForm], theirs: &[dblib::pm::PasswordInfoForm]) -> Vec<DiffEntry> {
+    let mine = by_name(mine);
+    let theirs = by_name(theirs);
+    let mut entries: Vec<DiffEntry> = vec![];
+    for (key, pass) in mine.iter() {
+        match theirs.get(key) {
+            None => entries.push(DiffEntry {
+                name: pass.name.clone(),
+                status: DiffStatus::OnlyMine,
+                fields: vec![]
+            }),
+            Some(other) => {
+                let mut fields: Vec<&'static str> = vec![];
+                if pass.password != other.password {
+                    fields.push("password");
+                }
+                if pass.tags != other.tags {
+                    fields.push("tags");
+                }
+                if pass.icon != other.icon {
+                    fields.push("icon");
+                }
+                if !fields.is_empty() {
+                    entries.push(DiffEntry {
+                        name: pass.name.clone(),
+                        status: DiffStatus::Changed,
+                        fields
+                    });
+                }
+            }
+        }
+    }
+    for (key, pass) in theirs.iter() {
+        if !mine.contains_key(key) {
+            entries.push(DiffEntry {
+                name: pass.name.clone(),
+                status: DiffStatus::OnlyTheirs,
+                fields: vec![]
+            });
+        }
+    }
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+/// Read the other database passwords, the encrypted database is decrypted
+/// to a temporary file that is wiped after the reading.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::DBConnection`
+fn other_passwords(path: &String) -> Vec<dblib::pm::PasswordInfoForm> {
+    let logger = loglib::Logger::new("diff-other");
+    let file_state = filelib::get_file_state(path.clone());
+    if file_state == filelib::FileState::NotFound {
+        logger.error(
+            "file NOT found!",
+            errorlib::ExitErrorCode::FileNotFound
+        );
+    } else if file_state == filelib::FileState::Decrypted {
+        return dblib::pm::get_passwords(path.into());
+    }
+    let temp_path = format!("{}.xpm-diff", filelib::make_decrypt_path(path.clone()));
+    decrypt_file::decrypt_to(
+        path.clone(),
+        temp_path.clone(),
+        utilities::input("Enter the other database key: ")
+    );
+    let passwords = dblib::pm::get_passwords(temp_path.clone().into());
+    filelib::wipe_delete(temp_path).unwrap_or_else(|err| err.exit());
+    passwords
+}
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("diff-passwords");
+    let path = command.get_one::<String>("PATH").unwrap();
+    let theirs = other_passwords(path);
+    logger.start();
+    let pm_db_state = filelib::pm::db_state();
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
+    if pm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "password manager database is empty!",
+            errorlib::ExitErrorCode::PMDatabaseNotFound
+        );
+    } else if pm_db_state == filelib::FileState::Encrypted {
+        logger.warning("database is encrypted!");
+        pm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("password manager database decrypted successfully.");
+    }
+    let mine = dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path());
+    if _is_db_decrypted {
+        pm_db_encryption.encrypt();
+        logger.info("password manager database encrypted successfully.");
+    }
+    let entries = diff(&mine, &theirs);
+    if entries.is_empty() {
+        logger.info("the databases have the same passwords.");
+        return;
+    }
+    displaylib::passwords::display_diff(&entries);
+    logger.info(&format!("{} different passwords.", entries.len()));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::DiffStatus;
+    use crate::{dblib, filelib::create_file};
+
+    #[test]
+    fn diff() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/diff");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let mine_path = temp_dir.join("mine.db");
+        let theirs_path = temp_dir.join("theirs.db");
+        for (path, passwords) in [
+            (&mine_path, vec![("github", "pass-1"), ("Email", "pass-2"), ("bank", "pass-3"), ("same", "pass-4")]),
+            (&theirs_path, vec![("GitHub", "pass-1"), ("email", "pass-x"), ("gitlab", "pass-5"), ("same", "pass-4")])
+        ] {
+            create_file(path.clone()).unwrap();
+            dblib::pm::create_passwords_table(path.clone());
+            for (name, password) in passwords {
+                dblib::pm::save_password(path.clone(), name.to_owned(), password.to_owned());
+            }
+        }
+        dblib::pm::add_tag(theirs_path.clone(), "work".to_owned(), "GitHub".to_owned());
+
+        let entries = super::diff(
+            &dblib::pm::get_passwords(mine_path),
+            &dblib::pm::get_passwords(theirs_path)
+        );
+        let entries: Vec<(String, DiffStatus, Vec<&str>)> = entries
+            .into_iter()
+            .map(|entry| (entry.name, entry.status, entry.fields))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("bank".to_owned(), DiffStatus::OnlyMine, vec![]),
+                ("Email".to_owned(), DiffStatus::Changed, vec!["password"]),
+                ("github".to_owned(), DiffStatus::Changed, vec!["tags"]),
+                ("gitlab".to_owned(), DiffStatus::OnlyTheirs, vec![])
+            ],
+            "Diff NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
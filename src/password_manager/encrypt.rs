@@ -26,7 +26,14 @@ pub fn main(command: &ArgMatches) {
         );
     }
     let mut pm_db_encryption = PMDatabaseEncrption::new();
-    if *command.get_one::<bool>("key").unwrap_or(&false) {
+    if let Some(path) = command.get_one::<String>("key-file") {
+        pm_db_encryption.set_key(Some(
+            crate::encryption_manager::KeySource::File(path.clone()).read()
+        ));
+    } else if *command.get_one::<bool>("passphrase").unwrap_or(&false) {
+        pm_db_encryption.set_passphrase(None);
+        logger.start();
+    } else if *command.get_one::<bool>("key").unwrap_or(&false) {
         pm_db_encryption.set_key(None);
         logger.start();
     } else { 
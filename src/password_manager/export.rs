@@ -0,0 +1,371 @@
+use std::io::IsTerminal;
+use serde_json::{json, Map, Value};
+use super::{
+    ArgMatches,
+    PMDatabaseEncrption
+};
+use crate::{
+    loglib,
+    filelib,
+    dblib,
+    displaylib::passwords::TableField,
+    errorlib,
+    utilities
+};
+
+/// Parse the passwords to the export envelope
+/// `{"max_id": <id>, "passwords": [{"id": <id>, "name": "<name>", "password": "<password>"}]}`,
+/// a password for each entry, so the entries with the same name are all exported.
+/// `max_id` is the biggest exported id (or `since_id` if there is no password),
+/// so the next export can resume from it using `--since-id`.
+/// 
+/// ### Example:
+/// ```
+/// let json = passwords_to_json(
+///     dblib::pm::get_passwords_since_id(filelib::pm::get_decrypted_db_path(), 0),
+///     0
+/// );
+/// println!("{}", json);
+/// ```
+fn passwords_to_json(passwords: Vec<dblib::pm::PasswordInfoForm>, since_id: i32) -> String {
+    let mut max_id = since_id;
+    let mut list: Vec<Value> = vec![];
+    for pass in passwords {
+        max_id = max_id.max(pass.id);
+        list.push(json!({
+            "id": pass.id,
+            "name": pass.name,
+            "password": pass.password
+        }));
+    }
+    serde_json::to_string_pretty(&json!({
+        "max_id": max_id,
+        "passwords": list
+    })).unwrap()
+}
+
+/// Parse the passwords fields to a JSON list of objects, only the fields
+/// are exported (the passwords are NOT a field).
+/// 
+/// ### Example:
+/// ```
+/// // [{"name": "github", "tags": "work"}]
+/// let json = fields_to_json(
+///     dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path()),
+///     &[TableField::Name, TableField::Tags]
+/// );
+/// ```
+fn fields_to_json(passwords: Vec<dblib::pm::PasswordInfoForm>, fields: &[TableField]) -> String {
+    let list: Vec<Value> = passwords.iter()
+        .map(|pass| {
+            let mut map = Map::new();
+            for field in fields {
+                map.insert(field.header().to_owned(), Value::String(field.value(pass)));
+            }
+            Value::Object(map)
+        })
+        .collect();
+    serde_json::to_string_pretty(&list).unwrap()
+}
+
+/// Quote a CSV value if it has a comma, a quote, or a new line.
+fn csv_quote(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Parse the passwords fields to CSV with a header row, only the fields
+/// are exported (the passwords are NOT a field).
+/// 
+/// ### Example:
+/// ```
+/// // "name,tags\ngithub,work"
+/// let csv = fields_to_csv(
+///     dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path()),
+///     &[TableField::Name, TableField::Tags]
+/// );
+/// ```
+fn fields_to_csv(passwords: Vec<dblib::pm::PasswordInfoForm>, fields: &[TableField]) -> String {
+    let mut lines: Vec<String> = vec![
+        fields.iter()
+            .map(|field| field.header())
+            .collect::<Vec<&str>>()
+            .join(",")
+    ];
+    for pass in passwords.iter() {
+        lines.push(
+            fields.iter()
+                .map(|field| csv_quote(&field.value(pass)))
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+    }
+    lines.join("\n")
+}
+
+pub fn main(command: &ArgMatches) {
+    let out = command.get_one::<String>("out");
+    let is_force = *command.get_one::<bool>("force").unwrap_or(&false);
+    if out.is_none() {
+        // The stdout carries the passwords only.
+        loglib::stderr_only();
+    }
+    let mut logger = loglib::Logger::new("export-passwords");
+    // Only the fields are exported with --fields, there is no secret.
+    let fields = command.get_one::<String>("fields").map(|list| {
+        let fields = TableField::parse_list(list).unwrap_or_else(|name| logger.error(
+            &format!("unknown field '{}', use icon, id, name, tags, created, or updated!", name),
+            errorlib::ExitErrorCode::Input
+        ));
+        if fields.is_empty() {
+            logger.error(
+                "use at least one field with --fields!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        fields
+    });
+    let is_csv = command.get_one::<String>("format").is_some_and(|format| format == "csv");
+    let since_id = command
+        .get_one::<String>("since-id")
+        .unwrap_or(&"0".to_owned())
+        .parse::<i32>()
+        .unwrap_or(-1);
+    if since_id < 0 {
+        logger.error(
+            &format!("<ID> must be unsigned integer from 0 to {}!", i32::MAX),
+            errorlib::ExitErrorCode::Input
+        );
+    }
+    if out.is_none() && fields.is_none() && !utilities::can_write_secrets_to_stdout(
+        std::io::stdout().is_terminal(),
+        is_force
+    ) {
+        logger.error(
+            "refusing to write the passwords to the terminal, use --out <PATH> or --force!",
+            errorlib::ExitErrorCode::StdoutIsTerminal
+        );
+    }
+    let pm_db_state = filelib::pm::db_state();
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
+    if pm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "password manager database is empty!",
+            errorlib::ExitErrorCode::PMDatabaseNotFound
+        );
+    } else if pm_db_state == filelib::FileState::Encrypted {
+        logger.warning("database is encrypted!");
+        pm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("password manager database decrypted successfully.");
+    }
+    let passwords = loglib::time("db-query", || {
+        dblib::pm::get_passwords_since_id(
+            filelib::pm::get_decrypted_db_path(),
+            since_id
+        )
+    });
+    if _is_db_decrypted {
+        pm_db_encryption.encrypt();
+        logger.info("password manager database encrypted successfully.");
+    }
+    // Every format writes one entry for each password, the same names too.
+    let number_of_passwords = passwords.len();
+    let data = match fields.as_ref() {
+        Some(fields) if is_csv => fields_to_csv(passwords, fields),
+        Some(fields) => fields_to_json(passwords, fields),
+        None => passwords_to_json(passwords, since_id)
+    };
+    if let Some(path) = out {
+        if std::fs::write(path, data).is_err() {
+            logger.error(
+                &format!("can NOT write to '{}'!", path),
+                errorlib::ExitErrorCode::FileWrite
+            );
+        }
+        if fields.is_none() {
+            logger.warning("the exported file is NOT encrypted, keep it safe!");
+        }
+    } else {
+        println!("{}", data);
+    }
+    dblib::log::register(
+        &format!(
+            "{} passwords exported{}",
+            number_of_passwords,
+            if fields.is_some() { " (fields only)" } else { "" }
+        ),
+        dblib::log::LogAction::Export,
+        filelib::log::get_log_db_path()
+    );
+    logger.info(
+        &format!("there is {} password exported.", number_of_passwords)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::path::PathBuf;
+    use serde_json::Value;
+    use crate::{dblib, displaylib::passwords::TableField, filelib::create_file};
+
+    #[test]
+    fn incremental_export() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/incremental_export");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        dblib::pm::save_password(db_path.clone(), "test-1".to_owned(), "pass-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "test-2".to_owned(), "pass-2".to_owned());
+
+        let first: Value = serde_json::from_str(&super::passwords_to_json(
+            dblib::pm::get_passwords_since_id(db_path.clone(), 0),
+            0
+        )).unwrap();
+        assert_eq!(first["max_id"], 2, "First export max id NOT match!!");
+        assert_eq!(
+            first["passwords"].as_array().unwrap().len(), 2,
+            "First export passwords NOT match!!"
+        );
+
+        dblib::pm::save_password(db_path.clone(), "test-3".to_owned(), "pass-3".to_owned());
+        let since_id = first["max_id"].as_i64().unwrap() as i32;
+        let second: Value = serde_json::from_str(&super::passwords_to_json(
+            dblib::pm::get_passwords_since_id(db_path.clone(), since_id),
+            since_id
+        )).unwrap();
+        let passwords = second["passwords"].as_array().unwrap();
+        assert_eq!(second["max_id"], 3, "Incremental export max id NOT match!!");
+        assert_eq!(passwords.len(), 1, "Incremental export passwords NOT match!!");
+        assert_eq!(passwords[0]["id"], 3, "Incremental export id NOT match!!");
+        assert_eq!(passwords[0]["name"], "test-3", "Incremental export name NOT match!!");
+        assert_eq!(passwords[0]["password"], "pass-3", "Incremental export password NOT match!!");
+
+        // Nothing new, the max id stays the same.
+        let third: Value = serde_json::from_str(&super::passwords_to_json(
+            dblib::pm::get_passwords_since_id(db_path.clone(), 3),
+            3
+        )).unwrap();
+        assert_eq!(third["max_id"], 3, "Empty export max id NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn incremental_export_same_name() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/incremental_export_same_name");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        // e.g. `restore --keep-both` saves the same name twice.
+        dblib::pm::save_password(db_path.clone(), "github".to_owned(), "pass-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "github".to_owned(), "pass-2".to_owned());
+
+        let export: Value = serde_json::from_str(&super::passwords_to_json(
+            dblib::pm::get_passwords_since_id(db_path.clone(), 0),
+            0
+        )).unwrap();
+        assert_eq!(export["max_id"], 2, "Same name export max id NOT match!!");
+        let passwords: Vec<(&str, &str)> = export["passwords"].as_array().unwrap()
+            .iter()
+            .map(|pass| (pass["name"].as_str().unwrap(), pass["password"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            passwords,
+            vec![("github", "pass-1"), ("github", "pass-2")],
+            "Same name export passwords NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn export_count() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/export_count");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        for (name, password) in [("github", "pass-1"), ("github", "pass-2"), ("gitlab", "pass-3")] {
+            dblib::pm::save_password(db_path.clone(), name.to_owned(), password.to_owned());
+        }
+        // The logged count is the number of the written entries.
+        let count = dblib::pm::get_passwords_since_id(db_path.clone(), 0).len();
+        let fields = TableField::parse_list("name").unwrap();
+        let export: Value = serde_json::from_str(&super::passwords_to_json(
+            dblib::pm::get_passwords_since_id(db_path.clone(), 0),
+            0
+        )).unwrap();
+        assert_eq!(export["passwords"].as_array().unwrap().len(), count, "Exported passwords count NOT match!!");
+        let list: Value = serde_json::from_str(&super::fields_to_json(dblib::pm::get_passwords(db_path.clone()), &fields)).unwrap();
+        assert_eq!(list.as_array().unwrap().len(), count, "Exported fields count NOT match!!");
+        let csv = super::fields_to_csv(dblib::pm::get_passwords(db_path.clone()), &fields);
+        assert_eq!(csv.lines().count() - 1, count, "Exported CSV rows count NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn export_fields() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/export_fields");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        dblib::pm::save_password(db_path.clone(), "github".to_owned(), "secret-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "bank, \"main\"".to_owned(), "secret-2".to_owned());
+        dblib::pm::add_tag(db_path.clone(), "work".to_owned(), "github".to_owned());
+        let fields = TableField::parse_list("name,tags").unwrap();
+
+        let csv = super::fields_to_csv(dblib::pm::get_passwords(db_path.clone()), &fields);
+        assert_eq!(
+            csv,
+            "name,tags\ngithub,work\n\"bank, \"\"main\"\"\",",
+            "CSV export NOT match!!"
+        );
+        let json = super::fields_to_json(dblib::pm::get_passwords(db_path.clone()), &fields);
+        let list: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(list[0]["name"], "github", "JSON export name NOT match!!");
+        assert_eq!(list[0]["tags"], "work", "JSON export tags NOT match!!");
+        assert_eq!(list[0].as_object().unwrap().len(), 2, "JSON export fields NOT match!!");
+
+        // The passwords are never exported, with all the fields too.
+        let all = TableField::parse_list("icon,id,name,tags,created,updated").unwrap();
+        for data in [
+            super::fields_to_csv(dblib::pm::get_passwords(db_path.clone()), &all),
+            super::fields_to_json(dblib::pm::get_passwords(db_path.clone()), &all)
+        ] {
+            assert!(!data.contains("secret-"), "Fields export has the passwords!!");
+        }
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
@@ -3,6 +3,7 @@ use super::{
     PMDatabaseEncrption
 };
 use crate::{
+    configlib,
     loglib,
     filelib,
     dblib,
@@ -11,8 +12,30 @@ use crate::{
 };
 
 pub fn main(command: &ArgMatches) {
+    let is_fold = *command.get_one::<bool>("fold").unwrap_or(&false);
+    let is_count_only = *command.get_one::<bool>("count-only").unwrap_or(&false);
+    let print0 = command.get_one::<String>("print0")
+        .map(|field| displaylib::passwords::TableField::from_name(field).unwrap());
+    let format = command.get_one::<String>("format")
+        .map(|name| displaylib::passwords::ListFormat::from_name(name).unwrap())
+        .unwrap_or(displaylib::passwords::ListFormat::Table);
+    if is_count_only || print0.is_some() || matches!(
+        format,
+        displaylib::passwords::ListFormat::Json | displaylib::passwords::ListFormat::Plain
+    ) {
+        // The stdout carries the number, the names, or the passwords only.
+        loglib::stderr_only();
+    }
     let mut logger = loglib::Logger::new("find-password");
     let string = command.get_one::<String>("STRING").unwrap();
+    let match_by = if *command.get_one::<bool>("exact").unwrap_or(&false) {
+        dblib::pm::MatchBy::Exact
+    } else {
+        dblib::pm::MatchBy::Substring
+    };
+    let config = configlib::Config::load();
+    let sort = super::resolve_sort(command.get_one::<String>("sort"), &config);
+    let max_name = super::resolve_max_name_length(&config);
     let pm_db_state = filelib::pm::db_state();
     let mut pm_db_encryption = PMDatabaseEncrption::new();
     let mut _is_db_decrypted: bool = false;
@@ -28,13 +51,55 @@ pub fn main(command: &ArgMatches) {
         _is_db_decrypted = true;
         logger.info("password manager database decrypted successfully.");
     }
-    let passwords = dblib::pm::find_password(
-        filelib::pm::get_decrypted_db_path(),
-        string.clone()
-    );
+    let (passwords, number) = loglib::time("db-query", || {
+        if is_fold {
+            let passwords = dblib::pm::find_password_folded(
+                filelib::pm::get_decrypted_db_path(),
+                string.clone(),
+                match_by,
+                sort
+            );
+            let number = passwords.len();
+            (passwords, number)
+        } else if is_count_only {
+            // Count in the database, without loading the passwords.
+            (vec![], dblib::pm::get_passwords_number_matching(
+                filelib::pm::get_decrypted_db_path(),
+                string.clone(),
+                match_by
+            ))
+        } else {
+            let passwords = dblib::pm::find_password_matching(
+                filelib::pm::get_decrypted_db_path(),
+                string.clone(),
+                match_by,
+                sort
+            );
+            let number = passwords.len();
+            (passwords, number)
+        }
+    });
     if _is_db_decrypted {
         pm_db_encryption.encrypt();
         logger.info("password manager database encrypted successfully.");
     }
-    displaylib::passwords::display_many(passwords, string.clone());
+    if is_count_only {
+        println!("{}", number);
+        return;
+    }
+    loglib::time("display", || {
+        if let Some(field) = print0 {
+            displaylib::passwords::display_print0(passwords, field);
+        } else if command.get_one::<String>("group-by").is_some() {
+            displaylib::passwords::display_grouped(passwords, string.clone());
+        } else {
+            displaylib::passwords::display_passwords(
+                passwords,
+                format,
+                &displaylib::passwords::TableField::DEFAULT,
+                string,
+                max_name
+            );
+        }
+    });
 }
\ No newline at end of file
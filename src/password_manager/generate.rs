@@ -6,15 +6,407 @@ use rand::seq::{
     IndexedRandom, 
     SliceRandom
 };
+use std::collections::HashMap;
+use std::io::IsTerminal;
 use crate::{
     errorlib,
     filelib,
     utilities,
     loglib,
     dblib,
-    displaylib
+    displaylib,
+    cliplib,
+    configlib
 };
 
+/// How many times a password generation with the repeat rules is
+/// retried, if the last characters can NOT fit the rules.
+const REPEAT_RULES_ATTEMPTS: u16 = 100;
+
+/// The characters repeat rules of the generated password.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct RepeatRules {
+    /// No character twice in a row (e.g. "aa").
+    pub no_repeats: bool,
+    /// The max occurrences of any character, None for no limit.
+    pub max_occurrences: Option<u16>
+}
+
+impl RepeatRules {
+    fn is_set(&self) -> bool {
+        self.no_repeats || self.max_occurrences.is_some()
+    }
+
+    /// Check if the character can be at the index of the password,
+    /// `counts` has the occurrences of the password characters.
+    fn allows(&self, chars: &[char], index: usize, c: char, counts: &HashMap<char, usize>) -> bool {
+        if self.no_repeats && (
+            (index > 0 && chars[index - 1] == c) || chars.get(index + 1) == Some(&c)
+        ) {
+            return false;
+        }
+        if let Some(max) = self.max_occurrences {
+            // The character at the index is replaced, it does NOT count.
+            let count = counts.get(&c).copied().unwrap_or(0) - (chars.get(index) == Some(&c)) as usize;
+            return count < max as usize;
+        }
+        true
+    }
+}
+
+/// The generation settings, from the config profile
+/// `[generate.<name>]` and overridden by the flags.
+#[derive(PartialEq, Debug)]
+pub struct GenerateProfile {
+    /// None for a random length.
+    pub length: Option<u16>,
+    /// The random length range, the default is `utilities::MIN_RANDOM_LENGTH`
+    /// to `utilities::MAX_RANDOM_LENGTH`.
+    pub min_length: u16,
+    pub max_length: u16,
+    pub sample: utilities::PasswordSample,
+    pub no_ambiguous: bool,
+    pub min_symbols: u16,
+    pub repeats: RepeatRules
+}
+
+impl GenerateProfile {
+    /// Load the profile `name` from the config, or the `default` profile
+    /// (the built-in settings if it's NOT in the config).
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// 
+    /// ### Example:
+    /// ```toml
+    /// [generate.default]
+    /// length = 24
+    /// min_length = 20 # the random length range, if there is no length
+    /// max_length = 40
+    /// sample = "ascii"
+    /// no_ambiguous = true
+    /// min_symbols = 1
+    /// no_repeats = true
+    /// max_occurrences = 2
+    /// ```
+    /// ```
+    /// let profile = GenerateProfile::load(&configlib::Config::load(), None);
+    /// assert_eq!(profile.length, Some(24));
+    /// ```
+    pub fn load(config: &configlib::Config, name: Option<&String>) -> GenerateProfile {
+        let logger = loglib::Logger::new("generate-profile");
+        let key = format!("generate.{}", name.map(|name| name.as_str()).unwrap_or("default"));
+        if name.is_some() && !config.contains(&key) {
+            logger.error(
+                &format!("profile '{}' NOT found in the config!", key),
+                errorlib::ExitErrorCode::InvalidConfig
+            );
+        }
+        let get_u16 = |field: &str| -> Option<u16> {
+            config.get_int(&format!("{}.{}", key, field)).map(|value| {
+                u16::try_from(value).unwrap_or_else(|_| logger.error(
+                    &format!("config '{}.{}' must be from 0 to {}!", key, field, u16::MAX),
+                    errorlib::ExitErrorCode::InvalidConfig
+                ))
+            })
+        };
+        let sample = match config.get_str(&format!("{}.sample", key)) {
+            Some(sample) => utilities::PasswordSample::from_name(&sample).unwrap_or_else(|| logger.error(
+                &format!("config '{}.sample' must be one of (ascii, no-symbols, hex)!", key),
+                errorlib::ExitErrorCode::InvalidConfig
+            )),
+            None => utilities::PasswordSample::Ascii
+        };
+        GenerateProfile {
+            length: get_u16("length"),
+            min_length: get_u16("min_length").unwrap_or(utilities::MIN_RANDOM_LENGTH),
+            max_length: get_u16("max_length").unwrap_or(utilities::MAX_RANDOM_LENGTH),
+            sample,
+            no_ambiguous: config.get_bool(&format!("{}.no_ambiguous", key)).unwrap_or(false),
+            min_symbols: get_u16("min_symbols").unwrap_or(0),
+            repeats: RepeatRules {
+                no_repeats: config.get_bool(&format!("{}.no_repeats", key)).unwrap_or(false),
+                max_occurrences: get_u16("max_occurrences").inspect(|max| {
+                    if *max == 0 {
+                        logger.error(
+                            &format!("config '{}.max_occurrences' must be at least 1!", key),
+                            errorlib::ExitErrorCode::InvalidConfig
+                        );
+                    }
+                })
+            }
+        }
+    }
+
+    /// Override the profile with the explicit flags.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::Input`
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut profile = GenerateProfile::load(&configlib::Config::load(), None);
+    /// profile.override_with(command);
+    /// ```
+    pub fn override_with(&mut self, command: &ArgMatches) {
+        let logger = loglib::Logger::new("generate-profile");
+        if let Some(length) = command.get_one::<String>("LENGTH") {
+            self.length = Some(length.parse::<u16>().unwrap_or_else(|_| logger.error(
+                &format!("<LENGTH> must be unsigned integer from 0 to {}!", u16::MAX),
+                errorlib::ExitErrorCode::Input
+            )));
+        }
+        for (name, length) in [("min-length", &mut self.min_length), ("max-length", &mut self.max_length)] {
+            if let Some(value) = command.get_one::<String>(name) {
+                *length = value.parse::<u16>().unwrap_or_else(|_| logger.error(
+                    &format!("--{} must be unsigned integer from 0 to {}!", name, u16::MAX),
+                    errorlib::ExitErrorCode::Input
+                ));
+            }
+        }
+        // The sample flags are exclusive, and named as the samples.
+        let sample_flag = ["ascii", "no-symbols", "hex"]
+            .into_iter()
+            .find(|name| *command.get_one::<bool>(name).unwrap_or(&false));
+        if let Some(name) = sample_flag {
+            self.sample = utilities::PasswordSample::from_name(name).unwrap();
+        }
+        // The profile symbols do NOT apply to a sample without symbols.
+        if self.sample != utilities::PasswordSample::Ascii {
+            self.min_symbols = 0;
+        }
+        if *command.get_one::<bool>("no-ambiguous").unwrap_or(&false) {
+            self.no_ambiguous = true;
+        }
+        if let Some(min_symbols) = command.get_one::<String>("min-symbols") {
+            self.min_symbols = min_symbols.parse::<u16>().unwrap_or_else(|_| logger.error(
+                &format!("--min-symbols must be unsigned integer from 0 to {}!", u16::MAX),
+                errorlib::ExitErrorCode::Input
+            ));
+        }
+        if *command.get_one::<bool>("no-repeats").unwrap_or(&false) {
+            self.repeats.no_repeats = true;
+        }
+        if let Some(max) = command.get_one::<String>("max-occurrences") {
+            self.repeats.max_occurrences = Some(
+                max.parse::<u16>().ok().filter(|max| *max > 0).unwrap_or_else(|| logger.error(
+                    &format!("--max-occurrences must be unsigned integer from 1 to {}!", u16::MAX),
+                    errorlib::ExitErrorCode::Input
+                ))
+            );
+        }
+    }
+
+    /// Get the profile sample, without the ambiguous characters if `no_ambiguous`.
+    pub fn get_sample(&self) -> Vec<char> {
+        let mut sample = utilities::get_sample(self.sample);
+        if self.no_ambiguous {
+            utilities::remove_ambiguous(&mut sample);
+        }
+        sample
+    }
+}
+
+/// The built-in site policy names, see `SitePolicy::builtin`.
+pub const POLICY_NAMES: [&str; 4] = ["legacy-bank", "alphanumeric", "short-symbols", "strict"];
+
+/// A site password policy: the length bounds, the allowed symbols, and the
+/// required character classes (at least one of each). The letters and the
+/// digits are always allowed.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SitePolicy {
+    pub min_length: u16,
+    pub max_length: u16,
+    /// The allowed symbols, empty for letters and digits only.
+    pub symbols: String,
+    pub require_lower: bool,
+    pub require_upper: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool
+}
+
+impl SitePolicy {
+    /// Get a built-in policy, the common rules of the sites:
+    /// - `legacy-bank`: 8-16 characters, the symbols `!@#$%^&*` only, one of each class.
+    /// - `alphanumeric`: 8-32 letters and digits, one of each class.
+    /// - `short-symbols`: 8-20 characters, the symbols `-_.` only, one of each class.
+    /// - `strict`: 16-64 characters, all the symbols, one of each class.
+    pub fn builtin(name: &str) -> Option<SitePolicy> {
+        let policy = |min_length: u16, max_length: u16, symbols: &str| SitePolicy {
+            min_length,
+            max_length,
+            symbols: symbols.to_owned(),
+            require_lower: true,
+            require_upper: true,
+            require_digit: true,
+            require_symbol: !symbols.is_empty()
+        };
+        match name {
+            "legacy-bank" => Some(policy(8, 16, "!@#$%^&*")),
+            "alphanumeric" => Some(policy(8, 32, "")),
+            "short-symbols" => Some(policy(8, 20, "-_.")),
+            "strict" => Some(policy(16, 64, "!@#$%^&()-+=~[]{}/|:;?,.<>")),
+            _ => None
+        }
+    }
+
+    /// The policy of `--require-classes`: any length of the Ascii sample,
+    /// with one lowercase, one uppercase, one digit, and one symbol.
+    pub fn all_classes() -> SitePolicy {
+        SitePolicy {
+            min_length: 4,
+            max_length: u16::MAX,
+            symbols: utilities::get_sample(utilities::PasswordSample::Ascii)
+                .into_iter()
+                .filter(|c| !c.is_ascii_alphanumeric())
+                .collect(),
+            require_lower: true,
+            require_upper: true,
+            require_digit: true,
+            require_symbol: true
+        }
+    }
+
+    /// Load the policy `name` from the config `[policy.<name>]`, or the
+    /// built-in policy. The config policy replaces a built-in one.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidConfig`
+    /// - `errorlib::ExitErrorCode::Input`
+    /// 
+    /// ### Example:
+    /// ```toml
+    /// [policy.my-bank]
+    /// min_length = 8
+    /// max_length = 12
+    /// symbols = "!#"
+    /// require_lower = true
+    /// require_upper = true
+    /// require_digit = true
+    /// require_symbol = false
+    /// ```
+    /// ```
+    /// let policy = SitePolicy::load(&configlib::Config::load(), "my-bank");
+    /// assert_eq!(policy.max_length, 12);
+    /// ```
+    pub fn load(config: &configlib::Config, name: &str) -> SitePolicy {
+        let logger = loglib::Logger::new("generate-policy");
+        let key = format!("policy.{}", name);
+        if !config.contains(&key) {
+            return SitePolicy::builtin(name).unwrap_or_else(|| logger.error(
+                &format!(
+                    "policy '{}' NOT found, use one of ({}) or add '[{}]' to the config!",
+                    name, POLICY_NAMES.join(", "), key
+                ),
+                errorlib::ExitErrorCode::Input
+            ));
+        }
+        let get_u16 = |field: &str| -> u16 {
+            config.get_int(&format!("{}.{}", key, field))
+                .and_then(|value| u16::try_from(value).ok())
+                .filter(|value| *value > 0)
+                .unwrap_or_else(|| logger.error(
+                    &format!("config '{}.{}' must be from 1 to {}!", key, field, u16::MAX),
+                    errorlib::ExitErrorCode::InvalidConfig
+                ))
+        };
+        let get_bool = |field: &str| config.get_bool(&format!("{}.{}", key, field)).unwrap_or(false);
+        let policy = SitePolicy {
+            min_length: get_u16("min_length"),
+            max_length: get_u16("max_length"),
+            symbols: config.get_str(&format!("{}.symbols", key)).unwrap_or_default(),
+            require_lower: get_bool("require_lower"),
+            require_upper: get_bool("require_upper"),
+            require_digit: get_bool("require_digit"),
+            require_symbol: get_bool("require_symbol")
+        };
+        if policy.min_length > policy.max_length {
+            logger.error(
+                &format!("config '{}.min_length' greater than '{}.max_length'!", key, key),
+                errorlib::ExitErrorCode::InvalidConfig
+            );
+        } else if policy.symbols.chars().any(|c| c.is_ascii_alphanumeric() || c.is_whitespace()) {
+            logger.error(
+                &format!("config '{}.symbols' must NOT have letters, digits, or spaces!", key),
+                errorlib::ExitErrorCode::InvalidConfig
+            );
+        } else if policy.require_symbol && policy.symbols.is_empty() {
+            logger.error(
+                &format!("config '{}.require_symbol' is set, but '{}.symbols' is empty!", key, key),
+                errorlib::ExitErrorCode::InvalidConfig
+            );
+        } else if (policy.required().len() as u16) > policy.min_length {
+            logger.error(
+                &format!("config '{}.min_length' is less than the required classes!", key),
+                errorlib::ExitErrorCode::InvalidConfig
+            );
+        }
+        policy
+    }
+
+    /// The required classes, as checks of the characters.
+    fn required(&self) -> Vec<fn(&char) -> bool> {
+        let mut classes: Vec<fn(&char) -> bool> = vec![];
+        if self.require_lower {
+            classes.push(|c| c.is_ascii_lowercase());
+        }
+        if self.require_upper {
+            classes.push(|c| c.is_ascii_uppercase());
+        }
+        if self.require_digit {
+            classes.push(|c| c.is_ascii_digit());
+        }
+        if self.require_symbol {
+            classes.push(|c| !c.is_ascii_alphanumeric());
+        }
+        classes
+    }
+
+    /// Get the policy sample, without the ambiguous characters if `no_ambiguous`.
+    fn get_sample(&self, no_ambiguous: bool) -> Vec<char> {
+        let mut sample = utilities::get_sample(utilities::PasswordSample::NoSymbols);
+        sample.extend(self.symbols.chars());
+        sample.sort();
+        sample.dedup();
+        if no_ambiguous {
+            utilities::remove_ambiguous(&mut sample);
+        }
+        sample
+    }
+
+    /// Make sure the password has one character of each required class from
+    /// the sample, by replacing random characters of the other classes.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let policy = SitePolicy::builtin("legacy-bank").unwrap();
+    /// let mut sample = policy.get_sample(false);
+    /// let password = policy.apply(generate(16, &mut sample), &sample);
+    /// ```
+    fn apply(&self, password: String, sample: &[char]) -> String {
+        let mut rng = rand::rng();
+        let mut chars: Vec<char> = password.chars().collect();
+        let classes = self.required();
+        for (class_index, class) in classes.iter().enumerate() {
+            if chars.iter().any(class) {
+                continue;
+            }
+            // The characters that are the only one of a required class must stay.
+            let replaceable: Vec<usize> = (0..chars.len())
+                .filter(|index| classes.iter().enumerate().all(|(other_index, other)| {
+                    other_index == class_index || !other(&chars[*index]) ||
+                    chars.iter().filter(|c| other(c)).count() > 1
+                }))
+                .collect();
+            let options: Vec<char> = sample.iter().filter(|c| class(c)).copied().collect();
+            if let (Some(index), Some(c)) = (replaceable.choose(&mut rng), options.choose(&mut rng)) {
+                chars[*index] = *c;
+            }
+        }
+        chars.into_iter().collect()
+    }
+}
+
 /// Generate password from a sample.
 /// 
 /// ### Example:
@@ -28,7 +420,7 @@ use crate::{
 /// assert_eq!(password.len(), 128);
 /// println!("Your password: {}", password);
 /// ```
-fn generate(length: u16, sample: &mut Vec<char> ) -> String {
+pub fn generate(length: u16, sample: &mut [char]) -> String {
     let mut rng = rand::rng();
     sample.shuffle(&mut rng);
     let mut password: String = String::new();
@@ -42,28 +434,194 @@ fn generate(length: u16, sample: &mut Vec<char> ) -> String {
     password
 }
 
-pub fn main(command: &ArgMatches) {
-    let mut logger = loglib::Logger::new("generate-password");
+/// Generate password from a sample with the repeat rules, every character is
+/// sampled from the sample characters that fit the rules, so the distribution
+/// stays the same for the allowed characters. The symbols are added with the
+/// rules too. Returns None if the sample can NOT fit the rules.
+/// 
+/// ### Example:
+/// ```
+/// let rules = RepeatRules { no_repeats: true, max_occurrences: None };
+/// let mut sample = utilities::get_sample(utilities::PasswordSample::Hex);
+/// let password = generate_with_rules(32, &mut sample, 0, &rules).unwrap();
+/// ```
+fn generate_with_rules(length: u16, sample: &mut [char], min_symbols: u16, rules: &RepeatRules) -> Option<String> {
+    let mut rng = rand::rng();
+    sample.shuffle(&mut rng);
+    let symbols: Vec<char> = sample
+        .iter()
+        .filter(|c| !c.is_ascii_alphanumeric())
+        .copied()
+        .collect();
+    'attempts: for _ in 0..REPEAT_RULES_ATTEMPTS {
+        let mut chars: Vec<char> = Vec::with_capacity(length as usize);
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for index in 0..length as usize {
+            let allowed: Vec<char> = sample
+                .iter()
+                .filter(|c| rules.allows(&chars, index, **c, &counts))
+                .copied()
+                .collect();
+            match allowed.choose(&mut rng) {
+                Some(c) => {
+                    chars.push(*c);
+                    *counts.entry(*c).or_insert(0) += 1;
+                },
+                None => continue 'attempts
+            }
+        }
 
-    // Get the length
-    let length = command
-        .get_one::<String>("LENGTH")
-        .unwrap_or(&utilities::get_ran_string_number())
-        .parse::<u16>();
-    if length.is_err() {
-        logger.error(
-            &format!("<LENGTH> must be unsigned integer from 0 to {}!", u16::MAX),
+        // Replace random letters or digits with the allowed symbols.
+        let mut others: Vec<usize> = (0..chars.len())
+            .filter(|index| chars[*index].is_ascii_alphanumeric())
+            .collect();
+        let mut missing = (min_symbols as usize).saturating_sub(chars.len() - others.len());
+        others.shuffle(&mut rng);
+        for index in others {
+            if missing == 0 {
+                break;
+            }
+            let allowed: Vec<char> = symbols
+                .iter()
+                .filter(|c| rules.allows(&chars, index, **c, &counts))
+                .copied()
+                .collect();
+            if let Some(c) = allowed.choose(&mut rng) {
+                *counts.get_mut(&chars[index]).unwrap() -= 1;
+                *counts.entry(*c).or_insert(0) += 1;
+                chars[index] = *c;
+                missing -= 1;
+            }
+        }
+        if missing == 0 {
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+/// Make sure the password has at least `min_symbols` symbols (NOT
+/// letters or digits) from the sample, by replacing random characters.
+/// 
+/// ### Example:
+/// ```
+/// let mut sample = utilities::get_sample(utilities::PasswordSample::Ascii);
+/// let password = ensure_symbols(generate(16, &mut sample), &sample, 2);
+/// ```
+fn ensure_symbols(password: String, sample: &[char], min_symbols: u16) -> String {
+    let mut rng = rand::rng();
+    let symbols: Vec<char> = sample
+        .iter()
+        .filter(|c| !c.is_ascii_alphanumeric())
+        .copied()
+        .collect();
+    let mut chars: Vec<char> = password.chars().collect();
+    let mut others: Vec<usize> = (0..chars.len())
+        .filter(|index| chars[*index].is_ascii_alphanumeric())
+        .collect();
+    let missing = (min_symbols as usize).saturating_sub(chars.len() - others.len());
+    others.shuffle(&mut rng);
+    for index in others.into_iter().take(missing) {
+        chars[index] = *symbols.choose(&mut rng).unwrap();
+    }
+    chars.into_iter().collect()
+}
+
+pub fn main(command: &ArgMatches) {
+    // Checked before the password is generated and saved.
+    let clear_after = cliplib::resolve_clear_after(command.get_one::<String>("clear-after"));
+    let count = command.get_one::<String>("count").map(|count| count.parse::<u32>().unwrap_or_else(|_| {
+        loglib::Logger::new("generate-password").error(
+            &format!("--count must be unsigned integer from 0 to {}!", u32::MAX),
             errorlib::ExitErrorCode::Input
         )
+    }));
+    if count == Some(0) {
+        return;
+    }
+    if count.is_some() {
+        // The stdout carries the passwords only.
+        loglib::stderr_only();
+    }
+    let logger = loglib::Logger::new("generate-password");
+    if count.is_some() && command.get_one::<String>("format").is_some_and(|format| format == "export") {
+        logger.error(
+            "--count can NOT be used with the export format, it exports one password!!",
+            errorlib::ExitErrorCode::Input
+        );
+    }
+
+    // The config profile, overridden by the flags.
+    let config = configlib::Config::load();
+    let mut profile = GenerateProfile::load(
+        &config,
+        command.get_one::<String>("profile")
+    );
+    profile.override_with(command);
+
+    // The site policy replaces the profile sample, length, and rules.
+    let policy = command.get_one::<String>("policy")
+        .map(|name| SitePolicy::load(&config, name));
+    if let Some(policy) = policy.as_ref() {
+        // The profile length is the parsed <LENGTH>, if there is one.
+        let length = match command.get_one::<String>("LENGTH") {
+            Some(_) => profile.length.unwrap(),
+            None => policy.max_length
+        };
+        if length < policy.min_length || length > policy.max_length {
+            logger.error(
+                &format!("the policy length must be from {} to {}!!", policy.min_length, policy.max_length),
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        profile.length = Some(length);
+        profile.min_symbols = 0;
+        profile.repeats = RepeatRules::default();
+    }
+    let length = profile.length.unwrap_or_else(|| {
+        utilities::get_ran_string_number(profile.min_length, profile.max_length)
+            .unwrap_or_else(|| logger.error(
+                &format!(
+                    "the min length ({}) greater than the max length ({})!!",
+                    profile.min_length,
+                    profile.max_length
+                ),
+                errorlib::ExitErrorCode::Input
+            ))
+            .parse::<u16>()
+            .unwrap()
+    });
+
+    // One character of each class with the Ascii sample, the length stays fixed.
+    let classes = command.get_one::<bool>("require-classes")
+        .is_some_and(|require| *require)
+        .then(SitePolicy::all_classes);
+    if let Some(classes) = classes.as_ref() {
+        if profile.sample != utilities::PasswordSample::Ascii {
+            logger.error(
+                "--require-classes works with the ascii sample only!!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        if profile.repeats.is_set() || profile.min_symbols > 0 {
+            logger.error(
+                "--require-classes can NOT be used with the repeat rules or the minimum symbols!!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        let required = classes.required().len() as u16;
+        if length < required {
+            logger.error(
+                &format!("the length ({}) is less than the required classes ({})!!", length, required),
+                errorlib::ExitErrorCode::Input
+            );
+        }
     }
 
     // generate the sample based on the type
-    let mut sample = if *command.get_one::<bool>("hex").unwrap_or(&false) {
-        utilities::get_sample(utilities::PasswordSample::Hex)
-    } else if *command.get_one::<bool>("no-symbols").unwrap_or(&false) {
-        utilities::get_sample(utilities::PasswordSample::NoSymbols)
-    } else {
-        utilities::get_sample(utilities::PasswordSample::Ascii)
+    let mut sample = match policy.as_ref() {
+        Some(policy) => policy.get_sample(profile.no_ambiguous),
+        None => profile.get_sample()
     };
 
     // Custom sample or add a custom set to the sample.
@@ -93,15 +651,109 @@ pub fn main(command: &ArgMatches) {
             }
             sample.push(c);
         }
+        // The custom characters are filtered too, before the random pick.
+        if profile.no_ambiguous {
+            utilities::remove_ambiguous(&mut sample);
+        }
     }
 
+    if sample.is_empty() {
+        logger.error(
+            if profile.no_ambiguous {
+                "the sample is empty without the ambiguous characters!!"
+            } else {
+                "the sample is empty!!"
+            },
+            errorlib::ExitErrorCode::Input
+        );
+    }
+    if profile.min_symbols > 0 {
+        if profile.min_symbols > length {
+            logger.error(
+                &format!("the minimum symbols ({}) greater than the length ({})!!", profile.min_symbols, length),
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        if sample.iter().all(|c| c.is_ascii_alphanumeric()) {
+            logger.error(
+                "the minimum symbols set, but the sample has no symbols!!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+    }
+
+    if profile.repeats.is_set() {
+        let mut distinct = sample.clone();
+        distinct.sort();
+        distinct.dedup();
+        if profile.repeats.no_repeats && distinct.len() < 2 && length > 1 {
+            logger.error(
+                "the sample needs at least 2 different characters for --no-repeats!!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        if let Some(max) = profile.repeats.max_occurrences {
+            if distinct.len() * (max as usize) < length as usize {
+                logger.error(
+                    &format!(
+                        "the sample has {} different characters, NOT enough for the length ({}) with --max-occurrences {}!!",
+                        distinct.len(), length, max
+                    ),
+                    errorlib::ExitErrorCode::Input
+                );
+            }
+        }
+    }
+
+    // The password characters are picked from the distinct sample characters.
+    let mut alphabet = sample.clone();
+    alphabet.sort();
+    alphabet.dedup();
+
     // generate the password from the sample
-    let mut _password: String = generate(
-        length.unwrap(),
-        &mut sample
-    );
-    logger.info("password generated successfully.");
-    
+    let mut generate_one = || -> String {
+        let password = if let Some(policy) = policy.as_ref() {
+            policy.apply(generate(length, &mut sample), &sample)
+        } else if profile.repeats.is_set() {
+            generate_with_rules(length, &mut sample, profile.min_symbols, &profile.repeats)
+                .unwrap_or_else(|| logger.error(
+                    "can NOT generate a password with the repeat rules, use a bigger sample!!",
+                    errorlib::ExitErrorCode::Input
+                ))
+        } else {
+            ensure_symbols(
+                generate(length, &mut sample),
+                &sample,
+                profile.min_symbols
+            )
+        };
+        match classes.as_ref() {
+            Some(classes) => classes.apply(password, &sample),
+            None => password
+        }
+    };
+    let passwords: Vec<String> = (0..count.unwrap_or(1)).map(|_| generate_one()).collect();
+    match count {
+        Some(count) => logger.info(&format!("{} passwords generated successfully.", count)),
+        None => logger.info("password generated successfully.")
+    }
+    let bits = utilities::estimate_entropy(&passwords[0], alphabet.len());
+    logger.info(&format!("password strength: {} ({:.1} bits of entropy).", utilities::entropy_label(bits), bits));
+    if count.is_some() {
+        let format = displaylib::passwords::DisplayFormat::from_name(
+            command.get_one::<String>("format")
+                .unwrap_or(&"pretty".to_owned())
+        );
+        displaylib::passwords::display_many(passwords, format, std::io::stdout().is_terminal());
+        return;
+    }
+    save_and_show(command, passwords.into_iter().next().unwrap(), clear_after);
+}
+
+/// Save the generated password with `--save`, then copy it to the
+/// clipboard (cleared after `clear_after` seconds) or print it.
+fn save_and_show(command: &ArgMatches, _password: String, clear_after: u64) {
+    let mut logger = loglib::Logger::new("generate-password");
     // save the password
     if let Some(password_name) = command.get_one::<String>("save") {
         let pm_db_state = filelib::pm::db_state();
@@ -109,9 +761,9 @@ pub fn main(command: &ArgMatches) {
         let mut _is_db_decrypted: bool = false;
         let pm_decrypted_path = filelib::pm::get_decrypted_db_path();
         if pm_db_state == filelib::FileState::NotFound {
-            filelib::create_file(pm_decrypted_path.clone());
+            filelib::create_file(pm_decrypted_path.clone()).unwrap_or_else(|err| err.exit());
             dblib::pm::create_passwords_table(pm_decrypted_path.clone());
-            dblib::log::register("create passwords table", filelib::log::get_log_db_path());
+            dblib::log::register("create passwords table", dblib::log::LogAction::Create, filelib::log::get_log_db_path());
         } else if pm_db_state == filelib::FileState::Encrypted {
             logger.warning("database is encrypted!");
             pm_db_encryption.decrypt();
@@ -126,6 +778,7 @@ pub fn main(command: &ArgMatches) {
         );
         dblib::log::register(
             &format!("'{}' saved successfully.", password_name), 
+            dblib::log::LogAction::Save, 
             filelib::log::get_log_db_path()
         );
         if _is_db_decrypted {
@@ -133,7 +786,22 @@ pub fn main(command: &ArgMatches) {
             logger.info("password manager database encrypted successfully.");
         }
     }
-    displaylib::passwords::display_one(_password);
+    if *command.get_one::<bool>("clipboard").unwrap_or(&false) {
+        let policy = if *command.get_one::<bool>("keep-clipboard").unwrap_or(&false) {
+            cliplib::ClearPolicy::Keep
+        } else if *command.get_one::<bool>("confirm-saved").unwrap_or(&false) {
+            cliplib::ClearPolicy::Confirm
+        } else {
+            cliplib::ClearPolicy::Timeout
+        };
+        cliplib::copy(_password, policy, clear_after);
+        return;
+    }
+    let format = displaylib::passwords::DisplayFormat::from_name(
+        command.get_one::<String>("format")
+            .unwrap_or(&"pretty".to_owned())
+    );
+    displaylib::passwords::display_one(_password, format);
 }
 
 
@@ -149,4 +817,191 @@ mod tests {
         );
         assert_eq!(password.len(), 512, "ASCII password length is NOT 512!!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn generate_profile() {
+        let temp_dir = std::path::PathBuf::new()
+            .join("./temp/generate_profile");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let config_path = temp_dir.join("config.toml");
+        super::filelib::create_file(config_path.clone()).unwrap();
+        std::fs::write(&config_path, concat!(
+            "[generate.default]\nlength = 24\nsample = \"ascii\"\nno_ambiguous = true\nmin_symbols = 3\n",
+            "[generate.work]\nlength = 16\nsample = \"hex\"\n"
+        )).expect("Can NOT write the test config!!");
+        let config = super::configlib::Config::load_from(config_path);
+
+        let profile = super::GenerateProfile::load(&config, None);
+        assert_eq!(
+            profile,
+            super::GenerateProfile {
+                length: Some(24),
+                min_length: super::utilities::MIN_RANDOM_LENGTH,
+                max_length: super::utilities::MAX_RANDOM_LENGTH,
+                sample: super::utilities::PasswordSample::Ascii,
+                no_ambiguous: true,
+                min_symbols: 3,
+                repeats: super::RepeatRules::default()
+            },
+            "Default profile NOT match!!"
+        );
+        let mut sample = profile.get_sample();
+        let password = super::ensure_symbols(
+            super::generate(profile.length.unwrap(), &mut sample),
+            &sample,
+            profile.min_symbols
+        );
+        assert_eq!(password.len(), 24, "Profile length NOT match!!");
+        assert!(
+            !password.chars().any(|c| super::utilities::AMBIGUOUS_CHARS.contains(&c)),
+            "Password has ambiguous characters!!"
+        );
+        assert!(
+            password.chars().filter(|c| !c.is_ascii_alphanumeric()).count() >= 3,
+            "Password minimum symbols NOT match!!"
+        );
+
+        let work = super::GenerateProfile::load(&config, Some(&"work".to_owned()));
+        assert_eq!(work.length, Some(16), "Named profile length NOT match!!");
+        assert_eq!(work.sample, super::utilities::PasswordSample::Hex, "Named profile sample NOT match!!");
+
+        // The flags override the profile.
+        let matches = clap::Command::new("xpm")
+            .subcommands(crate::commands::commands())
+            .try_get_matches_from(["xpm", "pm", "generate", "12", "--no-symbols"])
+            .expect("Can NOT parse the test command!!");
+        let command = matches
+            .subcommand_matches("password-manager").unwrap()
+            .subcommand_matches("generate").unwrap();
+        let mut profile = super::GenerateProfile::load(&config, None);
+        profile.override_with(command);
+        assert_eq!(
+            profile,
+            super::GenerateProfile {
+                length: Some(12),
+                min_length: super::utilities::MIN_RANDOM_LENGTH,
+                max_length: super::utilities::MAX_RANDOM_LENGTH,
+                sample: super::utilities::PasswordSample::NoSymbols,
+                no_ambiguous: true,
+                min_symbols: 0,
+                repeats: super::RepeatRules::default()
+            },
+            "Overridden profile NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn generate_no_repeats() {
+        let rules = super::RepeatRules {
+            no_repeats: true,
+            max_occurrences: None
+        };
+        // A small sample makes the repeats very likely without the rules.
+        let mut sample = ['a', 'b', 'c', '!'];
+        for _ in 0..50 {
+            let password = super::generate_with_rules(64, &mut sample, 8, &rules)
+                .expect("Password NOT generated!!");
+            let chars: Vec<char> = password.chars().collect();
+            assert_eq!(chars.len(), 64, "Password length NOT match!!");
+            assert!(
+                chars.windows(2).all(|pair| pair[0] != pair[1]),
+                "Password has repeated characters '{}'!!", password
+            );
+            assert!(chars.iter().filter(|c| **c == '!').count() >= 8, "Password minimum symbols NOT match!!");
+        }
+
+        let rules = super::RepeatRules {
+            no_repeats: true,
+            max_occurrences: Some(2)
+        };
+        let mut sample = super::utilities::get_sample(super::utilities::PasswordSample::Hex);
+        let password = super::generate_with_rules(32, &mut sample, 0, &rules)
+            .expect("Password NOT generated!!");
+        assert!(
+            password.chars().all(|c| password.matches(c).count() <= 2),
+            "Password max occurrences NOT match!!"
+        );
+        assert!(
+            super::generate_with_rules(3, &mut ['a'], 0, &rules).is_none(),
+            "Impossible rules NOT detected!!"
+        );
+    }
+
+    #[test]
+    fn generate_site_policy() {
+        let policy = super::SitePolicy::builtin("legacy-bank")
+            .expect("Built-in policy NOT found!!");
+        let mut sample = policy.get_sample(false);
+        for length in policy.min_length..=policy.max_length {
+            let password = policy.apply(super::generate(length, &mut sample), &sample);
+            assert_eq!(password.chars().count(), length as usize, "Policy length NOT match!!");
+            assert!(
+                password.chars().all(|c| c.is_ascii_alphanumeric() || "!@#$%^&*".contains(c)),
+                "Password '{}' has NOT allowed characters!!", password
+            );
+            assert!(password.chars().any(|c| c.is_ascii_lowercase()), "Password '{}' has NO lowercase!!", password);
+            assert!(password.chars().any(|c| c.is_ascii_uppercase()), "Password '{}' has NO uppercase!!", password);
+            assert!(password.chars().any(|c| c.is_ascii_digit()), "Password '{}' has NO digit!!", password);
+            assert!(password.chars().any(|c| "!@#$%^&*".contains(c)), "Password '{}' has NO symbol!!", password);
+        }
+    }
+
+    #[test]
+    fn generate_require_classes() {
+        let classes = super::SitePolicy::all_classes();
+        assert_eq!(classes.required().len(), 4, "Required classes NOT match!!");
+        let mut sample = super::utilities::get_sample(super::utilities::PasswordSample::Ascii);
+        super::utilities::remove_ambiguous(&mut sample);
+        for length in (4..12).cycle().take(800) {
+            let password = classes.apply(super::generate(length, &mut sample), &sample);
+            assert_eq!(password.chars().count(), length as usize, "Password length NOT match!!");
+            assert!(
+                classes.required().iter().all(|class| password.chars().any(|c| class(&c))),
+                "Password '{}' has NOT all the classes!!", password
+            );
+        }
+    }
+
+    #[test]
+    fn generate_sample_flags() {
+        let parse = |flags: &[&str]| {
+            clap::Command::new("xpm")
+                .subcommands(crate::commands::commands())
+                .try_get_matches_from([&["xpm", "pm", "generate", "64"], flags].concat())
+        };
+        for (flag, sample) in [
+            ("--ascii", super::utilities::PasswordSample::Ascii),
+            ("--no-symbols", super::utilities::PasswordSample::NoSymbols),
+            ("--hex", super::utilities::PasswordSample::Hex)
+        ] {
+            let matches = parse(&[flag]).expect("Can NOT parse the test command!!");
+            let command = matches
+                .subcommand_matches("password-manager").unwrap()
+                .subcommand_matches("generate").unwrap();
+            // A profile sample is overridden by the flag.
+            let config = super::configlib::Config::load_from(
+                std::path::PathBuf::new().join("./temp/generate_sample_flags/missing.toml")
+            );
+            let mut profile = super::GenerateProfile::load(&config, None);
+            profile.sample = super::utilities::PasswordSample::Hex;
+            profile.override_with(command);
+            assert_eq!(profile.sample, sample, "{} sample NOT match!!", flag);
+
+            let alphabet = super::utilities::get_sample(sample);
+            let password = super::generate(64, &mut profile.get_sample());
+            assert!(
+                password.chars().all(|c| alphabet.contains(&c)),
+                "{} password '{}' NOT in the sample!!", flag, password
+            );
+        }
+        for flags in [["--hex", "--no-symbols"], ["--ascii", "--hex"], ["--ascii", "--no-symbols"]] {
+            assert!(parse(&flags).is_err(), "{:?} NOT rejected!!", flags);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::Path;
+use arboard::Clipboard;
+use super::ArgMatches;
+use crate::{
+    dblib,
+    encryption_manager::{decrypt_file, encrypt_file},
+    filelib,
+    loglib,
+    sessionlib
+};
+
+/// The password manager database state after the lock.
+#[derive(PartialEq, Debug)]
+pub enum LockResult {
+    /// The decrypted database is encrypted and wiped.
+    Encrypted,
+    /// There is no decrypted database.
+    NothingDecrypted,
+    /// The decrypted database is left as it is, there is no
+    /// cached key (or it does NOT match the encrypted database).
+    NoKey
+}
+
+/// Re-encrypt the decrypted database with the session cached key and
+/// wipe the plaintext, then forget the cached key. The plaintext is
+/// NOT wiped if there is no cached key.
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileWrite`
+///
+/// ### Example:
+/// ```
+/// let result = lock(
+///     &filelib::pm::get_decrypted_db_path(),
+///     &filelib::pm::get_encrypted_db_path(),
+///     &sessionlib::get_session_path()
+/// );
+/// ```
+pub fn lock(de_path: &Path, en_path: &Path, session_path: &Path) -> LockResult {
+    let logger = loglib::Logger::new("lock");
+    let key = sessionlib::load(session_path, sessionlib::now())
+        .filter(|key| !en_path.exists() || decrypt_file::verify_key(en_path.to_str().unwrap(), key));
+    sessionlib::forget(session_path);
+    if !de_path.exists() {
+        return LockResult::NothingDecrypted;
+    }
+    let key = match key {
+        Some(key) => key,
+        None => return LockResult::NoKey
+    };
+    // The passphrase salt is in the encrypted database header, it is kept
+    // while the database is decrypted to the tmpfs.
+    let salt = if en_path.exists() {
+        decrypt_file::read_salt(en_path.to_str().unwrap())
+    } else {
+        vec![]
+    };
+    if !encrypt_file::encrypt_snapshot(de_path.to_str().unwrap(), en_path.to_str().unwrap(), &key, &salt) {
+        logger.error(
+            "can NOT write the encrypted database!",
+            crate::errorlib::ExitErrorCode::FileWrite
+        );
+    }
+    filelib::wipe_delete(de_path.to_str().unwrap().to_owned()).unwrap_or_else(|err| err.exit());
+    LockResult::Encrypted
+}
+
+pub fn main(_: &ArgMatches) {
+    let logger = loglib::Logger::new("lock");
+    match lock(
+        &filelib::pm::get_decrypted_db_path(),
+        &filelib::pm::get_encrypted_db_path(),
+        &sessionlib::get_session_path()
+    ) {
+        LockResult::Encrypted => {
+            logger.info("password manager database encrypted successfully.");
+            dblib::log::register(
+                "password manager database locked",
+                dblib::log::LogAction::Encrypt,
+                filelib::log::get_log_db_path()
+            );
+        },
+        LockResult::NothingDecrypted => logger.info("password manager database is NOT decrypted."),
+        LockResult::NoKey => {
+            logger.warning("password manager database is STILL DECRYPTED, there is no cached key to encrypt it!!");
+            logger.warning("use 'password-manager encrypt' to encrypt it NOW!!");
+        }
+    }
+    logger.info("the cached key deleted successfully.");
+    // The clipboard may have a copied password.
+    match Clipboard::new().and_then(|mut clipboard| clipboard.clear()) {
+        Ok(_) => logger.info("clipboard cleared successfully."),
+        Err(_) => logger.warning("can NOT clear the clipboard!")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::LockResult;
+    use crate::{
+        dblib,
+        encryption_manager::decrypt_file,
+        filelib::create_file,
+        sessionlib
+    };
+
+    #[test]
+    fn lock_database() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/lock_database");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let de_path = temp_dir.join("passwords.db");
+        let en_path = temp_dir.join("passwords.db.x");
+        let session_path = temp_dir.join("session");
+        create_file(de_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(de_path.clone());
+        dblib::pm::save_password(de_path.clone(), "github".to_owned(), "pass123".to_owned());
+
+        // Without a cached key the database is left decrypted.
+        assert_eq!(super::lock(&de_path, &en_path, &session_path), LockResult::NoKey, "Lock without key NOT match!!");
+        assert!(de_path.exists(), "Decrypted database deleted without a key!!");
+
+        let key = fernet::Fernet::generate_key();
+        sessionlib::store(&session_path, &key, sessionlib::now() + 300);
+        assert_eq!(super::lock(&de_path, &en_path, &session_path), LockResult::Encrypted, "Lock NOT match!!");
+        assert!(!de_path.exists(), "Decrypted database NOT wiped!!");
+        assert!(!session_path.exists(), "Session cache NOT forgotten!!");
+        assert_eq!(
+            super::lock(&de_path, &en_path, &session_path),
+            LockResult::NothingDecrypted,
+            "Lock of the encrypted database NOT match!!"
+        );
+
+        decrypt_file::decrypt(en_path.to_str().unwrap().to_owned(), key);
+        assert_eq!(dblib::pm::get_passwords(de_path)[0].password, "pass123", "Encrypted database NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+    #[test]
+    fn lock_keeps_salt() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/lock_keeps_salt");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let de_path = temp_dir.join("passwords.db");
+        let en_path = temp_dir.join("passwords.db.x");
+        let session_path = temp_dir.join("session");
+        create_file(de_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(de_path.clone());
+
+        // The encrypted database is kept while it is decrypted to the tmpfs.
+        let salt = crate::encryption_manager::generate_salt();
+        let key = crate::encryption_manager::derive_key_from_passphrase("xpm passphrase", &salt);
+        crate::encryption_manager::encrypt_file::encrypt_snapshot(
+            de_path.to_str().unwrap(),
+            en_path.to_str().unwrap(),
+            &key,
+            &salt
+        );
+        dblib::pm::save_password(de_path.clone(), "github".to_owned(), "pass123".to_owned());
+        sessionlib::store(&session_path, &key, sessionlib::now() + 300);
+        assert_eq!(super::lock(&de_path, &en_path, &session_path), LockResult::Encrypted, "Lock NOT match!!");
+        assert_eq!(
+            decrypt_file::read_salt(en_path.to_str().unwrap()),
+            salt,
+            "Header salt NOT kept!!"
+        );
+
+        decrypt_file::decrypt(en_path.to_str().unwrap().to_owned(), key);
+        assert_eq!(dblib::pm::get_passwords(de_path)[0].password, "pass123", "Encrypted database NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
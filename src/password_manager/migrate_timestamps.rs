@@ -0,0 +1,52 @@
+use super::{
+    ArgMatches,
+    PMDatabaseEncrption
+};
+use crate::{
+    configlib,
+    dblib,
+    loglib,
+    filelib,
+    errorlib
+};
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("migrate-timestamps");
+    let offset_string = command.get_one::<String>("from-offset").unwrap();
+    let offset = dblib::pm::parse_offset(offset_string).unwrap_or_else(|| logger.error(
+        &format!("invalid offset '{}', use +HH:MM or -HH:MM (e.g. +03:00)!", offset_string),
+        errorlib::ExitErrorCode::Input
+    ));
+    let pm_db_state = filelib::pm::db_state();
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
+    if pm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "password manager database is empty!",
+            errorlib::ExitErrorCode::PMDatabaseNotFound
+        );
+    } else if pm_db_state == filelib::FileState::Encrypted {
+        logger.warning("database is encrypted!");
+        pm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("password manager database decrypted successfully.");
+    }
+    let rows = dblib::pm::migrate_timestamps(
+        filelib::pm::get_decrypted_db_path(),
+        offset
+    );
+    if _is_db_decrypted {
+        pm_db_encryption.encrypt();
+        logger.info("password manager database encrypted successfully.");
+    }
+    dblib::log::register(
+        &format!("{} passwords timestamps migrated to UTC", rows),
+        dblib::log::LogAction::Update,
+        filelib::log::get_log_db_path()
+    );
+    logger.info(&format!("{} passwords timestamps migrated to UTC.", rows));
+    if !configlib::Config::load().get_bool("passwords.utc").unwrap_or(false) {
+        logger.warning("set 'passwords.utc = true' in the config to write the new timestamps in UTC too!");
+    }
+}
@@ -7,14 +7,117 @@ pub mod count;
 pub mod delete;
 pub mod encrypt;
 pub mod decrypt;
+pub mod tag;
+pub mod export;
+pub mod browse;
+pub mod verify_key;
+pub mod migrate_timestamps;
+pub mod diff;
+pub mod lock;
+pub mod strength;
+pub mod rotate;
 
 use clap::ArgMatches;
+use std::path::PathBuf;
+use crate::{
+    configlib,
+    dblib,
+    errorlib,
+    loglib
+};
+
+/// Get the passwords order from `--sort`, or from the config
+/// `passwords.sort` if the flag is NOT set, default is `id`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+/// 
+/// ### Example:
+/// ```
+/// let sort = resolve_sort(command.get_one::<String>("sort"), &configlib::Config::load());
+/// ```
+pub fn resolve_sort(flag: Option<&String>, config: &configlib::Config) -> dblib::pm::SortBy {
+    let logger = loglib::Logger::new("resolve-sort");
+    if let Some(name) = flag {
+        return dblib::pm::SortBy::from_name(name).unwrap_or(dblib::pm::SortBy::Id);
+    }
+    if let Some(name) = config.get_str("passwords.sort") {
+        return dblib::pm::SortBy::from_name(&name).unwrap_or_else(|| logger.error(
+            &format!("invalid config 'passwords.sort = \"{}\"', use id, name, or update!", name),
+            errorlib::ExitErrorCode::InvalidConfig
+        ));
+    }
+    dblib::pm::SortBy::Id
+}
+
+/// Get the max length of the names in the table from the config
+/// `passwords.max_name_length`, default is `MAX_NAME_LENGTH`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+/// 
+/// ### Example:
+/// ```
+/// let max_name = resolve_max_name_length(&configlib::Config::load());
+/// ```
+pub fn resolve_max_name_length(config: &configlib::Config) -> usize {
+    let logger = loglib::Logger::new("resolve-max-name-length");
+    match config.get_int("passwords.max_name_length") {
+        Some(length) => usize::try_from(length)
+            .ok()
+            .filter(|length| *length > 0)
+            .unwrap_or_else(|| logger.error(
+                "config 'passwords.max_name_length' must be a positive integer!",
+                errorlib::ExitErrorCode::InvalidConfig
+            )),
+        None => crate::displaylib::passwords::MAX_NAME_LENGTH
+    }
+}
+
+/// Get the password with the id, None if NOT found.
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBConnection`
+///
+/// ### Example:
+/// ```
+/// let pass = get_password_by_id(filelib::pm::get_decrypted_db_path(), "23");
+/// ```
+pub fn get_password_by_id(pm_db_path: PathBuf, id: &str) -> Option<dblib::pm::PasswordInfoForm> {
+    dblib::pm::get_passwords(pm_db_path)
+        .into_iter()
+        .find(|pass| pass.id.to_string() == id)
+}
+
+/// The max icon length, a single emoji or a short label.
+const MAX_ICON_LENGTH: usize = 8;
+
+/// Check the icon length, exit if it is longer than `MAX_ICON_LENGTH`.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::Input`
+/// 
+/// ### Example:
+/// ```
+/// check_icon("🐙");
+/// ```
+pub fn check_icon(icon: &str) {
+    let logger = loglib::Logger::new("check-icon");
+    if icon.chars().count() > MAX_ICON_LENGTH {
+        logger.error(
+            &format!("icon must be at most {} characters long!", MAX_ICON_LENGTH),
+            errorlib::ExitErrorCode::Input
+        );
+    }
+}
 
 /// Encrypt and decrypt password manager database.
 pub struct PMDatabaseEncrption {
     en_path: String,
     de_path: String,
-    key: String
+    key: String,
+    /// The passphrase salt, empty for a key.
+    salt: Vec<u8>
 }
 
 impl PMDatabaseEncrption {
@@ -36,10 +139,18 @@ impl PMDatabaseEncrption {
                 .to_str()
                 .unwrap()
                 .to_string(),
-            key: "".to_owned()
+            key: "".to_owned(),
+            // It is read from the encrypted database header on the decryption.
+            salt: vec![]
         }
     }
 
+    /// Check if the database is decrypted to a tmpfs (the config
+    /// `passwords.tmpfs`), NOT next to the encrypted database.
+    pub fn is_tmpfs(&self) -> bool {
+        crate::filelib::make_encrypt_path(self.de_path.clone()) != self.en_path
+    }
+
     /// Set the key. It will take the key from the user 
     /// using `utilities::input` if key is None, 
     /// else will use the input key.
@@ -55,25 +166,184 @@ impl PMDatabaseEncrption {
         } else {
             self.key = key.unwrap();
         }
+        self.salt = vec![];
+    }
+
+    /// Set the key derived from a passphrase with a new random salt. It
+    /// will ask the user to enter the passphrase twice if it is None.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidKey`
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut pm_db = PMDatabaseEncrption::new();
+    /// pm_db.set_passphrase(None);
+    /// pm_db.encrypt();
+    /// ```
+    pub fn set_passphrase(&mut self, passphrase: Option<String>) {
+        let passphrase = passphrase.unwrap_or_else(|| {
+            let passphrase = crate::utilities::input("Enter the passphrase: ");
+            if crate::utilities::input("Enter the passphrase again: ") != passphrase {
+                loglib::Logger::new("set-passphrase").error(
+                    "the passphrases do NOT match!",
+                    errorlib::ExitErrorCode::InvalidKey
+                );
+            }
+            passphrase
+        });
+        let salt = crate::encryption_manager::generate_salt();
+        self.set_key(Some(
+            crate::encryption_manager::derive_key_from_passphrase(&passphrase, &salt)
+        ));
+        self.salt = salt;
     }
 
     /// Decrypt the password manager database.
     /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::FileAlreadyDecrypted`
+    /// 
     /// ### Example:
     /// ```
     /// let mut pm_db = PMDatabaseEncrption::new();
     /// pm_db.decrypt();
     /// ```
     pub fn decrypt(&mut self) {
-        self.set_key(None);
-        crate::encryption_manager::decrypt_file::decrypt(
-            self.en_path.clone(),
-            self.key.clone()
-        );
-        crate::filelib::wipe_delete(self.en_path.clone());
+        if !self.try_decrypt() {
+            loglib::Logger::new("decrypt-pm-database").error(
+                &format!(
+                    "the decrypted database '{}' already exists, it may have unsaved changes! move it, or delete it if the encrypted database is up to date.",
+                    self.de_path
+                ),
+                errorlib::ExitErrorCode::FileAlreadyDecrypted
+            );
+        }
     }
 
-    /// Encrypt the password manager database.
+    /// Decrypt the password manager database like `decrypt`, but the
+    /// decrypted database is NOT overwritten if it already exists (e.g.
+    /// a crash or another command left it), `db_state` is `Encrypted` if
+    /// both exist. Returns false if nothing is decrypted.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut pm_db = PMDatabaseEncrption::new();
+    /// if !pm_db.try_decrypt() {
+    ///     println!("the database is already decrypted!");
+    /// }
+    /// ```
+    pub fn try_decrypt(&mut self) -> bool {
+        if std::path::Path::new(&self.de_path).exists() {
+            return false;
+        }
+        // Use the session cached key if the cache is on and the key is valid.
+        let ttl = crate::sessionlib::ttl(&configlib::Config::load());
+        let session_path = crate::sessionlib::get_session_path();
+        let cached_key = ttl
+            .and_then(|_| crate::sessionlib::load(&session_path, crate::sessionlib::now()))
+            .filter(|key| crate::encryption_manager::decrypt_file::verify_key(&self.en_path, key));
+        let is_cached = cached_key.is_some();
+        self.salt = crate::encryption_manager::decrypt_file::read_salt(&self.en_path);
+        if is_cached {
+            loglib::Logger::new("session").info("using the cached key, run 'xpm forget' to delete it.");
+        }
+        self.key = match cached_key {
+            Some(key) => key,
+            // A key set with `set_key` (e.g. `--key-file`) is NOT asked.
+            None if !self.key.is_empty() => {
+                if !crate::encryption_manager::decrypt_file::verify_key(&self.en_path, &self.key) {
+                    loglib::Logger::new("unlock").error(
+                        "wrong key!",
+                        errorlib::ExitErrorCode::InvalidKey
+                    );
+                }
+                self.key.clone()
+            },
+            None => self.unlock()
+        };
+        crate::loglib::time("decrypt", || {
+            if self.is_tmpfs() {
+                // Keep the encrypted database, it is replaced on the encryption.
+                crate::filelib::pm::create_tmpfs_dir(&self.de_path);
+                crate::encryption_manager::decrypt_file::decrypt_to(
+                    self.en_path.clone(),
+                    self.de_path.clone(),
+                    self.key.clone()
+                );
+                return;
+            }
+            crate::encryption_manager::decrypt_file::decrypt(
+                self.en_path.clone(),
+                self.key.clone()
+            );
+            crate::filelib::wipe_delete(self.en_path.clone()).unwrap_or_else(|err| err.exit());
+        });
+        if let (Some(ttl), false) = (ttl, is_cached) {
+            crate::sessionlib::store(&session_path, &self.key, crate::sessionlib::now() + ttl);
+        }
+        true
+    }
+
+    /// Ask the key, the prompt shows the attempt number. A wrong key is
+    /// counted in the failed attempts, after `sessionlib::MAX_ATTEMPTS`
+    /// failed attempts the unlock waits `sessionlib::COOLDOWN_SECS`.
+    /// The passphrase is asked instead if the database has a salt.
+    /// 
+    /// ### Exit:
+    /// - `errorlib::ExitErrorCode::InvalidKey`
+    fn unlock(&self) -> String {
+        let logger = loglib::Logger::new("unlock");
+        let path = crate::sessionlib::get_attempts_path();
+        let mut attempts = crate::sessionlib::Attempts::load(&path, crate::sessionlib::now());
+        if let Some(left) = attempts.cooldown(crate::sessionlib::now()) {
+            logger.error(
+                &format!("too many failed attempts, try again in {} seconds!", left),
+                errorlib::ExitErrorCode::InvalidKey
+            );
+        }
+        let key = if self.salt.is_empty() {
+            crate::utilities::input(&attempts.prompt("key"))
+        } else {
+            crate::encryption_manager::derive_key_from_passphrase(
+                &crate::utilities::input(&attempts.prompt("passphrase")),
+                &self.salt
+            )
+        };
+        if !crate::encryption_manager::decrypt_file::verify_key(&self.en_path, &key) {
+            attempts.fail(&path, crate::sessionlib::now());
+            let message = match attempts.remaining() {
+                0 => format!("wrong key! try again in {} seconds.", crate::sessionlib::COOLDOWN_SECS),
+                left => format!("wrong key! {} attempts left.", left)
+            };
+            logger.error(&message, errorlib::ExitErrorCode::InvalidKey);
+        }
+        crate::sessionlib::Attempts::reset(&path);
+        key
+    }
+
+    /// Save an encrypted snapshot of the decrypted database, the decrypted
+    /// database stays as it is. A crash after it loses the changes since the
+    /// snapshot only, and leaves the database encrypted. Returns false if
+    /// there is no key (the database was NOT encrypted) or it fails.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut pm_db = PMDatabaseEncrption::new();
+    /// pm_db.decrypt();
+    /// pm_db.snapshot();
+    /// ```
+    pub fn snapshot(&self) -> bool {
+        !self.key.is_empty() && crate::encryption_manager::encrypt_file::encrypt_snapshot(
+            &self.de_path,
+            &self.en_path,
+            &self.key,
+            &self.salt
+        )
+    }
+
+    /// Encrypt the password manager database, the passphrase salt
+    /// is saved in the encrypted database header.
     /// 
     /// ### Example:
     /// ```
@@ -81,10 +351,150 @@ impl PMDatabaseEncrption {
     /// pm_db.encrypt();
     /// ```
     pub fn encrypt(&self) {
-        crate::encryption_manager::encrypt_file::encrypt(
-            self.de_path.clone(), 
-            self.key.clone()
+        crate::loglib::time("encrypt", || {
+            // The encrypted database is written to a temp file and renamed
+            // over the old one, a crash never leaves a part of it.
+            if !crate::encryption_manager::encrypt_file::encrypt_snapshot(&self.de_path, &self.en_path, &self.key, &self.salt) {
+                loglib::Logger::new("encrypt-pm-database").error(
+                    "can NOT write the encrypted database!",
+                    errorlib::ExitErrorCode::FileWrite
+                );
+            }
+            crate::filelib::wipe_delete(self.de_path.clone()).unwrap_or_else(|err| err.exit());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::{configlib, dblib, filelib::create_file};
+
+    #[test]
+    fn resolve_sort() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/resolve_sort");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let config_path = temp_dir.join("config.toml");
+        let db_path = temp_dir.join("test.db");
+        create_file(config_path.clone()).unwrap();
+        create_file(db_path.clone()).unwrap();
+        std::fs::write(&config_path, "[passwords]\nsort = \"name\"\n")
+            .expect("Can NOT write the test config!!");
+        dblib::pm::create_passwords_table(db_path.clone());
+        for name in ["gitlab", "Email", "github"] {
+            dblib::pm::save_password(db_path.clone(), name.to_owned(), "pass123".to_owned());
+        }
+
+        let config = configlib::Config::load_from(config_path);
+        let sort = super::resolve_sort(None, &config);
+        assert_eq!(sort, dblib::pm::SortBy::Name, "Config sort NOT match!!");
+        let names: Vec<String> = dblib::pm::find_password_sorted(db_path.clone(), "".to_owned(), sort)
+            .into_iter()
+            .map(|pass| pass.name)
+            .collect();
+        assert_eq!(names, vec!["Email", "github", "gitlab"], "Passwords order NOT match!!");
+        assert_eq!(
+            super::resolve_sort(Some(&"id".to_owned()), &config),
+            dblib::pm::SortBy::Id,
+            "Flag sort NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn decrypt_keeps_plaintext() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/decrypt_keeps_plaintext");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let de_path = temp_dir.join("passwords.db");
+        let en_path = temp_dir.join("passwords.db.x");
+        create_file(de_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(de_path.clone());
+        dblib::pm::save_password(de_path.clone(), "github".to_owned(), "old-pass".to_owned());
+        let key = fernet::Fernet::generate_key();
+        crate::encryption_manager::encrypt_file::encrypt_snapshot(
+            de_path.to_str().unwrap(),
+            en_path.to_str().unwrap(),
+            &key,
+            &[]
         );
-        crate::filelib::wipe_delete(self.de_path.clone());
+        // Unsaved changes in the decrypted database, both databases exist.
+        dblib::pm::save_password(de_path.clone(), "gitlab".to_owned(), "new-pass".to_owned());
+        let before = std::fs::read(&de_path).unwrap();
+
+        let mut pm_db = super::PMDatabaseEncrption {
+            en_path: en_path.to_str().unwrap().to_owned(),
+            de_path: de_path.to_str().unwrap().to_owned(),
+            key: key.clone(),
+            salt: vec![]
+        };
+        assert!(!pm_db.try_decrypt(), "Decrypt over the decrypted database NOT refused!!");
+        assert_eq!(std::fs::read(&de_path).unwrap(), before, "Decrypted database overwritten!!");
+        assert!(en_path.exists(), "Encrypted database deleted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn encrypt_passphrase() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/encrypt_passphrase");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let de_path = temp_dir.join("passwords.db");
+        let en_path = temp_dir.join("passwords.db.x");
+        create_file(de_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(de_path.clone());
+        dblib::pm::save_password(de_path.clone(), "github".to_owned(), "pass123".to_owned());
+
+        let mut pm_db = super::PMDatabaseEncrption {
+            en_path: en_path.to_str().unwrap().to_owned(),
+            de_path: de_path.to_str().unwrap().to_owned(),
+            key: "".to_owned(),
+            salt: vec![]
+        };
+        pm_db.set_passphrase(Some("correct horse battery staple".to_owned()));
+        pm_db.encrypt();
+        assert!(!de_path.exists(), "Decrypted database NOT wiped!!");
+
+        // The salt is in the header only.
+        let salt = crate::encryption_manager::decrypt_file::read_salt(en_path.to_str().unwrap());
+        assert_eq!(salt.len(), crate::encryption_manager::SALT_SIZE, "Header salt size NOT match!!");
+        assert!(!temp_dir.join("passwords.db.salt").exists(), "Salt saved next to the database!!");
+        let key = crate::encryption_manager::derive_key_from_passphrase("correct horse battery staple", &salt);
+        assert_eq!(key, pm_db.key, "Derived key NOT match!!");
+        assert!(
+            crate::encryption_manager::decrypt_file::verify_key(en_path.to_str().unwrap(), &key),
+            "Derived key does NOT decrypt the database!!"
+        );
+        assert_ne!(
+            crate::encryption_manager::derive_key_from_passphrase("correct horse battery staple", &[7u8; 16]),
+            key,
+            "Key NOT changed with the salt!!"
+        );
+
+        // A key removes the salt.
+        crate::encryption_manager::decrypt_file::decrypt(en_path.to_str().unwrap().to_owned(), key);
+        pm_db.set_key(Some(fernet::Fernet::generate_key()));
+        pm_db.encrypt();
+        assert!(
+            crate::encryption_manager::decrypt_file::read_salt(en_path.to_str().unwrap()).is_empty(),
+            "Header salt NOT empty!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
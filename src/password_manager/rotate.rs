@@ -0,0 +1,259 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf
+};
+use super::{
+    ArgMatches,
+    PMDatabaseEncrption,
+    generate
+};
+use crate::{
+    backup_manager::backup,
+    configlib,
+    dblib,
+    errorlib,
+    filelib,
+    loglib,
+    utilities
+};
+
+/// The rotated passwords length, if the config `[generate.default]` has no length.
+const ROTATE_LENGTH: u16 = 24;
+
+/// Get the ids of the passwords used by many entries, sorted by the id.
+///
+/// ### Example:
+/// ```
+/// let ids = reused(&dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path()));
+/// ```
+fn reused(passwords: &[dblib::pm::PasswordInfoForm]) -> Vec<i32> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for pass in passwords {
+        *counts.entry(pass.password.as_str()).or_insert(0) += 1;
+    }
+    let mut ids: Vec<i32> = passwords.iter()
+        .filter(|pass| counts[pass.password.as_str()] > 1)
+        .map(|pass| pass.id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Get the ids of the passwords in the breached passwords list, sorted by the id.
+///
+/// ### Example:
+/// ```
+/// let ids = breached(
+///     &dblib::pm::get_passwords(filelib::pm::get_decrypted_db_path()),
+///     &HashSet::from(["123456".to_string()])
+/// );
+/// ```
+fn breached(passwords: &[dblib::pm::PasswordInfoForm], leaked: &HashSet<String>) -> Vec<i32> {
+    let mut ids: Vec<i32> = passwords.iter()
+        .filter(|pass| leaked.contains(&pass.password))
+        .map(|pass| pass.id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Read the breached passwords list, a password per line (e.g. a leaked
+/// passwords list download), the empty lines are skipped.
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::FileNotFound`
+/// - `errorlib::ExitErrorCode::FileRead`
+fn read_breached(path: &str) -> HashSet<String> {
+    let logger = loglib::Logger::new("read-breached");
+    if !PathBuf::new().join(path).is_file() {
+        logger.error(
+            &format!("breached passwords list '{}' NOT found!", path),
+            errorlib::ExitErrorCode::FileNotFound
+        );
+    }
+    let bytes = std::fs::read(path).unwrap_or_else(|_| logger.error(
+        &format!("can NOT read '{}'!", path),
+        errorlib::ExitErrorCode::FileRead
+    ));
+    // The leaked lists are NOT always valid UTF-8.
+    String::from_utf8_lossy(&bytes)
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+/// Give the passwords with the ids new generated passwords, in one
+/// transaction. Every new password is unique. Returns the rotated
+/// passwords names, sorted by the id.
+///
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::DBUpdate`
+/// - `errorlib::ExitErrorCode::DBConnection`
+///
+/// ### Example:
+/// ```
+/// let profile = generate::GenerateProfile::load(&configlib::Config::load(), None);
+/// let names = rotate(filelib::pm::get_decrypted_db_path(), &[2, 5], 24, &profile.get_sample());
+/// ```
+fn rotate(pm_db_path: PathBuf, ids: &[i32], length: u16, sample: &[char]) -> Vec<String> {
+    let passwords = dblib::pm::get_passwords(pm_db_path.clone());
+    let mut used: HashSet<String> = passwords.iter()
+        .map(|pass| pass.password.clone())
+        .collect();
+    let mut sample = sample.to_vec();
+    let mut updates: Vec<(i32, String)> = vec![];
+    let mut names: Vec<String> = vec![];
+    for pass in passwords.iter().filter(|pass| ids.contains(&pass.id)) {
+        let mut password = generate::generate(length, &mut sample);
+        while used.contains(&password) {
+            password = generate::generate(length, &mut sample);
+        }
+        used.insert(password.clone());
+        updates.push((pass.id, password));
+        names.push(pass.name.clone());
+    }
+    dblib::pm::update_passwords(pm_db_path, updates);
+    names
+}
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("rotate-passwords");
+    let breached_path = command.get_one::<String>("breached");
+    let is_reused = *command.get_one::<bool>("reused").unwrap_or(&false);
+    if breached_path.is_none() && !is_reused {
+        logger.error(
+            "Run with 'password-manager rotate --help'",
+            errorlib::ExitErrorCode::MissingArg
+        );
+    }
+    let leaked = breached_path.map(|path| read_breached(path));
+    let pm_db_state = filelib::pm::db_state();
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
+    if pm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "password manager database is empty!",
+            errorlib::ExitErrorCode::PMDatabaseNotFound
+        );
+    }
+    // Confirm before the decryption, so the database is NOT left decrypted.
+    logger.warning("the breached and reused passwords will get new passwords!");
+    utilities::confirm();
+    logger.start();
+    // Keep a copy of the old passwords (encrypted if the database is
+    // encrypted), the services still use them until they are updated.
+    let backup_path = backup::password_manager_snapshot(
+        if pm_db_state == filelib::FileState::Encrypted {
+            filelib::pm::get_encrypted_db_path()
+        } else {
+            filelib::pm::get_decrypted_db_path()
+        }
+    );
+    if pm_db_state == filelib::FileState::Encrypted {
+        logger.warning("database is encrypted!");
+        pm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("password manager database decrypted successfully.");
+    }
+    let pm_db_path = filelib::pm::get_decrypted_db_path();
+    let passwords = dblib::pm::get_passwords(pm_db_path.clone());
+    let mut ids: Vec<i32> = vec![];
+    if let Some(leaked) = leaked.as_ref() {
+        ids.extend(breached(&passwords, leaked));
+    }
+    if is_reused {
+        ids.extend(reused(&passwords));
+    }
+    ids.sort();
+    ids.dedup();
+
+    if ids.is_empty() {
+        filelib::wipe_delete(backup_path.to_str().unwrap().to_owned()).unwrap_or_else(|err| err.exit());
+        logger.info("there is no breached or reused password.");
+    } else {
+        logger.info(
+            &format!("the old passwords backup saved at '{}'.", backup_path.display())
+        );
+        let profile = generate::GenerateProfile::load(&configlib::Config::load(), None);
+        let names = rotate(
+            pm_db_path,
+            &ids,
+            profile.length.unwrap_or(ROTATE_LENGTH),
+            &profile.get_sample()
+        );
+        for name in names.iter() {
+            logger.info(&format!("'{}' rotated.", name));
+        }
+        dblib::log::register(
+            &format!("{} breached or reused passwords rotated", names.len()),
+            dblib::log::LogAction::Update,
+            filelib::log::get_log_db_path()
+        );
+        logger.info(&format!("{} passwords rotated successfully.", names.len()));
+        logger.warning("update the passwords in the services too, they still use the old passwords!!");
+    }
+
+    if _is_db_decrypted {
+        pm_db_encryption.encrypt();
+        logger.info("password manager database encrypted successfully.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, path::PathBuf};
+    use crate::{
+        dblib,
+        filelib::create_file,
+        utilities
+    };
+
+    #[test]
+    fn rotate_flagged_passwords() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/rotate_flagged_passwords");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        dblib::pm::save_password(db_path.clone(), "github".to_owned(), "shared-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "gitlab".to_owned(), "shared-1".to_owned());
+        dblib::pm::save_password(db_path.clone(), "email".to_owned(), "123456".to_owned());
+        dblib::pm::save_password(db_path.clone(), "bank".to_owned(), "Xk9#mT2v!q7Zr%wL".to_owned());
+
+        let passwords = dblib::pm::get_passwords(db_path.clone());
+        let reused = super::reused(&passwords);
+        assert_eq!(reused, vec![1, 2], "Reused passwords NOT match!!");
+        let breached = super::breached(&passwords, &HashSet::from(["123456".to_owned(), "qwerty".to_owned()]));
+        assert_eq!(breached, vec![3], "Breached passwords NOT match!!");
+
+        let ids = [reused, breached].concat();
+        let names = super::rotate(
+            db_path.clone(),
+            &ids,
+            24,
+            &utilities::get_sample(utilities::PasswordSample::Ascii)
+        );
+        assert_eq!(names, vec!["github", "gitlab", "email"], "Rotated names NOT match!!");
+
+        let rotated = dblib::pm::get_passwords(db_path.clone());
+        for (old, new) in passwords.iter().zip(rotated.iter()) {
+            if ids.contains(&old.id) {
+                assert_ne!(old.password, new.password, "'{}' NOT rotated!!", old.name);
+                assert_eq!(new.password.len(), 24, "'{}' new password length NOT match!!", old.name);
+            } else {
+                assert_eq!(old.password, new.password, "Clean '{}' changed!!", old.name);
+            }
+        }
+        assert!(super::reused(&rotated).is_empty(), "Rotated passwords are reused!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
@@ -10,15 +10,89 @@ use crate::{
     filelib
 };
 
+/// The batch passwords `(name, password)` and the malformed lines `(line number, error)`.
+type Batch = (Vec<(String, String)>, Vec<(usize, String)>);
+
+/// Parse the batch file contents, each line is `name=password` (both trimmed),
+/// blank lines and `#` comments are skipped.
+///
+/// ### Example:
+/// ```
+/// let (passwords, errors) = parse_batch("# work\ngithub=pass123\ngitlab");
+/// assert_eq!(passwords, vec![("github".to_owned(), "pass123".to_owned())]);
+/// assert_eq!(errors[0].0, 3);
+/// ```
+fn parse_batch(contents: &str) -> Batch {
+    let mut passwords: Vec<(String, String)> = vec![];
+    let mut errors: Vec<(usize, String)> = vec![];
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        // The password may have '=', split on the first one only.
+        match line.split_once('=') {
+            Some((name, password)) if !name.trim().is_empty() && !password.trim().is_empty() => {
+                passwords.push((name.trim().to_owned(), password.trim().to_owned()));
+            },
+            Some(_) => errors.push(
+                (line_number, "name and password must NOT be empty".to_owned())
+            ),
+            None => errors.push(
+                (line_number, "expected 'name=password'".to_owned())
+            )
+        }
+    }
+    (passwords, errors)
+}
+
 pub fn main(command: &ArgMatches) {
     let mut logger = loglib::Logger::new("save-password");
-    let name = command.get_one::<String>("NAME").unwrap();
-    let password: String = utilities::input("Enter the password: ");
-    if password.len() < 1 {
-        logger.error(
-            "password must be at least one character long!",
-            errorlib::ExitErrorCode::Input
-        );
+    let mut passwords: Vec<(String, String)> = vec![];
+    let batch_path = command.get_one::<String>("batch");
+    let batch = utilities::Batch::new(
+        command.get_one::<String>("on-error")
+            .and_then(|mode| utilities::OnError::from_name(mode))
+            .unwrap_or(utilities::OnError::Continue)
+    );
+    let icon = command.get_one::<String>("icon");
+    if let Some(icon) = icon {
+        super::check_icon(icon);
+    }
+    if let Some(path) = batch_path {
+        let contents = std::fs::read_to_string(path).unwrap_or_else(|_| logger.error(
+            &format!("can NOT read '{}'!", path),
+            errorlib::ExitErrorCode::FileRead
+        ));
+        let (batch_passwords, errors) = parse_batch(&contents);
+        for (line_number, error) in errors.iter() {
+            batch.fail(utilities::BatchFailure::new(
+                &format!("line {}", line_number),
+                errorlib::ExitErrorCode::Input,
+                error
+            ));
+        }
+        // Nothing is saved if the batch stops at a malformed line.
+        if batch.is_stopped() {
+            batch.finish(&logger);
+        }
+        if batch_passwords.is_empty() {
+            logger.error(
+                "no valid passwords in the batch file!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        passwords = batch_passwords;
+    } else {
+        let name = command.get_one::<String>("NAME").unwrap();
+        let password: String = utilities::input("Enter the password: ");
+        if password.is_empty() {
+            logger.error(
+                "password must be at least one character long!",
+                errorlib::ExitErrorCode::Input
+            );
+        }
+        passwords.push((name.clone(), password));
     }
     let pm_db_state = filelib::pm::db_state();
     let mut pm_db_encryption = PMDatabaseEncrption::new();
@@ -26,7 +100,9 @@ pub fn main(command: &ArgMatches) {
     if pm_db_state == filelib::FileState::NotFound {
         filelib::create_file(
             filelib::pm::get_decrypted_db_path()
-        );
+        ).unwrap_or_else(|err| err.exit());
+        dblib::pm::create_passwords_table(filelib::pm::get_decrypted_db_path());
+        dblib::log::register("create passwords table", dblib::log::LogAction::Create, filelib::log::get_log_db_path());
     } else if pm_db_state == filelib::FileState::Encrypted {
         logger.warning("database is encrypted!");
         pm_db_encryption.decrypt();
@@ -34,17 +110,80 @@ pub fn main(command: &ArgMatches) {
         _is_db_decrypted = true;
         logger.info("password manager database decrypted successfully.");
     }
-    dblib::pm::save_password(
-        filelib::pm::get_decrypted_db_path(),
-        name.clone(),
-        password
-    );
-    dblib::log::register(
-        &format!("'{}' saved successfully.", name), 
-        filelib::log::get_log_db_path()
-    );
+    if batch_path.is_none() {
+        let (name, password) = passwords.remove(0);
+        let id = dblib::pm::save_password(
+            filelib::pm::get_decrypted_db_path(),
+            name.clone(),
+            password
+        );
+        if let Some(icon) = icon {
+            dblib::pm::set_icon(
+                filelib::pm::get_decrypted_db_path(),
+                id.to_string(),
+                icon.clone()
+            );
+        }
+        dblib::log::register(
+            &format!("'{}' saved successfully.", name),
+            dblib::log::LogAction::Save,
+            filelib::log::get_log_db_path()
+        );
+    } else {
+        let rows = dblib::pm::save_passwords(
+            filelib::pm::get_decrypted_db_path(),
+            passwords
+        );
+        dblib::log::register(
+            &format!("{} passwords saved from a batch file.", rows),
+            dblib::log::LogAction::Save,
+            filelib::log::get_log_db_path()
+        );
+        logger.info(&format!("{} passwords saved successfully.", rows));
+    }
     if _is_db_decrypted {
         pm_db_encryption.encrypt();
         logger.info("password manager database encrypted successfully.");
     }
-}
\ No newline at end of file
+    // The malformed lines were skipped.
+    batch.finish(&logger);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::{dblib, filelib::create_file};
+
+    #[test]
+    fn save_batch() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/save_batch");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+
+        let (passwords, errors) = super::parse_batch(
+            "# work passwords\ngithub=pass=123\n\n  gitlab = pass456\nmalformed line\n=empty-name\n"
+        );
+        assert_eq!(
+            errors.iter().map(|(line, _)| *line).collect::<Vec<usize>>(),
+            vec![5, 6],
+            "Malformed lines NOT match!!"
+        );
+        let rows = dblib::pm::save_passwords(db_path.clone(), passwords);
+        assert_eq!(rows, 2, "Number of saved passwords NOT match!!");
+        let saved = dblib::pm::get_passwords(db_path.clone());
+        assert!(
+            saved[0].name == "github" && saved[0].password == "pass=123" &&
+            saved[1].name == "gitlab" && saved[1].password == "pass456",
+            "Saved passwords NOT match!!"
+        );
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
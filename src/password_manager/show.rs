@@ -3,6 +3,8 @@ use super::{
     PMDatabaseEncrption
 };
 use crate::{
+    cliplib,
+    configlib,
     loglib,
     filelib,
     dblib,
@@ -11,7 +13,38 @@ use crate::{
 };
 
 pub fn main(command: &ArgMatches) {
+    let format = if *command.get_one::<bool>("table").unwrap_or(&false) {
+        displaylib::passwords::ListFormat::Table
+    } else {
+        command.get_one::<String>("format")
+            .map(|name| displaylib::passwords::ListFormat::from_name(name).unwrap())
+            .unwrap_or(displaylib::passwords::ListFormat::Table)
+    };
+    if matches!(
+        format,
+        displaylib::passwords::ListFormat::Json | displaylib::passwords::ListFormat::Plain
+    ) {
+        // The stdout carries the passwords only.
+        loglib::stderr_only();
+    }
     let mut logger = loglib::Logger::new("show-passwords");
+    let fields = match command.get_one::<String>("fields") {
+        Some(list) => displaylib::passwords::TableField::parse_list(list)
+            .unwrap_or_else(|name| logger.error(
+                &format!("unknown field '{}', use icon, id, name, tags, created, or updated!", name),
+                errorlib::ExitErrorCode::Input
+            )),
+        None => displaylib::passwords::TableField::DEFAULT.to_vec()
+    };
+    if fields.is_empty() {
+        logger.error(
+            "use at least one field with --fields!",
+            errorlib::ExitErrorCode::Input
+        );
+    }
+    let config = configlib::Config::load();
+    let sort = super::resolve_sort(command.get_one::<String>("sort"), &config);
+    let max_name = super::resolve_max_name_length(&config);
     let pm_db_state = filelib::pm::db_state();
     let mut pm_db_encryption = PMDatabaseEncrption::new();
     let mut _is_db_decrypted: bool = false;
@@ -27,23 +60,47 @@ pub fn main(command: &ArgMatches) {
         _is_db_decrypted = true;
         logger.info("password manager database decrypted successfully.");
     }
-    let passwords = dblib::pm::get_passwords(
-        filelib::pm::get_decrypted_db_path()
-    );
+    // The password of `--clipboard <ID>`, before the database is encrypted again.
+    let copied = command.get_one::<String>("clipboard").map(|id| (
+        id,
+        super::get_password_by_id(filelib::pm::get_decrypted_db_path(), id)
+    ));
+    let passwords = loglib::time("db-query", || {
+        dblib::pm::find_password_sorted(
+            filelib::pm::get_decrypted_db_path(),
+            "".to_owned(),
+            sort
+        )
+    });
     if _is_db_decrypted {
         pm_db_encryption.encrypt();
         logger.info("password manager database encrypted successfully.");
     }
+    if let Some((id, pass)) = copied {
+        let pass = pass.unwrap_or_else(|| logger.error(
+            &format!("there is no password with id {}!", id),
+            errorlib::ExitErrorCode::Input
+        ));
+        logger.info(&format!("copying the password of '{}' to the clipboard...", pass.name));
+        cliplib::copy(
+            pass.password,
+            cliplib::ClearPolicy::Timeout,
+            cliplib::resolve_clear_after(command.get_one::<String>("clear-after"))
+        );
+        return;
+    }
     if passwords.len() < 1 {
         logger.error(
             "password manager database is empty!",
             errorlib::ExitErrorCode::PMDatabaseEmpty
         );
     }
-    if *command.get_one::<bool>("table").unwrap_or(&false) {
-        displaylib::passwords::display_as_table(passwords);
-    } else {
-        displaylib::passwords::display_many(passwords, "".to_owned());
-    }
+    loglib::time("display", || {
+        if command.get_one::<String>("group-by").is_some() {
+            displaylib::passwords::display_grouped(passwords, "".to_owned());
+        } else {
+            displaylib::passwords::display_passwords(passwords, format, &fields, "", max_name);
+        }
+    });
     logger.info("all passwords have been successfully displayed.");
 }
\ No newline at end of file
@@ -0,0 +1,318 @@
+use super::ArgMatches;
+use crate::{
+    displaylib,
+    errorlib,
+    loglib,
+    utilities
+};
+
+/// The min length of a password, shorter passwords are too short.
+pub const MIN_LENGTH: usize = 12;
+
+/// The min length of a sequence, keyboard pattern, or a dictionary word.
+const MIN_PATTERN_LENGTH: usize = 4;
+
+/// The common words of the leaked passwords lists (lowercase).
+const DICTIONARY: [&str; 40] = [
+    "password", "pass", "admin", "welcome", "login", "master", "secret",
+    "letmein", "dragon", "monkey", "shadow", "sunshine", "princess", "football",
+    "baseball", "soccer", "iloveyou", "love", "hello", "freedom", "whatever",
+    "trustno", "superman", "batman", "summer", "winter", "spring", "autumn",
+    "flower", "cookie", "cheese", "computer", "internet", "google", "apple",
+    "orange", "banana", "charlie", "michael", "jordan"
+];
+
+/// The keyboard rows and columns (QWERTY, lowercase).
+const KEYBOARD: [&str; 5] = [
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+    "qazwsxedcrfvtgbyhnujmikolp",
+    "1qaz2wsx3edc4rfv5tgb6yhn7ujm8ik9ol0p"
+];
+
+/// A weakness found in a password.
+#[derive(PartialEq, Debug)]
+pub enum Weakness {
+    /// Shorter than `MIN_LENGTH`, with the length.
+    TooShort(usize),
+    /// A single characters class (e.g. only lowercase).
+    OneClass(&'static str),
+    /// A common word (e.g. "password", also "p@ssw0rd").
+    DictionaryWord(String),
+    /// Sequential characters (e.g. "1234", "abcd", "4321").
+    Sequence(String),
+    /// Keyboard keys next to each other (e.g. "qwerty").
+    KeyboardPattern(String),
+    /// The same character 3 times or more in a row (e.g. "aaa").
+    Repeated(String)
+}
+
+impl Weakness {
+    /// What is wrong with the password.
+    pub fn message(&self) -> String {
+        match self {
+            Weakness::TooShort(length) => format!("too short ({} characters, at least {})", length, MIN_LENGTH),
+            Weakness::OneClass(class) => format!("only {}", class),
+            Weakness::DictionaryWord(word) => format!("contains the dictionary word '{}'", word),
+            Weakness::Sequence(sequence) => format!("contains the sequential characters '{}'", sequence),
+            Weakness::KeyboardPattern(pattern) => format!("contains the keyboard pattern '{}'", pattern),
+            Weakness::Repeated(repeated) => format!("contains the repeated characters '{}'", repeated)
+        }
+    }
+
+    /// How to fix the weakness.
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            Weakness::TooShort(_) => "use a longer password, or a passphrase of many random words",
+            Weakness::OneClass(_) => "mix lowercase, uppercase, digits, and symbols",
+            Weakness::DictionaryWord(_) => "do NOT use common words, even with replaced letters (e.g. p@ssw0rd)",
+            Weakness::Sequence(_) => "do NOT use sequences like 1234 or abcd, they are tried first",
+            Weakness::KeyboardPattern(_) => "do NOT use keys next to each other like qwerty or asdf",
+            Weakness::Repeated(_) => "do NOT repeat the same character"
+        }
+    }
+}
+
+/// The password strength, from very weak to very strong.
+#[derive(PartialEq, PartialOrd, Clone, Copy, Debug)]
+pub enum Strength {
+    VeryWeak,
+    Weak,
+    Fair,
+    Strong,
+    VeryStrong
+}
+
+impl Strength {
+    /// The strength label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Strength::VeryWeak => "very weak",
+            Strength::Weak => "weak",
+            Strength::Fair => "fair",
+            Strength::Strong => "strong",
+            Strength::VeryStrong => "very strong"
+        }
+    }
+
+    fn from_score(score: u8) -> Strength {
+        match score {
+            0 => Strength::VeryWeak,
+            1 => Strength::Weak,
+            2 => Strength::Fair,
+            3 => Strength::Strong,
+            _ => Strength::VeryStrong
+        }
+    }
+}
+
+/// Replace the look-alike digits and symbols with the letters (e.g. "p@ssw0rd" => "password").
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' | '|' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c.to_ascii_lowercase()
+    }
+}
+
+/// The maximal runs of `min` characters or more, where every two
+/// characters in a row match `is_next`.
+fn runs(chars: &[char], min: usize, is_next: impl Fn(char, char) -> bool) -> Vec<String> {
+    let mut found: Vec<String> = vec![];
+    let mut start = 0;
+    for end in 1..=chars.len() {
+        if end < chars.len() && is_next(chars[end - 1], chars[end]) {
+            continue;
+        }
+        if end - start >= min {
+            found.push(chars[start..end].iter().collect());
+        }
+        start = end;
+    }
+    found
+}
+
+fn is_sequential(a: char, b: char) -> bool {
+    let same_class = (a.is_ascii_digit() && b.is_ascii_digit())
+        || (a.is_ascii_alphabetic() && b.is_ascii_alphabetic());
+    let (a, b) = (a.to_ascii_lowercase() as i16, b.to_ascii_lowercase() as i16);
+    same_class && (b - a).abs() == 1
+}
+
+fn sequences(chars: &[char]) -> Vec<String> {
+    // Split the runs by the direction, "12321" is "123" and "321".
+    let up = runs(chars, MIN_PATTERN_LENGTH, |a, b| is_sequential(a, b) && b.to_ascii_lowercase() > a.to_ascii_lowercase());
+    let down = runs(chars, MIN_PATTERN_LENGTH, |a, b| is_sequential(a, b) && b.to_ascii_lowercase() < a.to_ascii_lowercase());
+    up.into_iter().chain(down).collect()
+}
+
+fn keyboard_patterns(chars: &[char]) -> Vec<String> {
+    let is_next = |a: char, b: char| {
+        let pair: String = [a, b].iter().map(|c| c.to_ascii_lowercase()).collect();
+        let reversed: String = pair.chars().rev().collect();
+        KEYBOARD.iter().any(|row| row.contains(&pair) || row.contains(&reversed))
+    };
+    runs(chars, MIN_PATTERN_LENGTH, is_next)
+        .into_iter()
+        // The letters sequences in the keyboard rows (e.g. "fghj") are sequences.
+        .filter(|pattern| !sequences(&pattern.chars().collect::<Vec<char>>()).contains(pattern))
+        .collect()
+}
+
+fn dictionary_words(password: &str) -> Vec<String> {
+    let plain: String = password.chars().map(unleet).collect();
+    let mut words: Vec<&str> = DICTIONARY
+        .iter()
+        .filter(|word| word.len() >= MIN_PATTERN_LENGTH && plain.contains(*word))
+        .copied()
+        .collect();
+    // The longest first, skip the words in another word (e.g. "pass" in "password").
+    words.sort_by_key(|word| std::cmp::Reverse(word.len()));
+    let mut found: Vec<String> = vec![];
+    for word in words {
+        if !found.iter().any(|other| other.contains(word)) {
+            found.push(word.to_owned());
+        }
+    }
+    found
+}
+
+/// Find the weaknesses of a password, an empty list if there is none.
+/// 
+/// ### Example:
+/// ```
+/// for weakness in weaknesses("password1234") {
+///     println!("{} - {}", weakness.message(), weakness.suggestion());
+/// }
+/// ```
+pub fn weaknesses(password: &str) -> Vec<Weakness> {
+    let chars: Vec<char> = password.chars().collect();
+    let mut found: Vec<Weakness> = vec![];
+    if chars.len() < MIN_LENGTH {
+        found.push(Weakness::TooShort(chars.len()));
+    }
+    if chars.is_empty() {
+        // Nothing to check, the password is too short.
+    } else if chars.iter().all(char::is_ascii_lowercase) {
+        found.push(Weakness::OneClass("lowercase"));
+    } else if chars.iter().all(char::is_ascii_uppercase) {
+        found.push(Weakness::OneClass("uppercase"));
+    } else if chars.iter().all(char::is_ascii_digit) {
+        found.push(Weakness::OneClass("digits"));
+    }
+    found.extend(dictionary_words(password).into_iter().map(Weakness::DictionaryWord));
+    found.extend(sequences(&chars).into_iter().map(Weakness::Sequence));
+    found.extend(keyboard_patterns(&chars).into_iter().map(Weakness::KeyboardPattern));
+    found.extend(runs(&chars, 3, |a, b| a == b).into_iter().map(Weakness::Repeated));
+    found
+}
+
+/// Get the password strength from the characters pool size and
+/// the length, every pattern weakness lowers the strength.
+/// 
+/// ### Example:
+/// ```
+/// let password = "password1234";
+/// println!("{}", strength(password, &weaknesses(password)).label());
+/// ```
+pub fn strength(password: &str, weaknesses: &[Weakness]) -> Strength {
+    let chars: Vec<char> = password.chars().collect();
+    let mut pool: f64 = 0.0;
+    if chars.iter().any(char::is_ascii_lowercase) { pool += 26.0; }
+    if chars.iter().any(char::is_ascii_uppercase) { pool += 26.0; }
+    if chars.iter().any(char::is_ascii_digit) { pool += 10.0; }
+    if chars.iter().any(|c| !c.is_ascii_alphanumeric()) { pool += 33.0; }
+    let bits = chars.len() as f64 * pool.max(1.0).log2();
+    let score: u8 = match bits {
+        bits if bits < 28.0 => 0,
+        bits if bits < 36.0 => 1,
+        bits if bits < 60.0 => 2,
+        bits if bits < 128.0 => 3,
+        _ => 4
+    };
+    let patterns = weaknesses
+        .iter()
+        .filter(|weakness| !matches!(weakness, Weakness::TooShort(_) | Weakness::OneClass(_)))
+        .count();
+    let mut score = score.saturating_sub(patterns as u8);
+    if chars.len() < MIN_LENGTH {
+        score = score.min(1);
+    }
+    Strength::from_score(score)
+}
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("password-strength");
+    // Read the password if it is NOT in the command, so it is NOT in the shell history.
+    let password = match command.get_one::<String>("PASSWORD") {
+        Some(password) => password.to_owned(),
+        None => utilities::input("Enter the password: ")
+    };
+    logger.start();
+    if password.is_empty() {
+        logger.error(
+            "the password is empty!",
+            errorlib::ExitErrorCode::Input
+        );
+    }
+    let found = weaknesses(&password);
+    displaylib::strength::display(
+        strength(&password, &found),
+        &found,
+        *command.get_one::<bool>("explain").unwrap_or(&false)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Strength, Weakness};
+
+    #[test]
+    fn strength_weaknesses() {
+        assert_eq!(super::weaknesses("Ab3$"), vec![Weakness::TooShort(4)], "Short password NOT match!!");
+        assert!(
+            super::weaknesses("abcxyzmnbvtr").contains(&Weakness::OneClass("lowercase")),
+            "Only lowercase NOT found!!"
+        );
+        assert!(
+            super::weaknesses("907315286409").contains(&Weakness::OneClass("digits")),
+            "Only digits NOT found!!"
+        );
+        assert_eq!(
+            super::weaknesses("Xk9#P@ssw0rd!Tq"),
+            vec![Weakness::DictionaryWord("password".to_owned())],
+            "Dictionary word NOT match!!"
+        );
+        assert_eq!(
+            super::weaknesses("Xk9#mT1234!vqZ"),
+            vec![Weakness::Sequence("1234".to_owned())],
+            "Sequence NOT match!!"
+        );
+        assert_eq!(
+            super::weaknesses("Xk9#mTzyxw!vqZ"),
+            vec![Weakness::Sequence("zyxw".to_owned())],
+            "Reversed sequence NOT match!!"
+        );
+        assert_eq!(
+            super::weaknesses("Xk9#QWERTY!v7Z"),
+            vec![Weakness::KeyboardPattern("QWERTY".to_owned())],
+            "Keyboard pattern NOT match!!"
+        );
+        assert_eq!(
+            super::weaknesses("Xk9#mTaaaa!v7Z"),
+            vec![Weakness::Repeated("aaaa".to_owned())],
+            "Repeated characters NOT match!!"
+        );
+        assert!(super::weaknesses("Xk9#mT2v!q7Zr%").is_empty(), "Strong password has weaknesses!!");
+
+        let weak = "password1234";
+        assert!(super::strength(weak, &super::weaknesses(weak)) <= Strength::Weak, "Weak strength NOT match!!");
+        let strong = "Xk9#mT2v!q7Zr%wL";
+        assert_eq!(super::strength(strong, &super::weaknesses(strong)), Strength::Strong, "Strong strength NOT match!!");
+    }
+}
@@ -0,0 +1,92 @@
+use super::{
+    ArgMatches,
+    PMDatabaseEncrption
+};
+use crate::{
+    loglib,
+    filelib,
+    dblib,
+    errorlib,
+    utilities
+};
+
+/// Normalize the tag with `utilities::normalize_tag`, None if nothing
+/// is left of it (e.g. "  " or ",").
+/// 
+/// ### Example:
+/// ```
+/// assert_eq!(normalized_tag(" My Work "), Some("my-work".to_owned()));
+/// assert_eq!(normalized_tag(","), None);
+/// ```
+fn normalized_tag(tag: &str) -> Option<String> {
+    Some(utilities::normalize_tag(tag)).filter(|tag| !tag.is_empty())
+}
+
+pub fn main(command: &ArgMatches) {
+    let mut logger = loglib::Logger::new("tag-passwords");
+    let (is_add, command) = match command.subcommand() {
+        Some(("add", command)) => (true, command),
+        Some(("remove", command)) => (false, command),
+        _ => logger.error(
+            "Run with 'password-manager tag --help'",
+            errorlib::ExitErrorCode::MissingArg
+        )
+    };
+    // Checked before the database is decrypted.
+    let tag = normalized_tag(command.get_one::<String>("TAG").unwrap()).unwrap_or_else(|| logger.error(
+        "the tag must have at least one letter or digit!",
+        errorlib::ExitErrorCode::Input
+    ));
+    let string = command.get_one::<String>("name").unwrap();
+    let pm_db_state = filelib::pm::db_state();
+    let mut pm_db_encryption = PMDatabaseEncrption::new();
+    let mut _is_db_decrypted: bool = false;
+    if pm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "password manager database is empty!",
+            errorlib::ExitErrorCode::PMDatabaseNotFound
+        );
+    } else if pm_db_state == filelib::FileState::Encrypted {
+        logger.warning("database is encrypted!");
+        pm_db_encryption.decrypt();
+        logger.start();
+        _is_db_decrypted = true;
+        logger.info("password manager database decrypted successfully.");
+    }
+    let pm_db_path = filelib::pm::get_decrypted_db_path();
+    let rows = if is_add {
+        dblib::pm::add_tag(pm_db_path, tag.clone(), string.clone())
+    } else {
+        dblib::pm::remove_tag(pm_db_path, tag.clone(), string.clone())
+    };
+    if rows > 0 {
+        dblib::log::register(
+            &format!(
+                "tag '{}' {} {} passwords",
+                tag,
+                if is_add { "added to" } else { "removed from" },
+                rows
+            ),
+            dblib::log::LogAction::Update,
+            filelib::log::get_log_db_path()
+        );
+    }
+    if _is_db_decrypted {
+        pm_db_encryption.encrypt();
+        logger.info("password manager database encrypted successfully.");
+    }
+    logger.info(
+        &format!("there is {} password changed.", rows)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn normalized_tag() {
+        assert_eq!(super::normalized_tag(" My Work "), Some("my-work".to_owned()), "Normalized tag NOT match!!");
+        for tag in ["", "  ", ",", " , ,"] {
+            assert_eq!(super::normalized_tag(tag), None, "Empty tag '{}' NOT rejected!!", tag);
+        }
+    }
+}
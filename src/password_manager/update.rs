@@ -10,6 +10,22 @@ use crate::{
     utilities
 };
 
+/// The update log message, with the names of the changed fields
+/// only (NOT the values), None if nothing changed.
+///
+/// ### Example:
+/// ```
+/// // "updated 'github': name, password"
+/// let log = update_log(&old, &new).unwrap();
+/// ```
+fn update_log(old: &dblib::pm::PasswordInfoForm, new: &dblib::pm::PasswordInfoForm) -> Option<String> {
+    let fields = old.changed_fields(new);
+    if fields.is_empty() {
+        return None;
+    }
+    Some(format!("updated '{}': {}", old.name, fields.join(", ")))
+}
+
 pub fn main(command: &ArgMatches) {
     let mut logger = loglib::Logger::new("update-password");
     let id = command.get_one::<String>("ID").unwrap();
@@ -38,6 +54,11 @@ pub fn main(command: &ArgMatches) {
         }
         _is_choose = true;
     }
+    let icon = command.get_one::<String>("icon");
+    if let Some(icon) = icon {
+        super::check_icon(icon);
+        _is_choose = true;
+    }
     if !_is_choose {
         logger.error(
             "Run with 'password-manager update --help'",
@@ -60,40 +81,88 @@ pub fn main(command: &ArgMatches) {
         logger.info("password manager database decrypted successfully.");
     }
     let pm_db_path = filelib::pm::get_decrypted_db_path();
+    let old = super::get_password_by_id(pm_db_path.clone(), id);
     if _password.len() > 0 {
         let rows = dblib::pm::update_password(
             pm_db_path.clone(), 
             id.clone(), 
             _password
         );
-        if rows > 0 {
-            dblib::log::register(
-                &format!("the name of the password with id {} updated", id),
-                filelib::log::get_log_db_path()
-            );
-        }
         logger.info(
             &format!("there is {} password updated successfully.", rows)
         );
     }
     if _name.len() > 0 {
         let rows = dblib::pm::update_password_name(
-            pm_db_path, 
+            pm_db_path.clone(), 
             id.clone(), 
             _name
         );
-        if rows > 0 {
+        logger.info(
+            &format!("there is {} password name update successfully.", rows)
+        );
+    }
+    if let Some(icon) = icon {
+        let rows = dblib::pm::set_icon(
+            pm_db_path.clone(),
+            id.clone(),
+            icon.clone()
+        );
+        logger.info(
+            &format!("there is {} password icon updated successfully.", rows)
+        );
+    }
+    if let (Some(old), Some(new)) = (old, super::get_password_by_id(pm_db_path, id)) {
+        if let Some(log) = update_log(&old, &new) {
             dblib::log::register(
-                &format!("password with id {} updated", id),
+                &log,
+                dblib::log::LogAction::Update,
                 filelib::log::get_log_db_path()
             );
         }
-        logger.info(
-            &format!("there is {} password name update successfully.", rows)
-        );
     }
     if _is_db_decrypted {
         pm_db_encryption.encrypt();
         logger.info("password manager database encrypted successfully.");
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::{dblib, filelib::create_file};
+
+    #[test]
+    fn update_log_fields() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/update_log_fields");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("test.db");
+        let log_db_path = temp_dir.join("logs.db");
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        let id = dblib::pm::save_password(db_path.clone(), "github".to_owned(), "pass123".to_owned()).to_string();
+        dblib::pm::add_tag(db_path.clone(), "work".to_owned(), "github".to_owned());
+
+        let old = super::super::get_password_by_id(db_path.clone(), &id).unwrap();
+        dblib::pm::update_password(db_path.clone(), id.clone(), "new-pass456".to_owned());
+        dblib::pm::update_password_name(db_path.clone(), id.clone(), "github-work".to_owned());
+        let new = super::super::get_password_by_id(db_path.clone(), &id).unwrap();
+        dblib::log::register(
+            &super::update_log(&old, &new).unwrap(),
+            dblib::log::LogAction::Update,
+            log_db_path.clone()
+        );
+        let logs = dblib::log::get_logs_by_action(log_db_path.clone(), dblib::log::LogAction::Update, 0);
+        assert_eq!(logs.len(), 1, "Number of update logs NOT match!!");
+        assert_eq!(logs[0].log, "updated 'github': name, password", "Update log NOT match!!");
+        assert!(!logs[0].log.contains("pass456"), "Secret leaked in the log!!");
+        assert!(super::update_log(&new, &new).is_none(), "Unchanged password logged!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
@@ -0,0 +1,37 @@
+use super::ArgMatches;
+use crate::{
+    encryption_manager::decrypt_file,
+    errorlib,
+    filelib,
+    loglib,
+    utilities
+};
+
+pub fn main(_: &ArgMatches) {
+    let mut logger = loglib::Logger::new("verify-pm-key");
+    let pm_db_state = filelib::pm::db_state();
+    if pm_db_state == filelib::FileState::NotFound {
+        logger.error(
+            "password manager database is empty!",
+            errorlib::ExitErrorCode::PMDatabaseNotFound
+        );
+    } else if pm_db_state == filelib::FileState::Decrypted {
+        logger.error(
+            "database is NOT encrypted!",
+            errorlib::ExitErrorCode::FileNotEncrypted
+        );
+    }
+    let key = utilities::input("Enter the key: ");
+    logger.start();
+    // Only the first block is decrypted, the database stays encrypted.
+    if !decrypt_file::verify_key(
+        filelib::pm::get_encrypted_db_path().to_str().unwrap(),
+        &key
+    ) {
+        logger.error(
+            "the key can NOT decrypt the database!",
+            errorlib::ExitErrorCode::InvalidKey
+        );
+    }
+    logger.info("the key is valid.");
+}
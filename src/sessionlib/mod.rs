@@ -0,0 +1,419 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use chrono::Local;
+use dirs::data_dir;
+use crate::{
+    configlib,
+    errorlib,
+    filelib,
+    loglib
+};
+
+/// The default session key cache time, in seconds.
+pub const DEFAULT_TTL_SECS: i64 = 300;
+
+/// The default auto-save interval of the interactive sessions, in minutes.
+pub const DEFAULT_AUTOSAVE_MINS: i64 = 5;
+
+/// The failed unlock attempts before the cooldown.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// The unlock cooldown after `MAX_ATTEMPTS` failed attempts, in seconds.
+/// The failed attempts are forgotten after it too.
+pub const COOLDOWN_SECS: i64 = 300;
+
+/// Get the session key cache file path.
+/// It will return the cache path in the user's data directory:
+/// - Linux: `/home/{user}/.local/share/XPManager/data/session`
+/// - MacOS: `/Users/{user}/Library/Application Support/XPManager/data/session`
+/// - Windows: `C:\Users\{user}\AppData\Roaming\XPManager\data\session`
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::SystemDataDirNotFound`
+/// 
+/// ### Example:
+/// ```
+/// let session_path = sessionlib::get_session_path();
+/// println!("Path is: {}", session_path.display());
+/// ```
+pub fn get_session_path() -> PathBuf {
+    let logger = loglib::Logger::new("get-session-path");
+    if let Some(data_path) = data_dir() {
+        return data_path.join("XPManager/data/session");
+    }
+    logger.error(
+        "can NOT get the system data directory path!",
+        errorlib::ExitErrorCode::SystemDataDirNotFound
+    );
+}
+
+/// Get the session key cache time from the config, None if the cache is off.
+/// The cache is off by default: the key is saved NOT encrypted (only the
+/// owner can read it) until it expires, anyone with access to your user
+/// can decrypt the database in this time. Use `xpm forget` to delete it.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+/// 
+/// ### Example:
+/// ```toml
+/// [session]
+/// cache = true
+/// ttl = 300 # seconds
+/// ```
+pub fn ttl(config: &configlib::Config) -> Option<i64> {
+    let logger = loglib::Logger::new("session-ttl");
+    if !config.get_bool("session.cache").unwrap_or(false) {
+        return None;
+    }
+    let ttl = config.get_int("session.ttl").unwrap_or(DEFAULT_TTL_SECS);
+    if ttl <= 0 {
+        logger.error(
+            "config 'session.ttl' must be more than 0 seconds!",
+            errorlib::ExitErrorCode::InvalidConfig
+        );
+    }
+    Some(ttl)
+}
+
+/// Get the auto-save interval of the interactive sessions (e.g. `browse`)
+/// from the config, None if the auto-save is off (`0`).
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::InvalidConfig`
+/// 
+/// ### Example:
+/// ```toml
+/// [session]
+/// autosave = 5 # minutes, 0 to turn it off
+/// ```
+pub fn autosave_interval(config: &configlib::Config) -> Option<Duration> {
+    let logger = loglib::Logger::new("session-autosave");
+    let minutes = config.get_int("session.autosave").unwrap_or(DEFAULT_AUTOSAVE_MINS);
+    if minutes < 0 {
+        logger.error(
+            "config 'session.autosave' must be 0 (off) or more minutes!",
+            errorlib::ExitErrorCode::InvalidConfig
+        );
+    }
+    (minutes > 0).then(|| Duration::from_secs(minutes as u64 * 60))
+}
+
+/// Trigger the periodic auto-save of a long session.
+pub struct AutoSave {
+    interval: Duration,
+    last: Instant
+}
+
+impl AutoSave {
+    /// Creates a new `AutoSave`, the first save is due after
+    /// one interval from `now`.
+    pub fn new(interval: Duration, now: Instant) -> AutoSave {
+        AutoSave { interval, last: now }
+    }
+
+    /// The time left until the next save, to wait for the events.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.interval.saturating_sub(now.duration_since(self.last))
+    }
+
+    /// Check if a save is due, the next save is due after
+    /// one interval from `now` if it is.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let mut autosave = AutoSave::new(Duration::from_secs(300), Instant::now());
+    /// if autosave.due(Instant::now()) {
+    ///     pm_db_encryption.snapshot();
+    /// }
+    /// ```
+    pub fn due(&mut self, now: Instant) -> bool {
+        if self.remaining(now).is_zero() {
+            self.last = now;
+            return true;
+        }
+        false
+    }
+}
+
+/// The current time in seconds.
+pub fn now() -> i64 {
+    Local::now().timestamp()
+}
+
+/// Save the key in the cache file as `<expire-at>\n<key>`,
+/// readable by the owner only. Returns false if it can NOT be saved.
+/// 
+/// ### Example:
+/// ```
+/// let ttl = 300;
+/// sessionlib::store(&sessionlib::get_session_path(), "<your-key>", sessionlib::now() + ttl);
+/// ```
+pub fn store(path: &Path, key: &str, expire_at: i64) -> bool {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    if let Some(parent) = path.parent() {
+        if std::fs::create_dir_all(parent).is_err() {
+            return false;
+        }
+    }
+    // The mode is set on the creation only, so create a new file.
+    let _ = std::fs::remove_file(path);
+    options.open(path)
+        .and_then(|mut file| write!(file, "{}\n{}", expire_at, key))
+        .is_ok()
+}
+
+/// Load the cached key, None if there is no key or it is expired
+/// (the expired cache file is deleted).
+/// 
+/// ### Example:
+/// ```
+/// if let Some(key) = sessionlib::load(&sessionlib::get_session_path(), sessionlib::now()) {
+///     println!("the key is cached.");
+/// }
+/// ```
+pub fn load(path: &Path, now: i64) -> Option<String> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let (expire_at, key) = contents.split_once('\n')?;
+    if expire_at.parse::<i64>().ok()? <= now {
+        forget(path);
+        return None;
+    }
+    Some(key.to_owned())
+}
+
+/// Delete the cached key. Returns false if there is no cached key.
+/// 
+/// ### Example:
+/// ```
+/// if sessionlib::forget(&sessionlib::get_session_path()) {
+///     println!("the cached key is deleted.");
+/// }
+/// ```
+pub fn forget(path: &Path) -> bool {
+    if !path.exists() {
+        return false;
+    }
+    filelib::wipe_delete(path.to_str().unwrap().to_owned()).unwrap_or_else(|err| err.exit());
+    true
+}
+
+/// Get the failed unlock attempts file path, next to the session file.
+/// 
+/// ### Exit:
+/// - `errorlib::ExitErrorCode::SystemDataDirNotFound`
+pub fn get_attempts_path() -> PathBuf {
+    get_session_path().with_file_name("attempts")
+}
+
+/// The failed unlock attempts, saved as `<failures>\n<last-failure-at>`.
+#[derive(PartialEq, Debug, Default)]
+pub struct Attempts {
+    pub failures: u32,
+    /// The time of the last failed attempt, in seconds.
+    pub last_failure_at: i64
+}
+
+impl Attempts {
+    /// Load the failed attempts, none if there is no attempts file or
+    /// the last failure is older than `COOLDOWN_SECS`.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let attempts = Attempts::load(&sessionlib::get_attempts_path(), sessionlib::now());
+    /// ```
+    pub fn load(path: &Path, now: i64) -> Attempts {
+        let attempts = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| {
+                let (failures, last_failure_at) = contents.split_once('\n')?;
+                Some(Attempts {
+                    failures: failures.parse().ok()?,
+                    last_failure_at: last_failure_at.trim().parse().ok()?
+                })
+            })
+            .unwrap_or_default();
+        if now - attempts.last_failure_at >= COOLDOWN_SECS {
+            return Attempts::default();
+        }
+        attempts
+    }
+
+    /// The attempts left before the cooldown.
+    pub fn remaining(&self) -> u32 {
+        MAX_ATTEMPTS.saturating_sub(self.failures)
+    }
+
+    /// The seconds left of the cooldown, None if the unlock is allowed.
+    pub fn cooldown(&self, now: i64) -> Option<i64> {
+        let left = self.last_failure_at + COOLDOWN_SECS - now;
+        (self.remaining() == 0 && left > 0).then_some(left)
+    }
+
+    /// The prompt of the secret with the attempt number (e.g. "Enter the key (attempt 2 of 5): ").
+    pub fn prompt(&self, secret: &str) -> String {
+        format!("Enter the {} (attempt {} of {}): ", secret, self.failures + 1, MAX_ATTEMPTS)
+    }
+
+    /// Count a failed attempt and save it. Returns false if it can NOT be saved.
+    pub fn fail(&mut self, path: &Path, now: i64) -> bool {
+        self.failures += 1;
+        self.last_failure_at = now;
+        if let Some(parent) = path.parent() {
+            if std::fs::create_dir_all(parent).is_err() {
+                return false;
+            }
+        }
+        std::fs::write(path, format!("{}\n{}", self.failures, self.last_failure_at)).is_ok()
+    }
+
+    /// Forget the failed attempts, after a successful unlock.
+    pub fn reset(path: &Path) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+pub fn main() {
+    let logger = loglib::Logger::new("forget");
+    if forget(&get_session_path()) {
+        logger.info("the cached key deleted successfully.");
+    } else {
+        logger.info("there is no cached key.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+    use super::AutoSave;
+    use crate::{
+        dblib,
+        encryption_manager::{decrypt_file, encrypt_file},
+        filelib::create_file
+    };
+
+    #[test]
+    fn session_cache() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/session_cache");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let path = temp_dir.join("session");
+        assert_eq!(super::load(&path, 100), None, "Missing cache NOT match!!");
+
+        // TTL of 2 seconds from the time 100.
+        assert!(super::store(&path, "key=123", 102), "Key NOT stored!!");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "Cache file permissions NOT match!!");
+        }
+        assert_eq!(super::load(&path, 101), Some("key=123".to_owned()), "Cached key NOT match!!");
+        assert_eq!(super::load(&path, 102), None, "Expired key loaded!!");
+        assert!(!path.exists(), "Expired cache NOT deleted!!");
+
+        super::store(&path, "key=123", 102);
+        assert!(super::forget(&path), "Cache NOT forgotten!!");
+        assert!(!super::forget(&path), "Missing cache forgotten!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn autosave() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/autosave");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let db_path = temp_dir.join("passwords.db");
+        let db_str = db_path.to_str().unwrap().to_owned();
+        let en_str = format!("{}.x", db_str);
+        create_file(db_path.clone()).unwrap();
+        dblib::pm::create_passwords_table(db_path.clone());
+        let key = fernet::Fernet::generate_key();
+
+        // Save every 50ms, persist the database when a save is due.
+        let start = Instant::now();
+        let mut autosave = AutoSave::new(Duration::from_millis(50), start);
+        let mut saves = 0;
+        for (ms, name) in [(10, "github"), (50, "gitlab"), (60, "email"), (120, "bank")] {
+            dblib::pm::save_password(db_path.clone(), name.to_owned(), "pass123".to_owned());
+            if autosave.due(start + Duration::from_millis(ms)) {
+                assert!(encrypt_file::encrypt_snapshot(&db_str, &en_str, &key, &[]), "Snapshot NOT saved!!");
+                saves += 1;
+            }
+        }
+        assert_eq!(saves, 2, "Number of saves NOT match!!");
+        assert_eq!(
+            autosave.remaining(start + Duration::from_millis(130)),
+            Duration::from_millis(40),
+            "Remaining time NOT match!!"
+        );
+        assert!(db_path.exists(), "Decrypted database deleted!!");
+        assert!(!PathBuf::from(format!("{}.xpm-autosave", en_str)).exists(), "Temp snapshot NOT deleted!!");
+
+        // A crash restores the last snapshot.
+        std::fs::remove_file(&db_path).unwrap();
+        decrypt_file::decrypt(en_str, key);
+        let names: Vec<String> = dblib::pm::get_passwords(db_path)
+            .into_iter()
+            .map(|pass| pass.name)
+            .collect();
+        assert_eq!(names, vec!["github", "gitlab", "email", "bank"], "Snapshot passwords NOT match!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+
+    #[test]
+    fn unlock_attempts() {
+        let temp_dir = PathBuf::new()
+            .join("./temp/unlock_attempts");
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(temp_dir.clone())
+                .expect("Can NOT delete temp dir!!");
+        }
+        let path = temp_dir.join("attempts");
+        let attempts = super::Attempts::load(&path, 1000);
+        assert_eq!(attempts.prompt("key"), "Enter the key (attempt 1 of 5): ", "First prompt NOT match!!");
+
+        // Two failed attempts in an earlier run.
+        let mut attempts = super::Attempts::load(&path, 1000);
+        assert!(attempts.fail(&path, 1000), "Failed attempt NOT saved!!");
+        assert!(attempts.fail(&path, 1010), "Failed attempt NOT saved!!");
+        let attempts = super::Attempts::load(&path, 1020);
+        assert_eq!(attempts.prompt("passphrase"), "Enter the passphrase (attempt 3 of 5): ", "Prompt NOT match!!");
+        assert_eq!(attempts.remaining(), 3, "Remaining attempts NOT match!!");
+        assert_eq!(attempts.cooldown(1020), None, "Unlock locked early!!");
+
+        let mut attempts = attempts;
+        for now in [1030, 1040, 1050] {
+            attempts.fail(&path, now);
+        }
+        let attempts = super::Attempts::load(&path, 1060);
+        assert_eq!(attempts.remaining(), 0, "Remaining attempts NOT match!!");
+        assert_eq!(attempts.cooldown(1060), Some(super::COOLDOWN_SECS - 10), "Cooldown NOT match!!");
+        let attempts = super::Attempts::load(&path, 1050 + super::COOLDOWN_SECS);
+        assert_eq!(attempts, super::Attempts::default(), "Attempts NOT forgotten after the cooldown!!");
+
+        super::Attempts::reset(&path);
+        assert!(!path.exists(), "Attempts file NOT deleted!!");
+
+        std::fs::remove_dir_all(temp_dir)
+            .expect("Can NOT delete temp dir!!");
+    }
+}
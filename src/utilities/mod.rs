@@ -6,16 +6,32 @@ use crate::{
 use colored::Colorize;
 use rand::seq::{IndexedRandom, IteratorRandom};
 use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex
+};
 
 /// The password sample types.
 /// uses to chooes sample to generate the password.
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum PasswordSample {
     Ascii,
     NoSymbols,
     Hex
 }
 
+impl PasswordSample {
+    /// Get the sample type from its name (ascii, no-symbols, hex).
+    pub fn from_name(name: &str) -> Option<PasswordSample> {
+        match name {
+            "ascii" => Some(PasswordSample::Ascii),
+            "no-symbols" => Some(PasswordSample::NoSymbols),
+            "hex" => Some(PasswordSample::Hex),
+            _ => None
+        }
+    }
+}
+
 /// Generate sample based on the type.
 /// types from PasswordSample enum:
 /// - Ascii: A-Z, a-z, 0-9 and some symbols.
@@ -52,21 +68,42 @@ pub fn get_sample(sample: PasswordSample) -> Vec<char> {
     }
 }
 
-/// Generate random number as `String` between 32 to 73.
+/// The characters that look alike (e.g. 0/O, 1/l/I).
+pub const AMBIGUOUS_CHARS: [char; 7] = ['0', 'O', 'o', '1', 'l', 'I', '|'];
+
+/// Remove the ambiguous characters from the sample.
+/// 
+/// ### Example:
+/// ```
+/// let mut sample = utilities::get_sample(utilities::PasswordSample::NoSymbols);
+/// utilities::remove_ambiguous(&mut sample);
+/// assert_eq!(sample.contains(&'O'), false);
+/// ```
+pub fn remove_ambiguous(sample: &mut Vec<char>) {
+    sample.retain(|c| !AMBIGUOUS_CHARS.contains(c));
+}
+
+/// The default random password length range.
+pub const MIN_RANDOM_LENGTH: u16 = 32;
+pub const MAX_RANDOM_LENGTH: u16 = 72;
+
+/// Generate random number as `String` between `min` and `max` (inclusive),
+/// None if `min` is greater than `max`.
 /// 
 /// ## Example:
 /// ```
-/// let number_str = utilities::get_ran_string_number();
+/// let number_str = utilities::get_ran_string_number(
+///     utilities::MIN_RANDOM_LENGTH,
+///     utilities::MAX_RANDOM_LENGTH
+/// ).unwrap();
 /// let number = number_str.parse::<i32>().unwrap_or(0);
 /// assert_eq!(number >= 32 && number <= 72, true);
 /// ```
-pub fn get_ran_string_number() -> String {
+pub fn get_ran_string_number(min: u16, max: u16) -> Option<String> {
     let mut rag = rand::rng();
-    // Choose random length between 32 to 72.
-    return (32..=72)
+    (min..=max)
         .choose(&mut rag)
-        .unwrap()
-        .to_string();
+        .map(|number| number.to_string())
 }
 
 /// Read a string from standard input.
@@ -82,8 +119,13 @@ pub fn get_ran_string_number() -> String {
 /// ```
 pub fn input(message: &str) -> String {
     use std::io::Write;
-    print!("{}", message);
-    std::io::stdout().flush().expect("Flush Error!");
+    if loglib::is_stderr_only() {
+        eprint!("{}", message);
+        std::io::stderr().flush().expect("Flush Error!");
+    } else {
+        print!("{}", message);
+        std::io::stdout().flush().expect("Flush Error!");
+    }
     let mut line: String = String::new();
     std::io::stdin()
         .read_line(&mut line)
@@ -176,9 +218,276 @@ pub fn distribute_paths(files_paths: Vec<PathBuf>) -> Vec<Vec<PathBuf>> {
 }
 
 
+/// What a batch does when an item fails, the `--on-error` modes.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum OnError {
+    /// Abort at the first failure.
+    Stop,
+    /// Run the remaining items and report the failures at the end.
+    Continue
+}
+
+impl OnError {
+    /// Get the mode from its name (stop, continue).
+    pub fn from_name(name: &str) -> Option<OnError> {
+        match name {
+            "stop" => Some(OnError::Stop),
+            "continue" => Some(OnError::Continue),
+            _ => None
+        }
+    }
+}
+
+/// A failed batch item, with the exit code of its error.
+#[derive(Clone, Debug)]
+pub struct BatchFailure {
+    pub item: String,
+    pub code: errorlib::ExitErrorCode,
+    pub reason: String
+}
+
+impl BatchFailure {
+    pub fn new(item: &str, code: errorlib::ExitErrorCode, reason: &str) -> BatchFailure {
+        BatchFailure {
+            item: item.to_owned(),
+            code,
+            reason: reason.to_owned()
+        }
+    }
+}
+
+/// The batch items runner, shared between the threads of one batch
+/// (e.g. encrypt-dir), it collects the failures and stops all the
+/// threads at the first failure in the stop mode.
+pub struct Batch {
+    on_error: OnError,
+    is_stopped: AtomicBool,
+    failures: Mutex<Vec<BatchFailure>>
+}
+
+impl Batch {
+    pub fn new(on_error: OnError) -> Batch {
+        Batch {
+            on_error,
+            is_stopped: AtomicBool::new(false),
+            failures: Mutex::new(vec![])
+        }
+    }
+
+    /// Run the task for every item, the remaining items are
+    /// skipped after a failure in the stop mode.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let batch = utilities::Batch::new(utilities::OnError::Continue);
+    /// batch.run(&files_paths, |file| encrypt_item(file));
+    /// batch.finish(&logger);
+    /// ```
+    pub fn run<T>(&self, items: &[T], mut task: impl FnMut(&T) -> Result<(), BatchFailure>) {
+        for item in items {
+            if self.is_stopped() {
+                return;
+            }
+            if let Err(failure) = task(item) {
+                self.fail(failure);
+            }
+        }
+    }
+
+    /// Record a failed item, it stops the batch in the stop mode.
+    pub fn fail(&self, failure: BatchFailure) {
+        self.failures.lock().unwrap().push(failure);
+        if self.on_error == OnError::Stop {
+            self.is_stopped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Check if the batch stopped at a failure.
+    pub fn is_stopped(&self) -> bool {
+        self.is_stopped.load(Ordering::Relaxed)
+    }
+
+    /// The failed items, in the failure order.
+    pub fn failures(&self) -> Vec<BatchFailure> {
+        self.failures.lock().unwrap().clone()
+    }
+
+    /// Report the failures and exit with the first failure error
+    /// code, nothing happens if all the items succeeded.
+    /// 
+    /// ### Exit:
+    /// - The first failure error code.
+    pub fn finish(&self, logger: &loglib::Logger) {
+        let failures = self.failures();
+        let first = match failures.first() {
+            Some(first) => first,
+            None => return
+        };
+        if self.on_error == OnError::Stop {
+            logger.error(
+                &format!("'{}': {}, the batch stopped!", first.item, first.reason),
+                first.code
+            );
+        }
+        for failure in failures.iter() {
+            logger.warning(&format!("'{}': {}!", failure.item, failure.reason));
+        }
+        logger.error(
+            &format!("{} items failed!", failures.len()),
+            first.code
+        );
+    }
+}
+
+/// Normalize the tag: trim it, make it lowercase and
+/// replace the spaces and commas with `-`.
+/// 
+/// ### Example:
+/// ```
+/// let tag = utilities::normalize_tag(" My Work ");
+/// assert_eq!(tag, "my-work");
+/// ```
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+/// Split the stored tags string to a list of tags.
+/// 
+/// ### Example:
+/// ```
+/// let tags = utilities::split_tags("work,social");
+/// assert_eq!(tags, vec!["work", "social"]);
+/// ```
+pub fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| tag.to_owned())
+        .collect()
+}
+
+/// Join a list of tags to be stored, the tags will be sorted.
+/// 
+/// ### Example:
+/// ```
+/// let tags = utilities::join_tags(vec!["work".to_string(), "social".to_string()]);
+/// assert_eq!(tags, "social,work");
+/// ```
+pub fn join_tags(mut tags: Vec<String>) -> String {
+    tags.sort();
+    tags.dedup();
+    tags.join(",")
+}
+
+/// Format size in bytes to a human readable string.
+/// 
+/// ### Example:
+/// ```
+/// let size = utilities::format_size(1536);
+/// assert_eq!(size, "1.50KB");
+/// ```
+pub fn format_size(size: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{}B", size);
+    }
+    format!("{:.2}{}", value, units[unit])
+}
+
+/// Check if plaintext secrets can be written to the standard output.
+/// Writing them to an interactive terminal leaves them on the screen
+/// and in the scrollback, so it needs the force.
+/// 
+/// ### Example:
+/// ```
+/// use std::io::IsTerminal;
+/// let is_terminal = std::io::stdout().is_terminal();
+/// if !utilities::can_write_secrets_to_stdout(is_terminal, false) {
+///     println!("use --out or pipe the output!");
+/// }
+/// ```
+pub fn can_write_secrets_to_stdout(is_terminal: bool, is_force: bool) -> bool {
+    !is_terminal || is_force
+}
+
+/// Estimate the entropy bits of a random password, from its length
+/// and the number of the characters it is picked from:
+/// `length * log2(alphabet_size)`.
+/// 
+/// ### Example:
+/// ```
+/// // 16 characters from 0-9 and A-F.
+/// assert_eq!(utilities::estimate_entropy("0123456789ABCDEF", 16), 64.0);
+/// ```
+pub fn estimate_entropy(password: &str, alphabet_size: usize) -> f64 {
+    if alphabet_size < 2 {
+        return 0.0;
+    }
+    password.chars().count() as f64 * (alphabet_size as f64).log2()
+}
+
+/// The strength label of the entropy bits (Weak, Fair, Strong, Excellent).
+/// 
+/// ### Example:
+/// ```
+/// assert_eq!(utilities::entropy_label(64.0), "Strong");
+/// ```
+pub fn entropy_label(bits: f64) -> &'static str {
+    match bits {
+        bits if bits < 36.0 => "Weak",
+        bits if bits < 60.0 => "Fair",
+        bits if bits < 128.0 => "Strong",
+        _ => "Excellent"
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn estimate_entropy() {
+        let hex = super::get_sample(super::PasswordSample::Hex);
+        let bits = super::estimate_entropy("0123456789ABCDEF", hex.len());
+        assert_eq!(bits, 64.0, "Hex entropy NOT match!!");
+        assert_eq!(super::entropy_label(bits), "Strong", "Hex label NOT match!!");
+        let no_symbols = super::get_sample(super::PasswordSample::NoSymbols);
+        let bits = super::estimate_entropy("aB3dE", no_symbols.len());
+        assert!((bits - 5.0 * 62f64.log2()).abs() < 1e-9, "NoSymbols entropy NOT match!!");
+        assert_eq!(super::entropy_label(bits), "Weak", "NoSymbols label NOT match!!");
+        assert_eq!(super::entropy_label(super::estimate_entropy(&"x".repeat(24), 90)), "Excellent", "Long label NOT match!!");
+        assert_eq!(super::estimate_entropy("aaaa", 1), 0.0, "One character entropy NOT match!!");
+    }
+
+    #[test]
+    fn remove_ambiguous() {
+        for (sample_type, removed) in [
+            (super::PasswordSample::Ascii, 7),
+            (super::PasswordSample::NoSymbols, 6)
+        ] {
+            let full = super::get_sample(sample_type);
+            let mut sample = full.clone();
+            super::remove_ambiguous(&mut sample);
+            assert_eq!(sample.len(), full.len() - removed, "{:?} sample size NOT match!!", sample_type);
+            assert!(
+                sample.iter().all(|c| !super::AMBIGUOUS_CHARS.contains(c)),
+                "{:?} sample has ambiguous characters!!", sample_type
+            );
+        }
+        let mut sample = vec!['0', 'O', 'l'];
+        super::remove_ambiguous(&mut sample);
+        assert!(sample.is_empty(), "Ambiguous sample NOT empty!!");
+    }
+
     #[test]
     fn get_sample() {
         let mut sample = super::get_sample(super::PasswordSample::Ascii);
@@ -206,11 +515,49 @@ mod tests {
 
     #[test]
     fn get_ran_string_number() {
-        let number_str = super::get_ran_string_number();
+        let number_str = super::get_ran_string_number(super::MIN_RANDOM_LENGTH, super::MAX_RANDOM_LENGTH)
+            .expect("Random number NOT generated!!");
         let number = number_str
             .parse::<u8>()
             .expect("Can NOT parse the random number to u8!!");
         assert!(number >= 32 && number <= 72, "Random number NOT in (32 <= x <= 72)!!");
+
+        for _ in 0..100 {
+            let number = super::get_ran_string_number(8, 12)
+                .expect("Random number NOT generated!!")
+                .parse::<u16>()
+                .expect("Can NOT parse the random number to u16!!");
+            assert!((8..=12).contains(&number), "Random number NOT in (8 <= x <= 12)!!");
+        }
+        assert_eq!(super::get_ran_string_number(16, 16), Some("16".to_owned()), "Single number range NOT match!!");
+        assert_eq!(super::get_ran_string_number(20, 10), None, "Min greater than max NOT rejected!!");
     }
 
+    #[test]
+    fn normalize_tag() {
+        assert_eq!(super::normalize_tag(" My  Work "), "my-work", "Tag NOT normalized!!");
+        assert_eq!(super::normalize_tag("a,b"), "a-b", "Tag NOT normalized!!");
+    }
+
+    #[test]
+    fn split_and_join_tags() {
+        let tags = super::split_tags("work,social");
+        assert_eq!(tags, vec!["work", "social"], "Tags NOT split!!");
+        assert_eq!(super::join_tags(tags), "social,work", "Tags NOT joined!!");
+        assert_eq!(super::split_tags("").len(), 0, "Empty tags NOT empty!!");
+    }
+
+    #[test]
+    fn format_size() {
+        assert_eq!(super::format_size(10), "10B", "Size format NOT match!!");
+        assert_eq!(super::format_size(1536), "1.50KB", "Size format NOT match!!");
+        assert_eq!(super::format_size(5*1024*1024), "5.00MB", "Size format NOT match!!");
+    }
+
+    #[test]
+    fn can_write_secrets_to_stdout() {
+        assert!(super::can_write_secrets_to_stdout(false, false), "Pipe NOT allowed!!");
+        assert!(!super::can_write_secrets_to_stdout(true, false), "Terminal allowed without force!!");
+        assert!(super::can_write_secrets_to_stdout(true, true), "Terminal NOT allowed with force!!");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output, Stdio}
+};
+
+/// Run xpm with its data in the temp dir, the stdin is closed so a
+/// key prompt fails instead of waiting.
+fn xpm(data_dir: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xpm"))
+        .args(args)
+        .env("XDG_DATA_HOME", data_dir)
+        .env_remove("XPM_KEY")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Can NOT run xpm!!")
+}
+
+#[test]
+fn decrypt_to_stdout() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/decrypt_to_stdout");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let file = temp_dir.join("test.txt");
+    let key_file = temp_dir.join("key.txt");
+    // More than one block of 64KB.
+    let message = "this is test message!\n".repeat(5_000);
+    std::fs::write(&file, &message).unwrap();
+    std::fs::write(&key_file, fernet::Fernet::generate_key()).unwrap();
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "encrypt-file", file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    assert!(output.status.success(), "Encrypt failed: {}", String::from_utf8_lossy(&output.stdout));
+    std::fs::remove_file(&file).unwrap();
+
+    let en_file = temp_dir.join("test.txt.x");
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--stdout", "--key-file", key_file.to_str().unwrap()
+    ]);
+    assert!(output.status.success(), "Decrypt failed: {}", String::from_utf8_lossy(&output.stderr));
+    // The stdout has the data only, the logs are in the stderr.
+    assert!(output.stdout == message.as_bytes(), "Decrypted data NOT match!!");
+    assert!(!output.stderr.is_empty(), "Logs NOT in the stderr!!");
+    assert!(!file.exists(), "Decrypted file created!!");
+    assert!(en_file.exists(), "Encrypted file deleted!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}
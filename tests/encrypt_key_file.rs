@@ -0,0 +1,73 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output, Stdio}
+};
+
+/// Run xpm with its data in the temp dir, the stdin is closed so a
+/// key prompt fails instead of waiting.
+fn xpm(data_dir: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xpm"))
+        .args(args)
+        .env("XDG_DATA_HOME", data_dir)
+        .env_remove("XPM_KEY")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Can NOT run xpm!!")
+}
+
+#[test]
+fn encrypt_with_key_file() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/encrypt_with_key_file");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let file = temp_dir.join("test.txt");
+    let key_file = temp_dir.join("key.txt");
+    std::fs::write(&file, "this is test message!").unwrap();
+    // The trailing whitespace is removed.
+    std::fs::write(&key_file, format!("{}  \r\n", fernet::Fernet::generate_key())).unwrap();
+
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "encrypt-file", file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    assert!(output.status.success(), "Encrypt with key file failed: {}", String::from_utf8_lossy(&output.stdout));
+    let en_file = temp_dir.join("test.txt.x");
+    assert!(en_file.exists(), "Encrypted file NOT found!!");
+    assert_ne!(std::fs::read(&en_file).unwrap(), b"this is test message!", "File NOT encrypted!!");
+
+    std::fs::remove_file(&file).unwrap();
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    assert!(output.status.success(), "Decrypt with key file failed: {}", String::from_utf8_lossy(&output.stdout));
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "this is test message!",
+        "Decrypted file NOT match!!"
+    );
+
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--force", "--key-file", temp_dir.join("missing.txt").to_str().unwrap()
+    ]);
+    assert!(!output.status.success(), "Missing key file NOT refused!!");
+
+    // A key file without a Fernet key, e.g. a wrong file.
+    std::fs::write(temp_dir.join("bad-key.txt"), "not a fernet key\n").unwrap();
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--force", "--key-file", temp_dir.join("bad-key.txt").to_str().unwrap()
+    ]);
+    // `errorlib::ExitErrorCode::InvalidKey`
+    assert_eq!(output.status.code(), Some(80), "Invalid key file exit code NOT match!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}
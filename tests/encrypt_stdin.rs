@@ -0,0 +1,57 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio}
+};
+
+/// Run xpm with its data in the temp dir, the data is written to its stdin.
+fn xpm_with_stdin(data_dir: &PathBuf, args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xpm"))
+        .args(args)
+        .env("XDG_DATA_HOME", data_dir)
+        .env_remove("XPM_KEY")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Can NOT run xpm!!");
+    child.stdin.take().unwrap().write_all(stdin).expect("Can NOT write the stdin!!");
+    child.wait_with_output().expect("Can NOT run xpm!!")
+}
+
+#[test]
+fn encrypt_from_stdin() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/encrypt_from_stdin");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let key_file = temp_dir.join("key.txt");
+    let en_file = temp_dir.join("backup.x");
+    std::fs::write(&key_file, fernet::Fernet::generate_key()).unwrap();
+    // More than one block of 64KB.
+    let message = "CREATE TABLE passwords;\n".repeat(5_000);
+
+    let output = xpm_with_stdin(&data_dir, &[
+        "encryption-manager", "encrypt-file", "--stdin",
+        "--out", en_file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ], message.as_bytes());
+    assert!(output.status.success(), "Encrypt failed: {}", String::from_utf8_lossy(&output.stdout));
+    assert!(en_file.exists(), "Encrypted file NOT found!!");
+    assert!(!temp_dir.join("backup.x.xpm-part").exists(), "Temp file NOT renamed!!");
+
+    let output = xpm_with_stdin(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--stdout", "--key-file", key_file.to_str().unwrap()
+    ], b"");
+    assert!(output.status.success(), "Decrypt failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout == message.as_bytes(), "Decrypted data NOT match!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}
@@ -0,0 +1,68 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio}
+};
+
+/// Run xpm with its data in the temp dir and the input lines in the stdin.
+fn xpm(data_dir: &PathBuf, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xpm"))
+        .args(args)
+        .env("XDG_DATA_HOME", data_dir)
+        .env_remove("XPM_KEY")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Can NOT run xpm!!");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().expect("Can NOT run xpm!!")
+}
+
+/// Get the printed key of the encrypt commands.
+fn printed_key(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Your Key: ").map(|key| key.trim().to_owned()))
+        .expect("Key NOT printed!!")
+}
+
+#[test]
+fn save_with_encrypted_log_database() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/save_with_encrypted_log_database");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let pm_dir = data_dir.join("XPManager/data");
+    let batch = temp_dir.join("batch.txt");
+
+    std::fs::write(&batch, "github=pass123\n").unwrap();
+    let output = xpm(&data_dir, &["password-manager", "save", "--batch", batch.to_str().unwrap()], "");
+    assert!(output.status.success(), "Save failed: {}", String::from_utf8_lossy(&output.stdout));
+    let output = xpm(&data_dir, &["password-manager", "encrypt"], "");
+    assert!(output.status.success(), "Encrypt failed: {}", String::from_utf8_lossy(&output.stdout));
+    let key = printed_key(&output);
+    let output = xpm(&data_dir, &["log-manager", "encrypt"], "");
+    assert!(output.status.success(), "Log encrypt failed: {}", String::from_utf8_lossy(&output.stdout));
+
+    // The save can NOT log, but the database is encrypted again.
+    std::fs::write(&batch, "gitlab=pass456\n").unwrap();
+    let output = xpm(
+        &data_dir,
+        &["password-manager", "save", "--batch", batch.to_str().unwrap()],
+        &format!("{}\n", key)
+    );
+    assert!(output.status.success(), "Save failed: {}", String::from_utf8_lossy(&output.stdout));
+    assert!(pm_dir.join("passwords.db.x").exists(), "Password database NOT encrypted!!");
+    assert!(!pm_dir.join("passwords.db").exists(), "Plaintext password database left!!");
+    assert!(pm_dir.join("xpm-log.db.x").exists(), "Log database NOT encrypted!!");
+    assert!(!pm_dir.join("xpm-log.db").exists(), "Plaintext log database created!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}
@@ -0,0 +1,206 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output, Stdio}
+};
+
+/// Run xpm with its data in the temp dir, the stdin is closed so a
+/// key prompt fails instead of waiting.
+fn xpm(data_dir: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_xpm"))
+        .args(args)
+        .env("XDG_DATA_HOME", data_dir)
+        .env_remove("XPM_KEY")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Can NOT run xpm!!")
+}
+
+#[test]
+fn decrypt_unsupported_format() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/decrypt_unsupported_format");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let key_file = temp_dir.join("key.txt");
+    std::fs::write(&key_file, format!("{}\n", fernet::Fernet::generate_key())).unwrap();
+
+    // A file from a newer XPManager: "<magic><version><buffer-size><compression>".
+    let en_file = temp_dir.join("test.txt.x");
+    let mut header: Vec<u8> = b"XPMF".to_vec();
+    header.push(99);
+    header.extend_from_slice(&(64 * 1024u32).to_be_bytes());
+    header.extend_from_slice(&[0, 0]);
+    std::fs::write(&en_file, header).unwrap();
+
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    // `errorlib::ExitErrorCode::UnsupportedFormat`
+    assert_eq!(output.status.code(), Some(83), "Unsupported format exit code NOT match!!");
+    assert!(!temp_dir.join("test.txt").exists(), "Unsupported file decrypted!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}
+
+#[test]
+fn decrypt_wrong_key_and_corrupt_block() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/decrypt_wrong_key_and_corrupt_block");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let file = temp_dir.join("test.txt");
+    let key_file = temp_dir.join("key.txt");
+    let wrong_key_file = temp_dir.join("wrong-key.txt");
+    // 3 blocks of 64KB and a short one.
+    std::fs::write(&file, "this is test message!\n".repeat(10_000)).unwrap();
+    std::fs::write(&key_file, fernet::Fernet::generate_key()).unwrap();
+    std::fs::write(&wrong_key_file, fernet::Fernet::generate_key()).unwrap();
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "encrypt-file", file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    assert!(output.status.success(), "Encrypt failed: {}", String::from_utf8_lossy(&output.stdout));
+    std::fs::remove_file(&file).unwrap();
+
+    let en_file = temp_dir.join("test.txt.x");
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--key-file", wrong_key_file.to_str().unwrap()
+    ]);
+    // `errorlib::ExitErrorCode::WrongKey`
+    assert_eq!(output.status.code(), Some(84), "Wrong key exit code NOT match!!");
+    assert!(!file.exists(), "Partial file NOT deleted!!");
+
+    // Change a byte of the second block, the header is 12 bytes.
+    let mut data = std::fs::read(&en_file).unwrap();
+    let first_size = u32::from_be_bytes(data[12..16].try_into().unwrap()) as usize;
+    let byte = 16 + first_size + 4 + 100;
+    data[byte] = if data[byte] == b'A' { b'B' } else { b'A' };
+    std::fs::write(&en_file, data).unwrap();
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    // `errorlib::ExitErrorCode::InvalidEncryptionData`
+    assert_eq!(output.status.code(), Some(81), "Corrupt block exit code NOT match!!");
+    let message = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(message.contains("corrupt at block 1"), "Corrupt block NOT match!!");
+    assert!(!file.exists(), "Partial file NOT deleted!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}
+
+#[test]
+fn decrypt_corrupt_compressed_block() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/decrypt_corrupt_compressed_block");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let key = fernet::Fernet::generate_key();
+    let fernet = fernet::Fernet::new(&key).unwrap();
+    let key_file = temp_dir.join("key.txt");
+    std::fs::write(&key_file, format!("{}\n", key)).unwrap();
+
+    // A zstd file: "<magic><version><buffer-size><compression-id><compression-level><salt-size>",
+    // the first block is written before the second one fails to decompress.
+    let en_file = temp_dir.join("test.txt.x");
+    let mut data: Vec<u8> = b"XPMF".to_vec();
+    data.push(3);
+    data.extend_from_slice(&(64 * 1024u32).to_be_bytes());
+    data.extend_from_slice(&[2, 3, 0]);
+    let blocks = [
+        zstd::encode_all("this is test message!".as_bytes(), 3).unwrap(),
+        b"this is NOT zstd data!".to_vec()
+    ];
+    for block in blocks {
+        let token = fernet.encrypt(&block);
+        data.extend_from_slice(&(token.len() as u32).to_be_bytes());
+        data.extend_from_slice(token.as_bytes());
+    }
+    std::fs::write(&en_file, data).unwrap();
+
+    let output = xpm(&data_dir, &[
+        "encryption-manager", "decrypt-file", en_file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    // `errorlib::ExitErrorCode::Compression`
+    assert_eq!(output.status.code(), Some(82), "Corrupt compressed block exit code NOT match!!");
+    assert!(!temp_dir.join("test.txt").exists(), "Partial file NOT deleted!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}
+
+#[test]
+fn upgrade_wrong_key() {
+    let temp_dir = PathBuf::new()
+        .join("./temp/upgrade_wrong_key");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(temp_dir.clone())
+            .expect("Can NOT delete temp dir!!");
+    }
+    std::fs::create_dir_all(temp_dir.join("data"))
+        .expect("Can NOT create temp dir!!");
+    let data_dir = std::fs::canonicalize(temp_dir.join("data")).unwrap();
+    let key = fernet::Fernet::generate_key();
+    let fernet = fernet::Fernet::new(&key).unwrap();
+    let key_file = temp_dir.join("key.txt");
+    let wrong_key_file = temp_dir.join("wrong-key.txt");
+    std::fs::write(&key_file, format!("{}\n", key)).unwrap();
+    std::fs::write(&wrong_key_file, format!("{}\n", fernet::Fernet::generate_key())).unwrap();
+
+    // Legacy format: "<length><en-data><length><en-data>", no header.
+    let en_file = temp_dir.join("test.txt.x");
+    let mut legacy: Vec<u8> = vec![];
+    for block in ["XPManager ", "legacy file"] {
+        let token = fernet.encrypt(block.as_bytes());
+        legacy.extend_from_slice(&(token.len() as u32).to_be_bytes());
+        legacy.extend_from_slice(token.as_bytes());
+    }
+    std::fs::write(&en_file, &legacy).unwrap();
+
+    let output = xpm(&data_dir, &[
+        "upgrade", en_file.to_str().unwrap(),
+        "--key-file", wrong_key_file.to_str().unwrap()
+    ]);
+    // `errorlib::ExitErrorCode::InvalidEncryptionData`
+    assert_eq!(output.status.code(), Some(81), "Wrong key exit code NOT match!!");
+    assert_eq!(std::fs::read(&en_file).unwrap(), legacy, "Legacy file changed!!");
+    let mut names: Vec<String> = std::fs::read_dir(&temp_dir).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["data", "key.txt", "test.txt.x", "wrong-key.txt"], "Upgrade temp files NOT deleted!!");
+
+    let output = xpm(&data_dir, &[
+        "upgrade", en_file.to_str().unwrap(),
+        "--key-file", key_file.to_str().unwrap()
+    ]);
+    assert!(output.status.success(), "Upgrade failed: {}", String::from_utf8_lossy(&output.stdout));
+    assert_eq!(&std::fs::read(&en_file).unwrap()[..4], b"XPMF", "File NOT upgraded!!");
+
+    std::fs::remove_dir_all(temp_dir)
+        .expect("Can NOT delete temp dir!!");
+}